@@ -43,6 +43,24 @@ impl fmt::Display for AssemblerFullError {
 #[cfg(feature = "std")]
 impl std::error::Error for AssemblerFullError {}
 
+/// How to reassemble a fragment overlapping data already received for the
+/// same packet.
+///
+/// Overlapping fragments are never sent by well-behaved hosts, but are used
+/// to evade intrusion detection systems that reassemble packets differently
+/// from the destination, so by default the whole packet is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FragmentOverlapPolicy {
+    /// Keep the data received first, only using the fragment to fill holes.
+    FirstWins,
+    /// Overwrite the data received first with the fragment.
+    LastWins,
+    /// Drop the packet being reassembled.
+    #[default]
+    DropDatagram,
+}
+
 /// Holds different fragments of one packet, used for assembling fragmented
 /// packets.
 ///
@@ -132,14 +150,24 @@ impl<K> PacketAssembler<K> {
         Ok(())
     }
 
-    /// Add a fragment into the packet that is being reassembled.
+    /// Add a fragment into the packet that is being reassembled, handling data
+    /// overlapping an earlier fragment according to `policy`.
     ///
     /// # Errors
     ///
     /// - Returns [`Error::PacketAssemblerBufferTooSmall`] when trying to add
     ///   data into the buffer at a non-existing
     /// place.
-    pub(crate) fn add(&mut self, data: &[u8], offset: usize) -> Result<(), AssemblerError> {
+    /// - Returns [`AssemblerError`] if the fragment overlaps received data,
+    ///   other than by duplicating it, and `policy` is
+    ///   [`FragmentOverlapPolicy::DropDatagram`]; the packet is dropped, and the
+    ///   assembler can be reused.
+    pub(crate) fn add(
+        &mut self,
+        data: &[u8],
+        offset: usize,
+        policy: FragmentOverlapPolicy,
+    ) -> Result<(), AssemblerError> {
         #[cfg(not(feature = "alloc"))]
         if self.buffer.len() < offset + data.len() {
             return Err(AssemblerError);
@@ -151,7 +179,42 @@ impl<K> PacketAssembler<K> {
         }
 
         let len = data.len();
-        self.buffer[offset..][..len].copy_from_slice(data);
+        let end = offset + len;
+        let overlaps = self
+            .assembler
+            .iter_data(0)
+            .any(|(start, stop)| start < end && offset < stop);
+        // Fragments may be duplicated by the network, which is harmless.
+        let duplicate = self
+            .assembler
+            .iter_data(0)
+            .any(|(start, stop)| start <= offset && end <= stop)
+            && self.buffer[offset..end] == *data;
+        match policy {
+            FragmentOverlapPolicy::DropDatagram if overlaps && !duplicate => {
+                net_debug!("frag assembler: overlapping fragment, dropping packet");
+                self.reset();
+                return Err(AssemblerError);
+            }
+            FragmentOverlapPolicy::FirstWins if overlaps => {
+                // Only copy the parts of the fragment falling into holes.
+                let mut from = offset;
+                for (start, stop) in self.assembler.iter_data(0) {
+                    if start >= end {
+                        break;
+                    }
+                    if start > from {
+                        self.buffer[from..start]
+                            .copy_from_slice(&data[from - offset..start - offset]);
+                    }
+                    from = from.max(stop);
+                }
+                if from < end {
+                    self.buffer[from..end].copy_from_slice(&data[from - offset..]);
+                }
+            }
+            _ => self.buffer[offset..][..len].copy_from_slice(data),
+        }
 
         net_debug!(
             "frag assembler: receiving {} octets at offset {}",
@@ -256,12 +319,62 @@ mod tests {
         p_assembler.set_total_size(5).unwrap();
 
         let data = b"Rust";
-        p_assembler.add(&data[..], 0);
-        p_assembler.add(&data[..], 1);
+        p_assembler.add(&data[..], 0, FragmentOverlapPolicy::LastWins);
+        p_assembler.add(&data[..], 1, FragmentOverlapPolicy::LastWins);
 
         assert_eq!(p_assembler.assemble(), Some(&b"RRust"[..]))
     }
 
+    #[test]
+    fn packet_assembler_overlap_first_wins() {
+        let mut p_assembler = PacketAssembler::<Key>::new();
+        let policy = FragmentOverlapPolicy::FirstWins;
+
+        p_assembler.set_total_size(12).unwrap();
+        p_assembler.add(b"AAAA", 0, policy).unwrap();
+        p_assembler.add(b"CCCC", 6, policy).unwrap();
+        // Overlaps both fragments, and fills the hole between them.
+        p_assembler.add(b"xxxxxxxx", 2, policy).unwrap();
+        p_assembler.add(b"xDD", 9, policy).unwrap();
+
+        assert_eq!(p_assembler.assemble(), Some(&b"AAAAxxCCCCDD"[..]))
+    }
+
+    #[test]
+    fn packet_assembler_overlap_last_wins() {
+        let mut p_assembler = PacketAssembler::<Key>::new();
+        let policy = FragmentOverlapPolicy::LastWins;
+
+        p_assembler.set_total_size(12).unwrap();
+        p_assembler.add(b"AAAA", 0, policy).unwrap();
+        p_assembler.add(b"CCCC", 6, policy).unwrap();
+        p_assembler.add(b"xxxxxxxx", 2, policy).unwrap();
+        p_assembler.add(b"DD", 10, policy).unwrap();
+
+        assert_eq!(p_assembler.assemble(), Some(&b"AAxxxxxxxxDD"[..]))
+    }
+
+    #[test]
+    fn packet_assembler_overlap_drop() {
+        let mut set = PacketAssemblerSet::new();
+        let key = Key { id: 1 };
+        let policy = FragmentOverlapPolicy::default();
+        assert_eq!(policy, FragmentOverlapPolicy::DropDatagram);
+
+        let assr = set.get(&key, Instant::ZERO).unwrap();
+        assr.set_total_size(8).unwrap();
+        assr.add(b"AAAA", 0, policy).unwrap();
+        // Adjacent and duplicate fragments do not overlap.
+        assr.add(b"BB", 4, policy).unwrap();
+        assr.add(b"AAAA", 0, policy).unwrap();
+        assert_eq!(assr.add(b"xx", 5, policy), Err(AssemblerError));
+
+        // The packet was dropped, so the rest of it never completes it.
+        let assr = set.get(&key, Instant::ZERO).unwrap();
+        assr.add(b"CC", 6, policy).unwrap();
+        assert_eq!(assr.assemble(), None);
+    }
+
     #[test]
     fn packet_assembler_assemble() {
         let mut p_assembler = PacketAssembler::<Key>::new();
@@ -270,10 +383,18 @@ mod tests {
 
         p_assembler.set_total_size(data.len()).unwrap();
 
-        p_assembler.add(b"Hello ", 0).unwrap();
+        p_assembler
+            .add(b"Hello ", 0, FragmentOverlapPolicy::DropDatagram)
+            .unwrap();
         assert_eq!(p_assembler.assemble(), None);
 
-        p_assembler.add(b"World!", b"Hello ".len()).unwrap();
+        p_assembler
+            .add(
+                b"World!",
+                b"Hello ".len(),
+                FragmentOverlapPolicy::DropDatagram,
+            )
+            .unwrap();
 
         assert_eq!(p_assembler.assemble(), Some(&b"Hello World!"[..]));
     }
@@ -286,10 +407,18 @@ mod tests {
 
         p_assembler.set_total_size(data.len()).unwrap();
 
-        p_assembler.add(b"World!", b"Hello ".len()).unwrap();
+        p_assembler
+            .add(
+                b"World!",
+                b"Hello ".len(),
+                FragmentOverlapPolicy::DropDatagram,
+            )
+            .unwrap();
         assert_eq!(p_assembler.assemble(), None);
 
-        p_assembler.add(b"Hello ", 0).unwrap();
+        p_assembler
+            .add(b"Hello ", 0, FragmentOverlapPolicy::DropDatagram)
+            .unwrap();
 
         assert_eq!(p_assembler.assemble(), Some(&b"Hello World!"[..]));
     }
@@ -341,10 +470,12 @@ mod tests {
         let key = Key { id: 2 };
         let assr = set.get(&key, Instant::ZERO).unwrap();
         assr.set_total_size(2).unwrap();
-        assr.add(&[0x00], 0).unwrap();
+        assr.add(&[0x00], 0, FragmentOverlapPolicy::DropDatagram)
+            .unwrap();
         assert_eq!(assr.assemble(), None);
         let assr = set.get(&key, Instant::ZERO).unwrap();
-        assr.add(&[0x01], 1).unwrap();
+        assr.add(&[0x01], 1, FragmentOverlapPolicy::DropDatagram)
+            .unwrap();
         assert_eq!(assr.assemble(), Some(&[0x00, 0x01][..]));
     }
 }
@@ -62,9 +62,10 @@ impl Interface {
                 } else if let Some(pkt) = self.inner.igmp_report_packet(IgmpVersion::Version2, addr)
                 {
                     // Send initial membership report
-                    let tx_token = device
-                        .transmit(timestamp)
-                        .ok_or(MulticastError::Exhausted)?;
+                    let tx_token = device.transmit(timestamp).ok_or_else(|| {
+                        self.inner.tx_exhausted();
+                        MulticastError::Exhausted
+                    })?;
 
                     // NOTE(unwrap): packet destination is multicast, which is always routable and
                     // doesn't require neighbor discovery.
@@ -106,9 +107,10 @@ impl Interface {
                     Ok(false)
                 } else if let Some(pkt) = self.inner.igmp_leave_packet(addr) {
                     // Send group leave packet
-                    let tx_token = device
-                        .transmit(timestamp)
-                        .ok_or(MulticastError::Exhausted)?;
+                    let tx_token = device.transmit(timestamp).ok_or_else(|| {
+                        self.inner.tx_exhausted();
+                        MulticastError::Exhausted
+                    })?;
 
                     // NOTE(unwrap): packet destination is multicast, which is always routable and
                     // doesn't require neighbor discovery.
@@ -154,6 +156,7 @@ impl Interface {
                             .dispatch_ip(tx_token, PacketMeta::default(), pkt, &mut self.fragmenter)
                             .unwrap();
                     } else {
+                        self.inner.tx_exhausted();
                         return false;
                     }
                 }
@@ -190,6 +193,7 @@ impl Interface {
                                     )
                                     .unwrap();
                             } else {
+                                self.inner.tx_exhausted();
                                 return false;
                             }
                         }
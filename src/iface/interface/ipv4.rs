@@ -27,6 +27,28 @@ impl InterfaceInner {
             net_debug!("non-unicast or unspecified source address");
             return None;
         }
+        if self.is_own_source(&ipv4_repr.into()) {
+            net_debug!(
+                "dropping packet from our own address {}",
+                ipv4_repr.src_addr
+            );
+            return None;
+        }
+
+        // Hand packets for other hosts to the router, fragments and all.
+        #[cfg(feature = "alloc")]
+        if self.should_forward_ipv4(&ipv4_repr) {
+            self.forwarding
+                .receive(&ipv4_packet.as_ref()[..ipv4_packet.total_len() as usize]);
+            return None;
+        }
+        if self.fails_urpf(ipv4_repr.src_addr) {
+            net_debug!(
+                "dropping packet failing the reverse-path check from {}",
+                ipv4_repr.src_addr
+            );
+            return None;
+        }
 
         #[cfg(feature = "proto-ipv4-fragmentation")]
         // 如果启用了 IPv4 分片功能，检查是否需要重组分片数据包
@@ -50,7 +72,11 @@ impl InterfaceInner {
                     ));
                 }
 
-                if let Err(e) = f.add(ipv4_packet.payload(), ipv4_packet.frag_offset() as usize) {
+                if let Err(e) = f.add(
+                    ipv4_packet.payload(),
+                    ipv4_packet.frag_offset() as usize,
+                    frag.overlap_policy,
+                ) {
                     net_debug!("fragmentation error: {:?}", e);
                     return None;
                 }
@@ -81,6 +107,7 @@ impl InterfaceInner {
         {
             if ipv4_repr.next_header == IpProtocol::Udp
                 && matches!(self.caps.medium, Medium::Ethernet)
+                && self.dhcpv4_permitted()
             {
                 let udp_packet = check!(UdpPacket::new_checked(ip_payload));
                 if let Some(dhcp_socket) = sockets
@@ -129,6 +156,18 @@ impl InterfaceInner {
             }
         }
 
+        if !self.accept_source_route {
+            let option_offset = ipv4_packet
+                .option_offset(Ipv4OptionType::LooseSourceRoute)
+                .or_else(|| ipv4_packet.option_offset(Ipv4OptionType::StrictSourceRoute));
+            if let Some(offset) = option_offset {
+                net_debug!("dropping source-routed packet");
+                let icmp_reply_repr =
+                    Icmpv4Repr::parameter_problem(offset as u8, ipv4_packet.clone().into_inner());
+                return self.icmpv4_reply(ipv4_repr, icmp_reply_repr);
+            }
+        }
+
         match ipv4_repr.next_header {
             IpProtocol::Icmp => self.process_icmpv4(sockets, ip_repr, ip_payload),
 
@@ -176,6 +215,27 @@ impl InterfaceInner {
         }
     }
 
+    /// Checks whether ARP packets for the given address are meant for us,
+    /// according to the configured [`ArpAnnounceScope`].
+    #[cfg(feature = "medium-ethernet")]
+    fn arp_target_in_scope(&self, addr: Ipv4Address) -> bool {
+        if self.has_ip_addr(addr) {
+            return true;
+        }
+
+        match self.arp_announce_scope {
+            ArpAnnounceScope::Assigned => false,
+            ArpAnnounceScope::AnyIp => {
+                self.any_ip
+                    && addr.is_unicast()
+                    && self
+                        .routes
+                        .lookup(&IpAddress::Ipv4(addr), self.now)
+                        .map_or(false, |router_addr| self.has_ip_addr(router_addr))
+            }
+        }
+    }
+
     #[cfg(feature = "medium-ethernet")]
     /// 检查和处理接收到的ARP数据包，并根据需要生成响应
     pub(super) fn process_arp<'frame>(
@@ -196,7 +256,7 @@ impl InterfaceInner {
             } => {
                 // Only process ARP packets for us.
                 // 检查目标协议地址是否属于当前设备。如果不是，则忽略该ARP数据包
-                if !self.has_ip_addr(target_protocol_addr) {
+                if !self.arp_target_in_scope(target_protocol_addr) {
                     return None;
                 }
 
@@ -206,6 +266,24 @@ impl InterfaceInner {
                     return None;
                 }
 
+                // An ARP probe (RFC 5227) is sent from the unspecified address by a
+                // host checking whether an address, such as a link-local one, is
+                // in use. It is answered so that the sender detects the conflict,
+                // but tells nothing about the sender's own mapping.
+                if operation == ArpOperation::Request
+                    && source_protocol_addr.is_unspecified()
+                    && source_hardware_addr.is_unicast()
+                {
+                    net_debug!("arp: probe for {}", target_protocol_addr);
+                    return Some(EthernetPacket::Arp(ArpRepr::EthernetIpv4 {
+                        operation: ArpOperation::Reply,
+                        source_hardware_addr: self.hardware_addr.ethernet_or_panic(),
+                        source_protocol_addr: target_protocol_addr,
+                        target_hardware_addr: source_hardware_addr,
+                        target_protocol_addr: source_protocol_addr,
+                    }));
+                }
+
                 // Discard packets with non-unicast source addresses.
                 // 检查源协议地址和源硬件地址是否为单播地址。如果不是，则忽略该ARP数据包
                 if !source_protocol_addr.is_unicast() || !source_hardware_addr.is_unicast() {
@@ -402,6 +480,7 @@ impl InterfaceInner {
             packet.set_more_frags(more_frags);
             packet.set_dont_frag(false);
             packet.set_frag_offset(frag.ipv4.frag_offset);
+            packet.set_dscp(frag.ipv4.dscp);
 
             if caps.checksum.ipv4.tx() {
                 packet.fill_checksum();
@@ -428,7 +507,7 @@ impl InterfaceInner {
             group_addr,
             version,
         };
-        let pkt = IpPacket::new_ipv4(
+        let pkt = IpPacket::new_ipv4_with_router_alert(
             Ipv4Repr {
                 src_addr: iface_addr,
                 // Send to the group being reported
@@ -450,7 +529,7 @@ impl InterfaceInner {
     ) -> Option<IpPacket<'any>> {
         self.ipv4_addr().map(|iface_addr| {
             let igmp_repr = IgmpRepr::LeaveGroup { group_addr };
-            IpPacket::new_ipv4(
+            IpPacket::new_ipv4_with_router_alert(
                 Ipv4Repr {
                     src_addr: iface_addr,
                     dst_addr: Ipv4Address::MULTICAST_ALL_ROUTERS,
@@ -1,8 +1,555 @@
-use super::{check, icmp_reply_payload_len, InterfaceInner, IpPacket, IpPayload, SocketSet};
+use super::{check, InterfaceInner, IpPacket, IpPayload, SocketSet};
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+use super::{
+    DadState, Interface, Ipv6IidMode, RaConfig, RouterAdvertState, TemporaryAddress,
+    TentativeAddress,
+};
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+use crate::iface::Route;
 #[cfg(feature = "socket-icmp")]
 use crate::socket::icmp;
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+use crate::{
+    phy::Device,
+    time::{Duration, Instant},
+};
 use crate::{phy::PacketMeta, socket::AnySocket, wire::*};
 
+/// The number of Neighbor Solicitations sent while performing Duplicate
+/// Address Detection, `DupAddrDetectTransmits` in RFC 4862 § 5.1.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+const DUP_ADDR_DETECT_TRANSMITS: u8 = 1;
+
+/// The time to wait for a Neighbor Advertisement after a Neighbor
+/// Solicitation, `RetransTimer` in RFC 4861 § 10.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+const RETRANS_TIMER: Duration = Duration::from_secs(1);
+
+/// The lifetime of a route installed by an ICMPv6 Redirect.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+const REDIRECT_ROUTE_LIFETIME: Duration = Duration::from_secs(600);
+
+/// The shortest time between two Router Advertisements sent to all nodes,
+/// `MIN_DELAY_BETWEEN_RAS` in RFC 4861 § 10.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+const MIN_DELAY_BETWEEN_RAS: Duration = Duration::from_secs(3);
+
+/// SipHash-2-4, the keyed pseudorandom function used to derive stable
+/// interface identifiers.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+fn siphash24(key: &[u8; 16], data: &[u8]) -> u64 {
+    fn sip_round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        sip_round(v);
+        sip_round(v);
+        v[0] ^= m;
+    }
+
+    let k0 = u64::from_le_bytes(key[..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(key[8..].try_into().unwrap());
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        compress(&mut v, u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let mut last = [0; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    last[7] = data.len() as u8;
+    compress(&mut v, u64::from_le_bytes(last));
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// Return whether an interface identifier is reserved, see RFC 5453.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+fn is_reserved_iid(iid: [u8; 8]) -> bool {
+    // The Subnet-Router anycast address, and the subnet anycast addresses.
+    iid == [0; 8] || (iid[..7] == [0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff] && iid[7] >= 0x80)
+}
+
+/// Replace the interface identifier of an address.
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+fn with_iid(prefix: Ipv6Address, iid: [u8; 8]) -> Ipv6Address {
+    let mut addr = prefix;
+    addr.0[8..].copy_from_slice(&iid);
+    addr
+}
+
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+impl Interface {
+    /// Derive a link-local IPv6 address from the hardware address, and start
+    /// assigning it to the interface.
+    ///
+    /// The address is made of the `fe80::/64` prefix and the modified EUI-64
+    /// identifier of the hardware address, or a stable private identifier if
+    /// the [interface identifier mode](super::Config::ipv6_iid_mode) is
+    /// [`Ipv6IidMode::StablePrivacy`]. It is only added to the addresses
+    /// of the interface once Duplicate Address Detection has completed
+    /// without finding another node using it, which takes about a second of
+    /// [polling](Self::poll). Use [`dad_state`](Self::dad_state) to find out
+    /// whether it succeeded.
+    ///
+    /// Returns the derived address, or `None` if the hardware address has no
+    /// EUI-64 form or too many addresses are already being assigned.
+    pub fn add_link_local_ipv6(&mut self) -> Option<Ipv6Address> {
+        let prefix = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 0);
+        let iid = match self.inner.ipv6_iid_mode {
+            Ipv6IidMode::StablePrivacy { secret } => self.inner.stable_iid(&secret, prefix),
+            _ => self.inner.eui_64_iid()?,
+        };
+        let addr = with_iid(prefix, iid);
+        self.inner
+            .start_dad(Ipv6Cidr::new(addr, 64))
+            .then_some(addr)
+    }
+
+    /// Form an IPv6 address in a /64 prefix, such as one advertised by a
+    /// router, and start assigning it to the interface.
+    ///
+    /// The interface identifier of the address is chosen according to the
+    /// [interface identifier mode](super::Config::ipv6_iid_mode). In
+    /// [`Ipv6IidMode::Temporary`] mode, the address is removed once its
+    /// lifetime is over, and replaced by a new one with another random
+    /// identifier. Like for [`add_link_local_ipv6`](Self::add_link_local_ipv6),
+    /// the address is only added once Duplicate Address Detection has
+    /// completed.
+    ///
+    /// Returns the formed address, or `None` if the prefix is not 64 bits
+    /// long, if the identifier is the EUI-64 one and the hardware address
+    /// has no EUI-64 form, or if too many addresses are already being
+    /// assigned.
+    pub fn add_slaac_ipv6(&mut self, prefix: Ipv6Cidr) -> Option<Ipv6Address> {
+        if prefix.prefix_len() != 64 {
+            return None;
+        }
+        let prefix = prefix.address();
+
+        match self.inner.ipv6_iid_mode {
+            Ipv6IidMode::Eui64 => {
+                let addr = with_iid(prefix, self.inner.eui_64_iid()?);
+                self.inner
+                    .start_dad(Ipv6Cidr::new(addr, 64))
+                    .then_some(addr)
+            }
+            Ipv6IidMode::StablePrivacy { secret } => {
+                let addr = with_iid(prefix, self.inner.stable_iid(&secret, prefix));
+                self.inner
+                    .start_dad(Ipv6Cidr::new(addr, 64))
+                    .then_some(addr)
+            }
+            Ipv6IidMode::Temporary { lifetime } => {
+                if self.inner.ipv6_temporary_addrs.is_full() {
+                    return None;
+                }
+                let cidr = Ipv6Cidr::new(with_iid(prefix, self.inner.temporary_iid()), 64);
+                if !self.inner.start_dad(cidr) {
+                    return None;
+                }
+                // NOTE(unwrap): checked above.
+                self.inner
+                    .ipv6_temporary_addrs
+                    .push(TemporaryAddress {
+                        cidr,
+                        expires_at: self.inner.now + lifetime,
+                    })
+                    .unwrap();
+                Some(cidr.address())
+            }
+        }
+    }
+
+    /// Return the Duplicate Address Detection state of an IPv6 address.
+    ///
+    /// Addresses assigned with [`update_ip_addrs`](Self::update_ip_addrs)
+    /// are reported as [`DadState::Assigned`]. Returns `None` if the interface
+    /// has never tried to assign the address.
+    pub fn dad_state(&self, addr: Ipv6Address) -> Option<DadState> {
+        self.inner.dad_state(addr)
+    }
+
+    /// Act as an IPv6 router advertising itself, or stop doing so.
+    ///
+    /// With a configuration, Router Advertisements are sent to all nodes
+    /// from the first link-local address of the interface, right away and
+    /// then at random intervals within those configured. A Router
+    /// Solicitation is answered with an advertisement at the next
+    /// [poll](Self::poll), unless one was sent less than 3 seconds before, in
+    /// which case it is sent once that delay is over. Without one, the
+    /// default, the interface behaves as a host.
+    pub fn set_router_advertisement(&mut self, config: Option<RaConfig>) {
+        self.inner.router_advert = config.map(|config| RouterAdvertState {
+            config,
+            next_at: self.inner.now,
+            last_at: None,
+        });
+    }
+
+    /// Send a Router Advertisement, if one is due.
+    pub(crate) fn router_advert_egress<D>(&mut self, device: &mut D) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        let now = self.inner.now;
+        let config = match &self.inner.router_advert {
+            Some(state) if now >= state.next_at => &state.config,
+            _ => return false,
+        };
+        let advert = Icmpv6Repr::Ndisc(NdiscRepr::router_advertisement(
+            config.hop_limit,
+            config.flags,
+            config.router_lifetime,
+            &config.prefixes,
+            config.mtu,
+            Some(self.inner.hardware_addr.into()),
+        ));
+
+        let src_addr = match self.inner.link_local_ipv6_addr() {
+            Some(src_addr) => src_addr,
+            None => {
+                net_debug!("no link-local address to send a router advertisement from");
+                self.inner.schedule_router_advert();
+                return false;
+            }
+        };
+
+        let tx_token = match device.transmit(now) {
+            Some(tx_token) => tx_token,
+            None => {
+                self.inner.tx_exhausted();
+                return false;
+            }
+        };
+        self.inner.schedule_router_advert();
+
+        let ip_repr = Ipv6Repr {
+            src_addr,
+            dst_addr: Ipv6Address::LINK_LOCAL_ALL_NODES,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: advert.buffer_len(),
+            hop_limit: 0xff,
+        };
+        // NOTE(unwrap): packet destination is multicast, which is always routable and
+        // doesn't require neighbor discovery.
+        self.inner
+            .dispatch_ip(
+                tx_token,
+                PacketMeta::default(),
+                IpPacket::new_ipv6(ip_repr, IpPayload::Icmpv6(advert)),
+                &mut self.fragmenter,
+            )
+            .unwrap();
+        true
+    }
+
+    /// Send the next Duplicate Address Detection probe, or assign an address
+    /// for which no answer was received.
+    pub(crate) fn dad_egress<D>(&mut self, device: &mut D) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        let now = self.inner.now;
+        let index = match self
+            .inner
+            .ipv6_tentative_addrs
+            .iter()
+            .position(|tentative| !tentative.duplicate && now >= tentative.timeout)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let tentative = self.inner.ipv6_tentative_addrs[index];
+        if tentative.probes_left == 0 {
+            self.inner.ipv6_tentative_addrs.swap_remove(index);
+            if self
+                .inner
+                .ip_addrs
+                .push(IpCidr::Ipv6(tentative.cidr))
+                .is_err()
+            {
+                net_debug!("cannot assign {}: address table full", tentative.cidr);
+            } else {
+                net_debug!("assigned {} after DAD", tentative.cidr);
+            }
+            return true;
+        }
+
+        let tx_token = match device.transmit(now) {
+            Some(tx_token) => tx_token,
+            None => {
+                self.inner.tx_exhausted();
+                return false;
+            }
+        };
+        self.inner.ipv6_tentative_addrs[index] = TentativeAddress {
+            probes_left: tentative.probes_left - 1,
+            timeout: now + RETRANS_TIMER,
+            ..tentative
+        };
+
+        let target_addr = tentative.cidr.address();
+        let solicit = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
+            target_addr,
+            lladdr: None,
+        });
+        let ip_repr = Ipv6Repr {
+            src_addr: Ipv6Address::UNSPECIFIED,
+            dst_addr: target_addr.solicited_node(),
+            next_header: IpProtocol::Icmpv6,
+            payload_len: solicit.buffer_len(),
+            hop_limit: 0xff,
+        };
+        // NOTE(unwrap): packet destination is multicast, which is always routable and
+        // doesn't require neighbor discovery.
+        self.inner
+            .dispatch_ip(
+                tx_token,
+                PacketMeta::default(),
+                IpPacket::new_ipv6(ip_repr, IpPayload::Icmpv6(solicit)),
+                &mut self.fragmenter,
+            )
+            .unwrap();
+        true
+    }
+}
+
+#[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+impl InterfaceInner {
+    /// Return the modified EUI-64 interface identifier of the hardware
+    /// address, if it has one.
+    fn eui_64_iid(&self) -> Option<[u8; 8]> {
+        match self.hardware_addr {
+            #[cfg(feature = "medium-ethernet")]
+            HardwareAddress::Ethernet(addr) => Some(addr.as_eui_64()),
+            #[cfg(feature = "medium-ieee802154")]
+            HardwareAddress::Ieee802154(addr) => addr.as_eui_64(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Derive a stable private interface identifier for a prefix from a
+    /// secret, as described in RFC 7217 § 5.
+    fn stable_iid(&self, secret: &[u8; 16], prefix: Ipv6Address) -> [u8; 8] {
+        // The prefix, the hardware address as the interface index, and the
+        // DAD counter, which is only increased to skip reserved identifiers.
+        let hardware_addr = self.hardware_addr.as_bytes();
+        let mut data = [0; 17];
+        data[..8].copy_from_slice(&prefix.as_bytes()[..8]);
+        data[8..8 + hardware_addr.len()].copy_from_slice(hardware_addr);
+        let data = &mut data[..8 + hardware_addr.len() + 1];
+
+        let mut dad_counter = 0u8;
+        loop {
+            *data.last_mut().unwrap() = dad_counter;
+            let iid = siphash24(secret, data).to_be_bytes();
+            if !is_reserved_iid(iid) {
+                return iid;
+            }
+            dad_counter = dad_counter.wrapping_add(1);
+        }
+    }
+
+    /// Generate a random interface identifier.
+    fn temporary_iid(&mut self) -> [u8; 8] {
+        loop {
+            let iid =
+                ((self.rand.rand_u32() as u64) << 32 | self.rand.rand_u32() as u64).to_be_bytes();
+            if !is_reserved_iid(iid) {
+                return iid;
+            }
+        }
+    }
+
+    /// Replace the temporary addresses whose lifetime is over by new ones.
+    pub(super) fn rotate_temporary_addrs(&mut self) -> bool {
+        let lifetime = match self.ipv6_iid_mode {
+            Ipv6IidMode::Temporary { lifetime } => lifetime,
+            _ => return false,
+        };
+
+        let mut rotated = false;
+        for index in 0..self.ipv6_temporary_addrs.len() {
+            let old_cidr = self.ipv6_temporary_addrs[index].cidr;
+            if self.now < self.ipv6_temporary_addrs[index].expires_at {
+                continue;
+            }
+
+            self.ip_addrs.retain(|cidr| *cidr != IpCidr::Ipv6(old_cidr));
+            self.ipv6_tentative_addrs
+                .retain(|tentative| tentative.cidr != old_cidr);
+            let cidr = Ipv6Cidr::new(with_iid(old_cidr.address(), self.temporary_iid()), 64);
+            net_debug!(
+                "temporary address {} expired, replacing it with {}",
+                old_cidr,
+                cidr
+            );
+            // The old address made room for the new one.
+            self.start_dad(cidr);
+            self.ipv6_temporary_addrs[index] = TemporaryAddress {
+                cidr,
+                expires_at: self.now + lifetime,
+            };
+            rotated = true;
+        }
+        rotated
+    }
+
+    /// Start Duplicate Address Detection for an address, unless it is
+    /// already assigned or tentative.
+    fn start_dad(&mut self, cidr: Ipv6Cidr) -> bool {
+        if self.has_ip_addr(cidr.address()) {
+            return true;
+        }
+        // Forget any earlier failed attempt, and try again.
+        self.ipv6_tentative_addrs
+            .retain(|tentative| !(tentative.duplicate && tentative.cidr == cidr));
+        if self
+            .ipv6_tentative_addrs
+            .iter()
+            .any(|tentative| tentative.cidr.address() == cidr.address())
+        {
+            return true;
+        }
+
+        self.ipv6_tentative_addrs
+            .push(TentativeAddress {
+                cidr,
+                duplicate: false,
+                probes_left: DUP_ADDR_DETECT_TRANSMITS,
+                timeout: self.now,
+            })
+            .is_ok()
+    }
+
+    fn dad_state(&self, addr: Ipv6Address) -> Option<DadState> {
+        if self.has_ip_addr(addr) {
+            return Some(DadState::Assigned);
+        }
+        self.ipv6_tentative_addrs
+            .iter()
+            .find(|tentative| tentative.cidr.address() == addr)
+            .map(|tentative| {
+                if tentative.duplicate {
+                    DadState::Duplicate
+                } else {
+                    DadState::Tentative
+                }
+            })
+    }
+
+    /// Return when the next Duplicate Address Detection step, or the
+    /// replacement of a temporary address, is due.
+    pub(super) fn dad_poll_at(&self) -> Option<Instant> {
+        let rotate_at = match self.ipv6_iid_mode {
+            Ipv6IidMode::Temporary { .. } => self
+                .ipv6_temporary_addrs
+                .iter()
+                .map(|temporary| temporary.expires_at)
+                .min(),
+            _ => None,
+        };
+        self.ipv6_tentative_addrs
+            .iter()
+            .filter(|tentative| !tentative.duplicate)
+            .map(|tentative| tentative.timeout)
+            .chain(rotate_at)
+            .min()
+    }
+
+    /// Return the first link-local IPv6 address of the interface.
+    fn link_local_ipv6_addr(&self) -> Option<Ipv6Address> {
+        self.ip_addrs.iter().find_map(|cidr| match cidr.address() {
+            IpAddress::Ipv6(addr) if addr.is_link_local() => Some(addr),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
+    }
+
+    /// Pick the time of the next unsolicited Router Advertisement, after one
+    /// is sent.
+    fn schedule_router_advert(&mut self) {
+        let now = self.now;
+        let rand = self.rand.rand_u32() as u64;
+        if let Some(state) = &mut self.router_advert {
+            let min = state.config.min_interval.total_millis();
+            let max = state.config.max_interval.total_millis().max(min);
+            state.last_at = Some(now);
+            state.next_at = now + Duration::from_millis(min + rand % (max - min + 1));
+        }
+    }
+
+    /// Return when the next Router Advertisement is due.
+    pub(super) fn router_advert_poll_at(&self) -> Option<Instant> {
+        self.router_advert.as_ref().map(|state| state.next_at)
+    }
+
+    /// Abandon a tentative address because another node on the link is using
+    /// or assigning it. Returns whether the address was tentative.
+    fn dad_conflict(&mut self, addr: Ipv6Address) -> bool {
+        match self
+            .ipv6_tentative_addrs
+            .iter_mut()
+            .find(|tentative| !tentative.duplicate && tentative.cidr.address() == addr)
+        {
+            Some(tentative) => {
+                net_debug!("DAD failed for {}: address in use", addr);
+                tentative.duplicate = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Process an ICMPv6 packet sent from the unspecified address, which is
+    /// only valid for Neighbor Solicitations of Duplicate Address Detection.
+    fn process_unspecified_icmpv6<'frame>(
+        &mut self,
+        ipv6_repr: Ipv6Repr,
+        ip_payload: &'frame [u8],
+    ) -> Option<IpPacket<'frame>> {
+        let icmp_packet = check!(Icmpv6Packet::new_checked(ip_payload));
+        let icmp_repr = check!(Icmpv6Repr::parse(
+            &ipv6_repr.src_addr.into(),
+            &ipv6_repr.dst_addr.into(),
+            &icmp_packet,
+            &self.caps.checksum,
+        ));
+
+        match icmp_repr {
+            Icmpv6Repr::Ndisc(repr @ NdiscRepr::NeighborSolicit { .. })
+                if ipv6_repr.hop_limit == 0xff =>
+            {
+                self.process_ndisc(ipv6_repr, repr)
+            }
+            _ => None,
+        }
+    }
+}
+
 impl InterfaceInner {
     #[cfg(feature = "proto-ipv6")]
     pub(super) fn process_ipv6<'frame>(
@@ -14,10 +561,31 @@ impl InterfaceInner {
         let ipv6_repr = check!(Ipv6Repr::parse(ipv6_packet));
 
         if !ipv6_repr.src_addr.is_unicast() {
+            // Nodes performing Duplicate Address Detection solicit from the
+            // unspecified address.
+            #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+            if ipv6_repr.src_addr.is_unspecified() && ipv6_repr.next_header == IpProtocol::Icmpv6 {
+                return self.process_unspecified_icmpv6(ipv6_repr, ipv6_packet.payload());
+            }
+
             // Discard packets with non-unicast source addresses.
             net_debug!("non-unicast source address");
             return None;
         }
+        if self.is_own_source(&ipv6_repr.into()) {
+            net_debug!(
+                "dropping packet from our own address {}",
+                ipv6_repr.src_addr
+            );
+            return None;
+        }
+        if self.fails_urpf(ipv6_repr.src_addr) {
+            net_debug!(
+                "dropping packet failing the reverse-path check from {}",
+                ipv6_repr.src_addr
+            );
+            return None;
+        }
 
         let ip_payload = ipv6_packet.payload();
 
@@ -84,15 +652,13 @@ impl InterfaceInner {
 
             _ => {
                 // Send back as much of the original payload as we can.
-                let payload_len =
-                    icmp_reply_payload_len(ip_payload.len(), IPV6_MIN_MTU, ipv6_repr.buffer_len());
-                let icmp_reply_repr = Icmpv6Repr::ParamProblem {
-                    reason: Icmpv6ParamProblem::UnrecognizedNxtHdr,
+                let icmp_reply_repr = Icmpv6Repr::parameter_problem(
+                    Icmpv6ParamProblem::UnrecognizedNxtHdr,
                     // The offending packet is after the IPv6 header.
-                    pointer: ipv6_repr.buffer_len() as u32,
-                    header: ipv6_repr,
-                    data: &ip_payload[0..payload_len],
-                };
+                    ipv6_repr.buffer_len() as u32,
+                    ipv6_repr,
+                    ip_payload,
+                );
                 self.icmpv6_reply(ipv6_repr, icmp_reply_repr)
             }
         }
@@ -177,11 +743,37 @@ impl InterfaceInner {
         repr: NdiscRepr<'frame>,
     ) -> Option<IpPacket<'frame>> {
         match repr {
+            NdiscRepr::RouterSolicit { lladdr } if self.router_advert.is_some() => {
+                if let Some(lladdr) = lladdr {
+                    let lladdr = check!(lladdr.parse(self.caps.medium));
+                    if !lladdr.is_unicast() || ip_repr.src_addr.is_unspecified() {
+                        return None;
+                    }
+                    self.neighbor_cache
+                        .fill(ip_repr.src_addr.into(), lladdr, self.now);
+                }
+
+                // Answer with the next advertisement to all nodes, as soon as
+                // allowed, see RFC 4861 § 6.2.6.
+                let now = self.now;
+                if let Some(state) = &mut self.router_advert {
+                    let answer_at = match state.last_at {
+                        Some(last_at) => now.max(last_at + MIN_DELAY_BETWEEN_RAS),
+                        None => now,
+                    };
+                    state.next_at = state.next_at.min(answer_at);
+                }
+                None
+            }
             NdiscRepr::NeighborAdvert {
                 lladdr,
                 target_addr,
                 flags,
             } => {
+                if self.dad_conflict(target_addr) {
+                    return None;
+                }
+
                 let ip_addr = ip_repr.src_addr.into();
                 if let Some(lladdr) = lladdr {
                     let lladdr = check!(lladdr.parse(self.caps.medium));
@@ -201,7 +793,13 @@ impl InterfaceInner {
                 lladdr,
                 ..
             } => {
-                if let Some(lladdr) = lladdr {
+                let from_dad = ip_repr.src_addr.is_unspecified();
+                if from_dad {
+                    // Another node is about to assign the target address.
+                    if lladdr.is_some() || self.dad_conflict(target_addr) {
+                        return None;
+                    }
+                } else if let Some(lladdr) = lladdr {
                     let lladdr = check!(lladdr.parse(self.caps.medium));
                     if !lladdr.is_unicast() || !target_addr.is_unicast() {
                         return None;
@@ -210,15 +808,44 @@ impl InterfaceInner {
                         .fill(ip_repr.src_addr.into(), lladdr, self.now);
                 }
 
-                if self.has_solicited_node(ip_repr.dst_addr) && self.has_ip_addr(target_addr) {
+                if !self.has_solicited_node(ip_repr.dst_addr) {
+                    None
+                } else if self.has_ip_addr(target_addr) {
+                    // Defend the address by advertising it to all nodes, see
+                    // RFC 4861 § 7.2.4.
+                    let (flags, dst_addr) = if from_dad {
+                        (
+                            NdiscNeighborFlags::empty(),
+                            Ipv6Address::LINK_LOCAL_ALL_NODES,
+                        )
+                    } else {
+                        (NdiscNeighborFlags::SOLICITED, ip_repr.src_addr)
+                    };
                     let advert = Icmpv6Repr::Ndisc(NdiscRepr::NeighborAdvert {
-                        flags: NdiscNeighborFlags::SOLICITED,
+                        flags,
                         target_addr,
                         #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
                         lladdr: Some(self.hardware_addr.into()),
                     });
                     let ip_repr = Ipv6Repr {
                         src_addr: target_addr,
+                        dst_addr,
+                        next_header: IpProtocol::Icmpv6,
+                        hop_limit: 0xff,
+                        payload_len: advert.buffer_len(),
+                    };
+                    Some(IpPacket::new_ipv6(ip_repr, IpPayload::Icmpv6(advert)))
+                } else if self.has_anycast_ipv6(target_addr) && !from_dad {
+                    // Anycast addresses are shared with other nodes, so they
+                    // are never defended, and advertisements for them are
+                    // sent from a unicast address, see RFC 4861 § 7.2.4.
+                    let advert = Icmpv6Repr::Ndisc(NdiscRepr::NeighborAdvert {
+                        flags: NdiscNeighborFlags::SOLICITED,
+                        target_addr,
+                        lladdr: Some(self.hardware_addr.into()),
+                    });
+                    let ip_repr = Ipv6Repr {
+                        src_addr: self.anycast_reply_source(ip_repr.src_addr)?,
                         dst_addr: ip_repr.src_addr,
                         next_header: IpProtocol::Icmpv6,
                         hop_limit: 0xff,
@@ -229,6 +856,50 @@ impl InterfaceInner {
                     None
                 }
             }
+            NdiscRepr::Redirect {
+                target_addr,
+                dest_addr,
+                lladdr,
+                ..
+            } if self.accept_redirects => {
+                // Only the router we currently send to the destination through
+                // may redirect us, see RFC 4861 § 8.1.
+                if !ip_repr.src_addr.is_link_local()
+                    || !dest_addr.is_unicast()
+                    || self.route(&dest_addr.into(), self.now) != Some(ip_repr.src_addr.into())
+                {
+                    net_debug!("ignoring redirect from {}", ip_repr.src_addr);
+                    return None;
+                }
+
+                if let Some(lladdr) = lladdr {
+                    let lladdr = check!(lladdr.parse(self.caps.medium));
+                    if !lladdr.is_unicast() {
+                        return None;
+                    }
+                    self.neighbor_cache
+                        .fill(target_addr.into(), lladdr, self.now);
+                }
+
+                let now = self.now;
+                let route = Route {
+                    cidr: IpCidr::new(dest_addr.into(), 128),
+                    via_router: target_addr.into(),
+                    preferred_until: None,
+                    expires_at: Some(now + REDIRECT_ROUTE_LIFETIME),
+                };
+                self.routes.update(|routes| {
+                    // Replace any earlier redirect, and make room by dropping
+                    // the routes that have expired.
+                    routes.retain(|r| {
+                        r.cidr != route.cidr && r.expires_at.map_or(true, |t| t >= now)
+                    });
+                    if routes.push(route).is_err() {
+                        net_debug!("route table full, ignoring redirect to {}", dest_addr);
+                    }
+                });
+                None
+            }
             _ => None,
         }
     }
@@ -250,6 +921,12 @@ impl InterfaceInner {
             let opt_repr = check!(opt_repr);
             match opt_repr {
                 Ipv6OptionRepr::Pad1 | Ipv6OptionRepr::PadN(_) => (),
+                // Hosts have nothing to do with a router alert.
+                Ipv6OptionRepr::RouterAlert(_) => (),
+                Ipv6OptionRepr::JumboPayload(_) => {
+                    net_debug!("jumbograms are not supported, dropping");
+                    return None;
+                }
                 #[cfg(feature = "proto-rpl")]
                 Ipv6OptionRepr::Rpl(_) => {}
 
@@ -274,7 +951,7 @@ impl InterfaceInner {
             ipv6_repr,
             hbh_repr.next_header,
             handled_by_raw_socket,
-            &ip_payload[hbh_repr.header_len() + hbh_repr.data.len()..],
+            &ip_payload[hbh_repr.buffer_len()..],
         )
     }
 
@@ -285,8 +962,14 @@ impl InterfaceInner {
         icmp_repr: Icmpv6Repr<'icmp>,
     ) -> Option<IpPacket<'frame>> {
         if ipv6_repr.dst_addr.is_unicast() {
+            // Replies to an anycast address are sent from a unicast one.
+            let src_addr = if self.has_anycast_ipv6(ipv6_repr.dst_addr) {
+                self.anycast_reply_source(ipv6_repr.src_addr)?
+            } else {
+                ipv6_repr.dst_addr
+            };
             let ipv6_reply_repr = Ipv6Repr {
-                src_addr: ipv6_repr.dst_addr,
+                src_addr,
                 dst_addr: ipv6_repr.src_addr,
                 next_header: IpProtocol::Icmpv6,
                 payload_len: icmp_repr.buffer_len(),
@@ -20,14 +20,33 @@ mod sixlowpan;
 #[cfg(feature = "proto-igmp")]
 mod igmp;
 
+#[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+mod neighbor_queue;
+#[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+mod router;
+#[cfg(feature = "alloc")]
+mod transform;
+
 use core::result::Result;
 
 use heapless::{LinearMap, Vec};
 #[cfg(feature = "proto-igmp")]
 pub use igmp::MulticastError;
+use managed::ManagedSlice;
+#[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+pub use router::Router;
+
+/// A resolver of the hardware address of a neighbor, see
+/// [Interface::set_neighbor_resolver].
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+pub type NeighborResolver =
+    alloc::boxed::Box<dyn FnMut(IpAddress) -> Option<HardwareAddress> + Send>;
 
 #[cfg(any(feature = "proto-ipv4", feature = "proto-sixlowpan"))]
-use super::fragmentation::PacketAssemblerSet;
+use super::fragmentation::{FragmentOverlapPolicy, PacketAssemblerSet};
 #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
 use super::neighbor::{Answer as NeighborAnswer, Cache as NeighborCache};
 use super::{ip_packet::*, socket_set::SocketSet};
@@ -42,7 +61,7 @@ use crate::{
         FRAGMENTATION_BUFFER_SIZE, IFACE_MAX_ADDR_COUNT, IFACE_MAX_MULTICAST_GROUP_COUNT,
         IFACE_MAX_SIXLOWPAN_ADDRESS_CONTEXT_COUNT,
     },
-    iface::Routes,
+    iface::{RouteCacheEntry, Routes},
     phy::{ChecksumCapabilities, Device, DeviceCapabilities, Medium, PacketMeta, RxToken, TxToken},
     rand::Rand,
     socket::*,
@@ -73,6 +92,10 @@ pub(crate) struct FragmentsBuffer {
     #[cfg(feature = "_proto-fragmentation")]
     /// 重组分片的超时时间
     reassembly_timeout: Duration,
+
+    #[cfg(feature = "_proto-fragmentation")]
+    /// How to reassemble overlapping fragments.
+    overlap_policy: FragmentOverlapPolicy,
 }
 
 #[cfg(not(feature = "_proto-fragmentation"))]
@@ -116,6 +139,8 @@ pub(crate) struct Ipv4Fragmenter {
     frag_offset: u16,
     /// The identifier of the stream.
     ident: u16,
+    /// The Differentiated Services Code Point of the packet.
+    dscp: u8,
 }
 
 #[cfg(feature = "proto-sixlowpan-fragmentation")]
@@ -158,6 +183,7 @@ impl Fragmenter {
                 dst_hardware_addr: EthernetAddress::default(),
                 frag_offset: 0,
                 ident: 0,
+                dscp: 0,
             },
 
             #[cfg(feature = "proto-sixlowpan-fragmentation")]
@@ -198,6 +224,7 @@ impl Fragmenter {
                 payload_len: 0,
                 hop_limit: 0,
             };
+            self.ipv4.dscp = 0;
             #[cfg(feature = "medium-ethernet")]
             {
                 self.ipv4.dst_hardware_addr = EthernetAddress::default();
@@ -244,6 +271,8 @@ pub struct Interface {
     fragments: FragmentsBuffer,
     /// 分片器，用于数据包分片
     fragmenter: Fragmenter,
+    #[cfg(feature = "alloc")]
+    transforms: transform::Transforms,
 }
 
 /// The device independent part of an Ethernet network interface.
@@ -280,12 +309,246 @@ pub struct InterfaceInner {
     #[cfg(feature = "proto-ipv4")]
     /// 是否接收所有IP地址的数据包
     any_ip: bool,
+    #[cfg(feature = "socket-dhcpv4")]
+    unaddressed_dhcpv4: bool,
+    #[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+    arp_announce_scope: ArpAnnounceScope,
+    #[cfg(feature = "proto-ipv4")]
+    accept_source_route: bool,
+    #[cfg(feature = "proto-ipv6")]
+    accept_redirects: bool,
+    drop_own_source: bool,
+    #[cfg(feature = "socket-tcp")]
+    tcp_reset_unbound: bool,
+    urpf: UrpfMode,
+    /// Packets passed to and from a router, if the interface is part of one.
+    #[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+    forwarding: router::Forwarding,
+    /// Packets waiting for the resolution of their neighbor.
+    #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+    neighbor_queue: neighbor_queue::NeighborQueue,
     routes: Routes,
+    /// Resolves neighbors ahead of the neighbor cache, e.g. from a table kept
+    /// by the operating system.
+    #[cfg(all(
+        feature = "alloc",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    neighbor_resolver: Option<NeighborResolver>,
+    tx_exhausted_handler: Option<fn()>,
+    tx_exhausted_count: u64,
+    #[cfg(feature = "socket-udp")]
+    multicast_loop: bool,
+    #[cfg(all(feature = "alloc", feature = "socket-udp"))]
+    multicast_looped: alloc::vec::Vec<LoopedDatagram>,
     #[cfg(feature = "proto-igmp")]
     ipv4_multicast_groups: LinearMap<Ipv4Address, (), IFACE_MAX_MULTICAST_GROUP_COUNT>,
     /// When to report for (all or) the next multicast group membership via IGMP
     #[cfg(feature = "proto-igmp")]
     igmp_report_state: IgmpReportState,
+    /// Anycast addresses, which packets are accepted for but never sent from.
+    #[cfg(feature = "proto-ipv6")]
+    ipv6_anycast_addrs: Vec<Ipv6Address, IFACE_MAX_ADDR_COUNT>,
+    /// Addresses undergoing (or having failed) Duplicate Address Detection.
+    #[cfg(all(
+        feature = "proto-ipv6",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    ipv6_tentative_addrs: Vec<TentativeAddress, IFACE_MAX_ADDR_COUNT>,
+    #[cfg(all(
+        feature = "proto-ipv6",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    ipv6_iid_mode: Ipv6IidMode,
+    /// Temporary addresses, and when to replace them.
+    #[cfg(all(
+        feature = "proto-ipv6",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    ipv6_temporary_addrs: Vec<TemporaryAddress, IFACE_MAX_ADDR_COUNT>,
+    /// The Router Advertisements to send, if the interface acts as a router.
+    #[cfg(all(
+        feature = "proto-ipv6",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    router_advert: Option<RouterAdvertState>,
+}
+
+/// The state of Duplicate Address Detection for an IPv6 address, see
+/// [RFC 4862 § 5.4].
+///
+/// [RFC 4862 § 5.4]: https://www.rfc-editor.org/rfc/rfc4862#section-5.4
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DadState {
+    /// Duplicate Address Detection is in progress, the address can not be
+    /// used yet.
+    Tentative,
+    /// No other node on the link uses the address, it is assigned to the
+    /// interface.
+    Assigned,
+    /// Another node on the link uses the address, it was not assigned.
+    Duplicate,
+}
+
+/// A multicast datagram sent by a local UDP socket, to be delivered back to
+/// the local sockets joined to the group.
+#[cfg(all(feature = "alloc", feature = "socket-udp"))]
+#[derive(Debug)]
+struct LoopedDatagram {
+    meta: PacketMeta,
+    ip_repr: IpRepr,
+    udp_repr: UdpRepr,
+    payload: alloc::vec::Vec<u8>,
+}
+
+/// An IPv6 address that is not assigned to the interface yet.
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, Clone, Copy)]
+struct TentativeAddress {
+    cidr: Ipv6Cidr,
+    duplicate: bool,
+    probes_left: u8,
+    timeout: Instant,
+}
+
+/// How the interface identifier of the IPv6 addresses formed by stateless
+/// address autoconfiguration is chosen.
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Ipv6IidMode {
+    /// The modified EUI-64 identifier of the hardware address, which is the
+    /// same on every network, and lets the host be tracked.
+    #[default]
+    Eui64,
+    /// An identifier derived from a secret and the prefix, see [RFC 7217].
+    ///
+    /// The address formed in a given prefix never changes, but addresses in
+    /// different prefixes can not be correlated. The secret should be
+    /// random, and kept across reboots.
+    ///
+    /// [RFC 7217]: https://www.rfc-editor.org/rfc/rfc7217
+    StablePrivacy { secret: [u8; 16] },
+    /// A random identifier, replaced by a new one every `lifetime`, see
+    /// [RFC 8981].
+    ///
+    /// Link-local addresses keep using the EUI-64 identifier.
+    ///
+    /// [RFC 8981]: https://www.rfc-editor.org/rfc/rfc8981
+    Temporary { lifetime: Duration },
+}
+
+/// An IPv6 address with a random interface identifier.
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, Clone, Copy)]
+struct TemporaryAddress {
+    cidr: Ipv6Cidr,
+    expires_at: Instant,
+}
+
+/// The Router Advertisements sent by an interface acting as an IPv6 router,
+/// see [RFC 4861 § 6.2].
+///
+/// [RFC 4861 § 6.2]: https://www.rfc-editor.org/rfc/rfc4861#section-6.2
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RaConfig {
+    /// The shortest time between unsolicited advertisements.
+    pub min_interval: Duration,
+    /// The longest time between unsolicited advertisements. Each interval
+    /// is picked at random between the shortest and the longest.
+    pub max_interval: Duration,
+    /// The hop limit hosts should use.
+    pub hop_limit: u8,
+    /// Whether addresses, and other configuration, are available via DHCPv6.
+    pub flags: NdiscRouterFlags,
+    /// How long hosts may use the interface as a default router, or zero if
+    /// they should not.
+    pub router_lifetime: Duration,
+    /// The prefixes advertised.
+    pub prefixes: Vec<NdiscPrefixInformation, NDISC_MAX_PREFIX_INFOS>,
+    /// The MTU of the link, if advertised.
+    pub mtu: Option<u32>,
+}
+
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+impl Default for RaConfig {
+    /// The default values of RFC 4861 § 6.2.1, advertising no prefix.
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(198),
+            max_interval: Duration::from_secs(600),
+            hop_limit: 64,
+            flags: NdiscRouterFlags::empty(),
+            router_lifetime: Duration::from_secs(1800),
+            prefixes: Vec::new(),
+            mtu: None,
+        }
+    }
+}
+
+/// The state of Router Advertisement emission.
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+#[derive(Debug, Clone)]
+struct RouterAdvertState {
+    config: RaConfig,
+    /// When the next advertisement is sent.
+    next_at: Instant,
+    /// When the last advertisement was sent.
+    last_at: Option<Instant>,
+}
+
+/// The set of addresses an interface answers ARP requests for.
+#[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArpAnnounceScope {
+    /// Only answer for addresses assigned to this interface.
+    #[default]
+    Assigned,
+    /// Also answer for addresses this interface accepts packets for through
+    /// AnyIP, i.e. addresses routed via one of its own addresses. See
+    /// [`Interface::set_any_ip`].
+    AnyIp,
+}
+
+/// The reverse-path forwarding check applied to incoming packets, see
+/// [`Config::urpf`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UrpfMode {
+    /// Accept packets from any source address.
+    #[default]
+    Disabled,
+    /// Drop packets unless the best route to their source address leads back
+    /// out of the interface they arrived on.
+    Strict,
+    /// Drop packets only if there is no route to their source address.
+    Loose,
 }
 
 /// Configuration structure used for creating a network interface.
@@ -310,6 +573,107 @@ pub struct Config {
     /// **NOTE**: we use the same PAN ID for destination and source.
     #[cfg(feature = "medium-ieee802154")]
     pub pan_id: Option<Ieee802154Pan>,
+
+    /// Let DHCPv4 sockets run while the interface has no IPv4 address.
+    ///
+    /// When enabled (the default), DHCPv4 sockets transmit from the
+    /// unspecified address `0.0.0.0` and receive replies before any IPv4
+    /// address has been assigned, which is what a DHCP client needs to
+    /// acquire its first lease. When disabled, DHCPv4 sockets stay silent
+    /// until an IPv4 address is configured by other means.
+    ///
+    /// Regardless of this setting, an interface without an IPv4 address
+    /// drops all other IPv4 traffic not sent to a broadcast or multicast
+    /// address, and has no address to answer ARP requests for. Once an
+    /// address is assigned, for example a link-local one, ARP probes for it
+    /// from the unspecified address `0.0.0.0` (RFC 5227) are answered, so that
+    /// other hosts probing for the same address detect the conflict.
+    #[cfg(feature = "socket-dhcpv4")]
+    pub unaddressed_dhcpv4: bool,
+
+    /// Set the addresses the interface answers ARP requests for.
+    ///
+    /// With the default [`ArpAnnounceScope::Assigned`], an interface never
+    /// answers for addresses it does not own, even if they are assigned to
+    /// another interface on the same host.
+    #[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+    pub arp_announce_scope: ArpAnnounceScope,
+
+    /// Accept IPv4 packets carrying a Loose or Strict Source Route option.
+    ///
+    /// Source routing lets a sender choose the path of its packets, which can
+    /// be used to bypass filtering, so by default such packets are dropped,
+    /// and answered with an ICMP Parameter Problem pointing at the option.
+    /// When enabled, they are processed like any other packet: the route
+    /// recorded in the option is not reported to sockets, and replies are
+    /// not source routed back along it.
+    #[cfg(feature = "proto-ipv4")]
+    pub accept_source_route: bool,
+
+    /// Accept ICMPv6 Redirect messages.
+    ///
+    /// A router may redirect a host to a better first hop for a destination.
+    /// When enabled, a valid Redirect sent by the router currently used to
+    /// reach the destination installs a temporary route to it via the new
+    /// first hop. Redirects can be forged by any node on the link, so they are
+    /// ignored by default.
+    #[cfg(feature = "proto-ipv6")]
+    pub accept_redirects: bool,
+
+    /// Drop incoming packets whose source address is one of the addresses of
+    /// the interface.
+    ///
+    /// Such packets can only be forged, for example to make a host answer
+    /// itself in a loop, so they are dropped by default. Packets from a
+    /// loopback address are always accepted, so that an interface can talk to
+    /// itself over a loopback device; disable this to have it talk to itself
+    /// from its other addresses. UDP datagrams sent to a multicast group
+    /// are only dropped if they don't come from one of our sockets, as the
+    /// device may reflect our own transmissions, see
+    /// [`Interface::set_multicast_loop`].
+    pub drop_own_source: bool,
+
+    /// Answer TCP segments that no socket accepts with a RST, as required by
+    /// RFC 9293.
+    ///
+    /// Enabled by default. When disabled, such segments are silently dropped,
+    /// which hides closed ports from port scans. RST segments themselves are
+    /// never answered.
+    #[cfg(feature = "socket-tcp")]
+    pub tcp_reset_unbound: bool,
+
+    /// Set the unicast reverse-path forwarding (uRPF) check, which drops
+    /// incoming packets with a spoofed source address.
+    ///
+    /// A route to the source address leads back out of the interface if the
+    /// address is on-link, or if it is routed via a router which is on-link
+    /// and not one of the addresses of the interface. When the interface is
+    /// part of a [`Router`], packets forwarded to another interface are checked
+    /// against the routes of all the interfaces of the router instead.
+    ///
+    /// Link-local and loopback source addresses are always accepted. Defaults
+    /// to [`UrpfMode::Disabled`].
+    ///
+    /// [`Router`]: crate::iface::Router
+    pub urpf: UrpfMode,
+
+    /// Set how fragments overlapping data already received for the same packet
+    /// are reassembled.
+    ///
+    /// Defaults to [`FragmentOverlapPolicy::DropDatagram`].
+    #[cfg(feature = "_proto-fragmentation")]
+    pub fragment_overlap_policy: FragmentOverlapPolicy,
+
+    /// Set how the interface identifier of autoconfigured IPv6 addresses is
+    /// chosen.
+    ///
+    /// Defaults to [`Ipv6IidMode::Eui64`]. See
+    /// [`Interface::add_link_local_ipv6`] and [`Interface::add_slaac_ipv6`].
+    #[cfg(all(
+        feature = "proto-ipv6",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    pub ipv6_iid_mode: Ipv6IidMode,
 }
 
 impl Config {
@@ -319,10 +683,60 @@ impl Config {
             hardware_addr,
             #[cfg(feature = "medium-ieee802154")]
             pan_id: None,
+            #[cfg(feature = "socket-dhcpv4")]
+            unaddressed_dhcpv4: true,
+            #[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+            arp_announce_scope: ArpAnnounceScope::default(),
+            #[cfg(feature = "proto-ipv4")]
+            accept_source_route: false,
+            #[cfg(feature = "proto-ipv6")]
+            accept_redirects: false,
+            drop_own_source: true,
+            #[cfg(feature = "socket-tcp")]
+            tcp_reset_unbound: true,
+            urpf: UrpfMode::default(),
+            #[cfg(feature = "_proto-fragmentation")]
+            fragment_overlap_policy: FragmentOverlapPolicy::default(),
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            ipv6_iid_mode: Ipv6IidMode::default(),
         }
     }
 }
 
+/// Error type for [`Interface::inject_raw`].
+#[cfg(feature = "socket-raw")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InjectError {
+    /// The packet is not a well-formed IPv4 or IPv6 packet.
+    Malformed,
+    /// The hardware device transmit buffer is full. Try again later.
+    Exhausted,
+    /// There is no route to the destination of the packet.
+    NoRoute,
+    /// The hardware address of the next hop is not known. Neighbor discovery
+    /// has been started, unless it failed recently; try again later.
+    NeighborUnknown,
+}
+
+#[cfg(feature = "socket-raw")]
+impl core::fmt::Display for InjectError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            InjectError::Malformed => write!(f, "Malformed"),
+            InjectError::Exhausted => write!(f, "Exhausted"),
+            InjectError::NoRoute => write!(f, "NoRoute"),
+            InjectError::NeighborUnknown => write!(f, "NeighborUnknown"),
+        }
+    }
+}
+
+#[cfg(all(feature = "socket-raw", feature = "std"))]
+impl std::error::Error for InjectError {}
+
 impl Interface {
     /// Create a network interface using the previously provided configuration.
     ///
@@ -383,8 +797,12 @@ impl Interface {
                 assembler: PacketAssemblerSet::new(),
                 #[cfg(feature = "_proto-fragmentation")]
                 reassembly_timeout: Duration::from_secs(60),
+                #[cfg(feature = "_proto-fragmentation")]
+                overlap_policy: config.fragment_overlap_policy,
             },
             fragmenter: Fragmenter::new(),
+            #[cfg(feature = "alloc")]
+            transforms: Default::default(),
             inner: InterfaceInner {
                 now,
                 caps,
@@ -392,13 +810,62 @@ impl Interface {
                 ip_addrs: Vec::new(),
                 #[cfg(feature = "proto-ipv4")]
                 any_ip: false,
+                #[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+                forwarding: Default::default(),
+                #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+                neighbor_queue: Default::default(),
+                #[cfg(feature = "socket-dhcpv4")]
+                unaddressed_dhcpv4: config.unaddressed_dhcpv4,
+                #[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+                arp_announce_scope: config.arp_announce_scope,
+                #[cfg(feature = "proto-ipv4")]
+                accept_source_route: config.accept_source_route,
+                #[cfg(feature = "proto-ipv6")]
+                accept_redirects: config.accept_redirects,
+                drop_own_source: config.drop_own_source,
+                #[cfg(feature = "socket-tcp")]
+                tcp_reset_unbound: config.tcp_reset_unbound,
+                urpf: config.urpf,
                 routes: Routes::new(),
+                #[cfg(all(
+                    feature = "alloc",
+                    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+                ))]
+                neighbor_resolver: None,
+                tx_exhausted_handler: None,
+                tx_exhausted_count: 0,
+                #[cfg(feature = "socket-udp")]
+                multicast_loop: true,
+                #[cfg(all(feature = "alloc", feature = "socket-udp"))]
+                multicast_looped: alloc::vec::Vec::new(),
                 #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
                 neighbor_cache: NeighborCache::new(),
                 #[cfg(feature = "proto-igmp")]
                 ipv4_multicast_groups: LinearMap::new(),
                 #[cfg(feature = "proto-igmp")]
                 igmp_report_state: IgmpReportState::Inactive,
+                #[cfg(feature = "proto-ipv6")]
+                ipv6_anycast_addrs: Vec::new(),
+                #[cfg(all(
+                    feature = "proto-ipv6",
+                    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+                ))]
+                ipv6_tentative_addrs: Vec::new(),
+                #[cfg(all(
+                    feature = "proto-ipv6",
+                    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+                ))]
+                ipv6_iid_mode: config.ipv6_iid_mode,
+                #[cfg(all(
+                    feature = "proto-ipv6",
+                    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+                ))]
+                ipv6_temporary_addrs: Vec::new(),
+                #[cfg(all(
+                    feature = "proto-ipv6",
+                    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+                ))]
+                router_advert: None,
                 #[cfg(feature = "medium-ieee802154")]
                 sequence_no,
                 #[cfg(feature = "medium-ieee802154")]
@@ -495,6 +962,33 @@ impl Interface {
         self.inner.has_ip_addr(addr)
     }
 
+    /// Add an IPv6 anycast address to the interface.
+    ///
+    /// An anycast address may be assigned to several nodes at once, so no
+    /// Duplicate Address Detection is performed for it, and it is not one of
+    /// the [addresses](Self::ip_addrs) of the interface. Packets sent to it
+    /// are accepted, and Neighbor Solicitations for it are answered, but it
+    /// is never used as the source address of outgoing packets: replies
+    /// generated by the interface itself, such as ICMPv6 echo replies, are
+    /// sent from one of the unicast addresses of the interface instead.
+    ///
+    /// Returns `false` if the address is not unicast, or if too many anycast
+    /// addresses are already assigned.
+    #[cfg(feature = "proto-ipv6")]
+    pub fn add_anycast_ipv6(&mut self, addr: Ipv6Address) -> bool {
+        if !addr.is_unicast() {
+            return false;
+        }
+        self.inner.has_anycast_ipv6(addr) || self.inner.ipv6_anycast_addrs.push(addr).is_ok()
+    }
+
+    /// Check whether the given address is an anycast address of the
+    /// interface, see [`add_anycast_ipv6`](Self::add_anycast_ipv6).
+    #[cfg(feature = "proto-ipv6")]
+    pub fn has_anycast_ipv6(&self, addr: Ipv6Address) -> bool {
+        self.inner.has_anycast_ipv6(addr)
+    }
+
     pub fn routes(&self) -> &Routes {
         &self.inner.routes
     }
@@ -503,6 +997,25 @@ impl Interface {
         &mut self.inner.routes
     }
 
+    /// Set the storage of the route cache.
+    ///
+    /// With many routes, this saves walking the whole table for every
+    /// packet. See [Routes::set_cache].
+    pub fn set_route_cache<S>(&mut self, storage: S)
+    where
+        S: Into<ManagedSlice<'static, Option<RouteCacheEntry>>>,
+    {
+        self.inner.routes.set_cache(storage);
+    }
+
+    /// Set the number of destinations the route lookup is cached for.
+    ///
+    /// See [Routes::set_cache_size].
+    #[cfg(feature = "alloc")]
+    pub fn set_route_cache_size(&mut self, size: usize) {
+        self.inner.routes.set_cache_size(size);
+    }
+
     /// Enable or disable the AnyIP capability.
     ///
     /// AnyIP allowins packets to be received
@@ -529,6 +1042,73 @@ impl Interface {
         self.inner.any_ip
     }
 
+    /// Set whether multicast datagrams sent by UDP sockets are delivered
+    /// back to the local sockets joined to the group, for sockets that do not
+    /// [set it themselves](crate::socket::udp::Socket::set_multicast_loop).
+    ///
+    /// Looped datagrams are delivered when they are sent, if the interface has
+    /// joined the group, without going through the device. Copies of our own
+    /// transmissions reflected by the device are dropped, so that they are not
+    /// received twice. Without the `alloc` feature, only the reflected copies
+    /// are delivered, and only when looping is enabled. By default, looping is
+    /// enabled.
+    #[cfg(feature = "socket-udp")]
+    pub fn set_multicast_loop(&mut self, multicast_loop: bool) {
+        self.inner.multicast_loop = multicast_loop;
+    }
+
+    /// Get whether multicast datagrams sent by UDP sockets are delivered back
+    /// to local sockets by default.
+    ///
+    /// See [`set_multicast_loop`](Self::set_multicast_loop) for details.
+    #[cfg(feature = "socket-udp")]
+    pub fn multicast_loop(&self) -> bool {
+        self.inner.multicast_loop
+    }
+
+    /// Set for how long a failed neighbor resolution is remembered.
+    ///
+    /// After a neighbor has not answered a few discovery requests, packets
+    /// to it are dropped without sending further ARP requests or Neighbor
+    /// Solicitations until `ttl` has elapsed. A zero duration, the default,
+    /// disables negative caching, and discovery is retried indefinitely.
+    #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+    pub fn set_neighbor_negative_cache_ttl(&mut self, ttl: Duration) {
+        self.inner.neighbor_cache.set_negative_ttl(ttl);
+    }
+
+    /// Get for how long a failed neighbor resolution is remembered.
+    ///
+    /// See [`set_neighbor_negative_cache_ttl`](Self::set_neighbor_negative_cache_ttl)
+    /// for details.
+    #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+    pub fn neighbor_negative_cache_ttl(&self) -> Duration {
+        self.inner.neighbor_cache.negative_ttl()
+    }
+
+    /// Enable or disable unicast probing of expired neighbors.
+    ///
+    /// When a neighbor mapping has expired, its reachability is first
+    /// confirmed with ARP requests or Neighbor Solicitations sent directly to
+    /// the cached hardware address, as described in RFC 1122 § 2.3.2.1 and
+    /// RFC 4861 § 7.3.3, instead of being broadcast or multicast to the whole
+    /// link. If a few probes go unanswered, the neighbor is resolved from
+    /// scratch. Unicast probing is only done on Ethernet. By default, it is
+    /// enabled.
+    #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+    pub fn set_neighbor_unicast_probes(&mut self, enabled: bool) {
+        self.inner.neighbor_cache.set_unicast_probes(enabled);
+    }
+
+    /// Get whether expired neighbors are probed with unicast requests.
+    ///
+    /// See [`set_neighbor_unicast_probes`](Self::set_neighbor_unicast_probes)
+    /// for details.
+    #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
+    pub fn neighbor_unicast_probes(&self) -> bool {
+        self.inner.neighbor_cache.unicast_probes()
+    }
+
     /// Get the 6LoWPAN address contexts.
     #[cfg(feature = "proto-sixlowpan")]
     pub fn sixlowpan_address_context(
@@ -560,6 +1140,117 @@ impl Interface {
         self.fragments.reassembly_timeout = timeout;
     }
 
+    /// Set a transform applied to the bytes of every frame, including its
+    /// link-layer header, right before it is handed to the device.
+    ///
+    /// The transform may change the length of the frame, e.g. to add an
+    /// authentication tag, as long as the result still fits the device. It
+    /// is applied by [poll](#method.poll) and [inject_raw](#method.inject_raw).
+    /// See also [set_ingress_transform](#method.set_ingress_transform).
+    #[cfg(feature = "alloc")]
+    pub fn set_egress_transform<F>(&mut self, transform: F)
+    where
+        F: FnMut(&mut alloc::vec::Vec<u8>) + Send + 'static,
+    {
+        self.transforms.egress = Some(alloc::boxed::Box::new(transform));
+    }
+
+    /// Set a transform applied to the bytes of every frame received from the
+    /// device, before it is processed.
+    ///
+    /// This is the inverse of the [egress transform](#method.set_egress_transform);
+    /// it may change the length of the frame as well.
+    #[cfg(feature = "alloc")]
+    pub fn set_ingress_transform<F>(&mut self, transform: F)
+    where
+        F: FnMut(&mut alloc::vec::Vec<u8>) + Send + 'static,
+    {
+        self.transforms.ingress = Some(alloc::boxed::Box::new(transform));
+    }
+
+    /// Remove the egress and ingress transforms.
+    #[cfg(feature = "alloc")]
+    pub fn clear_transforms(&mut self) {
+        self.transforms = Default::default();
+    }
+
+    /// Set a resolver consulted for the hardware address of a neighbor before
+    /// the neighbor cache, and before sending an ARP request or a Neighbor
+    /// Solicitation.
+    ///
+    /// This suits platforms where neighbor resolution is offloaded to the
+    /// operating system or the hardware. The resolver is given the address
+    /// of the next hop; if it returns `None`, the neighbor is resolved as usual.
+    #[cfg(all(
+        feature = "alloc",
+        any(feature = "medium-ethernet", feature = "medium-ieee802154")
+    ))]
+    pub fn set_neighbor_resolver(&mut self, resolver: Option<NeighborResolver>) {
+        self.inner.neighbor_resolver = resolver;
+    }
+
+    /// Set a handler called every time a packet could not be sent because
+    /// the device had no transmit token available.
+    ///
+    /// The packet is not lost: it stays queued in its socket, or its state
+    /// machine, and is sent again by a later [poll](#method.poll). The
+    /// handler is only meant to observe device-side backpressure.
+    /// See also [tx_exhausted_count](#method.tx_exhausted_count).
+    pub fn set_tx_exhausted_handler(&mut self, handler: Option<fn()>) {
+        self.inner.tx_exhausted_handler = handler;
+    }
+
+    /// Get the number of times a packet could not be sent because the
+    /// device had no transmit token available.
+    pub fn tx_exhausted_count(&self) -> u64 {
+        self.inner.tx_exhausted_count
+    }
+
+    /// Start closing all the given sockets, for a graceful shutdown.
+    ///
+    /// TCP sockets are [closed](tcp::Socket::close), so that the data
+    /// remaining in their transmit buffers is sent, followed by a FIN;
+    /// listening TCP sockets are closed right away. UDP sockets are closed
+    /// right away, and raw sockets [aborted](raw::Socket::abort), dropping any
+    /// queued datagrams. Other sockets have no connection to close and are
+    /// left as is.
+    ///
+    /// The interface must keep being polled until
+    /// [all_closed](#method.all_closed) returns `true`.
+    pub fn shutdown_all(&mut self, sockets: &mut SocketSet<'_>) {
+        for (_, socket) in sockets.iter_mut() {
+            match socket {
+                #[cfg(feature = "socket-raw")]
+                Socket::Raw(socket) => socket.abort(),
+                #[cfg(feature = "socket-udp")]
+                Socket::Udp(socket) => socket.close(),
+                #[cfg(feature = "socket-tcp")]
+                Socket::Tcp(socket) => socket.close(),
+                #[allow(unreachable_patterns)]
+                _ => (),
+            }
+        }
+    }
+
+    /// Check whether all the given sockets are closed, e.g. after a call to
+    /// [shutdown_all](#method.shutdown_all).
+    ///
+    /// A TCP socket is considered closed once it is no longer
+    /// [active](tcp::Socket::is_active), i.e. once its connection is fully
+    /// closed or in the TIME-WAIT state; a socket in the LISTEN state counts
+    /// as open. Sockets other than TCP and UDP sockets, which have no state
+    /// to wait for, are ignored.
+    pub fn all_closed(&self, sockets: &SocketSet<'_>) -> bool {
+        sockets.iter().all(|(_, socket)| match socket {
+            #[cfg(feature = "socket-udp")]
+            Socket::Udp(socket) => !socket.is_open(),
+            #[cfg(feature = "socket-tcp")]
+            Socket::Tcp(socket) => !socket.is_active() && socket.state() != tcp::State::Listen,
+            #[allow(unreachable_patterns)]
+            _ => true,
+        })
+    }
+
     /// Transmit packets queued in the given sockets, and receive packets queued
     /// in the device.
     ///
@@ -572,6 +1263,55 @@ impl Interface {
         device: &mut D,
         sockets: &mut SocketSet<'_>,
     ) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        #[cfg(feature = "alloc")]
+        {
+            let mut transforms = core::mem::take(&mut self.transforms);
+            let result = self.poll_device(timestamp, &mut transforms.wrap(device), sockets);
+            self.transforms = transforms;
+            result
+        }
+
+        #[cfg(not(feature = "alloc"))]
+        self.poll_device(timestamp, device, sockets)
+    }
+
+    /// Poll the interface until there is nothing left to do.
+    ///
+    /// [poll](#method.poll) is called repeatedly, at the time returned by
+    /// `clock` before each call, until it reports that no packets were
+    /// processed or emitted, or until it has been called `max_iters` times.
+    /// This is mostly useful in tests, to exchange packets over a loopback
+    /// device until all sockets have settled.
+    ///
+    /// Returns the number of calls that did some work; if it is `max_iters`,
+    /// the interface may not be idle yet.
+    pub fn poll_until_idle<D>(
+        &mut self,
+        device: &mut D,
+        sockets: &mut SocketSet<'_>,
+        mut clock: impl FnMut() -> Instant,
+        max_iters: usize,
+    ) -> usize
+    where
+        D: Device + ?Sized,
+    {
+        for iters in 0..max_iters {
+            if !self.poll(clock(), device, sockets) {
+                return iters;
+            }
+        }
+        max_iters
+    }
+
+    fn poll_device<D>(
+        &mut self,
+        timestamp: Instant,
+        device: &mut D,
+        sockets: &mut SocketSet<'_>,
+    ) -> bool
     where
         D: Device + ?Sized,
     {
@@ -611,6 +1351,26 @@ impl Interface {
                 did_something |= self.igmp_egress(device);
             }
 
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            {
+                did_something |= self.inner.rotate_temporary_addrs();
+                did_something |= self.dad_egress(device);
+                did_something |= self.router_advert_egress(device);
+            }
+
+            #[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+            {
+                did_something |= self.forward_egress(device);
+            }
+
+            #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+            {
+                did_something |= self.neighbor_queue_egress(device);
+            }
+
             if did_something {
                 readiness_may_have_changed = true;
             } else {
@@ -637,6 +1397,28 @@ impl Interface {
             return Some(Instant::from_millis(0));
         }
 
+        #[cfg(all(
+            feature = "proto-ipv6",
+            any(feature = "medium-ethernet", feature = "medium-ieee802154")
+        ))]
+        let (dad_at, router_advert_at) =
+            (self.inner.dad_poll_at(), self.inner.router_advert_poll_at());
+        #[cfg(not(all(
+            feature = "proto-ipv6",
+            any(feature = "medium-ethernet", feature = "medium-ieee802154")
+        )))]
+        let (dad_at, router_advert_at) = (None, None);
+
+        #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+        let neighbor_queue_at = self.inner.neighbor_queue.poll_at();
+        #[cfg(not(all(feature = "alloc", feature = "medium-ethernet")))]
+        let neighbor_queue_at = None;
+
+        #[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+        let forwarding_at = self.inner.forwarding.poll_at();
+        #[cfg(not(all(feature = "alloc", feature = "proto-ipv4")))]
+        let forwarding_at = None;
+
         let inner = &mut self.inner;
 
         sockets
@@ -652,6 +1434,10 @@ impl Interface {
                     PollAt::Now => Some(Instant::from_millis(0)),
                 }
             })
+            .chain(dad_at)
+            .chain(router_advert_at)
+            .chain(neighbor_queue_at)
+            .chain(forwarding_at)
             .min()
     }
 
@@ -671,6 +1457,72 @@ impl Interface {
         }
     }
 
+    /// Transmit a complete IP packet through the device right away, bypassing
+    /// socket buffering.
+    ///
+    /// The packet must start with an IPv4 or IPv6 header. The header is
+    /// re-emitted from its parsed representation, so IPv4 options are not
+    /// preserved and the IPv4 header checksum is filled in according to the
+    /// device's checksum capabilities. On Ethernet, the hardware address of
+    /// the next hop is resolved using the neighbor cache; if it is not known
+    /// yet, a discovery request is sent instead of the packet, and
+    /// [`InjectError::NeighborUnknown`] is returned.
+    ///
+    /// The timestamp of the last call to [poll](Self::poll) is used as the
+    /// current time.
+    #[cfg(feature = "socket-raw")]
+    pub fn inject_raw<D>(&mut self, device: &mut D, packet: &[u8]) -> Result<(), InjectError>
+    where
+        D: Device + ?Sized,
+    {
+        if packet.is_empty() {
+            return Err(InjectError::Malformed);
+        }
+
+        let ip_packet = match IpVersion::of_packet(packet) {
+            #[cfg(feature = "proto-ipv4")]
+            Ok(IpVersion::Ipv4) => {
+                let packet =
+                    Ipv4PacketWire::new_checked(packet).map_err(|_| InjectError::Malformed)?;
+                let repr = Ipv4Repr::parse(&packet, &ChecksumCapabilities::ignored())
+                    .map_err(|_| InjectError::Malformed)?;
+                IpPacket::new_ipv4(repr, IpPayload::Raw(packet.payload()))
+            }
+            #[cfg(feature = "proto-ipv6")]
+            Ok(IpVersion::Ipv6) => {
+                let packet =
+                    Ipv6PacketWire::new_checked(packet).map_err(|_| InjectError::Malformed)?;
+                let repr = Ipv6Repr::parse(&packet).map_err(|_| InjectError::Malformed)?;
+                IpPacket::new_ipv6(repr, IpPayload::Raw(packet.payload()))
+            }
+            _ => return Err(InjectError::Malformed),
+        };
+
+        if ip_packet.ip_repr().dst_addr().is_unspecified() {
+            return Err(InjectError::Malformed);
+        }
+
+        #[cfg(feature = "alloc")]
+        let device = &mut self.transforms.wrap(device);
+        let tx_token = device.transmit(self.inner.now).ok_or_else(|| {
+            self.inner.tx_exhausted();
+            InjectError::Exhausted
+        })?;
+        self.inner
+            .dispatch_ip(
+                tx_token,
+                PacketMeta::default(),
+                ip_packet,
+                &mut self.fragmenter,
+            )
+            .map_err(|err| match err {
+                DispatchError::NoRoute => InjectError::NoRoute,
+                DispatchError::NeighborPending | DispatchError::NeighborFailed => {
+                    InjectError::NeighborUnknown
+                }
+            })
+    }
+
     /// 用于处理网络设备上的数据包接收和处理。
     /// 它接受一个设备对象device和一个socket集合sockets，并返回一个布尔值，
     /// 表示是否处理了任何数据包。
@@ -761,6 +1613,36 @@ impl Interface {
 
         // 跟踪是否发送了任何数据包
         let mut emitted_any = false;
+
+        // Tear down the connections of removed sockets, on a best effort basis.
+        #[cfg(feature = "socket-tcp")]
+        sockets.send_teardowns(|(ip_repr, tcp_repr)| {
+            let Some(tx_token) = device.transmit(self.inner.now) else {
+                self.inner.tx_exhausted();
+                return false;
+            };
+            let packet = IpPacket::new(ip_repr.clone(), IpPayload::Tcp(*tcp_repr));
+            #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+            let result = self.inner.dispatch_ip_or_queue(
+                tx_token,
+                PacketMeta::default(),
+                packet,
+                &mut self.fragmenter,
+            );
+            #[cfg(not(all(feature = "alloc", feature = "medium-ethernet")))]
+            let result = self.inner.dispatch_ip(
+                tx_token,
+                PacketMeta::default(),
+                packet,
+                &mut self.fragmenter,
+            );
+            match result {
+                Ok(()) => emitted_any = true,
+                Err(e) => net_debug!("failed to tear down a removed socket: {:?}", e),
+            }
+            true
+        });
+
         // 遍历所有套接字项，逐一检查并处理每个套接字
         for item in sockets.items_mut() {
             if !item
@@ -779,13 +1661,16 @@ impl Interface {
                 // 尝试获取设备的传输令牌，如果失败则返回Exhausted错误
                 let t = device.transmit(inner.now).ok_or_else(|| {
                     net_debug!("failed to transmit IP: device exhausted");
+                    inner.tx_exhausted();
                     EgressError::Exhausted
                 })?;
 
                 // 调用dispatch_ip方法分发数据包，如果失败则返回Dispatch错误
-                inner
-                    .dispatch_ip(t, meta, response, &mut self.fragmenter)
-                    .map_err(EgressError::Dispatch)?;
+                #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+                let result = inner.dispatch_ip_or_queue(t, meta, response, &mut self.fragmenter);
+                #[cfg(not(all(feature = "alloc", feature = "medium-ethernet")))]
+                let result = inner.dispatch_ip(t, meta, response, &mut self.fragmenter);
+                result.map_err(EgressError::Dispatch)?;
                 // 如果发送成功，设置emitted_any为true
                 emitted_any = true;
 
@@ -824,18 +1709,36 @@ impl Interface {
                 }
                 #[cfg(feature = "socket-udp")]
                 Socket::Udp(socket) => {
+                    let dscp = socket.dscp();
+                    #[cfg(feature = "alloc")]
+                    let multicast_loop =
+                        socket.multicast_loop().unwrap_or(self.inner.multicast_loop);
                     socket.dispatch(&mut self.inner, |inner, meta, (ip, udp, payload)| {
-                        respond(inner, meta, IpPacket::new(ip, IpPayload::Udp(udp, payload)))
+                        #[cfg(feature = "alloc")]
+                        let looped = (multicast_loop && inner.has_multicast_group(ip.dst_addr()))
+                            .then(|| LoopedDatagram {
+                                meta,
+                                ip_repr: ip.clone(),
+                                udp_repr: udp,
+                                payload: payload.to_vec(),
+                            });
+                        let packet = IpPacket::new(ip, IpPayload::Udp(udp, payload));
+                        respond(inner, meta, packet.with_dscp(dscp))?;
+                        #[cfg(feature = "alloc")]
+                        inner.multicast_looped.extend(looped);
+                        Ok(())
                     })
                 }
                 #[cfg(feature = "socket-tcp")]
-                Socket::Tcp(socket) => socket.dispatch(&mut self.inner, |inner, (ip, tcp)| {
-                    respond(
-                        inner,
-                        PacketMeta::default(),
-                        IpPacket::new(ip, IpPayload::Tcp(tcp)),
-                    )
-                }),
+                Socket::Tcp(socket) => {
+                    let dscp = socket.dscp();
+                    socket.dispatch(&mut self.inner, |inner, (ip, tcp)| {
+                        let packet = IpPacket::new(ip, IpPayload::Tcp(tcp));
+                        respond(inner, PacketMeta::default(), packet.with_dscp(dscp))
+                    })
+                }
+                #[cfg(feature = "socket-dhcpv4")]
+                Socket::Dhcpv4(_) if !self.inner.dhcpv4_permitted() => Ok(()),
                 #[cfg(feature = "socket-dhcpv4")]
                 Socket::Dhcpv4(socket) => {
                     socket.dispatch(&mut self.inner, |inner, (ip, udp, dhcp)| {
@@ -873,6 +1776,10 @@ impl Interface {
                 Ok(()) => {}
             }
         }
+
+        #[cfg(all(feature = "alloc", feature = "socket-udp"))]
+        self.inner.deliver_multicast_looped(sockets);
+
         emitted_any
     }
 
@@ -902,6 +1809,7 @@ impl Interface {
                     .dispatch_ipv4_frag(tx_token, &mut self.fragmenter);
                 return true;
             }
+            self.inner.tx_exhausted();
         }
         false
     }
@@ -932,6 +1840,7 @@ impl Interface {
                     .dispatch_ieee802154_frag(tx_token, &mut self.fragmenter);
                 return true;
             }
+            self.inner.tx_exhausted();
         }
         false
     }
@@ -1046,9 +1955,38 @@ impl InterfaceInner {
             .unwrap(),
             rand: Rand::new(1234),
             routes: Routes::new(),
+            #[cfg(all(
+                feature = "alloc",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            neighbor_resolver: None,
+            tx_exhausted_handler: None,
+            tx_exhausted_count: 0,
+            #[cfg(feature = "socket-udp")]
+            multicast_loop: true,
+            #[cfg(all(feature = "alloc", feature = "socket-udp"))]
+            multicast_looped: alloc::vec::Vec::new(),
 
             #[cfg(feature = "proto-ipv4")]
             any_ip: false,
+            #[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+            forwarding: Default::default(),
+            #[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+            neighbor_queue: Default::default(),
+
+            #[cfg(feature = "socket-dhcpv4")]
+            unaddressed_dhcpv4: true,
+
+            #[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+            arp_announce_scope: ArpAnnounceScope::Assigned,
+            #[cfg(feature = "proto-ipv4")]
+            accept_source_route: false,
+            #[cfg(feature = "proto-ipv6")]
+            accept_redirects: false,
+            drop_own_source: true,
+            #[cfg(feature = "socket-tcp")]
+            tcp_reset_unbound: true,
+            urpf: UrpfMode::Disabled,
 
             #[cfg(feature = "medium-ieee802154")]
             pan_id: Some(crate::wire::Ieee802154Pan(0xabcd)),
@@ -1092,17 +2030,54 @@ impl InterfaceInner {
 
             #[cfg(feature = "proto-igmp")]
             igmp_report_state: IgmpReportState::Inactive,
+            #[cfg(feature = "proto-ipv6")]
+            ipv6_anycast_addrs: Vec::new(),
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            ipv6_tentative_addrs: Vec::new(),
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            ipv6_iid_mode: Ipv6IidMode::default(),
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            ipv6_temporary_addrs: Vec::new(),
+            #[cfg(all(
+                feature = "proto-ipv6",
+                any(feature = "medium-ethernet", feature = "medium-ieee802154")
+            ))]
+            router_advert: None,
             #[cfg(feature = "proto-igmp")]
             ipv4_multicast_groups: LinearMap::new(),
         }
     }
 
+    /// Record that a packet could not be sent because the device had no
+    /// transmit token available.
+    pub(crate) fn tx_exhausted(&mut self) {
+        self.tx_exhausted_count += 1;
+        if let Some(handler) = self.tx_exhausted_handler {
+            handler();
+        }
+    }
+
     #[cfg(test)]
     #[allow(unused)] // unused depending on which sockets are enabled
     pub(crate) fn set_now(&mut self, now: Instant) {
         self.now = now
     }
 
+    #[cfg(test)]
+    #[allow(unused)] // unused depending on which sockets are enabled
+    pub(crate) fn add_ip_addr(&mut self, cidr: IpCidr) {
+        self.ip_addrs.push(cidr).unwrap()
+    }
+
     #[cfg(any(feature = "medium-ethernet", feature = "medium-ieee802154"))]
     fn check_hardware_addr(addr: &HardwareAddress) {
         if !addr.is_unicast() {
@@ -1146,24 +2121,82 @@ impl InterfaceInner {
     /// [RFC 4291 § 2.7.1]: https://tools.ietf.org/html/rfc4291#section-2.7.1
     #[cfg(feature = "proto-ipv6")]
     pub fn has_solicited_node(&self, addr: Ipv6Address) -> bool {
-        self.ip_addrs.iter().any(|cidr| {
-            match *cidr {
-                IpCidr::Ipv6(cidr) if cidr.address() != Ipv6Address::LOOPBACK => {
-                    // Take the lower order 24 bits of the IPv6 address and
-                    // append those bits to FF02:0:0:0:0:1:FF00::/104.
-                    addr.as_bytes()[14..] == cidr.address().as_bytes()[14..]
-                }
-                _ => false,
-            }
-        })
+        let unicast = self.ip_addrs.iter().filter_map(|cidr| match *cidr {
+            IpCidr::Ipv6(cidr) if cidr.address() != Ipv6Address::LOOPBACK => Some(cidr.address()),
+            _ => None,
+        });
+        unicast
+            .chain(self.ipv6_anycast_addrs.iter().copied())
+            // Take the lower order 24 bits of the IPv6 address and
+            // append those bits to FF02:0:0:0:0:1:FF00::/104.
+            .any(|probe| addr.as_bytes()[14..] == probe.as_bytes()[14..])
+    }
+
+    /// Check whether the given address is an anycast address of the interface.
+    #[cfg(feature = "proto-ipv6")]
+    fn has_anycast_ipv6(&self, addr: Ipv6Address) -> bool {
+        self.ipv6_anycast_addrs.contains(&addr)
+    }
+
+    /// Return the unicast address to send a reply to a packet for one of the
+    /// anycast addresses of the interface from.
+    ///
+    /// A link-local destination is answered from a link-local address, and
+    /// any other one from an address of a larger scope, if possible.
+    #[cfg(feature = "proto-ipv6")]
+    fn anycast_reply_source(&self, dst_addr: Ipv6Address) -> Option<Ipv6Address> {
+        let mut candidates = self.ip_addrs.iter().filter_map(|cidr| match *cidr {
+            IpCidr::Ipv6(cidr) if !cidr.address().is_loopback() => Some(cidr.address()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        });
+        let first = candidates.clone().next();
+        candidates
+            .find(|addr| addr.is_link_local() == dst_addr.is_link_local())
+            .or(first)
     }
 
     /// Check whether the interface has the given IP address assigned.
-    fn has_ip_addr<T: Into<IpAddress>>(&self, addr: T) -> bool {
+    pub(crate) fn has_ip_addr<T: Into<IpAddress>>(&self, addr: T) -> bool {
         let addr = addr.into();
         self.ip_addrs.iter().any(|probe| probe.address() == addr)
     }
 
+    /// Check whether a packet must be dropped because its source address is
+    /// ours, see [`Config::drop_own_source`].
+    ///
+    /// Multicast UDP datagrams are left to `process_udp`, which tells the
+    /// reflected copies of our own transmissions apart.
+    fn is_own_source(&self, ip_repr: &IpRepr) -> bool {
+        #[cfg(feature = "socket-udp")]
+        if ip_repr.dst_addr().is_multicast() && ip_repr.next_header() == IpProtocol::Udp {
+            return false;
+        }
+        let addr = ip_repr.src_addr();
+        self.drop_own_source && !addr.is_loopback() && self.has_ip_addr(addr)
+    }
+
+    /// Check whether a packet from the given source address must be dropped
+    /// by the reverse-path forwarding check, see [`Config::urpf`].
+    fn fails_urpf<T: Into<IpAddress>>(&self, addr: T) -> bool {
+        let addr = addr.into();
+        if self.urpf == UrpfMode::Disabled
+            || !addr.is_unicast()
+            || addr.is_loopback()
+            || addr.is_link_local()
+        {
+            return false;
+        }
+
+        match self.route(&addr, self.now) {
+            None => true,
+            Some(next_hop) => {
+                self.urpf == UrpfMode::Strict
+                    && (!self.in_same_network(&next_hop) || self.has_ip_addr(next_hop))
+            }
+        }
+    }
+
     /// Get the first IPv4 address of the interface.
     #[cfg(feature = "proto-ipv4")]
     pub fn ipv4_addr(&self) -> Option<Ipv4Address> {
@@ -1272,6 +2305,36 @@ impl InterfaceInner {
         address.is_unicast() && !self.is_broadcast_v4(address)
     }
 
+    /// Checks whether DHCPv4 sockets may exchange packets right now, i.e.
+    /// either an IPv4 address is assigned or unaddressed DHCPv4 is enabled.
+    #[cfg(feature = "socket-dhcpv4")]
+    fn dhcpv4_permitted(&self) -> bool {
+        self.unaddressed_dhcpv4 || self.ipv4_addr().is_some()
+    }
+
+    /// Deliver the multicast datagrams sent by local UDP sockets back to the
+    /// local sockets joined to the group.
+    #[cfg(all(feature = "alloc", feature = "socket-udp"))]
+    fn deliver_multicast_looped(&mut self, sockets: &mut SocketSet) {
+        for looped in core::mem::take(&mut self.multicast_looped) {
+            for udp_socket in sockets
+                .items_mut()
+                .filter_map(|i| udp::Socket::downcast_mut(&mut i.socket))
+            {
+                if udp_socket.accepts(self, &looped.ip_repr, &looped.udp_repr) {
+                    udp_socket.process(
+                        self,
+                        looped.meta,
+                        &looped.ip_repr,
+                        &looped.udp_repr,
+                        &looped.payload,
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
     #[cfg(any(feature = "socket-udp", feature = "socket-dns"))]
     #[allow(clippy::too_many_arguments)]
     fn process_udp<'frame>(
@@ -1284,6 +2347,42 @@ impl InterfaceInner {
         udp_payload: &'frame [u8],
         ip_payload: &'frame [u8],
     ) -> Option<IpPacket<'frame>> {
+        // A multicast datagram from one of our addresses and the endpoint of
+        // one of our sockets is our own transmission, reflected by the device.
+        // With `alloc`, a local copy was already delivered when it was sent.
+        #[cfg(feature = "socket-udp")]
+        if ip_repr.dst_addr().is_multicast() && self.has_ip_addr(ip_repr.src_addr()) {
+            let sender = sockets
+                .items()
+                .filter_map(|i| udp::Socket::downcast(&i.socket))
+                .find(|udp_socket| {
+                    let endpoint = udp_socket.endpoint();
+                    endpoint.port == udp_repr.src_port
+                        && endpoint
+                            .addr
+                            .map_or(true, |addr| addr == ip_repr.src_addr())
+                });
+            match sender {
+                Some(sender) => {
+                    let multicast_loop = cfg!(not(feature = "alloc"))
+                        && sender.multicast_loop().unwrap_or(self.multicast_loop);
+                    if !multicast_loop {
+                        net_debug!("dropping reflected copy of own multicast datagram");
+                        return None;
+                    }
+                }
+                // Not sent by any of our sockets, so it can only be forged.
+                None if self.drop_own_source && !ip_repr.src_addr().is_loopback() => {
+                    net_debug!(
+                        "dropping packet from our own address {}",
+                        ip_repr.src_addr()
+                    );
+                    return None;
+                }
+                None => (),
+            }
+        }
+
         #[cfg(feature = "socket-udp")]
         for udp_socket in sockets
             .items_mut()
@@ -1364,6 +2463,10 @@ impl InterfaceInner {
             .filter_map(|i| tcp::Socket::downcast_mut(&mut i.socket))
         {
             if tcp_socket.accepts(self, &ip_repr, &tcp_repr) {
+                if !tcp_socket.authenticate(&ip_repr, &tcp_packet, &tcp_repr) {
+                    net_debug!("TCP segment failed authentication, dropping");
+                    return None;
+                }
                 return tcp_socket
                     .process(self, &ip_repr, &tcp_repr)
                     .map(|(ip, tcp)| IpPacket::new(ip, IpPayload::Tcp(tcp)));
@@ -1373,6 +2476,9 @@ impl InterfaceInner {
         if tcp_repr.control == TcpControl::Rst {
             // Never reply to a TCP RST packet with another TCP RST packet.
             None
+        } else if !self.tcp_reset_unbound {
+            net_debug!("no socket accepts TCP segment, dropping");
+            None
         } else {
             // The packet wasn't handled by a socket, send a TCP RST packet.
             let (ip, tcp) = tcp::Socket::rst_reply(&ip_repr, &tcp_repr);
@@ -1408,6 +2514,11 @@ impl InterfaceInner {
                     arp_repr.emit(&mut packet);
                 })
             }
+            #[cfg(feature = "alloc")]
+            EthernetPacket::Ip(packet) => {
+                self.dispatch_ip_or_queue(tx_token, PacketMeta::default(), packet, frag)
+            }
+            #[cfg(not(feature = "alloc"))]
             EthernetPacket::Ip(packet) => {
                 self.dispatch_ip(tx_token, PacketMeta::default(), packet, frag)
             }
@@ -1431,6 +2542,16 @@ impl InterfaceInner {
         self.routes.lookup(addr, timestamp)
     }
 
+    /// Return the next hop towards `addr` like [route](#method.route), and
+    /// remember the route found in the route cache.
+    fn route_and_cache(&mut self, addr: &IpAddress, timestamp: Instant) -> Option<IpAddress> {
+        if self.in_same_network(addr) || addr.is_broadcast() {
+            return Some(*addr);
+        }
+
+        self.routes.lookup_and_cache(addr, timestamp)
+    }
+
     fn has_neighbor(&self, addr: &IpAddress) -> bool {
         match self.route(addr, self.now) {
             Some(_routed_addr) => match self.caps.medium {
@@ -1503,21 +2624,48 @@ impl InterfaceInner {
         }
 
         let dst_addr = self
-            .route(dst_addr, self.now)
+            .route_and_cache(dst_addr, self.now)
             .ok_or(DispatchError::NoRoute)?;
 
+        #[cfg(feature = "alloc")]
+        if let Some(hardware_addr) = self
+            .neighbor_resolver
+            .as_mut()
+            .and_then(|resolver| resolver(dst_addr))
+        {
+            return Ok((hardware_addr, tx_token));
+        }
+
         match self.neighbor_cache.lookup(&dst_addr, self.now) {
             NeighborAnswer::Found(hardware_addr) => return Ok((hardware_addr, tx_token)),
             NeighborAnswer::RateLimited => return Err(DispatchError::NeighborPending),
+            NeighborAnswer::Failed => return Err(DispatchError::NeighborFailed),
             _ => (), // XXX
         }
 
+        if !self.neighbor_cache.may_request(&dst_addr, self.now) {
+            return Err(DispatchError::NeighborFailed);
+        }
+
+        // If the mapping has just expired, confirm it with a unicast probe.
+        #[cfg(feature = "medium-ethernet")]
+        let probe_hardware_addr = match self.caps.medium {
+            Medium::Ethernet => self
+                .neighbor_cache
+                .probe_addr(&dst_addr, self.now)
+                .map(|addr| addr.ethernet_or_panic()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        };
+
         match (src_addr, dst_addr) {
             #[cfg(feature = "proto-ipv4")]
             (&IpAddress::Ipv4(src_addr), IpAddress::Ipv4(dst_addr)) => {
+                let dst_hardware_addr = probe_hardware_addr.unwrap_or(EthernetAddress::BROADCAST);
                 net_debug!(
-                    "address {} not in neighbor cache, sending ARP request",
-                    dst_addr
+                    "address {} not in neighbor cache, sending ARP request to {}",
+                    dst_addr,
+                    dst_hardware_addr
                 );
                 let src_hardware_addr = self.hardware_addr.ethernet_or_panic();
 
@@ -1525,13 +2673,13 @@ impl InterfaceInner {
                     operation: ArpOperation::Request,
                     source_hardware_addr: src_hardware_addr,
                     source_protocol_addr: src_addr,
-                    target_hardware_addr: EthernetAddress::BROADCAST,
+                    target_hardware_addr: dst_hardware_addr,
                     target_protocol_addr: dst_addr,
                 };
 
                 if let Err(e) =
                     self.dispatch_ethernet(tx_token, arp_repr.buffer_len(), |mut frame| {
-                        frame.set_dst_addr(EthernetAddress::BROADCAST);
+                        frame.set_dst_addr(dst_hardware_addr);
                         frame.set_ethertype(EthernetProtocol::Arp);
 
                         arp_repr.emit(&mut ArpPacket::new_unchecked(frame.payload_mut()))
@@ -1544,16 +2692,59 @@ impl InterfaceInner {
 
             #[cfg(feature = "proto-ipv6")]
             (&IpAddress::Ipv6(src_addr), IpAddress::Ipv6(dst_addr)) => {
-                net_debug!(
-                    "address {} not in neighbor cache, sending Neighbor Solicitation",
-                    dst_addr
-                );
-
                 let solicit = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
                     target_addr: dst_addr,
                     lladdr: Some(self.hardware_addr.into()),
                 });
 
+                #[cfg(feature = "medium-ethernet")]
+                if let Some(dst_hardware_addr) = probe_hardware_addr {
+                    net_debug!(
+                        "address {} not in neighbor cache, sending Neighbor Solicitation to {}",
+                        dst_addr,
+                        dst_hardware_addr
+                    );
+
+                    // The solicitation is sent directly to the cached hardware address, since
+                    // dispatching it as a unicast IP packet would require resolving it first.
+                    let ip_repr = IpRepr::Ipv6(Ipv6Repr {
+                        src_addr,
+                        dst_addr,
+                        next_header: IpProtocol::Icmpv6,
+                        payload_len: solicit.buffer_len(),
+                        hop_limit: 0xff,
+                    });
+                    let checksum_caps = self.caps.checksum.clone();
+                    if let Err(e) =
+                        self.dispatch_ethernet(tx_token, ip_repr.buffer_len(), |mut frame| {
+                            frame.set_dst_addr(dst_hardware_addr);
+                            frame.set_ethertype(EthernetProtocol::Ipv6);
+
+                            let payload = frame.payload_mut();
+                            ip_repr.emit(&mut *payload, &checksum_caps);
+                            solicit.emit(
+                                &src_addr.into(),
+                                &dst_addr.into(),
+                                &mut Icmpv6Packet::new_unchecked(
+                                    &mut payload[ip_repr.header_len()..],
+                                ),
+                                &checksum_caps,
+                            );
+                        })
+                    {
+                        net_debug!("Failed to dispatch NDISC solicit: {:?}", e);
+                        return Err(DispatchError::NeighborPending);
+                    }
+
+                    self.neighbor_cache.probe_sent(self.now);
+                    return Err(DispatchError::NeighborPending);
+                }
+
+                net_debug!(
+                    "address {} not in neighbor cache, sending Neighbor Solicitation",
+                    dst_addr
+                );
+
                 let packet = IpPacket::new_ipv6(
                     Ipv6Repr {
                         src_addr,
@@ -1578,7 +2769,12 @@ impl InterfaceInner {
         }
 
         // The request got dispatched, limit the rate on the cache.
-        self.neighbor_cache.limit_rate(self.now);
+        #[cfg(feature = "medium-ethernet")]
+        if probe_hardware_addr.is_some() {
+            self.neighbor_cache.probe_sent(self.now);
+            return Err(DispatchError::NeighborPending);
+        }
+        self.neighbor_cache.request_sent(dst_addr, self.now);
         Err(DispatchError::NeighborPending)
     }
 
@@ -1626,7 +2822,8 @@ impl InterfaceInner {
         let ipv4_id = self.get_ipv4_ident();
 
         // First we calculate the total length that we will have to emit.
-        let mut total_len = ip_repr.buffer_len();
+        let options_len = packet.options_len();
+        let mut total_len = ip_repr.buffer_len() + options_len;
 
         // Add the size of the Ethernet header if the medium is Ethernet.
         // 如果媒介是以太网，增加以太网帧的长度
@@ -1679,11 +2876,33 @@ impl InterfaceInner {
         let emit_ip = |repr: &IpRepr, mut tx_buffer: &mut [u8]| {
             repr.emit(&mut tx_buffer, &self.caps.checksum);
 
-            let payload = &mut tx_buffer[repr.header_len()..];
+            match repr {
+                #[cfg(feature = "proto-ipv4")]
+                IpRepr::Ipv4(_) if options_len != 0 || packet.dscp() != 0 => {
+                    let mut ipv4_packet = Ipv4PacketWire::new_unchecked(&mut tx_buffer[..]);
+                    #[cfg(feature = "proto-igmp")]
+                    if packet.router_alert() {
+                        ipv4_packet.set_router_alert(0);
+                    }
+                    ipv4_packet.set_dscp(packet.dscp());
+                    if caps.checksum.ipv4.tx() {
+                        ipv4_packet.fill_checksum();
+                    }
+                }
+                #[cfg(feature = "proto-ipv6")]
+                IpRepr::Ipv6(_) if packet.dscp() != 0 => {
+                    let mut ipv6_packet = Ipv6PacketWire::new_unchecked(&mut tx_buffer[..]);
+                    let ecn = ipv6_packet.traffic_class() & 0x03;
+                    ipv6_packet.set_traffic_class((packet.dscp() << 2) | ecn);
+                }
+                _ => (),
+            }
+
+            let payload = &mut tx_buffer[repr.header_len() + options_len..];
             packet.emit_payload(repr, payload, &caps)
         };
 
-        let total_ip_len = ip_repr.buffer_len();
+        let total_ip_len = ip_repr.buffer_len() + options_len;
 
         match &mut ip_repr {
             #[cfg(feature = "proto-ipv4")]
@@ -1698,7 +2917,7 @@ impl InterfaceInner {
                         // Calculate how much we will send now (including the Ethernet header).
                         let tx_len = self.caps.max_transmission_unit;
 
-                        let ip_header_len = repr.buffer_len();
+                        let ip_header_len = repr.buffer_len() + options_len;
                         let first_frag_ip_len = self.caps.ip_mtu();
 
                         if frag.buffer.len() < total_ip_len {
@@ -1725,13 +2944,14 @@ impl InterfaceInner {
                         frag.sent_bytes = first_frag_ip_len;
 
                         // Modify the IP header
-                        repr.payload_len = first_frag_ip_len - repr.buffer_len();
+                        repr.payload_len = first_frag_ip_len - ip_header_len;
 
                         // Emit the IP header to the buffer.
                         emit_ip(&ip_repr, &mut frag.buffer);
 
                         let mut ipv4_packet = Ipv4PacketWire::new_unchecked(&mut frag.buffer[..]);
                         frag.ipv4.ident = ipv4_id;
+                        frag.ipv4.dscp = packet.dscp();
                         ipv4_packet.set_ident(ipv4_id);
                         ipv4_packet.set_more_frags(true);
                         ipv4_packet.set_dont_frag(false);
@@ -1808,4 +3028,7 @@ enum DispatchError {
     /// the neighbor for it yet. Discovery has been initiated, dispatch
     /// should be retried later.
     NeighborPending,
+    /// Discovery of the neighbor for this packet failed recently, and will
+    /// not be retried until its negative cache entry expires.
+    NeighborFailed,
 }
@@ -0,0 +1,202 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use super::{DispatchError, Interface, InterfaceInner, IpPacket, IpPayload, NeighborCache};
+use crate::{
+    phy::{Device, PacketMeta, TxToken},
+    time::{Duration, Instant},
+    wire::{IpAddress, IpRepr},
+};
+
+/// The maximum number of packets waiting for neighbor resolution, for all
+/// destinations together.
+const NEIGHBOR_QUEUE_LEN: usize = 32;
+
+/// For how long a packet waits for the resolution of its neighbor before it
+/// is dropped; this leaves time for the usual number of discovery requests.
+const NEIGHBOR_QUEUE_TIMEOUT: Duration =
+    Duration::from_millis(NeighborCache::SILENT_TIME.total_millis() * 3);
+
+/// An outgoing packet waiting for the hardware address of its next hop.
+struct PendingPacket {
+    meta: PacketMeta,
+    ip_repr: IpRepr,
+    dscp: u8,
+    payload: Vec<u8>,
+    /// When discovery is requested again if the neighbor is still unknown.
+    retry_at: Instant,
+    expires_at: Instant,
+}
+
+/// The packets of an interface waiting for neighbor resolution.
+#[derive(Default)]
+pub(crate) struct NeighborQueue {
+    /// The maximum number of packets waiting for each destination; zero
+    /// disables queueing.
+    pub(crate) depth: usize,
+    packets: VecDeque<PendingPacket>,
+}
+
+impl NeighborQueue {
+    /// Return the time at which the queue needs attention.
+    pub(crate) fn poll_at(&self) -> Option<Instant> {
+        self.packets
+            .iter()
+            .map(|packet| packet.retry_at.min(packet.expires_at))
+            .min()
+    }
+}
+
+impl InterfaceInner {
+    /// Dispatch an IP packet, or keep a copy of it until its neighbor is
+    /// resolved.
+    ///
+    /// When the neighbor is unknown and the packet could be queued, a
+    /// discovery request is sent and `Ok(())` is returned; the packet is
+    /// transmitted later by [`Interface::neighbor_queue_egress`].
+    pub(super) fn dispatch_ip_or_queue<Tx: TxToken>(
+        &mut self,
+        tx_token: Tx,
+        meta: PacketMeta,
+        packet: IpPacket,
+        frag: &mut super::Fragmenter,
+    ) -> Result<(), DispatchError> {
+        let ip_repr = packet.ip_repr();
+        let dst_addr = ip_repr.dst_addr();
+        if self.neighbor_queue.depth == 0
+            || packet.options_len() != 0
+            || !dst_addr.is_unicast()
+            || self.is_broadcast(&dst_addr)
+            || self.has_neighbor(&dst_addr)
+        {
+            return self.dispatch_ip(tx_token, meta, packet, frag);
+        }
+
+        let queued = self
+            .neighbor_queue
+            .packets
+            .iter()
+            .filter(|pending| pending.ip_repr.dst_addr() == dst_addr)
+            .count();
+        if queued >= self.neighbor_queue.depth
+            || self.neighbor_queue.packets.len() >= NEIGHBOR_QUEUE_LEN
+        {
+            net_debug!("neighbor queue for {} full", dst_addr);
+            return self.dispatch_ip(tx_token, meta, packet, frag);
+        }
+
+        let mut payload = vec![0; ip_repr.payload_len()];
+        packet.emit_payload(&ip_repr, &mut payload, &self.caps);
+        let dscp = packet.dscp();
+
+        match self.dispatch_ip(tx_token, meta, packet, frag) {
+            Err(DispatchError::NeighborPending) => {
+                net_trace!(
+                    "queueing packet to {} until its neighbor is resolved",
+                    dst_addr
+                );
+                self.neighbor_queue.packets.push_back(PendingPacket {
+                    meta,
+                    ip_repr,
+                    dscp,
+                    payload,
+                    retry_at: self.now + NeighborCache::SILENT_TIME,
+                    expires_at: self.now + NEIGHBOR_QUEUE_TIMEOUT,
+                });
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Drop the packets waiting for the given destination.
+    fn drop_queued(&mut self, dst_addr: IpAddress) {
+        self.neighbor_queue
+            .packets
+            .retain(|pending| pending.ip_repr.dst_addr() != dst_addr);
+    }
+}
+
+impl Interface {
+    /// Set the maximum number of outgoing packets held for each destination
+    /// whose neighbor is being resolved.
+    ///
+    /// When the hardware address of the next hop of a packet is unknown, an
+    /// ARP request or Neighbor Solicitation is sent, and the packet is held
+    /// until the neighbor answers, instead of being left to its socket or, for
+    /// replies generated by the interface itself, dropped. Held packets are
+    /// dropped when the queue of their destination overflows, when the
+    /// resolution fails, or after a few seconds without an answer. A depth of
+    /// zero, the default, disables queueing.
+    pub fn set_neighbor_queue_depth(&mut self, depth: usize) {
+        self.inner.neighbor_queue.depth = depth;
+        if depth == 0 {
+            self.inner.neighbor_queue.packets.clear();
+        }
+    }
+
+    /// Get the maximum number of outgoing packets held for each destination
+    /// whose neighbor is being resolved.
+    ///
+    /// See [`set_neighbor_queue_depth`](Self::set_neighbor_queue_depth) for
+    /// details.
+    pub fn neighbor_queue_depth(&self) -> usize {
+        self.inner.neighbor_queue.depth
+    }
+
+    /// Send the packets whose neighbor has been resolved, and request the
+    /// resolution of the others again when it is due.
+    pub(crate) fn neighbor_queue_egress<D>(&mut self, device: &mut D) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        let now = self.inner.now;
+        let mut emitted_any = false;
+
+        let mut index = 0;
+        while index < self.inner.neighbor_queue.packets.len() {
+            let pending = &self.inner.neighbor_queue.packets[index];
+            let dst_addr = pending.ip_repr.dst_addr();
+            if now >= pending.expires_at {
+                net_debug!(
+                    "neighbor {} not resolved, dropping queued packets",
+                    dst_addr
+                );
+                self.inner.drop_queued(dst_addr);
+                continue;
+            }
+            if now < pending.retry_at && !self.inner.has_neighbor(&dst_addr) {
+                index += 1;
+                continue;
+            }
+
+            let tx_token = match device.transmit(now) {
+                Some(tx_token) => tx_token,
+                None => {
+                    self.inner.tx_exhausted();
+                    break;
+                }
+            };
+
+            let mut pending = self.inner.neighbor_queue.packets.remove(index).unwrap();
+            let packet = IpPacket::new(pending.ip_repr.clone(), IpPayload::Raw(&pending.payload))
+                .with_dscp(pending.dscp);
+            match self
+                .inner
+                .dispatch_ip(tx_token, pending.meta, packet, &mut self.fragmenter)
+            {
+                Ok(()) => emitted_any = true,
+                Err(DispatchError::NeighborPending) => {
+                    pending.retry_at = now + NeighborCache::SILENT_TIME;
+                    self.inner.neighbor_queue.packets.insert(index, pending);
+                    index += 1;
+                }
+                Err(err) => {
+                    net_debug!("failed to send queued packet: {:?}", err);
+                    self.inner.drop_queued(dst_addr);
+                }
+            }
+        }
+
+        emitted_any
+    }
+}
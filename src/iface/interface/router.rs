@@ -0,0 +1,367 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+use super::{DispatchError, Fragmenter, Interface, InterfaceInner, SocketSet, UrpfMode};
+#[cfg(feature = "medium-ethernet")]
+use crate::wire::EthernetProtocol;
+use crate::{
+    phy::{Device, Medium, TxToken},
+    time::{Duration, Instant},
+    wire::{fragment_ipv4, IpAddress, Ipv4Address, Ipv4Packet, Ipv4Repr},
+};
+
+/// The maximum number of packets waiting in each direction of the forwarding
+/// queues of an interface.
+const FORWARD_QUEUE_LEN: usize = 16;
+
+/// How often the resolution of the next hop of a routed packet is requested
+/// again, which is as often as the neighbor cache allows.
+const FORWARD_NEIGHBOR_RETRY: Duration = Duration::from_millis(1_000);
+
+/// For how long a routed packet waits for the resolution of its next hop
+/// before it is dropped; this leaves time for the usual number of discovery
+/// requests.
+const FORWARD_NEIGHBOR_TIMEOUT: Duration = Duration::from_millis(3_000);
+
+/// A routed packet waiting to be sent.
+pub(crate) struct Forwarded {
+    packet: Vec<u8>,
+    /// When discovery is requested again if the next hop is still unknown.
+    retry_at: Instant,
+    /// When the packet is dropped, once it is waiting for its next hop to be
+    /// resolved.
+    expires_at: Option<Instant>,
+}
+
+/// The IPv4 packets an interface passes to and from a [`Router`].
+///
+/// [`Router`]: crate::iface::Router
+#[derive(Default)]
+pub(crate) struct Forwarding {
+    pub(crate) enabled: bool,
+    /// Packets received for other hosts, waiting to be routed.
+    pub(crate) rx: VecDeque<Vec<u8>>,
+    /// Routed packets waiting to be sent.
+    pub(crate) tx: VecDeque<Forwarded>,
+}
+
+impl Forwarding {
+    /// Queue a packet received for another host.
+    pub(crate) fn receive(&mut self, packet: &[u8]) {
+        if self.rx.len() < FORWARD_QUEUE_LEN {
+            self.rx.push_back(packet.to_vec());
+        } else {
+            net_debug!("forwarding queue full, dropping packet");
+        }
+    }
+
+    /// Queue a routed packet for transmission.
+    pub(crate) fn send(&mut self, packet: Vec<u8>) {
+        if self.tx.len() < FORWARD_QUEUE_LEN {
+            self.tx.push_back(Forwarded {
+                packet,
+                retry_at: Instant::ZERO,
+                expires_at: None,
+            });
+        } else {
+            net_debug!("forwarding queue full, dropping packet");
+        }
+    }
+
+    /// Return the time at which the packets waiting for their next hop to be
+    /// resolved need attention.
+    pub(crate) fn poll_at(&self) -> Option<Instant> {
+        self.tx
+            .iter()
+            .filter_map(|forwarded| {
+                let expires_at = forwarded.expires_at?;
+                Some(forwarded.retry_at.min(expires_at))
+            })
+            .min()
+    }
+}
+
+impl InterfaceInner {
+    /// Check whether a received IPv4 packet has to be handed to the router.
+    pub(super) fn should_forward_ipv4(&self, ipv4_repr: &Ipv4Repr) -> bool {
+        self.forwarding.enabled
+            && self.is_unicast_v4(ipv4_repr.dst_addr)
+            && !self.has_ip_addr(ipv4_repr.dst_addr)
+    }
+
+    /// Send a routed IPv4 packet as is, framed for the medium of the interface.
+    fn dispatch_forwarded<Tx: TxToken>(
+        &mut self,
+        tx_token: Tx,
+        packet: &[u8],
+        frag: &mut Fragmenter,
+    ) -> Result<(), DispatchError> {
+        let dst_addr = Ipv4Packet::new_unchecked(packet).dst_addr();
+        match self.caps.medium {
+            #[cfg(feature = "medium-ethernet")]
+            Medium::Ethernet => {
+                // Resolve the next hop from one of our own addresses, not from
+                // the source of the packet.
+                let src_addr = match self.get_source_address_ipv4(dst_addr) {
+                    Some(src_addr) => src_addr,
+                    None => Ipv4Packet::new_unchecked(packet).src_addr(),
+                };
+                let (dst_hardware_addr, tx_token) = self.lookup_hardware_addr(
+                    tx_token,
+                    &IpAddress::Ipv4(src_addr),
+                    &IpAddress::Ipv4(dst_addr),
+                    frag,
+                )?;
+                let dst_hardware_addr = dst_hardware_addr.ethernet_or_panic();
+                self.dispatch_ethernet(tx_token, packet.len(), |mut frame| {
+                    frame.set_dst_addr(dst_hardware_addr);
+                    frame.set_ethertype(EthernetProtocol::Ipv4);
+                    frame.payload_mut().copy_from_slice(packet);
+                })
+            }
+            #[cfg(feature = "medium-ip")]
+            Medium::Ip => tx_token.consume(packet.len(), |tx_buffer| {
+                tx_buffer.copy_from_slice(packet);
+                Ok(())
+            }),
+            #[allow(unreachable_patterns)]
+            _ => {
+                net_debug!("cannot forward IPv4 packets over this medium");
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Interface {
+    /// Send the routed packets waiting in the forwarding queue.
+    ///
+    /// Packets whose next hop is being resolved are skipped, so that they
+    /// don't hold up the others, and dropped if it is not resolved in time.
+    pub(crate) fn forward_egress<D>(&mut self, device: &mut D) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        let now = self.inner.now;
+        let mut emitted_any = false;
+
+        let mut index = 0;
+        while index < self.inner.forwarding.tx.len() {
+            let mut forwarded = self.inner.forwarding.tx.remove(index).unwrap();
+            if matches!(forwarded.expires_at, Some(expires_at) if now >= expires_at) {
+                net_debug!("next hop not resolved, dropping forwarded packet");
+                continue;
+            }
+
+            let packet = &forwarded.packet;
+            let (total_len, ident, dst_addr) = match Ipv4Packet::new_checked(&packet[..]) {
+                Ok(ipv4_packet) => (
+                    ipv4_packet.total_len() as usize,
+                    ipv4_packet.ident(),
+                    ipv4_packet.dst_addr(),
+                ),
+                Err(_) => continue,
+            };
+            if now < forwarded.retry_at && !self.inner.has_neighbor(&dst_addr.into()) {
+                self.inner.forwarding.tx.insert(index, forwarded);
+                index += 1;
+                continue;
+            }
+
+            // Split a packet too large for this interface further, keeping its
+            // identification so that the destination can still reassemble it.
+            let ip_mtu = self.inner.caps.ip_mtu();
+            if total_len > ip_mtu {
+                match fragment_ipv4(packet, ip_mtu, ident) {
+                    Ok(fragments) => {
+                        for fragment in fragments.into_iter().rev() {
+                            self.inner.forwarding.tx.insert(
+                                index,
+                                Forwarded {
+                                    packet: fragment,
+                                    retry_at: forwarded.retry_at,
+                                    expires_at: forwarded.expires_at,
+                                },
+                            );
+                        }
+                    }
+                    Err(_) => net_debug!("cannot fragment packet to {}, dropping it", dst_addr),
+                }
+                continue;
+            }
+
+            let tx_token = match device.transmit(now) {
+                Some(tx_token) => tx_token,
+                None => {
+                    self.inner.tx_exhausted();
+                    self.inner.forwarding.tx.insert(index, forwarded);
+                    break;
+                }
+            };
+
+            match self.inner.dispatch_forwarded(
+                tx_token,
+                &packet[..total_len],
+                &mut self.fragmenter,
+            ) {
+                Ok(()) => emitted_any = true,
+                // A neighbor discovery request was sent instead; try again later.
+                Err(DispatchError::NeighborPending) => {
+                    forwarded.retry_at = now + FORWARD_NEIGHBOR_RETRY;
+                    forwarded
+                        .expires_at
+                        .get_or_insert(now + FORWARD_NEIGHBOR_TIMEOUT);
+                    self.inner.forwarding.tx.insert(index, forwarded);
+                    index += 1;
+                }
+                Err(err) => net_debug!("failed to forward packet: {:?}", err),
+            }
+        }
+
+        emitted_any
+    }
+}
+
+/// A router forwarding IPv4 packets between several interfaces.
+///
+/// Every interface of the router is polled with its own device, which may use
+/// any medium, through [`poll`](Self::poll). An IPv4 packet received on one
+/// interface for a host other than the interface itself is sent out of the
+/// first interface that has the host on-link, or else of the first interface
+/// with a route to it, after its time-to-live is decremented. The packet is
+/// framed for the medium of that interface, resolving the hardware address
+/// of the next hop if needed; it is transmitted the next time that interface
+/// is polled.
+///
+/// Apart from the time-to-live and the checksum, the IPv4 header is left as
+/// received, options and fragmentation fields included: fragments are
+/// forwarded without being reassembled, if the `proto-ipv4-fragmentation`
+/// feature is enabled, and dropped otherwise. A packet larger than the MTU of
+/// the egress interface is fragmented further, unless its Don't Fragment flag
+/// is set.
+///
+/// Packets with an expired time-to-live, without a route, or which would
+/// leave through the interface they arrived on are dropped, as are packets
+/// failing the reverse-path forwarding check of the interface they arrived
+/// on.
+#[derive(Default)]
+pub struct Router {
+    interfaces: Vec<Interface>,
+}
+
+impl Router {
+    /// Create a router without any interfaces.
+    pub fn new() -> Router {
+        Router::default()
+    }
+
+    /// Add an interface to the router, and return its index.
+    pub fn add_interface(&mut self, mut iface: Interface) -> usize {
+        iface.inner.forwarding.enabled = true;
+        self.interfaces.push(iface);
+        self.interfaces.len() - 1
+    }
+
+    /// Return a reference to the interface with the given index.
+    ///
+    /// # Panics
+    /// This function panics if there is no such interface.
+    pub fn interface(&self, index: usize) -> &Interface {
+        &self.interfaces[index]
+    }
+
+    /// Return a mutable reference to the interface with the given index.
+    ///
+    /// # Panics
+    /// This function panics if there is no such interface.
+    pub fn interface_mut(&mut self, index: usize) -> &mut Interface {
+        &mut self.interfaces[index]
+    }
+
+    /// Poll the interface with the given index, using its device and sockets,
+    /// and route the packets it received for other hosts.
+    ///
+    /// See [`Interface::poll`] for the meaning of the return value.
+    ///
+    /// # Panics
+    /// This function panics if there is no such interface.
+    pub fn poll<D>(
+        &mut self,
+        index: usize,
+        timestamp: Instant,
+        device: &mut D,
+        sockets: &mut SocketSet<'_>,
+    ) -> bool
+    where
+        D: Device + ?Sized,
+    {
+        self.route();
+        let readiness_may_have_changed = self.interfaces[index].poll(timestamp, device, sockets);
+        self.route();
+        readiness_may_have_changed
+    }
+
+    /// Move the packets received for other hosts to the forwarding queue of
+    /// their egress interface.
+    fn route(&mut self) {
+        for ingress in 0..self.interfaces.len() {
+            while let Some(mut packet) = self.interfaces[ingress].inner.forwarding.rx.pop_front() {
+                let mut ipv4_packet = match Ipv4Packet::new_checked(&mut packet[..]) {
+                    Ok(ipv4_packet) => ipv4_packet,
+                    Err(_) => continue,
+                };
+
+                let hop_limit = ipv4_packet.hop_limit();
+                if hop_limit <= 1 {
+                    net_debug!("time-to-live expired, dropping packet");
+                    continue;
+                }
+
+                if self.fails_urpf(ingress, ipv4_packet.src_addr()) {
+                    net_debug!(
+                        "dropping packet failing the reverse-path check from {}",
+                        ipv4_packet.src_addr()
+                    );
+                    continue;
+                }
+
+                let dst_addr = ipv4_packet.dst_addr();
+                match self.egress_index(dst_addr) {
+                    Some(egress) if egress != ingress => {
+                        ipv4_packet.set_hop_limit(hop_limit - 1);
+                        ipv4_packet.fill_checksum();
+                        self.interfaces[egress].inner.forwarding.send(packet);
+                    }
+                    _ => net_debug!("no route to {}, dropping packet", dst_addr),
+                }
+            }
+        }
+    }
+
+    /// Check whether a packet from the given source address received on the
+    /// interface with the given index must be dropped by the reverse-path
+    /// forwarding check of that interface, see [`Config::urpf`].
+    ///
+    /// [`Config::urpf`]: crate::iface::Config::urpf
+    fn fails_urpf(&self, ingress: usize, addr: Ipv4Address) -> bool {
+        match self.interfaces[ingress].inner.urpf {
+            _ if !addr.is_unicast() || addr.is_loopback() || addr.is_link_local() => false,
+            UrpfMode::Disabled => false,
+            UrpfMode::Strict => self.egress_index(addr) != Some(ingress),
+            UrpfMode::Loose => self.egress_index(addr).is_none(),
+        }
+    }
+
+    /// Return the index of the interface to send a packet for the given
+    /// address out of.
+    fn egress_index(&self, addr: Ipv4Address) -> Option<usize> {
+        let addr = IpAddress::Ipv4(addr);
+        self.interfaces
+            .iter()
+            .position(|iface| iface.inner.in_same_network(&addr))
+            .or_else(|| {
+                self.interfaces
+                    .iter()
+                    .position(|iface| iface.inner.routes.lookup(&addr, iface.inner.now).is_some())
+            })
+    }
+}
@@ -111,7 +111,7 @@ impl InterfaceInner {
             }
         } else {
             // Add the fragment to the packet assembler.
-            if let Err(e) = frag_slot.add(frag.payload(), offset) {
+            if let Err(e) = frag_slot.add(frag.payload(), offset, f.overlap_policy) {
                 net_debug!("fragmentation error: {:?}", e);
                 return None;
             }
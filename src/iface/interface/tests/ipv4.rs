@@ -411,6 +411,68 @@ fn test_handle_valid_arp_request(#[case] medium: Medium) {
     );
 }
 
+#[rstest]
+#[case(Medium::Ethernet)]
+#[cfg(feature = "medium-ethernet")]
+fn test_handle_arp_probe(#[case] medium: Medium) {
+    let (mut iface, mut sockets, _device) = setup(medium);
+
+    let local_hw_addr = EthernetAddress([0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x00]);
+    let link_local_addr = Ipv4Address([169, 254, 10, 20]);
+
+    let probe_for = |target_protocol_addr| {
+        let repr = ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Request,
+            source_hardware_addr: remote_hw_addr,
+            source_protocol_addr: Ipv4Address::UNSPECIFIED,
+            target_hardware_addr: EthernetAddress::default(),
+            target_protocol_addr,
+        };
+        let mut eth_bytes = vec![0u8; 42];
+        let mut frame = EthernetFrame::new_unchecked(&mut eth_bytes);
+        frame.set_dst_addr(EthernetAddress::BROADCAST);
+        frame.set_src_addr(remote_hw_addr);
+        frame.set_ethertype(EthernetProtocol::Arp);
+        repr.emit(&mut ArpPacket::new_unchecked(frame.payload_mut()));
+        eth_bytes
+    };
+
+    // Nothing to defend before the link-local address is assigned.
+    assert_eq!(
+        iface.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &probe_for(link_local_addr),
+            &mut iface.fragments
+        ),
+        None
+    );
+
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs
+            .push(IpCidr::new(IpAddress::Ipv4(link_local_addr), 16))
+            .unwrap();
+    });
+
+    // The probe is answered, so that the other host picks another address.
+    assert_eq!(
+        iface.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &probe_for(link_local_addr),
+            &mut iface.fragments
+        ),
+        Some(EthernetPacket::Arp(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Reply,
+            source_hardware_addr: local_hw_addr,
+            source_protocol_addr: link_local_addr,
+            target_hardware_addr: remote_hw_addr,
+            target_protocol_addr: Ipv4Address::UNSPECIFIED,
+        }))
+    );
+}
+
 #[rstest]
 #[case(Medium::Ethernet)]
 #[cfg(feature = "medium-ethernet")]
@@ -709,6 +771,44 @@ fn test_handle_igmp(#[case] medium: Medium) {
     }
 }
 
+#[rstest]
+#[case(Medium::Ip)]
+#[cfg(all(feature = "proto-igmp", feature = "medium-ip"))]
+#[case(Medium::Ethernet)]
+#[cfg(all(feature = "proto-igmp", feature = "medium-ethernet"))]
+fn test_igmp_router_alert(#[case] medium: Medium) {
+    let (mut iface, _, mut device) = setup(medium);
+
+    let group = Ipv4Address::new(224, 0, 0, 56);
+    let timestamp = Instant::now();
+    iface
+        .join_multicast_group(&mut device, group, timestamp)
+        .unwrap();
+    iface
+        .leave_multicast_group(&mut device, group, timestamp)
+        .unwrap();
+
+    let frames = recv_all(&mut device, timestamp);
+    assert_eq!(frames.len(), 2);
+    for frame in &frames {
+        let ip_bytes = match medium {
+            #[cfg(feature = "medium-ethernet")]
+            Medium::Ethernet => &frame[EthernetFrame::<&[u8]>::header_len()..],
+            _ => &frame[..],
+        };
+        let ipv4_packet = Ipv4PacketWire::new_checked(ip_bytes).unwrap();
+        assert_eq!(ipv4_packet.header_len(), 24);
+        assert_eq!(ipv4_packet.router_alert(), Some(0));
+        assert!(ipv4_packet.verify_checksum());
+
+        let ipv4_repr = Ipv4Repr::parse(&ipv4_packet, &ChecksumCapabilities::default()).unwrap();
+        assert_eq!(ipv4_repr.next_header, IpProtocol::Igmp);
+        assert_eq!(ipv4_repr.hop_limit, 1);
+        let igmp_packet = IgmpPacket::new_checked(ipv4_packet.payload()).unwrap();
+        assert!(IgmpRepr::parse(&igmp_packet).is_ok());
+    }
+}
+
 #[rstest]
 #[case(Medium::Ip)]
 #[cfg(all(feature = "socket-raw", feature = "medium-ip"))]
@@ -968,3 +1068,2121 @@ fn test_icmp_reply_size(#[case] medium: Medium) {
         ))
     );
 }
+
+#[cfg(feature = "socket-dhcpv4")]
+fn dhcp_frames_from_unaddressed(unaddressed_dhcpv4: bool) -> Vec<Vec<u8>> {
+    let mut device = Loopback::new(Medium::Ethernet);
+    let mut config = Config::new(HardwareAddress::Ethernet(EthernetAddress([
+        0x02, 0x02, 0x02, 0x02, 0x02, 0x02,
+    ])));
+    config.unaddressed_dhcpv4 = unaddressed_dhcpv4;
+    let mut iface = Interface::new(config, &mut device, Instant::ZERO);
+    assert_eq!(iface.ipv4_addr(), None);
+
+    let mut sockets = SocketSet::new(vec![]);
+    sockets.add(crate::socket::dhcpv4::Socket::new());
+
+    // Only run the egress half of `poll`, as the loopback device would
+    // otherwise hand our own frames straight back to us.
+    iface.socket_egress(&mut device, &mut sockets);
+
+    let mut frames = Vec::new();
+    while let Some((rx, _tx)) = device.receive(Instant::ZERO) {
+        rx.consume(|frame| frames.push(frame.to_vec()));
+    }
+    frames
+}
+
+#[test]
+#[cfg(feature = "socket-dhcpv4")]
+fn test_dhcp_discover_without_address() {
+    let frames = dhcp_frames_from_unaddressed(true);
+    assert_eq!(frames.len(), 1);
+
+    let eth_frame = EthernetFrame::new_checked(&frames[0][..]).unwrap();
+    assert_eq!(eth_frame.dst_addr(), EthernetAddress::BROADCAST);
+    assert_eq!(eth_frame.ethertype(), EthernetProtocol::Ipv4);
+
+    let ipv4_packet = Ipv4PacketWire::new_checked(eth_frame.payload()).unwrap();
+    assert_eq!(ipv4_packet.src_addr(), Ipv4Address::UNSPECIFIED);
+    assert_eq!(ipv4_packet.dst_addr(), Ipv4Address::BROADCAST);
+    assert_eq!(ipv4_packet.next_header(), IpProtocol::Udp);
+
+    let udp_packet = UdpPacket::new_checked(ipv4_packet.payload()).unwrap();
+    assert_eq!(udp_packet.src_port(), DHCP_CLIENT_PORT);
+    assert_eq!(udp_packet.dst_port(), DHCP_SERVER_PORT);
+
+    let dhcp_packet = DhcpPacket::new_checked(udp_packet.payload()).unwrap();
+    let dhcp_repr = DhcpRepr::parse(&dhcp_packet).unwrap();
+    assert_eq!(dhcp_repr.message_type, DhcpMessageType::Discover);
+}
+
+#[test]
+#[cfg(feature = "socket-dhcpv4")]
+fn test_dhcp_silent_without_address_when_disabled() {
+    assert!(dhcp_frames_from_unaddressed(false).is_empty());
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ethernet"))]
+fn test_arp_negative_cache() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.clear();
+        ip_addrs
+            .push(IpCidr::new(IpAddress::v4(192, 168, 1, 1), 24))
+            .unwrap();
+    });
+    iface.set_neighbor_negative_cache_ttl(Duration::from_secs(10));
+
+    let udp_rx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 15]);
+    let udp_tx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 15]);
+    let mut udp_socket = udp::Socket::new(udp_rx_buffer, udp_tx_buffer);
+    udp_socket.bind(6969).unwrap();
+    udp_socket
+        .send_slice(b"hello", (IpAddress::v4(192, 168, 1, 5), 6969))
+        .unwrap();
+    sockets.add(udp_socket);
+
+    // Count the ARP requests sent at each second; the neighbor never answers.
+    let mut arp_requests = [0; 16];
+    for (secs, count) in arp_requests.iter_mut().enumerate() {
+        iface.inner.now = Instant::from_secs(secs as i64);
+        iface.socket_egress(&mut device, &mut sockets);
+
+        while let Some((rx, _tx)) = device.receive(iface.inner.now) {
+            rx.consume(|frame| {
+                let eth_frame = EthernetFrame::new_checked(frame).unwrap();
+                assert_eq!(eth_frame.ethertype(), EthernetProtocol::Arp);
+                *count += 1;
+            });
+        }
+    }
+
+    // Three attempts, silence for the negative cache duration, then retry.
+    assert_eq!(
+        arp_requests,
+        [1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1]
+    );
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ethernet"))]
+fn test_arp_negative_cache_after_unicast_probes() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.clear();
+        ip_addrs
+            .push(IpCidr::new(IpAddress::v4(192, 168, 1, 1), 24))
+            .unwrap();
+    });
+    iface.set_neighbor_negative_cache_ttl(Duration::from_secs(10));
+
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x05]);
+    iface.inner.neighbor_cache.fill(
+        IpAddress::v4(192, 168, 1, 5),
+        HardwareAddress::Ethernet(remote_hw_addr),
+        Instant::ZERO,
+    );
+
+    let udp_rx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 15]);
+    let udp_tx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 15]);
+    let mut udp_socket = udp::Socket::new(udp_rx_buffer, udp_tx_buffer);
+    udp_socket.bind(6969).unwrap();
+    udp_socket
+        .send_slice(b"hello", (IpAddress::v4(192, 168, 1, 5), 6969))
+        .unwrap();
+    sockets.add(udp_socket);
+
+    // Record the destination of the ARP requests sent at each second, once
+    // the mapping has expired; the neighbor never answers.
+    let mut arp_requests = [None; 8];
+    for (secs, dst_addr) in arp_requests.iter_mut().enumerate() {
+        iface.inner.now = Instant::from_secs(61 + secs as i64);
+        iface.socket_egress(&mut device, &mut sockets);
+
+        while let Some((rx, _tx)) = device.receive(iface.inner.now) {
+            rx.consume(|frame| {
+                let eth_frame = EthernetFrame::new_checked(frame).unwrap();
+                assert_eq!(eth_frame.ethertype(), EthernetProtocol::Arp);
+                assert_eq!(*dst_addr, None);
+                *dst_addr = Some(eth_frame.dst_addr());
+            });
+        }
+    }
+
+    // The unicast probes don't use up the broadcast requests.
+    let unicast = Some(remote_hw_addr);
+    let broadcast = Some(EthernetAddress::BROADCAST);
+    assert_eq!(
+        arp_requests,
+        [unicast, unicast, unicast, broadcast, broadcast, broadcast, None, None]
+    );
+}
+
+#[cfg(feature = "medium-ethernet")]
+fn arp_request_for(target_protocol_addr: Ipv4Address) -> std::vec::Vec<u8> {
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x00]);
+    let repr = ArpRepr::EthernetIpv4 {
+        operation: ArpOperation::Request,
+        source_hardware_addr: remote_hw_addr,
+        source_protocol_addr: Ipv4Address([0x7f, 0x00, 0x00, 0x09]),
+        target_hardware_addr: EthernetAddress::default(),
+        target_protocol_addr,
+    };
+
+    let mut eth_bytes = vec![0u8; 42];
+    let mut frame = EthernetFrame::new_unchecked(&mut eth_bytes);
+    frame.set_dst_addr(EthernetAddress::BROADCAST);
+    frame.set_src_addr(remote_hw_addr);
+    frame.set_ethertype(EthernetProtocol::Arp);
+    repr.emit(&mut ArpPacket::new_unchecked(frame.payload_mut()));
+    eth_bytes
+}
+
+#[rstest]
+#[case(Medium::Ethernet)]
+#[cfg(feature = "medium-ethernet")]
+fn test_arp_announce_scope_assigned(#[case] medium: Medium) {
+    // Two interfaces on the same link; only the first one owns the address.
+    let (mut owner, mut sockets, _device) = setup(medium);
+    let (mut other, _, _device) = setup(medium);
+    other.update_ip_addrs(|ip_addrs| {
+        ip_addrs[0] = IpCidr::new(IpAddress::v4(127, 0, 0, 2), 8);
+    });
+    assert_eq!(other.inner.arp_announce_scope, ArpAnnounceScope::Assigned);
+
+    let local_ip_addr = Ipv4Address([0x7f, 0x00, 0x00, 0x01]);
+    let frame = arp_request_for(local_ip_addr);
+
+    assert!(matches!(
+        owner.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut owner.fragments
+        ),
+        Some(EthernetPacket::Arp(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Reply,
+            source_protocol_addr,
+            ..
+        })) if source_protocol_addr == local_ip_addr
+    ));
+    assert_eq!(
+        other.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut other.fragments
+        ),
+        None
+    );
+}
+
+#[rstest]
+#[case(Medium::Ethernet)]
+#[cfg(feature = "medium-ethernet")]
+fn test_arp_announce_scope_any_ip(#[case] medium: Medium) {
+    let (mut iface, mut sockets, _device) = setup(medium);
+    iface.set_any_ip(true);
+    iface.routes_mut().update(|routes| {
+        routes
+            .push(crate::iface::Route {
+                cidr: IpCidr::new(IpAddress::v4(10, 0, 0, 0), 24),
+                via_router: IpAddress::v4(127, 0, 0, 1),
+                preferred_until: None,
+                expires_at: None,
+            })
+            .unwrap();
+    });
+
+    let routed_ip_addr = Ipv4Address([0x0a, 0x00, 0x00, 0x05]);
+    let frame = arp_request_for(routed_ip_addr);
+
+    // Addresses accepted through AnyIP are not answered for by default.
+    assert_eq!(
+        iface.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut iface.fragments
+        ),
+        None
+    );
+
+    iface.inner.arp_announce_scope = ArpAnnounceScope::AnyIp;
+    assert!(matches!(
+        iface.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut iface.fragments
+        ),
+        Some(EthernetPacket::Arp(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Reply,
+            source_protocol_addr,
+            ..
+        })) if source_protocol_addr == routed_ip_addr
+    ));
+}
+
+#[test]
+#[cfg(all(feature = "socket-raw", feature = "medium-ethernet"))]
+fn test_inject_raw_icmp_echo() {
+    let (mut iface, _sockets, mut device) = setup(Medium::Ethernet);
+
+    let remote_ip_addr = Ipv4Address([0x7f, 0x00, 0x00, 0x02]);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]);
+    iface.inner.neighbor_cache.fill(
+        IpAddress::Ipv4(remote_ip_addr),
+        HardwareAddress::Ethernet(remote_hw_addr),
+        Instant::ZERO,
+    );
+
+    let echo_repr = Icmpv4Repr::EchoRequest {
+        ident: 0x1234,
+        seq_no: 0xabcd,
+        data: b"hello",
+    };
+    let ip_repr = Ipv4Repr {
+        src_addr: Ipv4Address([0x7f, 0x00, 0x00, 0x01]),
+        dst_addr: remote_ip_addr,
+        next_header: IpProtocol::Icmp,
+        payload_len: echo_repr.buffer_len(),
+        hop_limit: 64,
+    };
+
+    let mut bytes = vec![0u8; ip_repr.buffer_len() + echo_repr.buffer_len()];
+    let mut packet = Ipv4PacketWire::new_unchecked(&mut bytes);
+    ip_repr.emit(&mut packet, &ChecksumCapabilities::default());
+    echo_repr.emit(
+        &mut Icmpv4Packet::new_unchecked(packet.payload_mut()),
+        &ChecksumCapabilities::default(),
+    );
+
+    assert_eq!(iface.inject_raw(&mut device, &bytes), Ok(()));
+
+    let (rx, _tx) = device.receive(Instant::ZERO).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(eth_frame.dst_addr(), remote_hw_addr);
+        assert_eq!(eth_frame.ethertype(), EthernetProtocol::Ipv4);
+
+        let ipv4_packet = Ipv4PacketWire::new_checked(eth_frame.payload()).unwrap();
+        let checksum_caps = ChecksumCapabilities::default();
+        assert_eq!(
+            Ipv4Repr::parse(&ipv4_packet, &checksum_caps).unwrap(),
+            ip_repr
+        );
+        let icmp_packet = Icmpv4Packet::new_checked(ipv4_packet.payload()).unwrap();
+        assert_eq!(
+            Icmpv4Repr::parse(&icmp_packet, &checksum_caps).unwrap(),
+            echo_repr
+        );
+    });
+    assert!(device.receive(Instant::ZERO).is_none());
+}
+
+#[test]
+#[cfg(all(feature = "socket-raw", feature = "medium-ip"))]
+fn test_inject_raw_malformed() {
+    let (mut iface, _sockets, mut device) = setup(Medium::Ip);
+
+    assert_eq!(
+        iface.inject_raw(&mut device, &[]),
+        Err(InjectError::Malformed)
+    );
+    assert_eq!(
+        iface.inject_raw(&mut device, &[0x45, 0x00, 0x00]),
+        Err(InjectError::Malformed)
+    );
+    assert_eq!(
+        iface.inject_raw(&mut device, &[0x10; 40]),
+        Err(InjectError::Malformed)
+    );
+    assert!(device.receive(Instant::ZERO).is_none());
+}
+
+#[rstest]
+#[case(Medium::Ip)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+#[case(Medium::Ethernet)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ethernet"))]
+fn test_tcp_simultaneous_open(#[case] medium: Medium) {
+    let (mut iface, mut sockets, mut device) = setup(medium);
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut socket_a = tcp_socket();
+    socket_a
+        .connect(iface.context(), (addr, 2000), (addr, 1000))
+        .unwrap();
+    let mut socket_b = tcp_socket();
+    socket_b
+        .connect(iface.context(), (addr, 1000), (addr, 2000))
+        .unwrap();
+    let handle_a = sockets.add(socket_a);
+    let handle_b = sockets.add(socket_b);
+
+    // Both SYNs are sent before either is received, so they cross.
+    iface.socket_egress(&mut device, &mut sockets);
+
+    for _ in 0..4 {
+        iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    }
+
+    assert_eq!(
+        sockets.get::<tcp::Socket>(handle_a).state(),
+        tcp::State::Established
+    );
+    assert_eq!(
+        sockets.get::<tcp::Socket>(handle_b).state(),
+        tcp::State::Established
+    );
+}
+
+#[test]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_tcp_max_advertised_window() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let addr = IpAddress::v4(127, 0, 0, 1);
+    let mut server = tcp::Socket::new(
+        tcp::SocketBuffer::new(vec![0; 4096]),
+        tcp::SocketBuffer::new(vec![0; 64]),
+    );
+    server.set_max_advertised_window(Some(256));
+    server.listen(1234).unwrap();
+    let mut client = tcp::Socket::new(
+        tcp::SocketBuffer::new(vec![0; 64]),
+        tcp::SocketBuffer::new(vec![0; 4096]),
+    );
+    client
+        .connect(iface.context(), (addr, 1234), (addr, 65000))
+        .unwrap();
+    let server = sockets.add(server);
+    let client = sockets.add(client);
+
+    // Look at every segment on its way through the loopback device, without
+    // the server application ever reading.
+    let checksum_caps = ChecksumCapabilities::default();
+    let mut last_ack = None;
+    let mut sent = 0;
+    let mut timestamp = Instant::ZERO;
+    for _ in 0..500 {
+        let client = sockets.get_mut::<tcp::Socket>(client);
+        if client.may_send() {
+            sent += client.send_slice(&[0x5a; 4096][sent..]).unwrap();
+        }
+
+        timestamp += Duration::from_millis(1);
+        iface.inner.now = timestamp;
+        iface.socket_egress(&mut device, &mut sockets);
+
+        let mut frames = std::vec::Vec::new();
+        while let Some((rx, _tx)) = device.receive(timestamp) {
+            rx.consume(|frame| frames.push(frame.to_vec()));
+        }
+        for frame in frames {
+            let ipv4_packet = Ipv4PacketWire::new_checked(&frame[..]).unwrap();
+            let tcp_packet = TcpPacket::new_checked(ipv4_packet.payload()).unwrap();
+            let tcp_repr = TcpRepr::parse(
+                &tcp_packet,
+                &ipv4_packet.src_addr().into(),
+                &ipv4_packet.dst_addr().into(),
+                &checksum_caps,
+            )
+            .unwrap();
+            if tcp_repr.src_port == 1234 {
+                assert!(tcp_repr.window_len <= 256);
+                last_ack = tcp_repr.ack_number;
+            } else if let Some(ack) = last_ack {
+                assert!(tcp_repr.seq_number + tcp_repr.payload.len() - ack <= 256);
+            }
+
+            let tx_token = device.transmit(timestamp).unwrap();
+            tx_token.consume(frame.len(), |buf| buf.copy_from_slice(&frame));
+        }
+        iface.socket_ingress(&mut device, &mut sockets);
+    }
+
+    // Everything was transferred, and buffered beyond the advertised window.
+    assert_eq!(sockets.get::<tcp::Socket>(server).recv_queue(), 4096);
+}
+
+#[test]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_tcp_rebind_local() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+    let addr = Ipv4Address::new(127, 0, 0, 1);
+    let other_addr = Ipv4Address::new(127, 0, 0, 2);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs
+            .push(IpCidr::new(IpAddress::Ipv4(other_addr), 8))
+            .unwrap();
+    });
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let mut server = tcp_socket();
+    server.listen(80).unwrap();
+    let mut client = tcp_socket();
+    client
+        .connect(iface.context(), (addr, 80), (addr, 1000))
+        .unwrap();
+    let server = sockets.add(server);
+    let client = sockets.add(client);
+    for _ in 0..4 {
+        iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    }
+    assert_eq!(
+        sockets.get::<tcp::Socket>(server).state(),
+        tcp::State::Established
+    );
+    assert_eq!(
+        sockets.get::<tcp::Socket>(client).state(),
+        tcp::State::Established
+    );
+
+    // Only an address of the interface can be used.
+    let socket = sockets.get_mut::<tcp::Socket>(server);
+    assert_eq!(
+        socket.rebind_local(iface.context(), IpAddress::v4(10, 0, 0, 1)),
+        Err(tcp::ConnectError::Unaddressable)
+    );
+    assert_eq!(
+        socket.rebind_local(iface.context(), other_addr.into()),
+        Ok(())
+    );
+    socket.send_slice(b"hello").unwrap();
+
+    // The data is sent from the new address.
+    iface.socket_egress(&mut device, &mut sockets);
+    let (rx, _tx) = device.receive(Instant::ZERO).unwrap();
+    rx.consume(|frame| {
+        let ipv4_packet = Ipv4PacketWire::new_checked(&*frame).unwrap();
+        assert_eq!(ipv4_packet.src_addr(), other_addr);
+        assert_eq!(ipv4_packet.dst_addr(), addr);
+        let tcp_packet = TcpPacket::new_checked(ipv4_packet.payload()).unwrap();
+        assert_eq!(tcp_packet.src_port(), 80);
+        assert_eq!(tcp_packet.payload(), b"hello");
+    });
+    assert!(device.receive(Instant::ZERO).is_none());
+}
+
+#[test]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_poll_until_idle() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut server = tcp_socket();
+    server.listen(1234).unwrap();
+    let mut client = tcp_socket();
+    client
+        .connect(iface.context(), (addr, 1234), (addr, 65000))
+        .unwrap();
+    let server = sockets.add(server);
+    let client = sockets.add(client);
+
+    let mut timestamp = Instant::ZERO;
+    let mut clock = || {
+        timestamp += Duration::from_millis(1);
+        timestamp
+    };
+    let iters = iface.poll_until_idle(&mut device, &mut sockets, &mut clock, 16);
+    assert!(iters > 0 && iters < 16);
+    assert_eq!(
+        sockets.get::<tcp::Socket>(server).state(),
+        tcp::State::Established
+    );
+    assert_eq!(
+        sockets.get::<tcp::Socket>(client).state(),
+        tcp::State::Established
+    );
+
+    sockets
+        .get_mut::<tcp::Socket>(client)
+        .send_slice(b"hello")
+        .unwrap();
+    let iters = iface.poll_until_idle(&mut device, &mut sockets, &mut clock, 16);
+    assert!(iters > 0 && iters < 16);
+
+    let mut buffer = [0; 8];
+    let len = sockets
+        .get_mut::<tcp::Socket>(server)
+        .recv_slice(&mut buffer)
+        .unwrap();
+    assert_eq!(&buffer[..len], b"hello");
+
+    // Nothing is left to do.
+    assert_eq!(
+        iface.poll_until_idle(&mut device, &mut sockets, &mut clock, 16),
+        0
+    );
+    assert!(device.queue.is_empty());
+}
+
+#[rstest]
+#[case(None, tcp::State::Established)]
+#[case(Some(tcp::CloseMode::Rst), tcp::State::Closed)]
+#[case(Some(tcp::CloseMode::GracefulBestEffort), tcp::State::CloseWait)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_tcp_close_on_drop(#[case] mode: Option<tcp::CloseMode>, #[case] server_state: tcp::State) {
+    use crate::wire::{Ipv4Packet, TcpPacket};
+
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut server = tcp_socket();
+    server.listen(1234).unwrap();
+    let mut client = tcp_socket();
+    client
+        .connect(iface.context(), (addr, 1234), (addr, 65000))
+        .unwrap();
+    client.set_close_on_drop(mode);
+    let server = sockets.add(server);
+    let client = sockets.add(client);
+
+    let mut timestamp = Instant::ZERO;
+    let mut clock = || {
+        timestamp += Duration::from_millis(1);
+        timestamp
+    };
+    iface.poll_until_idle(&mut device, &mut sockets, &mut clock, 16);
+    assert_eq!(
+        sockets.get::<tcp::Socket>(client).state(),
+        tcp::State::Established
+    );
+
+    let removed = sockets.remove(client);
+    // The removed socket is left as is.
+    assert_eq!(
+        tcp::Socket::downcast(&removed).unwrap().state(),
+        tcp::State::Established
+    );
+
+    iface.socket_egress(&mut device, &mut sockets);
+    let Some(mode) = mode else {
+        assert!(device.queue.is_empty());
+        iface.poll_until_idle(&mut device, &mut sockets, &mut clock, 16);
+        assert_eq!(sockets.get::<tcp::Socket>(server).state(), server_state);
+        return;
+    };
+    assert_eq!(device.queue.len(), 1);
+    let frame = device.queue.pop_front().unwrap();
+    let packet = Ipv4Packet::new_checked(&frame[..]).unwrap();
+    let tcp_packet = TcpPacket::new_checked(packet.payload()).unwrap();
+    assert_eq!(tcp_packet.src_port(), 65000);
+    assert_eq!(tcp_packet.rst(), mode == tcp::CloseMode::Rst);
+    assert_eq!(tcp_packet.fin(), mode == tcp::CloseMode::GracefulBestEffort);
+
+    // The segment is sent only once, and accepted by the remote endpoint.
+    iface.socket_egress(&mut device, &mut sockets);
+    assert!(device.queue.is_empty());
+    iface.inner.process_ip(
+        &mut sockets,
+        PacketMeta::default(),
+        &frame[..],
+        &mut iface.fragments,
+    );
+    assert_eq!(sockets.get::<tcp::Socket>(server).state(), server_state);
+}
+
+#[test]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_xor_transforms() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    // The ingress transform checks that the frame was scrambled by the egress
+    // transform before restoring it.
+    let egress_count = Arc::new(AtomicUsize::new(0));
+    let ingress_count = Arc::new(AtomicUsize::new(0));
+    let count = egress_count.clone();
+    iface.set_egress_transform(move |frame| {
+        count.fetch_add(1, Ordering::Relaxed);
+        frame.iter_mut().for_each(|byte| *byte ^= 0x5a);
+    });
+    let count = ingress_count.clone();
+    iface.set_ingress_transform(move |frame| {
+        count.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(frame[0] >> 4, (0x45 ^ 0x5a) >> 4);
+        frame.iter_mut().for_each(|byte| *byte ^= 0x5a);
+    });
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut server = tcp_socket();
+    server.listen(1234).unwrap();
+    let mut client = tcp_socket();
+    client
+        .connect(iface.context(), (addr, 1234), (addr, 65000))
+        .unwrap();
+    let server = sockets.add(server);
+    let client = sockets.add(client);
+
+    let mut timestamp = Instant::ZERO;
+    for _ in 0..4 {
+        iface.poll(timestamp, &mut device, &mut sockets);
+        timestamp += Duration::from_millis(1);
+    }
+    assert!(sockets.get::<tcp::Socket>(client).may_send());
+
+    sockets
+        .get_mut::<tcp::Socket>(client)
+        .send_slice(b"hello")
+        .unwrap();
+    for _ in 0..4 {
+        iface.poll(timestamp, &mut device, &mut sockets);
+        timestamp += Duration::from_millis(1);
+    }
+
+    let mut buffer = [0; 8];
+    let len = sockets
+        .get_mut::<tcp::Socket>(server)
+        .recv_slice(&mut buffer)
+        .unwrap();
+    assert_eq!(&buffer[..len], b"hello");
+
+    assert!(egress_count.load(Ordering::Relaxed) >= 4);
+    assert_eq!(
+        egress_count.load(Ordering::Relaxed),
+        ingress_count.load(Ordering::Relaxed)
+    );
+}
+
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_tcp_stealth_listen(#[case] stealth: bool) {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut server = tcp_socket();
+    server.set_accept_filter(Some(|remote| remote.port == 65000));
+    server.set_stealth_listen(stealth);
+    server.listen(1234).unwrap();
+    let server = sockets.add(server);
+
+    // A SYN from a rejected endpoint.
+    let mut rejected = tcp_socket();
+    rejected
+        .connect(iface.context(), (addr, 1234), (addr, 65001))
+        .unwrap();
+    let rejected = sockets.add(rejected);
+    iface.socket_egress(&mut device, &mut sockets);
+    assert_eq!(device.queue.len(), 1);
+
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    assert_eq!(
+        sockets.get::<tcp::Socket>(server).state(),
+        tcp::State::Listen
+    );
+    assert!(device.queue.is_empty());
+    if stealth {
+        // Neither a SYN-ACK nor a RST was sent.
+        assert_eq!(
+            sockets.get::<tcp::Socket>(rejected).state(),
+            tcp::State::SynSent
+        );
+    } else {
+        assert_eq!(
+            sockets.get::<tcp::Socket>(rejected).state(),
+            tcp::State::Closed
+        );
+    }
+    sockets.remove(rejected);
+
+    // A SYN from an accepted endpoint.
+    let mut client = tcp_socket();
+    client
+        .connect(iface.context(), (addr, 1234), (addr, 65000))
+        .unwrap();
+    let client = sockets.add(client);
+
+    let mut timestamp = Instant::ZERO;
+    for _ in 0..4 {
+        iface.poll(timestamp, &mut device, &mut sockets);
+        timestamp += Duration::from_millis(1);
+    }
+    assert_eq!(
+        sockets.get::<tcp::Socket>(server).state(),
+        tcp::State::Established
+    );
+    assert_eq!(
+        sockets.get::<tcp::Socket>(client).state(),
+        tcp::State::Established
+    );
+}
+
+#[test]
+#[cfg(all(feature = "socket-tcp", feature = "socket-udp", feature = "medium-ip"))]
+fn test_shutdown_all() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let tcp_socket = || {
+        tcp::Socket::new(
+            tcp::SocketBuffer::new(vec![0; 64]),
+            tcp::SocketBuffer::new(vec![0; 64]),
+        )
+    };
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let mut tcp_handles = std::vec::Vec::new();
+    for port in [1000, 1001] {
+        let mut server = tcp_socket();
+        server.listen(port).unwrap();
+        let mut client = tcp_socket();
+        client
+            .connect(iface.context(), (addr, port), (addr, port + 1000))
+            .unwrap();
+        tcp_handles.push(sockets.add(server));
+        tcp_handles.push(sockets.add(client));
+    }
+    let mut listener = tcp_socket();
+    listener.listen(1002).unwrap();
+    tcp_handles.push(sockets.add(listener));
+
+    let mut udp_socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+    );
+    udp_socket.bind(1003).unwrap();
+    let udp_handle = sockets.add(udp_socket);
+
+    let mut timestamp = Instant::ZERO;
+    for _ in 0..4 {
+        iface.poll(timestamp, &mut device, &mut sockets);
+        timestamp += Duration::from_millis(1);
+    }
+    for &handle in &tcp_handles[..4] {
+        assert_eq!(
+            sockets.get::<tcp::Socket>(handle).state(),
+            tcp::State::Established
+        );
+    }
+    sockets
+        .get_mut::<tcp::Socket>(tcp_handles[1])
+        .send_slice(b"bye")
+        .unwrap();
+    assert!(!iface.all_closed(&sockets));
+
+    iface.shutdown_all(&mut sockets);
+    assert!(!sockets.get::<udp::Socket>(udp_handle).is_open());
+    assert!(!iface.all_closed(&sockets));
+
+    let mut polls = 0;
+    while !iface.all_closed(&sockets) {
+        iface.poll(timestamp, &mut device, &mut sockets);
+        timestamp += Duration::from_millis(1);
+        polls += 1;
+        assert!(polls < 20, "sockets did not close");
+    }
+
+    // Both ends of each connection sent a FIN, and had it acknowledged.
+    for &handle in &tcp_handles[..4] {
+        assert_eq!(
+            sockets.get::<tcp::Socket>(handle).state(),
+            tcp::State::TimeWait
+        );
+    }
+    assert_eq!(
+        sockets.get::<tcp::Socket>(tcp_handles[4]).state(),
+        tcp::State::Closed
+    );
+
+    let mut buffer = [0; 8];
+    assert_eq!(
+        sockets
+            .get_mut::<tcp::Socket>(tcp_handles[0])
+            .recv_slice(&mut buffer),
+        Ok(3)
+    );
+    assert_eq!(&buffer[..3], b"bye");
+}
+
+#[test]
+#[cfg(all(feature = "socket-raw", feature = "medium-ip"))]
+fn test_shutdown_all_raw() {
+    use crate::wire::IpVersion;
+
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let buffer = || raw::PacketBuffer::new(vec![raw::PacketMetadata::EMPTY], vec![0; 48]);
+    let raw_socket = raw::Socket::new(IpVersion::Ipv4, IpProtocol::Unknown(63), buffer(), buffer());
+    let handle = sockets.add(raw_socket);
+
+    let repr = Ipv4Repr {
+        src_addr: Ipv4Address::new(127, 0, 0, 1),
+        dst_addr: Ipv4Address::new(127, 0, 0, 2),
+        next_header: IpProtocol::Unknown(63),
+        payload_len: 4,
+        hop_limit: 64,
+    };
+    let mut bytes = vec![0; repr.buffer_len() + 4];
+    repr.emit(
+        &mut Ipv4PacketWire::new_unchecked(&mut bytes),
+        &ChecksumCapabilities::default(),
+    );
+    let socket = sockets.get_mut::<raw::Socket>(handle);
+    socket.send_slice(&bytes).unwrap();
+    assert!(!socket.can_send());
+
+    // The queued packet is dropped, and the socket has nothing to wait for.
+    iface.shutdown_all(&mut sockets);
+    assert!(sockets.get::<raw::Socket>(handle).can_send());
+    assert!(iface.all_closed(&sockets));
+    assert!(!iface.poll(Instant::ZERO, &mut device, &mut sockets));
+}
+
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[cfg(feature = "medium-ip")]
+fn test_source_route_policy(#[case] accept_source_route: bool) {
+    let (mut iface, mut sockets, _device) = setup(Medium::Ip);
+    iface.inner.accept_source_route = accept_source_route;
+
+    let our_ipv4_addr = Ipv4Address([127, 0, 0, 1]);
+    let src_ipv4_addr = Ipv4Address([127, 0, 0, 2]);
+
+    let icmpv4_data: [u8; 4] = [0xaa, 0x00, 0x00, 0xff];
+    let icmpv4_repr = Icmpv4Repr::EchoRequest {
+        ident: 0x1234,
+        seq_no: 0xabcd,
+        data: &icmpv4_data,
+    };
+    let ipv4_repr = Ipv4Repr {
+        src_addr: src_ipv4_addr,
+        dst_addr: our_ipv4_addr,
+        next_header: IpProtocol::Icmp,
+        hop_limit: 64,
+        payload_len: icmpv4_repr.buffer_len(),
+    };
+
+    // A Loose Source Route option whose route was completed through
+    // 127.0.0.3, padded with an End of Option List.
+    let options = [0x83, 0x07, 0x08, 127, 0, 0, 3, 0x00];
+    let header_len = ipv4_repr.buffer_len() + options.len();
+    let mut bytes = vec![0u8; header_len + icmpv4_repr.buffer_len()];
+    let mut packet = Ipv4PacketWire::new_unchecked(&mut bytes[..]);
+    ipv4_repr.emit(&mut packet, &ChecksumCapabilities::default());
+    packet.set_header_len(header_len as u8);
+    packet.set_total_len((header_len + icmpv4_repr.buffer_len()) as u16);
+    bytes[ipv4_repr.buffer_len()..header_len].copy_from_slice(&options);
+    icmpv4_repr.emit(
+        &mut Icmpv4Packet::new_unchecked(&mut bytes[header_len..]),
+        &ChecksumCapabilities::default(),
+    );
+    let mut packet = Ipv4PacketWire::new_unchecked(&mut bytes[..]);
+    packet.fill_checksum();
+
+    let frame = Ipv4PacketWire::new_checked(&bytes[..]).unwrap();
+    assert_eq!(
+        frame.option_offset(Ipv4OptionType::LooseSourceRoute),
+        Some(20)
+    );
+
+    let expected_icmpv4_repr = if accept_source_route {
+        Icmpv4Repr::EchoReply {
+            ident: 0x1234,
+            seq_no: 0xabcd,
+            data: &icmpv4_data,
+        }
+    } else {
+        // The offending header is quoted with its options, so that the
+        // pointer refers to the Loose Source Route option.
+        Icmpv4Repr::ParamProblem {
+            reason: Icmpv4ParamProblem::AtPointer,
+            pointer: 20,
+            data: &bytes[..header_len + 8],
+        }
+    };
+    let expected_ipv4_repr = Ipv4Repr {
+        src_addr: our_ipv4_addr,
+        dst_addr: src_ipv4_addr,
+        next_header: IpProtocol::Icmp,
+        hop_limit: 64,
+        payload_len: expected_icmpv4_repr.buffer_len(),
+    };
+
+    assert_eq!(
+        iface.inner.process_ipv4(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut iface.fragments
+        ),
+        Some(IpPacket::new_ipv4(
+            expected_ipv4_repr,
+            IpPayload::Icmpv4(expected_icmpv4_repr)
+        ))
+    );
+}
+
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_drop_own_source(#[case] drop_own_source: bool) {
+    let (mut iface, mut sockets, _device) = setup(Medium::Ip);
+    iface.inner.drop_own_source = drop_own_source;
+
+    let our_ipv4_addr = Ipv4Address([192, 168, 1, 1]);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.clear();
+        ip_addrs
+            .push(IpCidr::new(our_ipv4_addr.into(), 24))
+            .unwrap();
+    });
+
+    let mut server = tcp::Socket::new(
+        tcp::SocketBuffer::new(vec![0; 64]),
+        tcp::SocketBuffer::new(vec![0; 64]),
+    );
+    server.listen(80).unwrap();
+    let server = sockets.add(server);
+
+    // A SYN pretending to come from ourselves.
+    let tcp_repr = TcpRepr {
+        src_port: 49500,
+        dst_port: 80,
+        control: TcpControl::Syn,
+        seq_number: TcpSeqNumber(1000),
+        ack_number: None,
+        window_len: 64,
+        window_scale: None,
+        max_seg_size: None,
+        sack_permitted: false,
+        sack_ranges: [None, None, None],
+        authentication: None,
+        accecn: None,
+        payload: &[],
+    };
+    let ipv4_repr = Ipv4Repr {
+        src_addr: our_ipv4_addr,
+        dst_addr: our_ipv4_addr,
+        next_header: IpProtocol::Tcp,
+        hop_limit: 64,
+        payload_len: tcp_repr.buffer_len(),
+    };
+    let mut bytes = vec![0u8; ipv4_repr.buffer_len() + tcp_repr.buffer_len()];
+    ipv4_repr.emit(
+        &mut Ipv4PacketWire::new_unchecked(&mut bytes[..]),
+        &ChecksumCapabilities::default(),
+    );
+    tcp_repr.emit(
+        &mut TcpPacket::new_unchecked(&mut bytes[ipv4_repr.buffer_len()..]),
+        &our_ipv4_addr.into(),
+        &our_ipv4_addr.into(),
+        &ChecksumCapabilities::default(),
+    );
+
+    let frame = Ipv4PacketWire::new_checked(&bytes[..]).unwrap();
+    assert_eq!(
+        iface.inner.process_ipv4(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut iface.fragments
+        ),
+        None
+    );
+    let expected_state = if drop_own_source {
+        tcp::State::Listen
+    } else {
+        tcp::State::SynReceived
+    };
+    assert_eq!(sockets.get::<tcp::Socket>(server).state(), expected_state);
+}
+
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[cfg(all(feature = "proto-igmp", feature = "socket-udp", feature = "medium-ip"))]
+fn test_drop_own_source_multicast(#[case] drop_own_source: bool) {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+    iface.inner.drop_own_source = drop_own_source;
+
+    let our_ipv4_addr = Ipv4Address([192, 168, 1, 1]);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.clear();
+        ip_addrs
+            .push(IpCidr::new(our_ipv4_addr.into(), 24))
+            .unwrap();
+    });
+    let group = Ipv4Address::new(224, 0, 0, 56);
+    iface
+        .join_multicast_group(&mut device, group, Instant::ZERO)
+        .unwrap();
+
+    let udp_socket = || {
+        udp::Socket::new(
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        )
+    };
+    let mut receiver = udp_socket();
+    receiver.bind((group, 2000)).unwrap();
+    let receiver = sockets.add(receiver);
+    let mut sender = udp_socket();
+    sender.bind(1000).unwrap();
+    sockets.add(sender);
+
+    let mut receive_from = |src_port| {
+        let udp_repr = UdpRepr {
+            src_port,
+            dst_port: 2000,
+        };
+        let ipv4_repr = Ipv4Repr {
+            src_addr: our_ipv4_addr,
+            dst_addr: group,
+            next_header: IpProtocol::Udp,
+            hop_limit: 1,
+            payload_len: udp_repr.header_len() + 5,
+        };
+        let mut bytes = vec![0u8; ipv4_repr.buffer_len() + ipv4_repr.payload_len];
+        ipv4_repr.emit(
+            &mut Ipv4PacketWire::new_unchecked(&mut bytes[..]),
+            &ChecksumCapabilities::default(),
+        );
+        udp_repr.emit(
+            &mut UdpPacket::new_unchecked(&mut bytes[ipv4_repr.buffer_len()..]),
+            &our_ipv4_addr.into(),
+            &group.into(),
+            5,
+            |buf| buf.copy_from_slice(b"hello"),
+            &ChecksumCapabilities::default(),
+        );
+        let frame = Ipv4PacketWire::new_checked(&bytes[..]).unwrap();
+        iface.inner.process_ipv4(
+            &mut sockets,
+            PacketMeta::default(),
+            &frame,
+            &mut iface.fragments,
+        );
+        let receiver = sockets.get_mut::<udp::Socket>(receiver);
+        let received = receiver.can_recv();
+        while receiver.recv().is_ok() {}
+        received
+    };
+
+    // A datagram reflected by the device is not mistaken for a forged one.
+    // Without `alloc`, it is how looped datagrams are delivered; with it, a
+    // local copy was delivered when it was sent.
+    assert_eq!(receive_from(1000), cfg!(not(feature = "alloc")));
+    // A datagram that none of our sockets sent can only be forged.
+    assert_eq!(receive_from(1001), !drop_own_source);
+}
+
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[cfg(all(feature = "socket-tcp", feature = "medium-ip"))]
+fn test_tcp_reset_unbound(#[case] tcp_reset_unbound: bool) {
+    let (mut iface, mut sockets, _device) = setup(Medium::Ip);
+    iface.inner.tcp_reset_unbound = tcp_reset_unbound;
+
+    let remote_addr = Ipv4Address([0x7f, 0x00, 0x00, 0x02]);
+    let local_addr = Ipv4Address([0x7f, 0x00, 0x00, 0x01]);
+    let tcp_repr = TcpRepr {
+        src_port: 49500,
+        dst_port: 80,
+        control: TcpControl::Syn,
+        seq_number: TcpSeqNumber(1000),
+        ack_number: None,
+        window_len: 64,
+        window_scale: None,
+        max_seg_size: None,
+        sack_permitted: false,
+        sack_ranges: [None, None, None],
+        authentication: None,
+        accecn: None,
+        payload: &[],
+    };
+    let ip_repr = IpRepr::Ipv4(Ipv4Repr {
+        src_addr: remote_addr,
+        dst_addr: local_addr,
+        next_header: IpProtocol::Tcp,
+        hop_limit: 64,
+        payload_len: tcp_repr.buffer_len(),
+    });
+    let mut bytes = vec![0u8; tcp_repr.buffer_len()];
+    tcp_repr.emit(
+        &mut TcpPacket::new_unchecked(&mut bytes[..]),
+        &remote_addr.into(),
+        &local_addr.into(),
+        &ChecksumCapabilities::default(),
+    );
+
+    let expected = tcp_reset_unbound.then(|| {
+        let rst_repr = TcpRepr {
+            src_port: 80,
+            dst_port: 49500,
+            control: TcpControl::Rst,
+            seq_number: TcpSeqNumber(0),
+            ack_number: Some(TcpSeqNumber(1001)),
+            window_len: 0,
+            ..tcp_repr
+        };
+        IpPacket::new_ipv4(
+            Ipv4Repr {
+                src_addr: local_addr,
+                dst_addr: remote_addr,
+                next_header: IpProtocol::Tcp,
+                hop_limit: 64,
+                payload_len: rst_repr.buffer_len(),
+            },
+            IpPayload::Tcp(rst_repr),
+        )
+    });
+    assert_eq!(
+        iface.inner.process_tcp(&mut sockets, ip_repr, &bytes),
+        expected
+    );
+}
+
+#[test]
+#[cfg(all(feature = "alloc", feature = "medium-ip"))]
+fn test_router_forward() {
+    let mut router = Router::new();
+    let mut devices = [Loopback::new(Medium::Ip), Loopback::new(Medium::Ip)];
+    let mut sockets = SocketSet::new(vec![]);
+    for (i, device) in devices.iter_mut().enumerate() {
+        let mut iface = Interface::new(Config::new(HardwareAddress::Ip), device, Instant::ZERO);
+        iface.update_ip_addrs(|ip_addrs| {
+            ip_addrs
+                .push(IpCidr::new(IpAddress::v4(10, 0, i as u8, 1), 24))
+                .unwrap();
+        });
+        assert_eq!(router.add_interface(iface), i);
+    }
+
+    let ipv4_repr = |hop_limit| Ipv4Repr {
+        src_addr: Ipv4Address::new(10, 0, 0, 2),
+        dst_addr: Ipv4Address::new(10, 0, 1, 2),
+        next_header: IpProtocol::Udp,
+        payload_len: 4,
+        hop_limit,
+    };
+    let send = |device: &mut Loopback, hop_limit| {
+        let repr = ipv4_repr(hop_limit);
+        let tx_token = device.transmit(Instant::ZERO).unwrap();
+        tx_token.consume(repr.buffer_len() + repr.payload_len, |buf| {
+            repr.emit(
+                &mut Ipv4PacketWire::new_unchecked(&mut *buf),
+                &ChecksumCapabilities::default(),
+            );
+            buf[repr.buffer_len()..].copy_from_slice(b"ping");
+        });
+    };
+
+    // A packet arriving on the first interface leaves through the second one.
+    send(&mut devices[0], 64);
+    router.poll(0, Instant::ZERO, &mut devices[0], &mut sockets);
+    assert!(router.interface_mut(1).forward_egress(&mut devices[1]));
+
+    let (rx, _tx) = devices[1].receive(Instant::ZERO).unwrap();
+    rx.consume(|frame| {
+        let packet = Ipv4PacketWire::new_checked(&*frame).unwrap();
+        assert_eq!(
+            Ipv4Repr::parse(&packet, &ChecksumCapabilities::default()),
+            Ok(ipv4_repr(63))
+        );
+        assert_eq!(packet.payload(), b"ping");
+    });
+    assert!(devices[1].receive(Instant::ZERO).is_none());
+
+    // A packet whose time-to-live expires is dropped.
+    send(&mut devices[0], 1);
+    router.poll(0, Instant::ZERO, &mut devices[0], &mut sockets);
+    assert!(!router.interface_mut(1).forward_egress(&mut devices[1]));
+    assert!(devices[1].receive(Instant::ZERO).is_none());
+}
+
+#[test]
+#[cfg(all(feature = "alloc", feature = "medium-ethernet"))]
+fn test_router_forward_unresolved_next_hop() {
+    let (mut iface, _sockets, mut device) = setup(Medium::Ethernet);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs.clear();
+        ip_addrs
+            .push(IpCidr::new(IpAddress::v4(10, 0, 1, 1), 24))
+            .unwrap();
+    });
+    let unresolved = Ipv4Address::new(10, 0, 1, 2);
+    let resolved = Ipv4Address::new(10, 0, 1, 3);
+    let resolved_hw_addr = EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x03]);
+    iface
+        .inner
+        .neighbor_cache
+        .fill(resolved.into(), resolved_hw_addr.into(), Instant::ZERO);
+
+    for dst_addr in [unresolved, resolved] {
+        let repr = Ipv4Repr {
+            src_addr: Ipv4Address::new(10, 0, 0, 2),
+            dst_addr,
+            next_header: IpProtocol::Udp,
+            payload_len: 4,
+            hop_limit: 63,
+        };
+        let mut packet = vec![0; repr.buffer_len() + repr.payload_len];
+        repr.emit(
+            &mut Ipv4PacketWire::new_unchecked(&mut packet[..]),
+            &ChecksumCapabilities::default(),
+        );
+        iface.inner.forwarding.send(packet);
+    }
+
+    let mut forward = |iface: &mut Interface, timestamp: Instant| {
+        iface.inner.now = timestamp;
+        iface.forward_egress(&mut device);
+        let mut frames = std::vec::Vec::new();
+        while let Some((rx, _tx)) = device.receive(timestamp) {
+            rx.consume(|frame| {
+                let eth_frame = EthernetFrame::new_checked(frame).unwrap();
+                frames.push((eth_frame.dst_addr(), eth_frame.ethertype()));
+            });
+        }
+        frames
+    };
+
+    // The packet waiting for its next hop does not hold up the other one.
+    assert_eq!(
+        forward(&mut iface, Instant::ZERO),
+        [
+            (EthernetAddress::BROADCAST, EthernetProtocol::Arp),
+            (resolved_hw_addr, EthernetProtocol::Ipv4),
+        ]
+    );
+    assert_eq!(iface.inner.forwarding.tx.len(), 1);
+    assert_eq!(
+        iface.inner.forwarding.poll_at(),
+        Some(Instant::from_millis(1000))
+    );
+
+    // Discovery is requested again, until the packet is dropped.
+    assert_eq!(forward(&mut iface, Instant::from_millis(500)), []);
+    assert_eq!(
+        forward(&mut iface, Instant::from_millis(1000)),
+        [(EthernetAddress::BROADCAST, EthernetProtocol::Arp)]
+    );
+    assert_eq!(
+        forward(&mut iface, Instant::from_millis(2000)),
+        [(EthernetAddress::BROADCAST, EthernetProtocol::Arp)]
+    );
+    assert_eq!(forward(&mut iface, Instant::from_millis(3000)), []);
+    assert!(iface.inner.forwarding.tx.is_empty());
+}
+
+#[test]
+#[cfg(all(
+    feature = "alloc",
+    feature = "medium-ip",
+    feature = "proto-ipv4-fragmentation"
+))]
+fn test_router_forward_fragment() {
+    /// The transmitting half of a loopback device, with a small MTU.
+    struct TxLoopback(Loopback);
+
+    impl Device for TxLoopback {
+        type RxToken<'a> = <Loopback as Device>::RxToken<'a>;
+        type TxToken<'a> = <Loopback as Device>::TxToken<'a>;
+
+        fn capabilities(&self) -> DeviceCapabilities {
+            DeviceCapabilities {
+                max_transmission_unit: 44,
+                ..self.0.capabilities()
+            }
+        }
+
+        fn receive(
+            &mut self,
+            _timestamp: Instant,
+        ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+            None
+        }
+
+        fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+            self.0.transmit(timestamp)
+        }
+    }
+
+    let mut router = Router::new();
+    let mut ingress = Loopback::new(Medium::Ip);
+    let mut egress = TxLoopback(Loopback::new(Medium::Ip));
+    let mut sockets = SocketSet::new(vec![]);
+    for i in 0..2 {
+        let mut iface = if i == 0 {
+            Interface::new(
+                Config::new(HardwareAddress::Ip),
+                &mut ingress,
+                Instant::ZERO,
+            )
+        } else {
+            Interface::new(Config::new(HardwareAddress::Ip), &mut egress, Instant::ZERO)
+        };
+        iface.update_ip_addrs(|ip_addrs| {
+            ip_addrs
+                .push(IpCidr::new(IpAddress::v4(10, 0, i as u8, 1), 24))
+                .unwrap();
+        });
+        router.add_interface(iface);
+    }
+
+    let payload: std::vec::Vec<u8> = (0..56).collect();
+    // Send a fragment of a datagram through the router, and return what
+    // leaves it.
+    let mut forward = |offset: usize, len: usize, more_frags: bool| {
+        let repr = Ipv4Repr {
+            src_addr: Ipv4Address::new(10, 0, 0, 2),
+            dst_addr: Ipv4Address::new(10, 0, 1, 2),
+            next_header: IpProtocol::Udp,
+            payload_len: len,
+            hop_limit: 64,
+        };
+        let tx_token = ingress.transmit(Instant::ZERO).unwrap();
+        tx_token.consume(repr.buffer_len() + len, |buf| {
+            let mut packet = Ipv4PacketWire::new_unchecked(&mut *buf);
+            repr.emit(&mut packet, &ChecksumCapabilities::default());
+            packet.set_ident(0x1234);
+            packet.set_dscp(46);
+            packet.set_ecn(1);
+            packet.set_dont_frag(false);
+            packet.set_more_frags(more_frags);
+            packet.set_frag_offset(offset as u16);
+            packet.fill_checksum();
+            buf[repr.buffer_len()..].copy_from_slice(&payload[offset..offset + len]);
+        });
+
+        router.poll(0, Instant::ZERO, &mut ingress, &mut sockets);
+        router.poll(1, Instant::ZERO, &mut egress, &mut sockets);
+
+        let mut frames = std::vec::Vec::new();
+        while let Some((rx, _tx)) = egress.0.receive(Instant::ZERO) {
+            rx.consume(|frame| frames.push(frame.to_vec()));
+        }
+        frames
+    };
+    let check = |frame: &[u8], offset: usize, len: usize, more_frags: bool| {
+        let packet = Ipv4PacketWire::new_checked(frame).unwrap();
+        assert!(packet.verify_checksum());
+        assert_eq!(packet.hop_limit(), 63);
+        assert_eq!(packet.ident(), 0x1234);
+        assert_eq!(packet.dscp(), 46);
+        assert_eq!(packet.ecn(), 1);
+        assert!(!packet.dont_frag());
+        assert_eq!(packet.more_frags(), more_frags);
+        assert_eq!(packet.frag_offset() as usize, offset);
+        assert_eq!(packet.payload(), &payload[offset..offset + len]);
+    };
+
+    // A fragment that fits is forwarded as is.
+    let frames = forward(0, 16, true);
+    assert_eq!(frames.len(), 1);
+    check(&frames[0], 0, 16, true);
+
+    // The last fragment does not fit, and is split further.
+    let frames = forward(16, 40, false);
+    assert_eq!(frames.len(), 2);
+    check(&frames[0], 16, 24, true);
+    check(&frames[1], 40, 16, false);
+}
+
+#[rstest]
+#[case(UrpfMode::Disabled)]
+#[case(UrpfMode::Strict)]
+#[case(UrpfMode::Loose)]
+#[cfg(feature = "medium-ip")]
+fn test_urpf(#[case] urpf: UrpfMode) {
+    let (mut iface, mut sockets, _device) = setup(Medium::Ip);
+    iface.inner.urpf = urpf;
+
+    let our_ipv4_addr = Ipv4Address([192, 168, 1, 1]);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs
+            .push(IpCidr::new(our_ipv4_addr.into(), 24))
+            .unwrap();
+    });
+    // The route to 10.0.1.0/24 leads through a router which is not on-link.
+    iface.routes_mut().update(|routes| {
+        routes
+            .push(crate::iface::Route {
+                cidr: IpCidr::new(IpAddress::v4(10, 0, 1, 0), 24),
+                via_router: IpAddress::v4(172, 16, 0, 1),
+                preferred_until: None,
+                expires_at: None,
+            })
+            .unwrap();
+    });
+
+    let icmpv4_repr = Icmpv4Repr::EchoRequest {
+        ident: 0x1234,
+        seq_no: 0xabcd,
+        data: &[0xaa, 0x00, 0x00, 0xff],
+    };
+    let mut answered = |src_addr: Ipv4Address| {
+        let ipv4_repr = Ipv4Repr {
+            src_addr,
+            dst_addr: our_ipv4_addr,
+            next_header: IpProtocol::Icmp,
+            hop_limit: 64,
+            payload_len: icmpv4_repr.buffer_len(),
+        };
+        let mut bytes = vec![0u8; ipv4_repr.buffer_len() + icmpv4_repr.buffer_len()];
+        ipv4_repr.emit(
+            &mut Ipv4PacketWire::new_unchecked(&mut bytes[..]),
+            &ChecksumCapabilities::default(),
+        );
+        icmpv4_repr.emit(
+            &mut Icmpv4Packet::new_unchecked(&mut bytes[ipv4_repr.buffer_len()..]),
+            &ChecksumCapabilities::default(),
+        );
+        let frame = Ipv4PacketWire::new_checked(&bytes[..]).unwrap();
+        iface
+            .inner
+            .process_ipv4(
+                &mut sockets,
+                PacketMeta::default(),
+                &frame,
+                &mut iface.fragments,
+            )
+            .is_some()
+    };
+
+    // On-link sources pass every check.
+    assert!(answered(Ipv4Address([192, 168, 1, 2])));
+    // A source without any route fails both checks.
+    assert_eq!(
+        answered(Ipv4Address([10, 0, 0, 2])),
+        urpf == UrpfMode::Disabled
+    );
+    // A source whose route does not lead back out of the interface fails
+    // only the strict check.
+    assert_eq!(
+        answered(Ipv4Address([10, 0, 1, 2])),
+        urpf != UrpfMode::Strict
+    );
+}
+
+#[rstest]
+#[case(UrpfMode::Strict)]
+#[case(UrpfMode::Loose)]
+#[cfg(all(feature = "alloc", feature = "medium-ip"))]
+fn test_router_urpf(#[case] urpf: UrpfMode) {
+    let mut router = Router::new();
+    let mut devices = [Loopback::new(Medium::Ip), Loopback::new(Medium::Ip)];
+    let mut sockets = SocketSet::new(vec![]);
+    for (i, device) in devices.iter_mut().enumerate() {
+        let mut config = Config::new(HardwareAddress::Ip);
+        config.urpf = urpf;
+        let mut iface = Interface::new(config, device, Instant::ZERO);
+        iface.update_ip_addrs(|ip_addrs| {
+            ip_addrs
+                .push(IpCidr::new(IpAddress::v4(10, 0, i as u8, 1), 24))
+                .unwrap();
+        });
+        assert_eq!(router.add_interface(iface), i);
+    }
+
+    let mut forwarded = |src_addr: Ipv4Address| {
+        let repr = Ipv4Repr {
+            src_addr,
+            dst_addr: Ipv4Address::new(10, 0, 1, 2),
+            next_header: IpProtocol::Udp,
+            payload_len: 4,
+            hop_limit: 64,
+        };
+        let tx_token = devices[0].transmit(Instant::ZERO).unwrap();
+        tx_token.consume(repr.buffer_len() + repr.payload_len, |buf| {
+            repr.emit(
+                &mut Ipv4PacketWire::new_unchecked(&mut *buf),
+                &ChecksumCapabilities::default(),
+            );
+            buf[repr.buffer_len()..].copy_from_slice(b"ping");
+        });
+        router.poll(0, Instant::ZERO, &mut devices[0], &mut sockets);
+        router.interface_mut(1).forward_egress(&mut devices[1])
+    };
+
+    // A source on the link the packet arrived from passes every check.
+    assert!(forwarded(Ipv4Address::new(10, 0, 0, 2)));
+    // A source without any route fails both checks.
+    assert!(!forwarded(Ipv4Address::new(172, 16, 0, 2)));
+    // A source on the link of another interface fails only the strict check.
+    assert_eq!(
+        forwarded(Ipv4Address::new(10, 0, 1, 3)),
+        urpf == UrpfMode::Loose
+    );
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ip"))]
+fn test_tx_exhausted() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A loopback device without room for any transmitted packet.
+    struct FullLoopback(Loopback);
+
+    impl Device for FullLoopback {
+        type RxToken<'a> = <Loopback as Device>::RxToken<'a>;
+        type TxToken<'a> = <Loopback as Device>::TxToken<'a>;
+
+        fn capabilities(&self) -> DeviceCapabilities {
+            self.0.capabilities()
+        }
+
+        fn receive(
+            &mut self,
+            timestamp: Instant,
+        ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+            self.0.receive(timestamp)
+        }
+
+        fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+            None
+        }
+    }
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    fn handler() {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let (mut iface, mut sockets, device) = setup(Medium::Ip);
+    let mut device = FullLoopback(device);
+    iface.set_tx_exhausted_handler(Some(handler));
+
+    let mut udp_socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+    );
+    udp_socket.bind(1000).unwrap();
+    let handle = sockets.add(udp_socket);
+
+    // Nothing to send, so the device is not asked for a token.
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    assert_eq!(iface.tx_exhausted_count(), 0);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+    sockets
+        .get_mut::<udp::Socket>(handle)
+        .send_slice(b"abc", (IpAddress::v4(127, 0, 0, 1), 2000))
+        .unwrap();
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    assert_eq!(iface.tx_exhausted_count(), 1);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    // The datagram is kept for a later poll.
+    assert!(!sockets.get::<udp::Socket>(handle).can_send());
+
+    iface.set_tx_exhausted_handler(None);
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    assert_eq!(iface.tx_exhausted_count(), 2);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+#[cfg(feature = "medium-ip")]
+fn test_route_cache() {
+    let (mut iface, _, _) = setup(Medium::Ip);
+    let route = |cidr, via_router| crate::iface::Route {
+        cidr,
+        via_router,
+        preferred_until: None,
+        expires_at: Some(Instant::from_secs(10)),
+    };
+    let gateway_a = IpAddress::v4(127, 0, 0, 2);
+    let gateway_b = IpAddress::v4(127, 0, 0, 3);
+    let dst_addr = IpAddress::v4(10, 0, 0, 5);
+    iface.routes_mut().update(|routes| {
+        routes
+            .push(route(IpCidr::new(IpAddress::v4(10, 0, 0, 0), 8), gateway_a))
+            .unwrap();
+    });
+
+    // Without a cache, every lookup walks the table.
+    assert_eq!(
+        iface.inner.route_and_cache(&dst_addr, Instant::ZERO),
+        Some(gateway_a)
+    );
+    assert_eq!(
+        iface.inner.route_and_cache(&dst_addr, Instant::ZERO),
+        Some(gateway_a)
+    );
+    assert_eq!(iface.routes().cache_hits(), 0);
+
+    let cache: &'static mut [Option<RouteCacheEntry>] = std::vec![None; 2].leak();
+    iface.set_route_cache(cache);
+    for hits in 0..3 {
+        assert_eq!(
+            iface.inner.route_and_cache(&dst_addr, Instant::ZERO),
+            Some(gateway_a)
+        );
+        assert_eq!(iface.routes().cache_hits(), hits);
+    }
+
+    // Adding a more specific route invalidates the cache.
+    iface.routes_mut().update(|routes| {
+        routes
+            .push(route(
+                IpCidr::new(IpAddress::v4(10, 0, 0, 0), 24),
+                gateway_b,
+            ))
+            .unwrap();
+    });
+    assert_eq!(
+        iface.inner.route_and_cache(&dst_addr, Instant::ZERO),
+        Some(gateway_b)
+    );
+    assert_eq!(iface.routes().cache_hits(), 2);
+    assert_eq!(
+        iface.inner.route_and_cache(&dst_addr, Instant::ZERO),
+        Some(gateway_b)
+    );
+    assert_eq!(iface.routes().cache_hits(), 3);
+
+    // So does the expiry of the route found.
+    assert_eq!(
+        iface
+            .inner
+            .route_and_cache(&dst_addr, Instant::from_secs(11)),
+        None
+    );
+    assert_eq!(iface.routes().cache_hits(), 3);
+
+    iface
+        .routes_mut()
+        .add_default_ipv4_route(Ipv4Address::new(127, 0, 0, 4))
+        .unwrap();
+    assert_eq!(
+        iface
+            .inner
+            .route_and_cache(&dst_addr, Instant::from_secs(11)),
+        Some(IpAddress::v4(127, 0, 0, 4))
+    );
+    assert_eq!(iface.routes().cache_hits(), 3);
+}
+
+#[test]
+#[cfg(all(feature = "medium-ethernet", feature = "socket-udp"))]
+fn test_neighbor_resolver() {
+    // Send a datagram to `dst_addr`, and return the destination and type of
+    // the frames transmitted.
+    fn send_to(
+        iface: &mut Interface,
+        device: &mut Loopback,
+        sockets: &mut SocketSet,
+        handle: crate::iface::SocketHandle,
+        dst_addr: IpAddress,
+    ) -> std::vec::Vec<(EthernetAddress, EthernetProtocol)> {
+        sockets
+            .get_mut::<udp::Socket>(handle)
+            .send_slice(b"hello", (dst_addr, 2000))
+            .unwrap();
+        iface.socket_egress(device, sockets);
+
+        let mut frames = vec![];
+        while let Some((rx, _tx)) = device.receive(Instant::ZERO) {
+            rx.consume(|frame| {
+                let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+                frames.push((eth_frame.dst_addr(), eth_frame.ethertype()));
+            });
+        }
+        frames
+    }
+
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    let remote_ip_addr = IpAddress::v4(127, 0, 0, 2);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0, 0, 0, 2]);
+    iface.set_neighbor_resolver(Some(Box::new(move |addr| {
+        (addr == remote_ip_addr).then_some(HardwareAddress::Ethernet(remote_hw_addr))
+    })));
+
+    let mut socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+    );
+    socket.bind(1000).unwrap();
+    let handle = sockets.add(socket);
+
+    // The resolved neighbor is sent to right away, without an ARP request.
+    assert_eq!(
+        send_to(
+            &mut iface,
+            &mut device,
+            &mut sockets,
+            handle,
+            remote_ip_addr
+        ),
+        [(remote_hw_addr, EthernetProtocol::Ipv4)]
+    );
+    assert!(!iface.inner.has_neighbor(&remote_ip_addr));
+
+    // Other neighbors are resolved with ARP.
+    assert_eq!(
+        send_to(
+            &mut iface,
+            &mut device,
+            &mut sockets,
+            handle,
+            IpAddress::v4(127, 0, 0, 3)
+        ),
+        [(EthernetAddress::BROADCAST, EthernetProtocol::Arp)]
+    );
+}
+
+#[rstest]
+#[case(Some(true), true)]
+#[case(Some(false), false)]
+#[case(None, true)]
+#[cfg(all(feature = "proto-igmp", feature = "socket-udp", feature = "medium-ip"))]
+fn test_multicast_loop(#[case] socket_loop: Option<bool>, #[case] delivered: bool) {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+    let group = Ipv4Address::new(224, 0, 0, 56);
+    iface
+        .join_multicast_group(&mut device, group, Instant::ZERO)
+        .unwrap();
+
+    let udp_socket = || {
+        udp::Socket::new(
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        )
+    };
+    let mut receiver = udp_socket();
+    receiver.bind(2000).unwrap();
+    let receiver = sockets.add(receiver);
+    let mut sender = udp_socket();
+    sender.bind(1000).unwrap();
+    if let Some(socket_loop) = socket_loop {
+        sender.set_multicast_loop(socket_loop);
+    }
+    let sender = sockets.add(sender);
+
+    for iface_loop in [true, false] {
+        iface.set_multicast_loop(iface_loop);
+        sockets
+            .get_mut::<udp::Socket>(sender)
+            .send_slice(b"hello", (IpAddress::from(group), 2000))
+            .unwrap();
+        for _ in 0..2 {
+            iface.poll(Instant::ZERO, &mut device, &mut sockets);
+        }
+
+        // A socket setting overrides the interface default.
+        let delivered = delivered && (socket_loop.is_some() || iface_loop);
+        let receiver = sockets.get_mut::<udp::Socket>(receiver);
+        assert_eq!(receiver.can_recv(), delivered);
+        if delivered {
+            let (payload, meta) = receiver.recv().unwrap();
+            assert_eq!(payload, b"hello");
+            assert_eq!(meta.endpoint.port, 1000);
+        }
+        // The copy reflected by the loopback device is not received again.
+        assert!(!receiver.can_recv());
+    }
+}
+
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[cfg(all(
+    feature = "alloc",
+    feature = "proto-igmp",
+    feature = "socket-udp",
+    feature = "medium-ip"
+))]
+fn test_multicast_loop_local_delivery(#[case] multicast_loop: bool) {
+    // A device that does not reflect transmitted frames, like most real ones.
+    struct TxOnly(Loopback);
+
+    impl Device for TxOnly {
+        type RxToken<'a> = <Loopback as Device>::RxToken<'a>;
+        type TxToken<'a> = <Loopback as Device>::TxToken<'a>;
+
+        fn capabilities(&self) -> DeviceCapabilities {
+            self.0.capabilities()
+        }
+
+        fn receive(
+            &mut self,
+            _timestamp: Instant,
+        ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+            None
+        }
+
+        fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+            self.0.transmit(timestamp)
+        }
+    }
+
+    let mut device = TxOnly(Loopback::new(Medium::Ip));
+    let mut iface = Interface::new(Config::new(HardwareAddress::Ip), &mut device, Instant::ZERO);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs
+            .push(IpCidr::new(IpAddress::v4(192, 168, 1, 1), 24))
+            .unwrap();
+    });
+    let group = Ipv4Address::new(224, 0, 0, 56);
+    iface
+        .join_multicast_group(&mut device, group, Instant::ZERO)
+        .unwrap();
+    iface.set_multicast_loop(multicast_loop);
+
+    let mut sockets = SocketSet::new(vec![]);
+    let udp_socket = || {
+        udp::Socket::new(
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        )
+    };
+    let mut receiver = udp_socket();
+    receiver.bind((group, 2000)).unwrap();
+    let receiver = sockets.add(receiver);
+    let mut sender = udp_socket();
+    sender.bind(1000).unwrap();
+    sender
+        .send_slice(b"hello", (IpAddress::from(group), 2000))
+        .unwrap();
+    sockets.add(sender);
+
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+
+    // The datagram still goes out on the wire, after the IGMP report.
+    let mut protocols = Vec::new();
+    while let Some((rx, _tx)) = device.0.receive(Instant::ZERO) {
+        rx.consume(|frame| {
+            let ipv4_packet = Ipv4PacketWire::new_checked(&*frame).unwrap();
+            assert_eq!(ipv4_packet.dst_addr(), group);
+            protocols.push(ipv4_packet.next_header());
+        });
+    }
+    assert_eq!(protocols, [IpProtocol::Igmp, IpProtocol::Udp]);
+
+    let receiver = sockets.get_mut::<udp::Socket>(receiver);
+    if multicast_loop {
+        let (payload, meta) = receiver.recv().unwrap();
+        assert_eq!(payload, b"hello");
+        assert_eq!(
+            meta.endpoint,
+            (Ipv4Address::new(192, 168, 1, 1), 1000).into()
+        );
+    }
+    assert!(!receiver.can_recv());
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "socket-tcp", feature = "medium-ip"))]
+fn test_socket_dscp() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+    let addr = IpAddress::v4(127, 0, 0, 1);
+
+    let udp_socket = |port| {
+        let mut socket = udp::Socket::new(
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+            udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        );
+        socket.bind(port).unwrap();
+        socket.send_slice(b"abc", (addr, 2000)).unwrap();
+        socket
+    };
+    let mut marked = udp_socket(1000);
+    marked.set_dscp(46);
+    sockets.add(marked);
+    sockets.add(udp_socket(1001));
+
+    let mut tcp_socket = tcp::Socket::new(
+        tcp::SocketBuffer::new(vec![0; 64]),
+        tcp::SocketBuffer::new(vec![0; 64]),
+    );
+    tcp_socket.set_dscp(46);
+    tcp_socket
+        .connect(iface.context(), (addr, 2000), 1002)
+        .unwrap();
+    sockets.add(tcp_socket);
+
+    iface.socket_egress(&mut device, &mut sockets);
+
+    let mut dscps = device
+        .queue
+        .iter()
+        .map(|frame| {
+            let packet = Ipv4PacketWire::new_checked(&frame[..]).unwrap();
+            assert!(packet.verify_checksum());
+            let src_port = u16::from_be_bytes([packet.payload()[0], packet.payload()[1]]);
+            (src_port, packet.dscp())
+        })
+        .collect::<std::vec::Vec<_>>();
+    dscps.sort();
+    assert_eq!(dscps, [(1000, 46), (1001, 0), (1002, 46)]);
+}
+
+#[test]
+#[should_panic(expected = "the DSCP value must fit in six bits")]
+#[cfg(feature = "socket-udp")]
+fn test_socket_dscp_range() {
+    let mut socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![], vec![]),
+        udp::PacketBuffer::new(vec![], vec![]),
+    );
+    socket.set_dscp(64);
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ethernet"))]
+fn test_neighbor_queue() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    iface.set_neighbor_queue_depth(2);
+
+    let remote_ip_addr = Ipv4Address([0x7f, 0x00, 0x00, 0x02]);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]);
+
+    let mut socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 16]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 16]),
+    );
+    socket.bind(6969).unwrap();
+    socket
+        .send_slice(b"first", (IpAddress::Ipv4(remote_ip_addr), 9))
+        .unwrap();
+    let handle = sockets.add(socket);
+
+    // The neighbor is unknown: an ARP request is sent, and the datagram is
+    // held by the interface instead of its socket.
+    assert!(iface.socket_egress(&mut device, &mut sockets));
+    assert!(sockets.get::<udp::Socket>(handle).can_send());
+    let (rx, _tx) = device.receive(Instant::ZERO).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(eth_frame.ethertype(), EthernetProtocol::Arp);
+    });
+    assert!(device.queue.is_empty());
+    assert!(!iface.neighbor_queue_egress(&mut device));
+
+    let arp_repr = ArpRepr::EthernetIpv4 {
+        operation: ArpOperation::Reply,
+        source_hardware_addr: remote_hw_addr,
+        source_protocol_addr: remote_ip_addr,
+        target_hardware_addr: iface.hardware_addr().ethernet_or_panic(),
+        target_protocol_addr: Ipv4Address([0x7f, 0x00, 0x00, 0x01]),
+    };
+    let mut eth_bytes = vec![0u8; 42];
+    let mut frame = EthernetFrame::new_unchecked(&mut eth_bytes);
+    frame.set_dst_addr(iface.hardware_addr().ethernet_or_panic());
+    frame.set_src_addr(remote_hw_addr);
+    frame.set_ethertype(EthernetProtocol::Arp);
+    arp_repr.emit(&mut ArpPacket::new_unchecked(frame.payload_mut()));
+    assert_eq!(
+        iface.inner.process_ethernet(
+            &mut sockets,
+            PacketMeta::default(),
+            &eth_bytes,
+            &mut iface.fragments
+        ),
+        None
+    );
+
+    // Once the neighbor answers, the queued datagram is sent.
+    assert!(iface.neighbor_queue_egress(&mut device));
+    let (rx, _tx) = device.receive(Instant::ZERO).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(eth_frame.dst_addr(), remote_hw_addr);
+        assert_eq!(eth_frame.ethertype(), EthernetProtocol::Ipv4);
+
+        let ipv4_packet = Ipv4PacketWire::new_checked(eth_frame.payload()).unwrap();
+        assert_eq!(ipv4_packet.dst_addr(), remote_ip_addr);
+        let udp_packet = UdpPacket::new_checked(ipv4_packet.payload()).unwrap();
+        assert_eq!(udp_packet.dst_port(), 9);
+        assert_eq!(udp_packet.payload(), b"first");
+    });
+    assert!(device.queue.is_empty());
+    assert!(!iface.neighbor_queue_egress(&mut device));
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ethernet"))]
+fn test_neighbor_queue_timeout() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    iface.set_neighbor_queue_depth(1);
+
+    let mut socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY; 2], vec![0; 32]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY; 2], vec![0; 32]),
+    );
+    socket.bind(6969).unwrap();
+    let endpoint = (IpAddress::v4(127, 0, 0, 2), 9);
+    socket.send_slice(b"first", endpoint).unwrap();
+    socket.send_slice(b"second", endpoint).unwrap();
+    let handle = sockets.add(socket);
+
+    // Only one datagram fits in the queue; the other one stays in its socket.
+    assert!(iface.socket_egress(&mut device, &mut sockets));
+    assert!(!iface.socket_egress(&mut device, &mut sockets));
+    let socket = sockets.get_mut::<udp::Socket>(handle);
+    assert_eq!(socket.send_slice(b"third", endpoint), Ok(()));
+    assert_eq!(
+        socket.send_slice(b"fourth", endpoint),
+        Err(udp::SendError::BufferFull)
+    );
+    assert_eq!(
+        iface.poll_at(Instant::ZERO, &sockets),
+        Some(Instant::from_secs(1))
+    );
+
+    // Discovery is retried until the queued datagram expires.
+    device.queue.clear();
+    for secs in 1..3 {
+        iface.inner.now = Instant::from_secs(secs);
+        assert!(!iface.neighbor_queue_egress(&mut device));
+        assert_eq!(device.queue.len(), 1);
+        device.queue.clear();
+    }
+    iface.inner.now = Instant::from_secs(3);
+    assert!(!iface.neighbor_queue_egress(&mut device));
+    assert!(device.queue.is_empty());
+    assert_eq!(iface.inner.neighbor_queue.poll_at(), None);
+}
@@ -12,6 +12,8 @@ fn parse_ipv6(data: &[u8]) -> crate::wire::Result<IpPacket<'_>> {
         IpProtocol::Udp => todo!(),
         IpProtocol::Ipv6Route => todo!(),
         IpProtocol::Ipv6Frag => todo!(),
+        IpProtocol::IpSecEsp => todo!(),
+        IpProtocol::IpSecAh => todo!(),
         IpProtocol::Icmpv6 => {
             let icmp = Icmpv6Repr::parse(
                 &ipv6.src_addr.into(),
@@ -103,6 +105,54 @@ fn hop_by_hop_skip_with_icmp(#[case] medium: Medium) {
     );
 }
 
+#[rstest]
+#[case::ip(Medium::Ip)]
+#[cfg(feature = "medium-ip")]
+#[case::ethernet(Medium::Ethernet)]
+#[cfg(feature = "medium-ethernet")]
+#[case::ieee802154(Medium::Ieee802154)]
+#[cfg(feature = "medium-ieee802154")]
+fn hop_by_hop_malformed_router_alert(#[case] medium: Medium) {
+    // The following contains:
+    // - IPv6 header
+    // - Hop-by-hop, with options:
+    //  - PADN (skipped)
+    //  - Router Alert without a value (skipped like an unknown option)
+    // - ICMP echo request
+    let data = [
+        0x60, 0x0, 0x0, 0x0, 0x0, 0x1b, 0x0, 0x40, 0xfd, 0xbe, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2, 0xfd, 0xbe, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x1, 0x3a, 0x0, 0x1, 0x0, 0x5, 0x0, 0x1, 0x0, 0x80, 0x0, 0x2c, 0x88,
+        0x0, 0x2a, 0x1, 0xa4, 0x4c, 0x6f, 0x72, 0x65, 0x6d, 0x20, 0x49, 0x70, 0x73, 0x75, 0x6d,
+    ];
+
+    let response = Some(IpPacket::new_ipv6(
+        Ipv6Repr {
+            src_addr: Ipv6Address::from_parts(&[0xfdbe, 0, 0, 0, 0, 0, 0, 0x0001]),
+            dst_addr: Ipv6Address::from_parts(&[0xfdbe, 0, 0, 0, 0, 0, 0, 0x0002]),
+            hop_limit: 64,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: 19,
+        },
+        IpPayload::Icmpv6(Icmpv6Repr::EchoReply {
+            ident: 42,
+            seq_no: 420,
+            data: b"Lorem Ipsum",
+        }),
+    ));
+
+    let (mut iface, mut sockets, _device) = setup(medium);
+
+    assert_eq!(
+        iface.inner.process_ipv6(
+            &mut sockets,
+            PacketMeta::default(),
+            &Ipv6PacketWire::new_checked(&data[..]).unwrap()
+        ),
+        response
+    );
+}
+
 #[rstest]
 #[case::ip(Medium::Ip)]
 #[cfg(feature = "medium-ip")]
@@ -734,3 +784,557 @@ fn test_icmp_reply_size(#[case] medium: Medium) {
         ))
     );
 }
+
+#[test]
+#[cfg(all(feature = "medium-ethernet", feature = "socket-udp"))]
+fn test_stale_neighbor_unicast_probe() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+
+    let local_ip_addr = Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 1);
+    let remote_ip_addr = Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 2);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]);
+
+    iface.inner.neighbor_cache.fill(
+        IpAddress::Ipv6(remote_ip_addr),
+        HardwareAddress::Ethernet(remote_hw_addr),
+        Instant::ZERO,
+    );
+
+    let rx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]);
+    let tx_buffer = udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]);
+    let mut socket = udp::Socket::new(rx_buffer, tx_buffer);
+    socket.bind((local_ip_addr, 0x1234)).unwrap();
+    socket
+        .send_slice(b"hello", (remote_ip_addr, 0x4321))
+        .unwrap();
+    sockets.add(socket);
+
+    // Let the reachability timer expire, and trigger a send.
+    iface.inner.now = Instant::from_secs(61);
+    iface.socket_egress(&mut device, &mut sockets);
+
+    let (rx, _tx) = device.receive(iface.inner.now).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(eth_frame.dst_addr(), remote_hw_addr);
+        assert_eq!(eth_frame.ethertype(), EthernetProtocol::Ipv6);
+
+        let ipv6_packet = Ipv6PacketWire::new_checked(eth_frame.payload()).unwrap();
+        assert_eq!(ipv6_packet.dst_addr(), remote_ip_addr);
+
+        let expected = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
+            target_addr: remote_ip_addr,
+            lladdr: Some(EthernetAddress::default().into()),
+        });
+        assert_eq!(
+            parse_ipv6(eth_frame.payload()).unwrap(),
+            IpPacket::new_ipv6(
+                Ipv6Repr {
+                    src_addr: local_ip_addr,
+                    dst_addr: remote_ip_addr,
+                    next_header: IpProtocol::Icmpv6,
+                    payload_len: expected.buffer_len(),
+                    hop_limit: 0xff,
+                },
+                IpPayload::Icmpv6(expected)
+            )
+        );
+    });
+    assert!(device.receive(iface.inner.now).is_none());
+
+    // Without unicast probing, the solicitation is multicast.
+    iface.set_neighbor_unicast_probes(false);
+    iface.inner.now = Instant::from_secs(63);
+    iface.socket_egress(&mut device, &mut sockets);
+
+    let (rx, _tx) = device.receive(iface.inner.now).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(
+            eth_frame.dst_addr(),
+            EthernetAddress([0x33, 0x33, 0xff, 0x00, 0x00, 0x02])
+        );
+        let ipv6_packet = Ipv6PacketWire::new_checked(eth_frame.payload()).unwrap();
+        assert_eq!(ipv6_packet.dst_addr(), remote_ip_addr.solicited_node());
+    });
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_link_local_dad() {
+    let (mut iface, _sockets, mut device) = setup(Medium::Ethernet);
+    iface.set_hardware_addr(HardwareAddress::Ethernet(EthernetAddress([
+        0x52, 0x54, 0x00, 0x12, 0x34, 0x56,
+    ])));
+
+    let link_local_addr = Ipv6Address::new(0xfe80, 0, 0, 0, 0x5054, 0x00ff, 0xfe12, 0x3456);
+    assert_eq!(iface.add_link_local_ipv6(), Some(link_local_addr));
+    assert_eq!(iface.dad_state(link_local_addr), Some(DadState::Tentative));
+    assert!(!iface.has_ip_addr(link_local_addr));
+
+    // A Neighbor Solicitation for the tentative address is sent right away.
+    assert!(iface.dad_egress(&mut device));
+    let (rx, _tx) = device.receive(iface.inner.now).unwrap();
+    rx.consume(|frame| {
+        let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+        assert_eq!(
+            eth_frame.dst_addr(),
+            EthernetAddress([0x33, 0x33, 0xff, 0x12, 0x34, 0x56])
+        );
+
+        let expected = Icmpv6Repr::Ndisc(NdiscRepr::NeighborSolicit {
+            target_addr: link_local_addr,
+            lladdr: None,
+        });
+        assert_eq!(
+            parse_ipv6(eth_frame.payload()).unwrap(),
+            IpPacket::new_ipv6(
+                Ipv6Repr {
+                    src_addr: Ipv6Address::UNSPECIFIED,
+                    dst_addr: link_local_addr.solicited_node(),
+                    next_header: IpProtocol::Icmpv6,
+                    payload_len: expected.buffer_len(),
+                    hop_limit: 0xff,
+                },
+                IpPayload::Icmpv6(expected)
+            )
+        );
+    });
+    assert!(!iface.dad_egress(&mut device));
+    assert_eq!(
+        iface.poll_at(Instant::ZERO, &SocketSet::new(vec![])),
+        Some(Instant::from_secs(1))
+    );
+
+    // Nobody answered, so the address becomes usable.
+    iface.inner.now = Instant::from_secs(1);
+    assert!(iface.dad_egress(&mut device));
+    assert!(device.receive(iface.inner.now).is_none());
+    assert_eq!(iface.dad_state(link_local_addr), Some(DadState::Assigned));
+    assert!(iface.has_ip_addr(link_local_addr));
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_link_local_dad_duplicate() {
+    let (mut iface, _sockets, mut device) = setup(Medium::Ethernet);
+
+    let link_local_addr = iface.add_link_local_ipv6().unwrap();
+    assert!(iface.dad_egress(&mut device));
+
+    // Another node answers for the address.
+    let ipv6_repr = Ipv6Repr {
+        src_addr: link_local_addr,
+        dst_addr: Ipv6Address::LINK_LOCAL_ALL_NODES,
+        next_header: IpProtocol::Icmpv6,
+        payload_len: 32,
+        hop_limit: 0xff,
+    };
+    let advert = NdiscRepr::NeighborAdvert {
+        flags: NdiscNeighborFlags::OVERRIDE,
+        target_addr: link_local_addr,
+        lladdr: Some(EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]).into()),
+    };
+    assert_eq!(iface.inner.process_ndisc(ipv6_repr, advert), None);
+    assert_eq!(iface.dad_state(link_local_addr), Some(DadState::Duplicate));
+
+    iface.inner.now = Instant::from_secs(1);
+    assert!(!iface.dad_egress(&mut device));
+    assert!(!iface.has_ip_addr(link_local_addr));
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_slaac_stable_privacy() {
+    let hardware_addr = EthernetAddress([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+    let prefix = Ipv6Cidr::new(Ipv6Address::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, 0), 64);
+    let slaac_addr = |secret: [u8; 16], prefix: Ipv6Cidr| {
+        let (mut iface, _sockets, _device) = setup(Medium::Ethernet);
+        iface.set_hardware_addr(HardwareAddress::Ethernet(hardware_addr));
+        iface.inner.ipv6_iid_mode = Ipv6IidMode::StablePrivacy { secret };
+        iface.add_slaac_ipv6(prefix).unwrap()
+    };
+
+    let addr = slaac_addr([0x42; 16], prefix);
+    assert!(prefix.contains_addr(&addr));
+    assert_eq!(slaac_addr([0x42; 16], prefix), addr);
+    assert_ne!(
+        addr,
+        Ipv6Address::new(0x2001, 0xdb8, 0, 1, 0x5054, 0x00ff, 0xfe12, 0x3456)
+    );
+
+    // Another secret, or another prefix, gives an unrelated identifier.
+    assert_ne!(slaac_addr([0x43; 16], prefix).0[8..], addr.0[8..]);
+    let other_prefix = Ipv6Cidr::new(Ipv6Address::new(0x2001, 0xdb8, 0, 2, 0, 0, 0, 0), 64);
+    assert_ne!(slaac_addr([0x42; 16], other_prefix).0[8..], addr.0[8..]);
+
+    // Only /64 prefixes are autoconfigured.
+    let (mut iface, _sockets, _device) = setup(Medium::Ethernet);
+    let long_prefix = Ipv6Cidr::new(Ipv6Address::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, 0), 96);
+    assert_eq!(iface.add_slaac_ipv6(long_prefix), None);
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_slaac_temporary_rotation() {
+    let (mut iface, sockets, mut device) = setup(Medium::Ethernet);
+    iface.update_ip_addrs(|ip_addrs| ip_addrs.clear());
+    iface.inner.ipv6_iid_mode = Ipv6IidMode::Temporary {
+        lifetime: Duration::from_secs(60),
+    };
+
+    let prefix = Ipv6Cidr::new(Ipv6Address::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, 0), 64);
+    let addr = iface.add_slaac_ipv6(prefix).unwrap();
+    assert!(prefix.contains_addr(&addr));
+    assert_eq!(iface.dad_state(addr), Some(DadState::Tentative));
+
+    assert!(iface.dad_egress(&mut device));
+    iface.inner.now = Instant::from_secs(1);
+    assert!(iface.dad_egress(&mut device));
+    assert!(iface.has_ip_addr(addr));
+    assert_eq!(
+        iface.poll_at(iface.inner.now, &sockets),
+        Some(Instant::from_secs(60))
+    );
+
+    // Once its lifetime is over, the address is replaced by another one in
+    // the same prefix.
+    iface.inner.now = Instant::from_secs(59);
+    assert!(!iface.inner.rotate_temporary_addrs());
+    iface.inner.now = Instant::from_secs(60);
+    assert!(iface.inner.rotate_temporary_addrs());
+    assert!(!iface.has_ip_addr(addr));
+    assert_eq!(iface.dad_state(addr), None);
+
+    let new_addr = iface.inner.ipv6_temporary_addrs[0].cidr.address();
+    assert_ne!(new_addr, addr);
+    assert!(prefix.contains_addr(&new_addr));
+    assert_eq!(iface.dad_state(new_addr), Some(DadState::Tentative));
+    assert_eq!(
+        iface.inner.ipv6_temporary_addrs[0].expires_at,
+        Instant::from_secs(120)
+    );
+}
+
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[cfg(feature = "medium-ethernet")]
+fn test_redirect(#[case] accept_redirects: bool) {
+    let (mut iface, _sockets, _device) = setup(Medium::Ethernet);
+    iface.inner.accept_redirects = accept_redirects;
+
+    let router_addr = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+    let target_addr = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 2);
+    let dest_addr = IpAddress::v6(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2);
+    iface
+        .routes_mut()
+        .add_default_ipv6_route(router_addr)
+        .unwrap();
+
+    let redirect = NdiscRepr::Redirect {
+        target_addr,
+        dest_addr: Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2),
+        lladdr: Some(EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]).into()),
+        redirected_hdr: None,
+    };
+    let mut ipv6_repr = Ipv6Repr {
+        src_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 3),
+        dst_addr: Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 1),
+        next_header: IpProtocol::Icmpv6,
+        payload_len: 48,
+        hop_limit: 0xff,
+    };
+
+    // A redirect from a node that is not our first hop is never honored.
+    assert_eq!(iface.inner.process_ndisc(ipv6_repr, redirect), None);
+    assert_eq!(
+        iface.inner.route(&dest_addr, iface.inner.now),
+        Some(router_addr.into())
+    );
+
+    ipv6_repr.src_addr = router_addr;
+    assert_eq!(iface.inner.process_ndisc(ipv6_repr, redirect), None);
+    let next_hop = if accept_redirects {
+        target_addr
+    } else {
+        router_addr
+    };
+    assert_eq!(
+        iface.inner.route(&dest_addr, iface.inner.now),
+        Some(next_hop.into())
+    );
+    assert_eq!(
+        iface
+            .inner
+            .neighbor_cache
+            .lookup(&target_addr.into(), iface.inner.now)
+            .found(),
+        accept_redirects
+    );
+
+    // The route installed by the redirect is temporary.
+    iface.inner.now = Instant::from_secs(601);
+    assert_eq!(
+        iface.inner.route(&dest_addr, iface.inner.now),
+        Some(router_addr.into())
+    );
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_router_advertisement() {
+    let (mut iface, sockets, mut device) = setup(Medium::Ethernet);
+    let hardware_addr = EthernetAddress([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]);
+    iface.set_hardware_addr(HardwareAddress::Ethernet(hardware_addr));
+    let link_local_addr = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+    iface.update_ip_addrs(|ip_addrs| {
+        ip_addrs
+            .push(IpCidr::new(link_local_addr.into(), 64))
+            .unwrap();
+    });
+
+    let prefix = NdiscPrefixInformation {
+        prefix_len: 64,
+        flags: NdiscPrefixInfoFlags::ON_LINK | NdiscPrefixInfoFlags::ADDRCONF,
+        valid_lifetime: Duration::from_secs(86400),
+        preferred_lifetime: Duration::from_secs(14400),
+        prefix: Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 0),
+    };
+    let mut config = RaConfig {
+        min_interval: Duration::from_secs(4),
+        max_interval: Duration::from_secs(6),
+        ..RaConfig::default()
+    };
+    config.prefixes.push(prefix).unwrap();
+    iface.set_router_advertisement(Some(config));
+
+    let recv_advert = |device: &mut Loopback, now: Instant| {
+        let (rx, _tx) = device.receive(now).unwrap();
+        rx.consume(|frame| {
+            let eth_frame = EthernetFrame::new_checked(&*frame).unwrap();
+            let expected = Icmpv6Repr::Ndisc(NdiscRepr::router_advertisement(
+                64,
+                NdiscRouterFlags::empty(),
+                Duration::from_secs(1800),
+                &[prefix],
+                None,
+                Some(hardware_addr.into()),
+            ));
+            assert_eq!(
+                parse_ipv6(eth_frame.payload()).unwrap(),
+                IpPacket::new_ipv6(
+                    Ipv6Repr {
+                        src_addr: link_local_addr,
+                        dst_addr: Ipv6Address::LINK_LOCAL_ALL_NODES,
+                        next_header: IpProtocol::Icmpv6,
+                        payload_len: expected.buffer_len(),
+                        hop_limit: 0xff,
+                    },
+                    IpPayload::Icmpv6(expected)
+                )
+            );
+        });
+    };
+
+    // The first advertisement is sent right away.
+    assert_eq!(
+        iface.poll_at(Instant::ZERO, &sockets),
+        Some(Instant::from_millis(0))
+    );
+    assert!(iface.router_advert_egress(&mut device));
+    recv_advert(&mut device, iface.inner.now);
+    assert!(!iface.router_advert_egress(&mut device));
+
+    // The next ones are sent at random within the configured interval.
+    let mut last_at = Instant::ZERO;
+    for _ in 0..10 {
+        let next_at = iface.poll_at(last_at, &sockets).unwrap();
+        assert!(next_at >= last_at + Duration::from_secs(4));
+        assert!(next_at <= last_at + Duration::from_secs(6));
+        iface.inner.now = next_at - Duration::from_millis(1);
+        assert!(!iface.router_advert_egress(&mut device));
+        iface.inner.now = next_at;
+        assert!(iface.router_advert_egress(&mut device));
+        recv_advert(&mut device, iface.inner.now);
+        last_at = next_at;
+    }
+
+    // A solicitation is answered right away, but not sooner than 3 seconds
+    // after the last advertisement.
+    let ip_repr = Ipv6Repr {
+        src_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 2),
+        dst_addr: Ipv6Address::LINK_LOCAL_ALL_ROUTERS,
+        next_header: IpProtocol::Icmpv6,
+        payload_len: 16,
+        hop_limit: 0xff,
+    };
+    let solicit = NdiscRepr::RouterSolicit {
+        lladdr: Some(EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x02]).into()),
+    };
+    iface.inner.now = last_at + Duration::from_secs(1);
+    assert_eq!(iface.inner.process_ndisc(ip_repr, solicit), None);
+    assert_eq!(
+        iface.poll_at(iface.inner.now, &sockets),
+        Some(last_at + Duration::from_secs(3))
+    );
+    assert!(!iface.router_advert_egress(&mut device));
+    iface.inner.now = last_at + Duration::from_secs(3);
+    assert!(iface.router_advert_egress(&mut device));
+    recv_advert(&mut device, iface.inner.now);
+
+    last_at = iface.inner.now;
+    iface.inner.now = last_at + Duration::from_secs(3);
+    assert_eq!(iface.inner.process_ndisc(ip_repr, solicit), None);
+    assert!(iface.router_advert_egress(&mut device));
+    recv_advert(&mut device, iface.inner.now);
+
+    // Without a configuration, the interface stops advertising.
+    iface.set_router_advertisement(None);
+    assert_eq!(iface.poll_at(iface.inner.now, &sockets), None);
+    assert_eq!(iface.inner.process_ndisc(ip_repr, solicit), None);
+    iface.inner.now += Duration::from_secs(600);
+    assert!(!iface.router_advert_egress(&mut device));
+}
+
+#[test]
+#[cfg(feature = "medium-ethernet")]
+fn test_anycast_ipv6() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ethernet);
+    let local_ip_addr = Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 1);
+    let anycast_addr = Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 0x100);
+    let remote_ip_addr = Ipv6Address::new(0xfdbe, 0, 0, 0, 0, 0, 0, 2);
+    let remote_hw_addr = EthernetAddress([0x52, 0x54, 0x00, 0x00, 0x00, 0x00]);
+
+    assert!(iface.add_anycast_ipv6(anycast_addr));
+    assert!(!iface.add_anycast_ipv6(Ipv6Address::LINK_LOCAL_ALL_NODES));
+    assert!(iface.has_anycast_ipv6(anycast_addr));
+    assert!(!iface.has_ip_addr(anycast_addr));
+
+    // No Duplicate Address Detection is performed.
+    assert_eq!(iface.dad_state(anycast_addr), None);
+    iface.poll(Instant::ZERO, &mut device, &mut sockets);
+    assert!(device.receive(Instant::ZERO).is_none());
+
+    // Packets to the anycast address are accepted, and answered from a
+    // unicast address.
+    let ip_repr = Ipv6Repr {
+        src_addr: remote_ip_addr,
+        dst_addr: anycast_addr,
+        next_header: IpProtocol::Icmpv6,
+        hop_limit: 64,
+        payload_len: 0,
+    };
+    let echo = Icmpv6Repr::EchoRequest {
+        ident: 42,
+        seq_no: 420,
+        data: b"anycast",
+    };
+    let ip_repr = Ipv6Repr {
+        payload_len: echo.buffer_len(),
+        ..ip_repr
+    };
+    let mut bytes = vec![0; ip_repr.buffer_len() + echo.buffer_len()];
+    ip_repr.emit(&mut Ipv6PacketWire::new_unchecked(&mut bytes[..]));
+    echo.emit(
+        &remote_ip_addr.into(),
+        &anycast_addr.into(),
+        &mut Icmpv6Packet::new_unchecked(&mut bytes[ip_repr.buffer_len()..]),
+        &ChecksumCapabilities::default(),
+    );
+    let reply = Icmpv6Repr::EchoReply {
+        ident: 42,
+        seq_no: 420,
+        data: b"anycast",
+    };
+    assert_eq!(
+        iface.inner.process_ipv6(
+            &mut sockets,
+            PacketMeta::default(),
+            &Ipv6PacketWire::new_checked(&bytes[..]).unwrap()
+        ),
+        Some(IpPacket::new_ipv6(
+            Ipv6Repr {
+                src_addr: local_ip_addr,
+                dst_addr: remote_ip_addr,
+                next_header: IpProtocol::Icmpv6,
+                hop_limit: 64,
+                payload_len: reply.buffer_len(),
+            },
+            IpPayload::Icmpv6(reply)
+        ))
+    );
+
+    // Neighbor Solicitations for it are answered from a unicast address too.
+    let solicit = NdiscRepr::NeighborSolicit {
+        target_addr: anycast_addr,
+        lladdr: Some(remote_hw_addr.into()),
+    };
+    let ip_repr = Ipv6Repr {
+        src_addr: remote_ip_addr,
+        dst_addr: anycast_addr.solicited_node(),
+        next_header: IpProtocol::Icmpv6,
+        hop_limit: 0xff,
+        payload_len: Icmpv6Repr::Ndisc(solicit).buffer_len(),
+    };
+    let advert = Icmpv6Repr::Ndisc(NdiscRepr::NeighborAdvert {
+        flags: NdiscNeighborFlags::SOLICITED,
+        target_addr: anycast_addr,
+        lladdr: Some(iface.inner.hardware_addr.into()),
+    });
+    assert_eq!(
+        iface.inner.process_ndisc(ip_repr, solicit),
+        Some(IpPacket::new_ipv6(
+            Ipv6Repr {
+                src_addr: local_ip_addr,
+                dst_addr: remote_ip_addr,
+                next_header: IpProtocol::Icmpv6,
+                hop_limit: 0xff,
+                payload_len: advert.buffer_len(),
+            },
+            IpPayload::Icmpv6(advert)
+        ))
+    );
+
+    // The address is not defended against another node assigning it.
+    let ip_repr = Ipv6Repr {
+        src_addr: Ipv6Address::UNSPECIFIED,
+        ..ip_repr
+    };
+    let solicit = NdiscRepr::NeighborSolicit {
+        target_addr: anycast_addr,
+        lladdr: None,
+    };
+    assert_eq!(iface.inner.process_ndisc(ip_repr, solicit), None);
+
+    // It is never chosen as the source of outgoing packets.
+    assert_ne!(
+        iface.inner.get_source_address_ipv6(remote_ip_addr),
+        Some(anycast_addr)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "socket-udp", feature = "medium-ip"))]
+fn test_socket_dscp() {
+    let (mut iface, mut sockets, mut device) = setup(Medium::Ip);
+
+    let mut socket = udp::Socket::new(
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+        udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY], vec![0; 64]),
+    );
+    socket.bind(1000).unwrap();
+    socket.set_dscp(46);
+    socket
+        .send_slice(b"abc", (IpAddress::v6(0, 0, 0, 0, 0, 0, 0, 1), 2000))
+        .unwrap();
+    sockets.add(socket);
+
+    iface.socket_egress(&mut device, &mut sockets);
+
+    // The DSCP goes in the upper six bits of the traffic class, leaving ECN
+    // unset.
+    let frame = device.queue.pop_front().unwrap();
+    let packet = Ipv6PacketWire::new_checked(&frame[..]).unwrap();
+    assert_eq!(packet.traffic_class(), 46 << 2);
+}
@@ -0,0 +1,130 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    iface::SocketSet,
+    phy::{self, Device, DeviceCapabilities, PacketMeta},
+    time::Instant,
+};
+
+/// A transform applied to the bytes of a frame.
+pub(crate) type Transform = Box<dyn FnMut(&mut Vec<u8>) + Send>;
+
+/// The frame transforms installed on an interface.
+#[derive(Default)]
+pub(crate) struct Transforms {
+    pub(crate) egress: Option<Transform>,
+    pub(crate) ingress: Option<Transform>,
+}
+
+impl Transforms {
+    /// Wrap a device, so that frames going through it are transformed.
+    pub(crate) fn wrap<'a, D>(&'a mut self, device: &'a mut D) -> TransformDevice<'a, D>
+    where
+        D: Device + ?Sized,
+    {
+        TransformDevice {
+            inner: device,
+            transforms: self,
+        }
+    }
+}
+
+/// A device applying the transforms of an interface to the frames of
+/// another device.
+pub(crate) struct TransformDevice<'a, D: Device + ?Sized> {
+    inner: &'a mut D,
+    transforms: &'a mut Transforms,
+}
+
+impl<'a, D: Device + ?Sized> Device for TransformDevice<'a, D> {
+    type RxToken<'b>
+        = RxToken<'b, D::RxToken<'b>>
+    where
+        Self: 'b;
+    type TxToken<'b>
+        = TxToken<'b, D::TxToken<'b>>
+    where
+        Self: 'b;
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn receive(&mut self, timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let (rx_token, tx_token) = self.inner.receive(timestamp)?;
+        let rx = RxToken {
+            token: rx_token,
+            transform: &mut self.transforms.ingress,
+        };
+        let tx = TxToken {
+            token: tx_token,
+            transform: &mut self.transforms.egress,
+        };
+        Some((rx, tx))
+    }
+
+    fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let tx_token = self.inner.transmit(timestamp)?;
+        Some(TxToken {
+            token: tx_token,
+            transform: &mut self.transforms.egress,
+        })
+    }
+}
+
+pub(crate) struct RxToken<'a, Rx: phy::RxToken> {
+    token: Rx,
+    transform: &'a mut Option<Transform>,
+}
+
+impl<'a, Rx: phy::RxToken> phy::RxToken for RxToken<'a, Rx> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        match self.transform {
+            Some(transform) => self.token.consume(|buffer| {
+                let mut frame = buffer.to_vec();
+                transform(&mut frame);
+                f(&mut frame)
+            }),
+            None => self.token.consume(f),
+        }
+    }
+
+    fn preprocess(&self, sockets: &mut SocketSet<'_>) {
+        self.token.preprocess(sockets)
+    }
+
+    fn meta(&self) -> PacketMeta {
+        self.token.meta()
+    }
+}
+
+pub(crate) struct TxToken<'a, Tx: phy::TxToken> {
+    token: Tx,
+    transform: &'a mut Option<Transform>,
+}
+
+impl<'a, Tx: phy::TxToken> phy::TxToken for TxToken<'a, Tx> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        match self.transform {
+            Some(transform) => {
+                let mut frame = alloc::vec![0; len];
+                let result = f(&mut frame);
+                transform(&mut frame);
+                self.token
+                    .consume(frame.len(), |buffer| buffer.copy_from_slice(&frame));
+                result
+            }
+            None => self.token.consume(len, f),
+        }
+    }
+
+    fn set_meta(&mut self, meta: PacketMeta) {
+        self.token.set_meta(meta)
+    }
+}
@@ -33,10 +33,70 @@ impl<'p> IpPacket<'p> {
     pub(crate) fn new_ipv4(ip_repr: Ipv4Repr, payload: IpPayload<'p>) -> Self {
         Self::Ipv4(Ipv4Packet {
             header: ip_repr,
+            #[cfg(feature = "proto-igmp")]
+            router_alert: false,
+            dscp: 0,
+            payload,
+        })
+    }
+
+    /// Create an IPv4 packet carrying the Router Alert option.
+    #[cfg(feature = "proto-igmp")]
+    pub(crate) fn new_ipv4_with_router_alert(ip_repr: Ipv4Repr, payload: IpPayload<'p>) -> Self {
+        Self::Ipv4(Ipv4Packet {
+            header: ip_repr,
+            router_alert: true,
+            dscp: 0,
             payload,
         })
     }
 
+    /// Set the Differentiated Services Code Point of the packet.
+    #[cfg(any(
+        feature = "socket-udp",
+        feature = "socket-tcp",
+        all(feature = "alloc", feature = "medium-ethernet")
+    ))]
+    pub(crate) fn with_dscp(mut self, dscp: u8) -> Self {
+        match &mut self {
+            #[cfg(feature = "proto-ipv4")]
+            IpPacket::Ipv4(p) => p.dscp = dscp,
+            #[cfg(feature = "proto-ipv6")]
+            IpPacket::Ipv6(p) => p.dscp = dscp,
+        }
+        self
+    }
+
+    /// Return the Differentiated Services Code Point of the packet.
+    pub(crate) fn dscp(&self) -> u8 {
+        match self {
+            #[cfg(feature = "proto-ipv4")]
+            IpPacket::Ipv4(p) => p.dscp,
+            #[cfg(feature = "proto-ipv6")]
+            IpPacket::Ipv6(p) => p.dscp,
+        }
+    }
+
+    /// Return whether the packet carries the IPv4 Router Alert option.
+    #[cfg(feature = "proto-igmp")]
+    pub(crate) fn router_alert(&self) -> bool {
+        match self {
+            #[cfg(feature = "proto-ipv4")]
+            IpPacket::Ipv4(p) => p.router_alert,
+            #[cfg(feature = "proto-ipv6")]
+            IpPacket::Ipv6(_) => false,
+        }
+    }
+
+    /// Return the length of the IP options following the fixed header.
+    pub(crate) fn options_len(&self) -> usize {
+        #[cfg(feature = "proto-igmp")]
+        if self.router_alert() {
+            return IPV4_ROUTER_ALERT_LEN;
+        }
+        0
+    }
+
     #[cfg(feature = "proto-ipv6")]
     pub(crate) fn new_ipv6(ip_repr: Ipv6Repr, payload: IpPayload<'p>) -> Self {
         Self::Ipv6(Ipv6Packet {
@@ -47,6 +107,7 @@ impl<'p> IpPacket<'p> {
             fragment: None,
             #[cfg(feature = "proto-ipv6-routing")]
             routing: None,
+            dscp: 0,
             payload,
         })
     }
@@ -89,7 +150,13 @@ impl<'p> IpPacket<'p> {
                 &mut Icmpv6Packet::new_unchecked(payload),
                 &caps.checksum,
             ),
-            #[cfg(feature = "socket-raw")]
+            #[cfg(any(
+                feature = "socket-raw",
+                all(
+                    feature = "alloc",
+                    any(feature = "proto-ipv4", feature = "medium-ethernet")
+                )
+            ))]
             IpPayload::Raw(raw_packet) => payload.copy_from_slice(raw_packet),
             #[cfg(any(feature = "socket-udp", feature = "socket-dns"))]
             IpPayload::Udp(udp_repr, inner_payload) => udp_repr.emit(
@@ -109,7 +176,9 @@ impl<'p> IpPacket<'p> {
                 // this would result in our peer pushing our window and sever packet loss.
                 //
                 // I'm really not happy about this "solution" but I don't know what else to do.
-                if let Some(max_burst_size) = caps.max_burst_size {
+                // The window of authenticated segments is covered by their MAC.
+                if let (Some(max_burst_size), None) = (caps.max_burst_size, tcp_repr.authentication)
+                {
                     let mut max_segment_size = caps.max_transmission_unit;
                     max_segment_size -= _ip_repr.header_len();
                     max_segment_size -= tcp_repr.header_len();
@@ -145,6 +214,9 @@ impl<'p> IpPacket<'p> {
 #[cfg(feature = "proto-ipv4")]
 pub(crate) struct Ipv4Packet<'p> {
     header: Ipv4Repr,
+    #[cfg(feature = "proto-igmp")]
+    router_alert: bool,
+    dscp: u8,
     payload: IpPayload<'p>,
 }
 
@@ -159,6 +231,7 @@ pub(crate) struct Ipv6Packet<'p> {
     fragment: Option<Ipv6FragmentRepr>,
     #[cfg(feature = "proto-ipv6-routing")]
     routing: Option<Ipv6RoutingRepr<'p>>,
+    dscp: u8,
     payload: IpPayload<'p>,
 }
 
@@ -171,7 +244,13 @@ pub(crate) enum IpPayload<'p> {
     Igmp(IgmpRepr),
     #[cfg(feature = "proto-ipv6")]
     Icmpv6(Icmpv6Repr<'p>),
-    #[cfg(feature = "socket-raw")]
+    #[cfg(any(
+        feature = "socket-raw",
+        all(
+            feature = "alloc",
+            any(feature = "proto-ipv4", feature = "medium-ethernet")
+        )
+    ))]
     Raw(&'p [u8]),
     #[cfg(any(feature = "socket-udp", feature = "socket-dns"))]
     Udp(UdpRepr, &'p [u8]),
@@ -17,10 +17,28 @@ mod socket_set;
 
 mod ip_packet;
 
+#[cfg(feature = "_proto-fragmentation")]
+pub use self::fragmentation::FragmentOverlapPolicy;
+#[cfg(all(feature = "medium-ethernet", feature = "proto-ipv4"))]
+pub use self::interface::ArpAnnounceScope;
+#[cfg(feature = "socket-raw")]
+pub use self::interface::InjectError;
 #[cfg(feature = "proto-igmp")]
 pub use self::interface::MulticastError;
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+pub use self::interface::NeighborResolver;
+#[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+pub use self::interface::Router;
+#[cfg(all(
+    feature = "proto-ipv6",
+    any(feature = "medium-ethernet", feature = "medium-ieee802154")
+))]
+pub use self::interface::{DadState, Ipv6IidMode, RaConfig};
 pub use self::{
-    interface::{Config, Interface, InterfaceInner as Context},
-    route::{Route, RouteTableFull, Routes},
+    interface::{Config, Interface, InterfaceInner as Context, UrpfMode},
+    route::{Route, RouteCacheEntry, RouteTableFull, Routes},
     socket_set::{SocketHandle, SocketSet, SocketStorage},
 };
@@ -18,6 +18,9 @@ use crate::{
 pub struct Neighbor {
     hardware_addr: HardwareAddress,
     expires_at: Instant,
+    /// The number of unicast probes sent since the mapping expired.
+    #[cfg(feature = "medium-ethernet")]
+    probes: u8,
 }
 
 /// An answer to a neighbor cache lookup.
@@ -31,6 +34,9 @@ pub(crate) enum Answer {
     /// The neighbor address is not in the cache, or has expired,
     /// and a lookup has been made recently.
     RateLimited,
+    /// Resolving the neighbor address failed recently, and no new lookup
+    /// should be made until the negative cache entry expires.
+    Failed,
 }
 
 impl Answer {
@@ -43,11 +49,23 @@ impl Answer {
     }
 }
 
+/// An outstanding or failed neighbor resolution.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Pending {
+    requests: u8,
+    last_request_at: Instant,
+    failed_until: Option<Instant>,
+}
+
 /// A neighbor cache backed by a map.
 #[derive(Debug)]
 pub struct Cache {
     storage: LinearMap<IpAddress, Neighbor, IFACE_NEIGHBOR_CACHE_COUNT>,
+    pending: LinearMap<IpAddress, Pending, IFACE_NEIGHBOR_CACHE_COUNT>,
     silent_until: Instant,
+    negative_ttl: Duration,
+    unicast_probes: bool,
 }
 
 impl Cache {
@@ -57,11 +75,50 @@ impl Cache {
     /// Neighbor entry lifetime, in milliseconds.
     pub(crate) const ENTRY_LIFETIME: Duration = Duration::from_millis(60_000);
 
+    /// Number of unanswered broadcast or multicast discovery requests after
+    /// which resolution of an address is considered failed. Unicast probes are
+    /// counted separately, see [`MAX_UNICAST_PROBES`](Self::MAX_UNICAST_PROBES).
+    pub(crate) const MAX_REQUESTS: u8 = 3;
+
+    /// Number of unicast probes sent to the cached hardware address of an
+    /// expired neighbor before falling back to broadcast or multicast
+    /// discovery (`MAX_UNICAST_SOLICIT` in RFC 4861).
+    #[cfg(feature = "medium-ethernet")]
+    pub(crate) const MAX_UNICAST_PROBES: u8 = 3;
+
     /// Create a cache.
     pub fn new() -> Self {
         Self {
             storage: LinearMap::new(),
+            pending: LinearMap::new(),
             silent_until: Instant::from_millis(0),
+            negative_ttl: Duration::ZERO,
+            unicast_probes: true,
+        }
+    }
+
+    /// Return whether expired neighbors are probed with unicast requests.
+    pub fn unicast_probes(&self) -> bool {
+        self.unicast_probes
+    }
+
+    /// Set whether expired neighbors are probed with unicast requests.
+    pub fn set_unicast_probes(&mut self, enabled: bool) {
+        self.unicast_probes = enabled
+    }
+
+    /// Return how long a failed resolution is remembered.
+    pub fn negative_ttl(&self) -> Duration {
+        self.negative_ttl
+    }
+
+    /// Set how long a failed resolution is remembered.
+    ///
+    /// A zero duration, the default, disables negative caching.
+    pub fn set_negative_ttl(&mut self, ttl: Duration) {
+        self.negative_ttl = ttl;
+        if ttl == Duration::ZERO {
+            self.pending.clear();
         }
     }
 
@@ -77,7 +134,10 @@ impl Cache {
         let neighbor = Neighbor {
             expires_at: timestamp + Self::ENTRY_LIFETIME,
             hardware_addr,
+            #[cfg(feature = "medium-ethernet")]
+            probes: 0,
         };
+        self.pending.remove(&protocol_addr);
         match self.storage.insert(protocol_addr, neighbor) {
             Ok(Some(old_neighbor)) => {
                 if old_neighbor.hardware_addr != hardware_addr {
@@ -126,6 +186,7 @@ impl Cache {
         if let Some(&Neighbor {
             expires_at,
             hardware_addr,
+            ..
         }) = self.storage.get(protocol_addr)
         {
             if timestamp < expires_at {
@@ -133,6 +194,16 @@ impl Cache {
             }
         }
 
+        if let Some(&Pending {
+            failed_until: Some(failed_until),
+            ..
+        }) = self.pending.get(protocol_addr)
+        {
+            if timestamp < failed_until {
+                return Answer::Failed;
+            }
+        }
+
         if timestamp < self.silent_until {
             Answer::RateLimited
         } else {
@@ -140,12 +211,120 @@ impl Cache {
         }
     }
 
+    /// Return the hardware address a unicast probe should be sent to, if the
+    /// mapping for the given address has expired but may still be valid.
+    ///
+    /// Each call accounts for one probe; once the probes are exhausted, the
+    /// expired mapping is forgotten, and discovery falls back to broadcast or
+    /// multicast requests.
+    #[cfg(feature = "medium-ethernet")]
+    pub(crate) fn probe_addr(
+        &mut self,
+        protocol_addr: &IpAddress,
+        timestamp: Instant,
+    ) -> Option<HardwareAddress> {
+        if !self.unicast_probes {
+            return None;
+        }
+
+        let neighbor = self.storage.get_mut(protocol_addr)?;
+        if timestamp < neighbor.expires_at {
+            return None;
+        }
+        if neighbor.probes >= Self::MAX_UNICAST_PROBES {
+            net_trace!("unicast probes to {} unanswered", protocol_addr);
+            self.storage.remove(protocol_addr);
+            return None;
+        }
+
+        neighbor.probes += 1;
+        Some(neighbor.hardware_addr)
+    }
+
     pub(crate) fn limit_rate(&mut self, timestamp: Instant) {
         self.silent_until = timestamp + Self::SILENT_TIME;
     }
 
+    /// Check whether another discovery request may be sent for the given
+    /// address, and record a failed resolution if the retry limit has been
+    /// reached.
+    pub(crate) fn may_request(&mut self, protocol_addr: &IpAddress, timestamp: Instant) -> bool {
+        if self.negative_ttl == Duration::ZERO {
+            return true;
+        }
+
+        let negative_ttl = self.negative_ttl;
+        let pending = match self.pending.get_mut(protocol_addr) {
+            Some(pending) => pending,
+            None => return true,
+        };
+
+        match pending.failed_until {
+            Some(failed_until) if timestamp < failed_until => false,
+            Some(_) => {
+                net_trace!("negative entry for {} expired", protocol_addr);
+                self.pending.remove(protocol_addr);
+                true
+            }
+            None if pending.requests >= Self::MAX_REQUESTS => {
+                net_debug!(
+                    "failed to resolve {}, not retrying until t+{}",
+                    protocol_addr,
+                    negative_ttl
+                );
+                pending.failed_until = Some(timestamp + negative_ttl);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Record that a unicast probe has been sent.
+    ///
+    /// Probes are accounted for by [`probe_addr`](Self::probe_addr), so that
+    /// they don't use up the broadcast or multicast requests made once they
+    /// go unanswered; only the rate is limited here.
+    #[cfg(feature = "medium-ethernet")]
+    pub(crate) fn probe_sent(&mut self, timestamp: Instant) {
+        self.limit_rate(timestamp);
+    }
+
+    /// Record that a broadcast or multicast discovery request has been sent
+    /// for the given address.
+    pub(crate) fn request_sent(&mut self, protocol_addr: IpAddress, timestamp: Instant) {
+        self.limit_rate(timestamp);
+
+        if self.negative_ttl == Duration::ZERO {
+            return;
+        }
+
+        if let Some(pending) = self.pending.get_mut(&protocol_addr) {
+            pending.requests = pending.requests.saturating_add(1);
+            pending.last_request_at = timestamp;
+            return;
+        }
+
+        let pending = Pending {
+            requests: 1,
+            last_request_at: timestamp,
+            failed_until: None,
+        };
+        if let Err((protocol_addr, pending)) = self.pending.insert(protocol_addr, pending) {
+            // Forget about the resolution that has been idle for the longest time.
+            let old_protocol_addr = *self
+                .pending
+                .iter()
+                .min_by_key(|(_, pending)| pending.last_request_at)
+                .expect("empty pending neighbor storage")
+                .0;
+            self.pending.remove(&old_protocol_addr);
+            let _ = self.pending.insert(protocol_addr, pending);
+        }
+    }
+
     pub(crate) fn flush(&mut self) {
-        self.storage.clear()
+        self.storage.clear();
+        self.pending.clear();
     }
 }
 
@@ -163,6 +342,37 @@ mod test {
     const HADDR_C: HardwareAddress = HardwareAddress::Ethernet(EthernetAddress([0, 0, 0, 0, 0, 3]));
     const HADDR_D: HardwareAddress = HardwareAddress::Ethernet(EthernetAddress([0, 0, 0, 0, 0, 4]));
 
+    #[test]
+    #[cfg(feature = "medium-ethernet")]
+    fn test_probe_addr() {
+        let mut cache = Cache::new();
+
+        cache.fill(MOCK_IP_ADDR_1, HADDR_A, Instant::from_millis(0));
+        assert_eq!(
+            cache.probe_addr(&MOCK_IP_ADDR_1, Instant::from_millis(0)),
+            None
+        );
+        assert_eq!(
+            cache.probe_addr(&MOCK_IP_ADDR_2, Instant::from_millis(0)),
+            None
+        );
+
+        let expired = Instant::from_millis(0) + Cache::ENTRY_LIFETIME;
+        for _ in 0..Cache::MAX_UNICAST_PROBES {
+            assert_eq!(cache.probe_addr(&MOCK_IP_ADDR_1, expired), Some(HADDR_A));
+        }
+        assert_eq!(cache.probe_addr(&MOCK_IP_ADDR_1, expired), None);
+        assert_eq!(cache.probe_addr(&MOCK_IP_ADDR_1, expired), None);
+
+        // An answer restores the probes.
+        cache.fill(MOCK_IP_ADDR_1, HADDR_A, expired);
+        let expired = expired + Cache::ENTRY_LIFETIME;
+        assert_eq!(cache.probe_addr(&MOCK_IP_ADDR_1, expired), Some(HADDR_A));
+
+        cache.set_unicast_probes(false);
+        assert_eq!(cache.probe_addr(&MOCK_IP_ADDR_1, expired), None);
+    }
+
     #[test]
     fn test_fill() {
         let mut cache = Cache::new();
@@ -273,6 +483,63 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_negative_cache() {
+        let mut cache = Cache::new();
+        cache.set_negative_ttl(Duration::from_secs(10));
+
+        for i in 0..Cache::MAX_REQUESTS {
+            let timestamp = Instant::from_secs(i as i64 * 2);
+            assert_eq!(cache.lookup(&MOCK_IP_ADDR_1, timestamp), Answer::NotFound);
+            assert!(cache.may_request(&MOCK_IP_ADDR_1, timestamp));
+            cache.request_sent(MOCK_IP_ADDR_1, timestamp);
+        }
+
+        // The retry limit has been reached.
+        assert!(!cache.may_request(&MOCK_IP_ADDR_1, Instant::from_secs(6)));
+        assert_eq!(
+            cache.lookup(&MOCK_IP_ADDR_1, Instant::from_secs(15)),
+            Answer::Failed
+        );
+        assert!(cache.may_request(&MOCK_IP_ADDR_2, Instant::from_secs(15)));
+
+        // The negative entry has expired.
+        assert_eq!(
+            cache.lookup(&MOCK_IP_ADDR_1, Instant::from_secs(16)),
+            Answer::NotFound
+        );
+        assert!(cache.may_request(&MOCK_IP_ADDR_1, Instant::from_secs(16)));
+    }
+
+    #[test]
+    fn test_negative_cache_cleared_by_fill() {
+        let mut cache = Cache::new();
+        cache.set_negative_ttl(Duration::from_secs(10));
+
+        for _ in 0..Cache::MAX_REQUESTS {
+            cache.request_sent(MOCK_IP_ADDR_1, Instant::from_millis(0));
+        }
+        assert!(!cache.may_request(&MOCK_IP_ADDR_1, Instant::from_millis(0)));
+
+        cache.fill(MOCK_IP_ADDR_1, HADDR_A, Instant::from_millis(100));
+        assert_eq!(
+            cache.lookup(&MOCK_IP_ADDR_1, Instant::from_millis(100)),
+            Answer::Found(HADDR_A)
+        );
+        cache.flush();
+        assert!(cache.may_request(&MOCK_IP_ADDR_1, Instant::from_millis(100)));
+    }
+
+    #[test]
+    fn test_negative_cache_disabled() {
+        let mut cache = Cache::new();
+
+        for _ in 0..Cache::MAX_REQUESTS * 2 {
+            assert!(cache.may_request(&MOCK_IP_ADDR_1, Instant::from_millis(0)));
+            cache.request_sent(MOCK_IP_ADDR_1, Instant::from_millis(0));
+        }
+    }
+
     #[test]
     fn test_flush() {
         let mut cache = Cache::new();
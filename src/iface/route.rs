@@ -1,4 +1,5 @@
 use heapless::Vec;
+use managed::ManagedSlice;
 
 #[cfg(feature = "proto-ipv4")]
 use crate::wire::{Ipv4Address, Ipv4Cidr};
@@ -65,10 +66,26 @@ impl Route {
     }
 }
 
+/// The result of a route lookup, remembered for its destination.
+///
+/// The route cache is made of these; see [Routes::set_cache].
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RouteCacheEntry {
+    addr: IpAddress,
+    via_router: Option<IpAddress>,
+    /// When the route found expires; `None` means "forever".
+    expires_at: Option<Instant>,
+}
+
 /// A routing table.
 #[derive(Debug)]
 pub struct Routes {
     storage: Vec<Route, IFACE_MAX_ROUTE_COUNT>,
+    /// The results of recent lookups, replaced in turn.
+    cache: ManagedSlice<'static, Option<RouteCacheEntry>>,
+    cache_next: usize,
+    cache_hits: u64,
 }
 
 impl Routes {
@@ -76,14 +93,55 @@ impl Routes {
     pub fn new() -> Self {
         Self {
             storage: Vec::new(),
+            cache: ManagedSlice::Borrowed(&mut []),
+            cache_next: 0,
+            cache_hits: 0,
         }
     }
 
     /// Update the routes of this node.
+    ///
+    /// Like every other method changing the routes, this empties the route
+    /// cache, which also remembers destinations without a route.
     pub fn update<F: FnOnce(&mut Vec<Route, IFACE_MAX_ROUTE_COUNT>)>(&mut self, f: F) {
+        self.invalidate_cache();
         f(&mut self.storage);
     }
 
+    /// Set the storage of the route cache, which remembers the result of the
+    /// route lookup for as many destinations as it has entries.
+    ///
+    /// The cache is emptied whenever the routes change. Empty storage, the
+    /// default, disables it.
+    pub fn set_cache<S>(&mut self, storage: S)
+    where
+        S: Into<ManagedSlice<'static, Option<RouteCacheEntry>>>,
+    {
+        self.cache = storage.into();
+        self.cache_next = 0;
+        self.invalidate_cache();
+    }
+
+    /// Set the number of destinations the result of the route lookup is
+    /// remembered for, allocating the storage of the route cache.
+    ///
+    /// See also the [set_cache](#method.set_cache) method.
+    #[cfg(feature = "alloc")]
+    pub fn set_cache_size(&mut self, size: usize) {
+        self.set_cache(alloc::vec![None; size]);
+    }
+
+    /// Return the number of route lookups answered from the cache.
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits
+    }
+
+    fn invalidate_cache(&mut self) {
+        for entry in self.cache.iter_mut() {
+            *entry = None;
+        }
+    }
+
     /// Add a default ipv4 gateway (ie. "ip route add 0.0.0.0/0 via `gateway`").
     ///
     /// On success, returns the previous default route, if any.
@@ -92,6 +150,7 @@ impl Routes {
         &mut self,
         gateway: Ipv4Address,
     ) -> Result<Option<Route>, RouteTableFull> {
+        self.invalidate_cache();
         let old = self.remove_default_ipv4_route();
         self.storage
             .push(Route::new_ipv4_gateway(gateway))
@@ -107,6 +166,7 @@ impl Routes {
         &mut self,
         gateway: Ipv6Address,
     ) -> Result<Option<Route>, RouteTableFull> {
+        self.invalidate_cache();
         let old = self.remove_default_ipv6_route();
         self.storage
             .push(Route::new_ipv6_gateway(gateway))
@@ -119,6 +179,7 @@ impl Routes {
     /// On success, returns the previous default route, if any.
     #[cfg(feature = "proto-ipv4")]
     pub fn remove_default_ipv4_route(&mut self) -> Option<Route> {
+        self.invalidate_cache();
         if let Some((i, _)) = self
             .storage
             .iter()
@@ -136,6 +197,7 @@ impl Routes {
     /// On success, returns the previous default route, if any.
     #[cfg(feature = "proto-ipv6")]
     pub fn remove_default_ipv6_route(&mut self) -> Option<Route> {
+        self.invalidate_cache();
         if let Some((i, _)) = self
             .storage
             .iter()
@@ -151,6 +213,59 @@ impl Routes {
     pub(crate) fn lookup(&self, addr: &IpAddress, timestamp: Instant) -> Option<IpAddress> {
         assert!(addr.is_unicast());
 
+        match self.cached(addr, timestamp) {
+            Some(entry) => entry.via_router,
+            None => self.find(addr, timestamp).map(|route| route.via_router),
+        }
+    }
+
+    /// Look up the route to `addr` like [lookup](#method.lookup), and
+    /// remember the result in the route cache.
+    pub(crate) fn lookup_and_cache(
+        &mut self,
+        addr: &IpAddress,
+        timestamp: Instant,
+    ) -> Option<IpAddress> {
+        assert!(addr.is_unicast());
+
+        if let Some(entry) = self.cached(addr, timestamp) {
+            self.cache_hits += 1;
+            return entry.via_router;
+        }
+
+        let route = self.find(addr, timestamp).copied();
+        if !self.cache.is_empty() {
+            let index = self
+                .cache
+                .iter()
+                .position(|entry| matches!(entry, Some(entry) if entry.addr == *addr))
+                .unwrap_or_else(|| {
+                    let index = self.cache_next;
+                    self.cache_next = (index + 1) % self.cache.len();
+                    index
+                });
+            self.cache[index] = Some(RouteCacheEntry {
+                addr: *addr,
+                via_router: route.map(|route| route.via_router),
+                expires_at: route.and_then(|route| route.expires_at),
+            });
+        }
+
+        route.map(|route| route.via_router)
+    }
+
+    fn cached(&self, addr: &IpAddress, timestamp: Instant) -> Option<RouteCacheEntry> {
+        // A route only stops being the best one when it expires, and a
+        // destination without a route only gets one when the routes change,
+        // since every change empties the cache.
+        self.cache.iter().find_map(|entry| {
+            entry.filter(|entry| {
+                entry.addr == *addr && entry.expires_at.map_or(true, |at| timestamp <= at)
+            })
+        })
+    }
+
+    fn find(&self, addr: &IpAddress, timestamp: Instant) -> Option<&Route> {
         self.storage
             .iter()
             // Keep only matching routes
@@ -164,7 +279,6 @@ impl Routes {
             })
             // pick the most specific one (highest prefix_len)
             .max_by_key(|route| route.cidr.prefix_len())
-            .map(|route| route.via_router)
     }
 }
 
@@ -1,9 +1,13 @@
 use core::fmt;
 
+#[cfg(feature = "socket-tcp")]
+use heapless::Vec;
 use managed::ManagedSlice;
 
 use super::socket_meta::Meta;
 use crate::socket::{AnySocket, Socket};
+#[cfg(feature = "socket-tcp")]
+use crate::wire::{IpRepr, TcpRepr};
 
 /// Opaque struct with space for storing one socket.
 ///
@@ -18,6 +22,11 @@ impl<'a> SocketStorage<'a> {
     pub const EMPTY: Self = Self { inner: None };
 }
 
+/// The maximum number of segments tearing down the connections of removed TCP
+/// sockets that wait for the next poll of the interface.
+#[cfg(feature = "socket-tcp")]
+const TEARDOWN_QUEUE_SIZE: usize = 2;
+
 /// An item of a socket set.
 #[derive(Debug)]
 pub(crate) struct Item<'a> {
@@ -44,6 +53,10 @@ impl fmt::Display for SocketHandle {
 #[derive(Debug)]
 pub struct SocketSet<'a> {
     sockets: ManagedSlice<'a, SocketStorage<'a>>,
+    /// The segments tearing down the connections of removed TCP sockets,
+    /// waiting to be sent.
+    #[cfg(feature = "socket-tcp")]
+    teardowns: Vec<(IpRepr, TcpRepr<'static>), TEARDOWN_QUEUE_SIZE>,
 }
 
 impl<'a> SocketSet<'a> {
@@ -53,7 +66,11 @@ impl<'a> SocketSet<'a> {
         SocketsT: Into<ManagedSlice<'a, SocketStorage<'a>>>,
     {
         let sockets = sockets.into();
-        SocketSet { sockets }
+        SocketSet {
+            sockets,
+            #[cfg(feature = "socket-tcp")]
+            teardowns: Vec::new(),
+        }
     }
 
     /// Add a socket to the set, and return its handle.
@@ -67,9 +84,7 @@ impl<'a> SocketSet<'a> {
             let handle = SocketHandle(index);
             let mut meta = Meta::default();
             meta.handle = handle;
-            *slot = SocketStorage {
-                inner: Some(Item { meta, socket }),
-            };
+            slot.inner = Some(Item { meta, socket });
             handle
         }
 
@@ -85,7 +100,7 @@ impl<'a> SocketSet<'a> {
             ManagedSlice::Borrowed(_) => panic!("adding a socket to a full SocketSet"),
             #[cfg(feature = "alloc")]
             ManagedSlice::Owned(sockets) => {
-                sockets.push(SocketStorage { inner: None });
+                sockets.push(SocketStorage::EMPTY);
                 let index = sockets.len() - 1;
                 put(index, &mut sockets[index], socket)
             }
@@ -119,15 +134,34 @@ impl<'a> SocketSet<'a> {
         }
     }
 
-    /// Remove a socket from the set, without changing its state.
+    /// Remove a socket from the set, and return it.
+    ///
+    /// If it is a TCP socket with a [close-on-drop] mode, the segment tearing
+    /// down its connection is queued, and sent by the next poll of the
+    /// interface; the connection state of the returned socket is left as is.
+    /// Only a couple of segments are queued at a time: if the queue is full,
+    /// the connection is not torn down.
     ///
     /// # Panics
     /// This function may panic if the handle does not belong to this socket
     /// set.
+    ///
+    /// [close-on-drop]: crate::socket::tcp::Socket::set_close_on_drop
     pub fn remove(&mut self, handle: SocketHandle) -> Socket<'a> {
         net_trace!("[{}]: removing", handle.0);
         match self.sockets[handle.0].inner.take() {
-            Some(item) => item.socket,
+            #[cfg_attr(not(feature = "socket-tcp"), allow(unused_mut))]
+            Some(mut item) => {
+                #[cfg(feature = "socket-tcp")]
+                if let Socket::Tcp(socket) = &mut item.socket {
+                    if let Some(teardown) = socket.teardown() {
+                        if self.teardowns.push(teardown).is_err() {
+                            net_debug!("[{}]: teardown queue full, not closing", handle.0);
+                        }
+                    }
+                }
+                item.socket
+            }
             None => panic!("handle does not refer to a valid socket"),
         }
     }
@@ -142,6 +176,22 @@ impl<'a> SocketSet<'a> {
         self.items_mut().map(|i| (i.meta.handle, &mut i.socket))
     }
 
+    /// Call `f` with the segments tearing down the connections of removed
+    /// TCP sockets, which are forgotten once `f` returns `true`, until it
+    /// returns `false`.
+    #[cfg(feature = "socket-tcp")]
+    pub(crate) fn send_teardowns<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&(IpRepr, TcpRepr<'static>)) -> bool,
+    {
+        while let Some(teardown) = self.teardowns.first() {
+            if !f(teardown) {
+                break;
+            }
+            self.teardowns.remove(0);
+        }
+    }
+
     /// Iterate every socket in this set.
     pub(crate) fn items(&self) -> impl Iterator<Item = &Item<'a>> + '_ {
         self.sockets.iter().filter_map(|x| x.inner.as_ref())
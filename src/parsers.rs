@@ -523,6 +523,26 @@ mod test {
         assert_eq!(EthernetAddress::from_str("002:00:00:00:00:00"), Err(()));
         assert_eq!(EthernetAddress::from_str("02:00:00:00:00:000"), Err(()));
         assert_eq!(EthernetAddress::from_str("02:00:00:00:00:0x"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02:00:00:00:00:g0"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02:00:00:00:00"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02:00:00:00:00:00:01"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02:00:00:00:00:"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02-00-00:00:00:00"), Err(()));
+        assert_eq!(EthernetAddress::from_str("02.00.00.00.00.00"), Err(()));
+    }
+
+    #[test]
+    #[cfg(feature = "medium-ethernet")]
+    fn test_mac_display_round_trip() {
+        let addr = EthernetAddress([0x02, 0x00, 0x5e, 0xab, 0xcd, 0xef]);
+        assert_eq!(addr.to_string(), "02-00-5e-ab-cd-ef");
+        assert_eq!(EthernetAddress::from_str(&addr.to_string()), Ok(addr));
+        assert_eq!(
+            EthernetAddress::from_str("02:00:5E:AB:CD:EF")
+                .unwrap()
+                .to_string(),
+            "02-00-5e-ab-cd-ef"
+        );
     }
 
     #[test]
@@ -42,6 +42,24 @@ pub struct Config<'a> {
     pub packet: Option<DhcpPacket<&'a [u8]>>,
 }
 
+/// Configuration parameters provided by the DHCP server in reply to a
+/// DHCPINFORM.
+///
+/// Unlike [`Config`], this carries no address: the client is expected to
+/// already have one, configured by other means.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Information<'a> {
+    /// Information on how to reach the DHCP server that responded.
+    pub server: ServerInfo,
+    /// Router address, also known as default gateway.
+    pub router: Option<Ipv4Address>,
+    /// DNS servers
+    pub dns_servers: Vec<Ipv4Address, DHCP_MAX_DNS_SERVER_COUNT>,
+    /// Received DHCP packet
+    pub packet: Option<DhcpPacket<&'a [u8]>>,
+}
+
 /// Information on how to reach a DHCP server.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -73,6 +91,19 @@ struct RequestState {
     requested_ip: Ipv4Address,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct InformState {
+    /// When to send next inform
+    retry_at: Instant,
+    /// How many retries have been done
+    retry: u16,
+    /// Statically configured address we're informing from.
+    address: Ipv4Address,
+    /// Parameters received from the server, once it has replied.
+    information: Option<Information<'static>>,
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 struct RenewState {
@@ -109,6 +140,8 @@ enum ClientState {
     Requesting(RequestState),
     /// Having an address, refresh it periodically.
     Renewing(RenewState),
+    /// Having a static address, asking for the other parameters.
+    Informing(InformState),
 }
 
 /// Timeout and retry configuration.
@@ -174,11 +207,18 @@ pub struct Socket<'a> {
     outgoing_options: &'a [DhcpOption<'a>],
     /// A buffer containing all requested parameters.
     parameter_request_list: Option<&'a [u8]>,
+    /// The client identifier sent instead of the hardware address.
+    client_identifier: Option<&'a [u8]>,
+    /// The vendor class identifier sent, if any.
+    vendor_class: Option<&'a [u8]>,
 
     /// Incoming DHCP packets are copied into this buffer, overwriting the
     /// previous.
     receive_packet_buffer: Option<&'a mut [u8]>,
 
+    /// Called when a lease is acquired, renewed or lost.
+    on_config_change: Option<fn(Option<Config<'_>>)>,
+
     /// Waker registration
     #[cfg(feature = "async")]
     waker: WakerRegistration,
@@ -204,7 +244,10 @@ impl<'a> Socket<'a> {
             ignore_naks: false,
             outgoing_options: &[],
             parameter_request_list: None,
+            client_identifier: None,
+            vendor_class: None,
             receive_packet_buffer: None,
+            on_config_change: None,
             #[cfg(feature = "async")]
             waker: WakerRegistration::new(),
             server_port: DHCP_SERVER_PORT,
@@ -235,6 +278,35 @@ impl<'a> Socket<'a> {
         self.parameter_request_list = Some(parameter_request_list);
     }
 
+    /// Set the client identifier option sent in DISCOVER and REQUEST messages.
+    ///
+    /// The identifier is opaque data, whose first octet is its type, see
+    /// [RFC 2132 § 9.14]. By default, the hardware address is used, with the
+    /// Ethernet type.
+    ///
+    /// [RFC 2132 § 9.14]: https://www.rfc-editor.org/rfc/rfc2132#section-9.14
+    pub fn set_client_identifier(&mut self, client_identifier: &'a [u8]) {
+        self.client_identifier = Some(client_identifier);
+    }
+
+    /// Set the vendor class identifier option sent in DISCOVER and REQUEST
+    /// messages.
+    ///
+    /// By default, no vendor class identifier is sent.
+    pub fn set_vendor_class(&mut self, vendor_class: &'a [u8]) {
+        self.vendor_class = Some(vendor_class);
+    }
+
+    /// Set a callback invoked when the lease changes.
+    ///
+    /// The callback is given the configuration when a lease is acquired or
+    /// renewed, and `None` when it is lost, because it expired or the server
+    /// refused to renew it. Events are still returned by [`poll`](Self::poll)
+    /// as well.
+    pub fn set_on_config_change(&mut self, callback: Option<fn(Option<Config<'_>>)>) {
+        self.on_config_change = callback;
+    }
+
     /// Get the configured max lease duration.
     ///
     /// See also [`Self::set_max_lease_duration()`]
@@ -292,6 +364,12 @@ impl<'a> Socket<'a> {
                 state.renew_at.min(state.rebind_at)
             }
             .min(state.expires_at),
+            ClientState::Informing(state) => {
+                if state.information.is_some() || state.retry >= self.retry_config.request_retries {
+                    return PollAt::Ingress;
+                }
+                state.retry_at
+            }
         };
         PollAt::Time(t)
     }
@@ -387,6 +465,7 @@ impl<'a> Socket<'a> {
                         rebinding: false,
                     });
                     self.config_changed();
+                    self.notify_config_change();
                 }
             }
             (ClientState::Requesting(_), DhcpMessageType::Nak) => {
@@ -418,6 +497,7 @@ impl<'a> Socket<'a> {
                     if config_changed {
                         self.config_changed();
                     }
+                    self.notify_config_change();
                 }
             }
             (ClientState::Renewing(_), DhcpMessageType::Nak) => {
@@ -425,6 +505,21 @@ impl<'a> Socket<'a> {
                     self.reset();
                 }
             }
+            (ClientState::Informing(state), DhcpMessageType::Ack) => {
+                // The reply to a DHCPINFORM carries no lease; only pick up
+                // the parameters, and leave the address alone.
+                state.information = Some(Information {
+                    server: ServerInfo {
+                        address: src_ip,
+                        identifier: server_identifier,
+                    },
+                    router: dhcp_repr.router,
+                    dns_servers: Self::parse_dns_servers(&dhcp_repr),
+                    packet: None,
+                });
+                #[cfg(feature = "async")]
+                self.waker.wake();
+            }
             _ => {
                 net_debug!(
                     "DHCP ignoring {:?}: unexpected in current state",
@@ -469,27 +564,11 @@ impl<'a> Socket<'a> {
             lease_duration = lease_duration.min(max_lease_duration);
         }
 
-        // Cleanup the DNS servers list, keeping only unicasts/
-        // TP-Link TD-W8970 sends 0.0.0.0 as second DNS server if there's only one
-        // configured :(
-        let mut dns_servers = Vec::new();
-
-        dhcp_repr
-            .dns_servers
-            .iter()
-            .flatten()
-            .filter(|s| s.is_unicast())
-            .for_each(|a| {
-                // This will never produce an error, as both the arrays and `dns_servers`
-                // have length DHCP_MAX_DNS_SERVER_COUNT
-                dns_servers.push(*a).ok();
-            });
-
         let config = Config {
             server,
             address: Ipv4Cidr::new(dhcp_repr.your_ip, prefix_len),
             router: dhcp_repr.router,
-            dns_servers,
+            dns_servers: Self::parse_dns_servers(dhcp_repr),
             packet: None,
         };
 
@@ -533,6 +612,26 @@ impl<'a> Socket<'a> {
         Some((config, renew_at, rebind_at, expires_at))
     }
 
+    fn parse_dns_servers(dhcp_repr: &DhcpRepr) -> Vec<Ipv4Address, DHCP_MAX_DNS_SERVER_COUNT> {
+        // Cleanup the DNS servers list, keeping only unicasts/
+        // TP-Link TD-W8970 sends 0.0.0.0 as second DNS server if there's only one
+        // configured :(
+        let mut dns_servers = Vec::new();
+
+        dhcp_repr
+            .dns_servers
+            .iter()
+            .flatten()
+            .filter(|s| s.is_unicast())
+            .for_each(|a| {
+                // This will never produce an error, as both the arrays and `dns_servers`
+                // have length DHCP_MAX_DNS_SERVER_COUNT
+                dns_servers.push(*a).ok();
+            });
+
+        dns_servers
+    }
+
     #[cfg(not(test))]
     fn random_transaction_id(cx: &mut Context) -> u32 {
         cx.rand().rand_u32()
@@ -575,6 +674,8 @@ impl<'a> Socket<'a> {
             broadcast: false,
             requested_ip: None,
             client_identifier: Some(ethernet_addr),
+            opaque_client_identifier: self.client_identifier,
+            vendor_class_identifier: self.vendor_class,
             server_identifier: None,
             parameter_request_list: Some(
                 self.parameter_request_list
@@ -585,6 +686,7 @@ impl<'a> Socket<'a> {
             renew_duration: None,
             rebind_duration: None,
             dns_servers: None,
+            relay_agent_information: None,
             additional_options: self.outgoing_options,
         };
 
@@ -699,6 +801,36 @@ impl<'a> Socket<'a> {
                             .min(state.rebind_at - now);
                 }
 
+                self.transaction_id = next_transaction_id;
+                Ok(())
+            }
+            ClientState::Informing(state) => {
+                if state.information.is_some() || cx.now() < state.retry_at {
+                    return Ok(());
+                }
+
+                if state.retry >= self.retry_config.request_retries {
+                    net_debug!("DHCP inform retries exceeded, giving up");
+                    return Ok(());
+                }
+
+                ipv4_repr.src_addr = state.address;
+                dhcp_repr.message_type = DhcpMessageType::Inform;
+                dhcp_repr.client_ip = state.address;
+
+                net_debug!(
+                    "DHCP send inform to {}: {:?}",
+                    ipv4_repr.dst_addr,
+                    dhcp_repr
+                );
+                ipv4_repr.payload_len = udp_repr.header_len() + dhcp_repr.buffer_len();
+                emit(cx, (ipv4_repr, udp_repr, dhcp_repr))?;
+
+                // Same backoff as for requests.
+                state.retry_at = cx.now()
+                    + (self.retry_config.initial_request_timeout << (state.retry as u32 / 2));
+                state.retry += 1;
+
                 self.transaction_id = next_transaction_id;
                 Ok(())
             }
@@ -711,12 +843,37 @@ impl<'a> Socket<'a> {
     /// network if a link was down and it is now back up.
     pub fn reset(&mut self) {
         net_trace!("DHCP reset");
-        if let ClientState::Renewing(_) = &self.state {
+        let was_bound = matches!(self.state, ClientState::Renewing(_));
+        self.state = ClientState::Discovering(DiscoverState {
+            retry_at: Instant::from_millis(0),
+        });
+        if was_bound {
             self.config_changed();
+            self.notify_config_change();
         }
-        self.state = ClientState::Discovering(DiscoverState {
+    }
+
+    /// Ask the DHCP server for configuration parameters, without acquiring
+    /// an address.
+    ///
+    /// Use this when the interface already has the address `ciaddr`,
+    /// configured by other means: a DHCPINFORM is broadcast from it instead
+    /// of going through discovery, and the reply is returned by
+    /// [`informed`](Self::informed). Any lease currently held is dropped.
+    /// Call [`reset`](Self::reset) to go back to discovery.
+    pub fn inform(&mut self, ciaddr: Ipv4Address) {
+        net_trace!("DHCP inform from {}", ciaddr);
+        let was_bound = matches!(self.state, ClientState::Renewing(_));
+        self.state = ClientState::Informing(InformState {
             retry_at: Instant::from_millis(0),
+            retry: 0,
+            address: ciaddr,
+            information: None,
         });
+        if was_bound {
+            self.config_changed();
+            self.notify_config_change();
+        }
     }
 
     /// Query the socket for configuration changes.
@@ -725,10 +882,41 @@ impl<'a> Socket<'a> {
     /// set, this function returns the configuration and resets the flag.
     pub fn poll(&mut self) -> Option<Event> {
         if !self.config_changed {
-            None
-        } else if let ClientState::Renewing(state) = &self.state {
-            self.config_changed = false;
-            Some(Event::Configured(Config {
+            return None;
+        }
+
+        self.config_changed = false;
+        match self.config() {
+            Some(config) => Some(Event::Configured(config)),
+            None => Some(Event::Deconfigured),
+        }
+    }
+
+    /// Return the parameters received in reply to a DHCPINFORM, if any.
+    ///
+    /// See [`inform`](Self::inform).
+    pub fn informed(&self) -> Option<Information<'_>> {
+        match &self.state {
+            ClientState::Informing(InformState {
+                information: Some(information),
+                ..
+            }) => Some(Information {
+                server: information.server,
+                router: information.router,
+                dns_servers: information.dns_servers.clone(),
+                packet: self
+                    .receive_packet_buffer
+                    .as_deref()
+                    .map(DhcpPacket::new_unchecked),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Return the current configuration, if a lease is held.
+    fn config(&self) -> Option<Config<'_>> {
+        match &self.state {
+            ClientState::Renewing(state) => Some(Config {
                 server: state.config.server,
                 address: state.config.address,
                 router: state.config.router,
@@ -737,10 +925,15 @@ impl<'a> Socket<'a> {
                     .receive_packet_buffer
                     .as_deref()
                     .map(DhcpPacket::new_unchecked),
-            }))
-        } else {
-            self.config_changed = false;
-            Some(Event::Deconfigured)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Call the [lease change callback](Self::set_on_config_change), if any.
+    fn notify_config_change(&self) {
+        if let Some(callback) = self.on_config_change {
+            callback(self.config());
         }
     }
 
@@ -757,8 +950,8 @@ impl<'a> Socket<'a> {
     /// Register a waker.
     ///
     /// The waker is woken on state changes that might affect the return value
-    /// of `poll` method calls, which indicates a new state in the DHCP
-    /// configuration provided by this DHCP socket.
+    /// of `poll` or `informed` method calls, which indicates a new state in
+    /// the DHCP configuration provided by this DHCP socket.
     ///
     /// Notes:
     ///
@@ -962,6 +1155,8 @@ mod test {
         broadcast: false,
         requested_ip: None,
         client_identifier: None,
+        opaque_client_identifier: None,
+        vendor_class_identifier: None,
         server_identifier: None,
         parameter_request_list: None,
         dns_servers: None,
@@ -969,6 +1164,7 @@ mod test {
         renew_duration: None,
         rebind_duration: None,
         lease_duration: None,
+        relay_agent_information: None,
         additional_options: &[],
     };
 
@@ -1171,6 +1367,129 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_client_identifier_vendor_class() {
+        let mut s = socket();
+        s.set_client_identifier(b"\x00device-1");
+        s.set_vendor_class(b"smoltcp");
+
+        recv!(
+            s,
+            [(
+                IP_BROADCAST,
+                UDP_SEND,
+                DhcpRepr {
+                    opaque_client_identifier: Some(b"\x00device-1"),
+                    vendor_class_identifier: Some(b"smoltcp"),
+                    ..DHCP_DISCOVER
+                }
+            )]
+        );
+        send!(s, (IP_RECV, UDP_RECV, dhcp_offer()));
+        recv!(
+            s,
+            [(
+                IP_BROADCAST,
+                UDP_SEND,
+                DhcpRepr {
+                    opaque_client_identifier: Some(b"\x00device-1"),
+                    vendor_class_identifier: Some(b"smoltcp"),
+                    ..DHCP_REQUEST
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_on_config_change() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static BOUND: AtomicUsize = AtomicUsize::new(0);
+        static LOST: AtomicUsize = AtomicUsize::new(0);
+
+        fn on_config_change(config: Option<Config>) {
+            match config {
+                Some(config) => {
+                    assert_eq!(config.address, Ipv4Cidr::new(MY_IP, 24));
+                    assert_eq!(config.router, Some(SERVER_IP));
+                    BOUND.fetch_add(1, Ordering::SeqCst);
+                }
+                None => {
+                    LOST.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let mut s = socket();
+        s.set_on_config_change(Some(on_config_change));
+
+        recv!(s, [(IP_BROADCAST, UDP_SEND, DHCP_DISCOVER)]);
+        send!(s, (IP_RECV, UDP_RECV, dhcp_offer()));
+        recv!(s, [(IP_BROADCAST, UDP_SEND, DHCP_REQUEST)]);
+        assert_eq!(BOUND.load(Ordering::SeqCst), 0);
+        send!(s, (IP_RECV, UDP_RECV, dhcp_ack()));
+        assert_eq!(BOUND.load(Ordering::SeqCst), 1);
+        assert_eq!(LOST.load(Ordering::SeqCst), 0);
+
+        // Lease expires without being renewed.
+        recv!(s, time 1_000_000, [(IP_BROADCAST, UDP_SEND, DHCP_DISCOVER)]);
+        assert_eq!(BOUND.load(Ordering::SeqCst), 1);
+        assert_eq!(LOST.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_inform() {
+        let mut s = socket();
+        s.inform(MY_IP);
+
+        const DHCP_INFORM: DhcpRepr = DhcpRepr {
+            message_type: DhcpMessageType::Inform,
+            client_identifier: Some(MY_MAC),
+            client_ip: MY_IP,
+            max_size: Some(1432),
+            parameter_request_list: Some(&[1, 3, 6]),
+            ..DHCP_DEFAULT
+        };
+
+        recv!(s, [(IP_BROADCAST_ADDRESSED, UDP_SEND, DHCP_INFORM)]);
+        assert_eq!(s.informed(), None);
+        recv!(s, time 1_000, []);
+        recv!(s, time 5_000, [(IP_BROADCAST_ADDRESSED, UDP_SEND, DHCP_INFORM)]);
+
+        send!(
+            s,
+            time 6_000,
+            (
+                IP_RECV,
+                UDP_RECV,
+                DhcpRepr {
+                    your_ip: Ipv4Address::UNSPECIFIED,
+                    subnet_mask: None,
+                    lease_duration: None,
+                    ..dhcp_ack()
+                }
+            )
+        );
+        assert_eq!(
+            s.informed(),
+            Some(Information {
+                server: ServerInfo {
+                    address: SERVER_IP,
+                    identifier: SERVER_IP,
+                },
+                dns_servers: Vec::from_slice(DNS_IPS).unwrap(),
+                router: Some(SERVER_IP),
+                packet: None,
+            })
+        );
+        // No configuration is acquired.
+        assert_eq!(s.poll(), None);
+
+        // No lease, so nothing else is ever sent.
+        assert!(matches!(s.state, ClientState::Informing(_)));
+        recv!(s, time 100_000, []);
+    }
+
     #[test]
     fn test_discover_retransmit() {
         let mut s = socket();
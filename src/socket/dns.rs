@@ -513,6 +513,9 @@ impl<'a> Socket<'a> {
                                 return;
                             }
                         }
+                        RecordData::Opt(_) => {
+                            net_trace!("OPT record in answer section, ignoring")
+                        }
                         RecordData::Other(type_, data) => {
                             net_trace!("unknown: {:?} {:?}", type_, data)
                         }
@@ -601,6 +604,7 @@ impl<'a> Socket<'a> {
                         name: &pq.name,
                         type_: pq.type_,
                     },
+                    edns0: None,
                 };
 
                 let mut payload = [0u8; 512];
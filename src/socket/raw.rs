@@ -163,6 +163,21 @@ impl<'a> Socket<'a> {
         self.ip_protocol
     }
 
+    /// Abort the socket, dropping the packets queued in both directions.
+    ///
+    /// The socket stays bound to its IP version and protocol, and keeps
+    /// receiving the matching packets.
+    pub fn abort(&mut self) {
+        self.tx_buffer.reset();
+        self.rx_buffer.reset();
+
+        #[cfg(feature = "async")]
+        {
+            self.rx_waker.wake();
+            self.tx_waker.wake();
+        }
+    }
+
     /// Check whether the transmit buffer is full.
     #[inline]
     pub fn can_send(&self) -> bool {
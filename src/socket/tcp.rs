@@ -9,12 +9,14 @@ use core::{cmp, fmt, fmt::Display, mem};
 #[cfg(feature = "async")]
 use crate::socket::WakerRegistration;
 use crate::{
+    phy::ChecksumCapabilities,
     socket::{Context, PollAt},
     storage::{Assembler, RingBuffer},
     time::{Duration, Instant},
     wire::{
-        IpAddress, IpEndpoint, IpListenEndpoint, IpProtocol, IpRepr, TcpControl, TcpRepr,
-        TcpSeqNumber, TCP_HEADER_LEN,
+        IpAddress, IpEndpoint, IpListenEndpoint, IpProtocol, IpRepr, TcpAuthentication, TcpControl,
+        TcpOption, TcpPacket, TcpRepr, TcpSeqNumber, TCP_AO_MAC_LEN, TCP_AO_OPTION_LEN,
+        TCP_HEADER_LEN,
     },
 };
 
@@ -163,6 +165,42 @@ const RTTE_MIN_MARGIN: u32 = 5;
 const RTTE_MIN_RTO: u32 = 10;
 const RTTE_MAX_RTO: u32 = 10000;
 
+/// Measures the rate at which data is acknowledged, over intervals of at
+/// least one round-trip time.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct DeliveryRateEstimator {
+    /// The start of the current interval, and the octets acknowledged since.
+    interval: Option<(Instant, u64)>,
+    /// The rate measured over the last complete interval, in octets per second.
+    rate: Option<u64>,
+}
+
+impl DeliveryRateEstimator {
+    fn on_ack(&mut self, timestamp: Instant, len: usize, min_interval: Duration) {
+        let Some((start, delivered)) = self.interval else {
+            // The octets acknowledged now were sent before the interval began.
+            self.interval = Some((timestamp, 0));
+            return;
+        };
+
+        let delivered = delivered + len as u64;
+        let elapsed = timestamp - start;
+        if elapsed >= min_interval && elapsed > Duration::ZERO {
+            self.rate = Some(delivered * 1_000_000 / elapsed.total_micros());
+            self.interval = Some((timestamp, 0));
+            tcp_trace!("delivery rate: {} octets/s", self.rate.unwrap());
+        } else {
+            self.interval = Some((start, delivered));
+        }
+    }
+
+    fn on_idle(&mut self) {
+        // Time spent with nothing to send says nothing about the path.
+        self.interval = None;
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 struct RttEstimator {
@@ -261,6 +299,144 @@ impl RttEstimator {
     }
 }
 
+/// The largest configurable initial congestion window, in segments, as
+/// allowed by [RFC 6928].
+///
+/// [RFC 6928]: https://tools.ietf.org/html/rfc6928
+const MAX_INITIAL_WINDOW_SEGMENTS: usize = 10;
+
+/// Return the initial congestion window for the given maximum segment size.
+///
+/// Without an explicit number of segments, this is the window specified in
+/// [RFC 5681 § 3.1].
+///
+/// [RFC 5681 § 3.1]: https://tools.ietf.org/html/rfc5681#section-3.1
+fn initial_window(mss: usize, segments: Option<usize>) -> usize {
+    match segments {
+        Some(segments) => segments * mss,
+        None => cmp::min(4 * mss, cmp::max(2 * mss, 4380)),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct CongestionController {
+    /// Congestion window, in octets.
+    cwnd: usize,
+    /// Slow start threshold, in octets.
+    ssthresh: usize,
+    /// Octets acknowledged since the window last grew in congestion
+    /// avoidance.
+    acked: usize,
+    /// Whether the retransmission timer expired since the last ACK of new
+    /// data.
+    rto_expired: bool,
+    /// Whether the window is inflated by the duplicate ACKs that triggered a
+    /// fast retransmit, until new data is acknowledged.
+    fast_recovery: bool,
+    /// Initial congestion window, in segments, if overridden.
+    initial_segments: Option<usize>,
+    /// Whether the window limits the data in flight at all.
+    enabled: bool,
+}
+
+impl CongestionController {
+    fn new(mss: usize) -> Self {
+        Self {
+            cwnd: initial_window(mss, None),
+            ssthresh: usize::MAX,
+            acked: 0,
+            rto_expired: false,
+            fast_recovery: false,
+            initial_segments: None,
+            enabled: true,
+        }
+    }
+
+    fn window(&self) -> usize {
+        if self.enabled {
+            self.cwnd
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn slow_start_threshold(&self) -> Option<usize> {
+        Some(self.ssthresh).filter(|&ssthresh| self.enabled && ssthresh != usize::MAX)
+    }
+
+    fn reset(&mut self, mss: usize) {
+        self.cwnd = initial_window(mss, self.initial_segments);
+        self.ssthresh = usize::MAX;
+        self.acked = 0;
+        self.rto_expired = false;
+        self.fast_recovery = false;
+    }
+
+    fn on_ack(&mut self, len: usize, mss: usize) {
+        self.rto_expired = false;
+        if self.fast_recovery {
+            // Deflate the window once the retransmission is acknowledged.
+            self.fast_recovery = false;
+            self.cwnd = self.ssthresh;
+        } else if self.cwnd < self.ssthresh {
+            // Slow start: grow by at most one segment per ACK.
+            self.cwnd = self.cwnd.saturating_add(len.min(mss));
+        } else {
+            // Congestion avoidance: grow by one segment per window of
+            // acknowledged data, see RFC 5681 § 3.1.
+            self.acked += len;
+            if self.acked >= self.cwnd {
+                self.acked -= self.cwnd;
+                self.cwnd = self.cwnd.saturating_add(mss);
+            }
+        }
+    }
+
+    /// Halve the amount of data in flight, as the slow start threshold, after
+    /// a loss (RFC 5681 equation 4).
+    fn reduce_ssthresh(&mut self, flight_size: usize, mss: usize) {
+        self.ssthresh = cmp::max(flight_size / 2, 2 * mss);
+        self.acked = 0;
+    }
+
+    fn on_retransmit_timeout(&mut self, flight_size: usize, mss: usize) {
+        // The threshold is kept if the retransmission is itself lost.
+        if !self.rto_expired {
+            self.reduce_ssthresh(flight_size, mss);
+        }
+        self.rto_expired = true;
+        self.fast_recovery = false;
+        tcp_trace!("cwnd: retransmission timeout, ssthresh={}", self.ssthresh);
+        self.cwnd = mss;
+    }
+
+    fn on_fast_retransmit(&mut self, flight_size: usize, mss: usize) {
+        self.reduce_ssthresh(flight_size, mss);
+        tcp_trace!("cwnd: fast retransmit, ssthresh={}", self.ssthresh);
+        // The segments that triggered the duplicate ACKs have left the
+        // network, see RFC 5681 § 3.2.
+        self.fast_recovery = true;
+        self.cwnd = self.ssthresh.saturating_add(3 * mss);
+    }
+
+    fn on_dup_ack(&mut self, mss: usize) {
+        // Every further duplicate ACK during fast recovery also signals a
+        // segment that has left the network, see RFC 5681 § 3.2 step 4.
+        if self.fast_recovery {
+            self.cwnd = self.cwnd.saturating_add(mss);
+        }
+    }
+
+    fn restart(&mut self, mss: usize) {
+        let restart_window = initial_window(mss, self.initial_segments);
+        if self.cwnd > restart_window {
+            tcp_trace!("cwnd: restarting slow start after idle");
+            self.cwnd = restart_window;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 enum Timer {
@@ -391,6 +567,334 @@ enum AckDelayTimer {
     Immediate,
 }
 
+/// A policy for retrying failed connection attempts.
+///
+/// The delay before the `n`th retry is `base * 2^(n-1)`, capped at `max`.
+/// With `jitter`, each delay is randomly picked between half and all of
+/// that value, so that clients which failed at the same time do not retry in
+/// lockstep.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConnectRetryPolicy {
+    /// The delay before the first retry.
+    pub base: Duration,
+    /// The maximum delay between two attempts.
+    pub max: Duration,
+    /// Whether to randomize the delays.
+    pub jitter: bool,
+    /// The maximum number of retries after the initial attempt.
+    pub max_retries: u8,
+}
+
+/// A configuration for pacing outgoing segments.
+///
+/// With pacing, segments carrying data are spaced out over the round-trip
+/// time instead of being sent back to back, at a rate of `rate_percent`
+/// percent of the send window per smoothed round-trip time. For example,
+/// Linux uses 200 in slow start and 120 in congestion avoidance.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacingConfig {
+    /// The pacing rate, as a percentage of the send window per round trip.
+    pub rate_percent: u16,
+}
+
+/// An algorithm for detecting lost segments.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LossDetection {
+    /// Duplicate ACK counting and the retransmission timeout, as described
+    /// in RFC 5681 and RFC 6298.
+    #[default]
+    Classic,
+    /// Time-based loss detection with tail loss probes, as described in
+    /// [RFC 8985].
+    ///
+    /// [RFC 8985]: https://tools.ietf.org/html/rfc8985
+    RackTlp,
+}
+
+/// How the connection of a socket removed from its socket set is torn down,
+/// see [Socket::set_close_on_drop].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CloseMode {
+    /// Abort the connection with a RST.
+    Rst,
+    /// Close the connection with a FIN, which is never retransmitted. The
+    /// remote endpoint does not get any data that was not acknowledged yet.
+    GracefulBestEffort,
+}
+
+/// The worst case delay the remote side may wait before acknowledging a
+/// single segment, see RFC 8985 § 7.2.
+const TLP_MAX_ACK_DELAY: Duration = Duration::from_millis(200);
+
+/// The state of RACK-TLP loss detection.
+///
+/// The transmit buffer doesn't keep track of individual segments, so the
+/// transmission time of the oldest unacknowledged segment is only known when
+/// it was the last one sent, and is otherwise overestimated as the time of the
+/// last transmission. This errs on the side of deeming segments lost later.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct RackTlp {
+    /// The time the oldest unacknowledged segment was sent, at the earliest.
+    una_sent_at: Option<Instant>,
+    /// The time the oldest unacknowledged segment is deemed lost, once a
+    /// segment sent after it was delivered.
+    lost_at: Option<Instant>,
+    /// The time a tail loss probe is sent, if one is scheduled.
+    probe_at: Option<Instant>,
+}
+
+/// A snapshot of the state, tunables and counters of a socket, returned by
+/// [Socket::stats].
+///
+/// All values are in octets unless noted otherwise.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    /// See [Socket::state].
+    pub state: State,
+    /// See [Socket::local_endpoint].
+    pub local_endpoint: Option<IpEndpoint>,
+    /// See [Socket::remote_endpoint].
+    pub remote_endpoint: Option<IpEndpoint>,
+    /// The congestion window, or `usize::MAX` if congestion control is
+    /// disabled.
+    pub cwnd: usize,
+    /// The slow start threshold, if it was lowered after a loss.
+    pub ssthresh: Option<usize>,
+    /// The smoothed round-trip time.
+    pub rtt: Duration,
+    /// The current retransmission timeout.
+    pub rto: Duration,
+    /// The maximum segment size of outgoing segments.
+    pub mss: usize,
+    /// See [Socket::bytes_in_flight].
+    pub bytes_in_flight: usize,
+    /// The number of times unacknowledged data was sent again, after a
+    /// retransmission timeout or a detected loss.
+    pub retransmits: u32,
+    /// The window last advertised by the remote endpoint.
+    pub send_window: usize,
+    /// The window the socket can currently advertise.
+    pub recv_window: usize,
+    /// See [Socket::send_queue].
+    pub send_queue: usize,
+    /// See [Socket::recv_queue].
+    pub recv_queue: usize,
+}
+
+/// The length of the traffic keys of the TCP Authentication Option.
+///
+/// This is the output length of KDF_HMAC_SHA1, see RFC 5926 § 3.1.1.1;
+/// AES-128 uses the first 16 octets.
+pub const AO_TRAFFIC_KEY_LEN: usize = 20;
+
+/// A master key tuple of the TCP Authentication Option, see RFC 5925 § 3.1.
+///
+/// The traffic keys segments are authenticated with are derived from the
+/// master key, for each connection and direction, as described in
+/// RFC 5925 § 5.2.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AoKey<'a> {
+    /// The KeyID of outgoing segments authenticated with this key.
+    pub send_id: u8,
+    /// The KeyID of incoming segments authenticated with this key.
+    pub recv_id: u8,
+    pub master_key: &'a [u8],
+}
+
+/// A function deriving a traffic key of the TCP Authentication Option from a
+/// master key and a connection context, such as KDF_HMAC_SHA1 or
+/// KDF_AES_128_CMAC, see RFC 5926 § 3.1.1.
+pub type AoKdfFn = fn(master_key: &[u8], context: &[u8]) -> [u8; AO_TRAFFIC_KEY_LEN];
+
+/// A function computing the MAC of the TCP Authentication Option, such as
+/// HMAC-SHA-1-96 or AES-128-CMAC-96, with a traffic key over a message made
+/// of several parts.
+pub type AoMacFn = fn(traffic_key: &[u8], message: &[&[u8]]) -> [u8; TCP_AO_MAC_LEN];
+
+#[derive(Debug, Clone, Copy)]
+struct AoState<'a> {
+    keys: &'a [AoKey<'a>],
+    kdf: AoKdfFn,
+    mac: AoMacFn,
+    /// The index of the key outgoing segments are authenticated with.
+    current: usize,
+    /// The state of the synchronized connection, if any.
+    conn: Option<AoConnection>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AoConnection {
+    /// The local and remote initial sequence numbers.
+    isns: (TcpSeqNumber, TcpSeqNumber),
+    /// The last SND.NXT a segment was sent at, and its Sequence Number
+    /// Extension.
+    snd_sne: (TcpSeqNumber, u32),
+    /// The last RCV.NXT a segment was received at, and its Sequence Number
+    /// Extension.
+    rcv_sne: (TcpSeqNumber, u32),
+    /// The index of the key the traffic keys were derived from, and the send
+    /// and receive traffic keys.
+    traffic_keys: Option<(usize, [u8; AO_TRAFFIC_KEY_LEN], [u8; AO_TRAFFIC_KEY_LEN])>,
+}
+
+impl<'a> AoState<'a> {
+    /// Return the state of the connection with the given local and remote
+    /// ISNs, starting afresh if it is a new one.
+    fn connection(&mut self, isns: (TcpSeqNumber, TcpSeqNumber)) -> &mut AoConnection {
+        if self.conn.map_or(true, |conn| conn.isns != isns) {
+            self.conn = Some(AoConnection {
+                isns,
+                snd_sne: (isns.0, 0),
+                rcv_sne: (isns.1, 0),
+                traffic_keys: None,
+            });
+        }
+        self.conn.as_mut().unwrap()
+    }
+
+    /// Return the send and receive traffic keys of the key with the given
+    /// index, for the connection between `local` and `remote` with the given
+    /// local and remote ISNs.
+    fn traffic_keys(
+        &mut self,
+        index: usize,
+        local: IpEndpoint,
+        remote: IpEndpoint,
+        isns: (TcpSeqNumber, TcpSeqNumber),
+    ) -> ([u8; AO_TRAFFIC_KEY_LEN], [u8; AO_TRAFFIC_KEY_LEN]) {
+        let (kdf, master_key) = (self.kdf, self.keys[index].master_key);
+        let conn = self.connection(isns);
+        match conn.traffic_keys {
+            Some((derived, send_key, recv_key)) if derived == index => (send_key, recv_key),
+            _ => {
+                let send_key = ao_traffic_key(kdf, master_key, local, remote, isns.0, isns.1);
+                let recv_key = ao_traffic_key(kdf, master_key, remote, local, isns.1, isns.0);
+                conn.traffic_keys = Some((index, send_key, recv_key));
+                (send_key, recv_key)
+            }
+        }
+    }
+
+    /// Add the TCP Authentication Option to an outgoing segment, given the
+    /// ISNs, SND.UNA and SND.NXT of the connection.
+    fn sign(
+        &mut self,
+        isns: Option<(TcpSeqNumber, TcpSeqNumber)>,
+        local_seq_no: TcpSeqNumber,
+        snd_nxt: TcpSeqNumber,
+        ip_repr: &mut IpRepr,
+        repr: &mut TcpRepr,
+    ) {
+        let index = self.current;
+        let key = self.keys[index];
+        repr.authentication = Some(TcpAuthentication {
+            key_id: key.send_id,
+            rnext_key_id: key.recv_id,
+            mac: [0; TCP_AO_MAC_LEN],
+        });
+        ip_repr.set_payload_len(repr.buffer_len());
+
+        let mut header = [0; 60];
+        let header = &mut header[..repr.header_len()];
+        let header_repr = TcpRepr {
+            payload: &[],
+            ..*repr
+        };
+        header_repr.emit(
+            &mut TcpPacket::new_unchecked(&mut *header),
+            &ip_repr.src_addr(),
+            &ip_repr.dst_addr(),
+            &ChecksumCapabilities::ignored(),
+        );
+
+        let local = IpEndpoint::new(ip_repr.src_addr(), repr.src_port);
+        let remote = IpEndpoint::new(ip_repr.dst_addr(), repr.dst_port);
+        let (traffic_key, sne) = match (repr.control, isns) {
+            // SYN segments carry the ISNs their traffic key is derived from,
+            // and are at the start of the sequence number space.
+            (TcpControl::Syn, _) => {
+                let remote_isn = repr.ack_number.map_or(TcpSeqNumber(0), |ack| ack - 1);
+                let traffic_key = ao_traffic_key(
+                    self.kdf,
+                    key.master_key,
+                    local,
+                    remote,
+                    repr.seq_number,
+                    remote_isn,
+                );
+                (traffic_key, 0)
+            }
+            (_, Some(isns)) => {
+                let (send_key, _) = self.traffic_keys(index, local, remote, isns);
+                let conn = self.connection(isns);
+                conn.snd_sne = (snd_nxt, ao_sne(snd_nxt, conn.snd_sne));
+                (send_key, ao_sne(repr.seq_number, conn.snd_sne))
+            }
+            // The remote ISN is not known yet.
+            (_, None) => {
+                let traffic_key = ao_traffic_key(
+                    self.kdf,
+                    key.master_key,
+                    local,
+                    remote,
+                    local_seq_no,
+                    TcpSeqNumber(0),
+                );
+                (traffic_key, 0)
+            }
+        };
+        let mac = Socket::ao_mac(self.mac, &traffic_key, sne, ip_repr, header, repr.payload);
+        if let Some(authentication) = &mut repr.authentication {
+            authentication.mac = mac;
+        }
+    }
+}
+
+/// Derive the traffic key of the TCP Authentication Option for the segments
+/// from `src` to `dst`, from the context of RFC 5926 § 3.1.1.
+fn ao_traffic_key(
+    kdf: AoKdfFn,
+    master_key: &[u8],
+    src: IpEndpoint,
+    dst: IpEndpoint,
+    src_isn: TcpSeqNumber,
+    dst_isn: TcpSeqNumber,
+) -> [u8; AO_TRAFFIC_KEY_LEN] {
+    let mut context = [0; 44];
+    let mut len = 0;
+    for part in [
+        src.addr.as_bytes(),
+        dst.addr.as_bytes(),
+        &src.port.to_be_bytes(),
+        &dst.port.to_be_bytes(),
+        &src_isn.0.to_be_bytes(),
+        &dst_isn.0.to_be_bytes(),
+    ] {
+        context[len..len + part.len()].copy_from_slice(part);
+        len += part.len();
+    }
+    kdf(master_key, &context[..len])
+}
+
+/// Return the Sequence Number Extension of `seq`, given a nearby sequence
+/// number and its extension, see RFC 5925 § 6.2.
+fn ao_sne(seq: TcpSeqNumber, (reference, sne): (TcpSeqNumber, u32)) -> u32 {
+    match (seq >= reference, (seq.0 as u32) < (reference.0 as u32)) {
+        // `seq` is after a wraparound `reference` is before.
+        (true, true) => sne.wrapping_add(1),
+        // `seq` is before a wraparound `reference` is after.
+        (false, false) => sne.wrapping_sub(1),
+        _ => sne,
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 struct Tuple {
@@ -415,6 +919,8 @@ pub struct Socket<'a> {
     state: State,
     timer: Timer,
     rtte: RttEstimator,
+    delivery_rate: DeliveryRateEstimator,
+    congestion: CongestionController,
     assembler: Assembler,
     rx_buffer: SocketBuffer<'a>,
     rx_fin_received: bool,
@@ -427,10 +933,30 @@ pub struct Socket<'a> {
     /// The time-to-live (IPv4) or hop limit (IPv6) value used in outgoing
     /// packets.
     hop_limit: Option<u8>,
+    /// The Differentiated Services Code Point of outgoing packets.
+    dscp: u8,
+    /// How the connection is torn down when the socket is removed from its
+    /// socket set.
+    close_on_drop: Option<CloseMode>,
     /// Address passed to listen(). Listen address is set when listen() is
     /// called and used every time the socket is reset back to the LISTEN
     /// state.
     listen_endpoint: IpListenEndpoint,
+    /// The initial sequence number of the next connection accepted, if it
+    /// was given to listen_with_isn() instead of drawn from the PRNG.
+    listen_isn: Option<TcpSeqNumber>,
+    /// The predicate a remote endpoint must satisfy for its SYN to be accepted
+    /// in the LISTEN state.
+    accept_filter: Option<fn(IpEndpoint) -> bool>,
+    /// Whether SYNs rejected by the accept filter are dropped silently instead
+    /// of answered with a RST.
+    stealth_listen: bool,
+    /// The keys segments are authenticated with, if the TCP Authentication
+    /// Option is used.
+    ao: Option<AoState<'a>>,
+    /// The local and remote initial sequence numbers, once the connection is
+    /// synchronized.
+    isns: Option<(TcpSeqNumber, TcpSeqNumber)>,
     /// Current 4-tuple (local and remote endpoints).
     tuple: Option<Tuple>,
     /// The sequence number corresponding to the beginning of the transmit
@@ -448,6 +974,11 @@ pub struct Socket<'a> {
     remote_last_ack: Option<TcpSeqNumber>,
     /// The last window length sent.
     remote_last_win: u16,
+    /// Whether a zero window was ever advertised because the receive buffer
+    /// was full.
+    rx_window_zeroed: bool,
+    /// The time application data was last sent or received.
+    last_activity: Option<Instant>,
     /// The sending window scaling factor advertised to remotes which support
     /// RFC 1323. It is zero if the window <= 64KiB and/or the remote does
     /// not support it.
@@ -463,6 +994,11 @@ pub struct Socket<'a> {
     remote_has_sack: bool,
     /// The maximum number of data octets that the remote side may receive.
     remote_mss: usize,
+    /// The maximum number of data octets advertised in our SYN.
+    local_mss: usize,
+    /// The upper bound on our maximum segment size, if lower than the one
+    /// allowed by the MTU.
+    mss_clamp: Option<usize>,
     /// The timestamp of the last packet received.
     remote_last_ts: Option<Instant>,
     /// The sequence number of the last packet received, used for sACK
@@ -472,6 +1008,14 @@ pub struct Socket<'a> {
     /// The number of packets received directly after
     /// each other which have the same ACK number.
     local_rx_dup_acks: u8,
+    /// The number of duplicate ACKs triggering a fast retransmit.
+    dup_ack_threshold: u8,
+    /// The algorithm lost segments are detected with.
+    loss_detection: LossDetection,
+    /// The state of RACK-TLP loss detection, if enabled.
+    rack: RackTlp,
+    /// The number of times unacknowledged data was sent again.
+    retransmits: u32,
 
     /// Duration for Delayed ACK. If None no ACKs will be delayed.
     ack_delay: Option<Duration>,
@@ -482,9 +1026,52 @@ pub struct Socket<'a> {
     /// Used for rate-limiting: No more challenge ACKs will be sent until this
     /// instant.
     challenge_ack_timer: Instant,
+    /// The maximum number of challenge ACKs sent per second.
+    challenge_ack_limit: u32,
 
     /// Nagle's Algorithm enabled.
     nagle: bool,
+    /// The end of the data to send right away, regardless of Nagle's
+    /// Algorithm, if flush() was called.
+    flush_seq: Option<TcpSeqNumber>,
+    /// An empty segment was requested by poke().
+    poke: bool,
+
+    /// Zero the buffers when the socket is reused.
+    zero_on_reuse: bool,
+
+    /// Ignore RSTs in the TIME-WAIT state (RFC 1337).
+    time_wait_rst_ignored: bool,
+
+    /// The maximum amount of octets the receive buffer may hold, if lower
+    /// than its capacity.
+    max_recv_buffer: Option<usize>,
+    /// The maximum window advertised to the remote side, if lower than the
+    /// free space in the receive buffer.
+    max_advertised_window: Option<u32>,
+
+    /// The policy for retrying failed connection attempts.
+    connect_retry: Option<ConnectRetryPolicy>,
+    /// The number of connection attempts retried so far.
+    connect_retries: u8,
+    /// The number of times the SYN of the current attempt was retransmitted.
+    syn_retransmits: u8,
+    /// The time at which the next connection attempt is made, if one failed.
+    connect_retry_at: Option<Instant>,
+
+    /// Slow-start restart after idle (RFC 5681 § 4.1) enabled.
+    slow_start_restart: bool,
+    /// Idle period after which slow start is restarted. If None, the current
+    /// retransmission timeout is used.
+    slow_start_restart_idle: Option<Duration>,
+    /// The timestamp of the last segment carrying data or flags sent.
+    local_last_ts: Option<Instant>,
+
+    /// The pacing configuration, if outgoing segments are paced.
+    pacing: Option<PacingConfig>,
+    /// The time before which no further segment carrying data may be sent,
+    /// if pacing.
+    pacing_next_at: Option<Instant>,
 
     #[cfg(feature = "async")]
     rx_waker: WakerRegistration,
@@ -494,6 +1081,17 @@ pub struct Socket<'a> {
 
 const DEFAULT_MSS: usize = 536;
 
+/// The number of duplicate ACKs triggering a fast retransmit by default, see
+/// RFC 5681 § 3.2.
+const DEFAULT_DUP_ACK_THRESHOLD: u8 = 3;
+
+/// The number of challenge ACKs sent per second by default.
+const DEFAULT_CHALLENGE_ACK_LIMIT: u32 = 1;
+
+/// The number of unanswered SYN retransmissions after which a connection
+/// attempt fails, if failed attempts are retried.
+const CONNECT_SYN_RETRANSMITS: u8 = 3;
+
 impl<'a> Socket<'a> {
     #[allow(unused_comparisons)] // small usize platforms always pass rx_capacity check
     /// Create a socket using the given buffers.
@@ -517,6 +1115,8 @@ impl<'a> Socket<'a> {
             state: State::Closed,
             timer: Timer::new(),
             rtte: RttEstimator::default(),
+            delivery_rate: DeliveryRateEstimator::default(),
+            congestion: CongestionController::new(DEFAULT_MSS),
             assembler: Assembler::new(),
             tx_buffer,
             rx_buffer,
@@ -524,26 +1124,57 @@ impl<'a> Socket<'a> {
             timeout: None,
             keep_alive: None,
             hop_limit: None,
+            dscp: 0,
+            close_on_drop: None,
             listen_endpoint: IpListenEndpoint::default(),
+            listen_isn: None,
+            accept_filter: None,
+            stealth_listen: false,
+            ao: None,
+            isns: None,
             tuple: None,
             local_seq_no: TcpSeqNumber::default(),
             remote_seq_no: TcpSeqNumber::default(),
             remote_last_seq: TcpSeqNumber::default(),
             remote_last_ack: None,
             remote_last_win: 0,
+            rx_window_zeroed: false,
+            last_activity: None,
             remote_win_len: 0,
             remote_win_shift: rx_cap_log2.saturating_sub(16) as u8,
             remote_win_scale: None,
             remote_has_sack: false,
             remote_mss: DEFAULT_MSS,
+            local_mss: DEFAULT_MSS,
+            mss_clamp: None,
             remote_last_ts: None,
             local_rx_last_ack: None,
             local_rx_last_seq: None,
             local_rx_dup_acks: 0,
+            dup_ack_threshold: DEFAULT_DUP_ACK_THRESHOLD,
+            loss_detection: LossDetection::Classic,
+            rack: RackTlp::default(),
+            retransmits: 0,
             ack_delay: Some(ACK_DELAY_DEFAULT),
             ack_delay_timer: AckDelayTimer::Idle,
             challenge_ack_timer: Instant::from_secs(0),
+            challenge_ack_limit: DEFAULT_CHALLENGE_ACK_LIMIT,
             nagle: true,
+            flush_seq: None,
+            poke: false,
+            zero_on_reuse: false,
+            time_wait_rst_ignored: false,
+            max_recv_buffer: None,
+            max_advertised_window: None,
+            connect_retry: None,
+            connect_retries: 0,
+            syn_retransmits: 0,
+            connect_retry_at: None,
+            slow_start_restart: true,
+            slow_start_restart_idle: None,
+            local_last_ts: None,
+            pacing: None,
+            pacing_next_at: None,
 
             #[cfg(feature = "async")]
             rx_waker: WakerRegistration::new(),
@@ -610,6 +1241,49 @@ impl<'a> Socket<'a> {
         self.nagle
     }
 
+    /// Return whether the buffers are zeroed when the socket is reused.
+    ///
+    /// See also the [set_zero_on_reuse](#method.set_zero_on_reuse) method.
+    pub fn zero_on_reuse(&self) -> bool {
+        self.zero_on_reuse
+    }
+
+    /// Return whether RSTs are ignored in the TIME-WAIT state.
+    ///
+    /// See also the [set_time_wait_rst_ignored](#method.set_time_wait_rst_ignored) method.
+    pub fn time_wait_rst_ignored(&self) -> bool {
+        self.time_wait_rst_ignored
+    }
+
+    /// Return the maximum number of challenge ACKs sent per second.
+    ///
+    /// See also the [set_challenge_ack_limit](#method.set_challenge_ack_limit) method.
+    pub fn challenge_ack_limit(&self) -> u32 {
+        self.challenge_ack_limit
+    }
+
+    /// Return whether congestion control is enabled.
+    ///
+    /// See also the [set_congestion_control](#method.set_congestion_control) method.
+    pub fn congestion_control(&self) -> bool {
+        self.congestion.enabled
+    }
+
+    /// Return whether slow-start restart after idle is enabled.
+    ///
+    /// See also the [set_slow_start_restart](#method.set_slow_start_restart) method.
+    pub fn slow_start_restart(&self) -> bool {
+        self.slow_start_restart
+    }
+
+    /// Return the idle period after which slow start is restarted, if one was
+    /// set explicitly.
+    ///
+    /// See also the [set_slow_start_restart_idle](#method.set_slow_start_restart_idle) method.
+    pub fn slow_start_restart_idle(&self) -> Option<Duration> {
+        self.slow_start_restart_idle
+    }
+
     /// Return the current window field value, including scaling according to
     /// RFC 1323.
     ///
@@ -617,11 +1291,25 @@ impl<'a> Socket<'a> {
     #[inline]
     fn scaled_window(&self) -> u16 {
         cmp::min(
-            self.rx_buffer.window() >> self.remote_win_shift as usize,
+            self.rx_window() >> self.remote_win_shift as usize,
             (1 << 16) - 1,
         ) as u16
     }
 
+    /// Return the receive window, i.e. the amount of octets that can be
+    /// received, limited by the receive buffer ceiling.
+    #[inline]
+    fn rx_window(&self) -> usize {
+        let mut window = self.rx_buffer.window();
+        if let Some(max) = self.max_recv_buffer {
+            window = window.min(max.saturating_sub(self.rx_buffer.len()));
+        }
+        if let Some(max) = self.max_advertised_window {
+            window = window.min(max as usize);
+        }
+        window
+    }
+
     /// Set the timeout duration.
     ///
     /// A socket with a timeout duration set will abort the connection if either
@@ -646,6 +1334,129 @@ impl<'a> Socket<'a> {
         self.ack_delay = duration
     }
 
+    /// Return the maximum segment size clamp.
+    ///
+    /// See also the [set_mss_clamp](#method.set_mss_clamp) method.
+    pub fn mss_clamp(&self) -> Option<usize> {
+        self.mss_clamp
+    }
+
+    /// Set an upper bound on the maximum segment size, in octets.
+    ///
+    /// By default, the maximum segment size advertised to the remote side and
+    /// used for sending is derived from the MTU of the interface. With a clamp,
+    /// it never exceeds the given value, which is useful when the path to the
+    /// remote side has a lower MTU, e.g. because of tunnels. The clamp applies
+    /// to connections established after it is set.
+    ///
+    /// # Panics
+    /// This function panics if the clamp is zero.
+    pub fn set_mss_clamp(&mut self, clamp: Option<usize>) {
+        assert!(clamp != Some(0), "MSS clamp must not be zero");
+        self.mss_clamp = clamp
+    }
+
+    /// Return the maximum segment size in use, once the connection is
+    /// synchronized.
+    ///
+    /// This is the smaller of the maximum segment size advertised by the
+    /// remote side, and the one we advertised, which is limited by the MTU
+    /// of the interface and the [clamp](#method.set_mss_clamp). Returns
+    /// `None` before the handshake completes, and once the socket is closed.
+    pub fn effective_mss(&self) -> Option<usize> {
+        match self.state {
+            State::Closed | State::Listen | State::SynSent | State::SynReceived => None,
+            _ => Some(self.local_mss.min(self.remote_mss)),
+        }
+    }
+
+    /// Return the receive buffer ceiling.
+    ///
+    /// See also the [set_max_recv_buffer](#method.set_max_recv_buffer) method.
+    pub fn max_recv_buffer(&self) -> Option<usize> {
+        self.max_recv_buffer
+    }
+
+    /// Set the receive buffer ceiling, in octets.
+    ///
+    /// The receive buffer is never filled beyond this amount, regardless of its
+    /// capacity, and the advertised window shrinks accordingly. This bounds the
+    /// memory a connection may tie up while the application is not reading,
+    /// independently of the buffer that was allocated for the socket. A ceiling
+    /// at or above the capacity of the receive buffer has no effect.
+    pub fn set_max_recv_buffer(&mut self, max: usize) {
+        self.max_recv_buffer = Some(max)
+    }
+
+    /// Return the maximum advertised window.
+    ///
+    /// See also the [set_max_advertised_window](#method.set_max_advertised_window) method.
+    pub fn max_advertised_window(&self) -> Option<u32> {
+        self.max_advertised_window
+    }
+
+    /// Set the maximum window advertised to the remote side, in octets.
+    ///
+    /// The window never exceeds this amount, however much free space the
+    /// receive buffer has, so the remote side never has more than this much
+    /// unacknowledged data in flight. Unlike with
+    /// [set_max_recv_buffer](#method.set_max_recv_buffer), the buffer may
+    /// still fill up to its capacity if the application does not read. This
+    /// is mostly useful to simulate a slow receiver. If None, the window is
+    /// only limited by the receive buffer.
+    pub fn set_max_advertised_window(&mut self, max: Option<u32>) {
+        self.max_advertised_window = max
+    }
+
+    /// Return the connection retry policy.
+    ///
+    /// See also the [set_connect_retry](#method.set_connect_retry) method.
+    pub fn connect_retry(&self) -> Option<ConnectRetryPolicy> {
+        self.connect_retry
+    }
+
+    /// Set the connection retry policy.
+    ///
+    /// A connection attempt fails when the remote endpoint resets it, when
+    /// the SYN was retransmitted three times without an answer, or when it
+    /// does not answer within the [timeout](#method.set_timeout), if any.
+    /// With a retry policy, instead of closing, the socket stays in the
+    /// SYN-SENT state, waits for the delay given by the policy, and sends a
+    /// new SYN, until the connection is established or the retries are
+    /// exhausted; the socket is then closed.
+    ///
+    /// By default, failed connection attempts are not retried.
+    pub fn set_connect_retry(&mut self, policy: Option<ConnectRetryPolicy>) {
+        self.connect_retry = policy
+    }
+
+    /// Return the segment pacing configuration.
+    ///
+    /// See also the [set_pacing](#method.set_pacing) method.
+    pub fn pacing(&self) -> Option<PacingConfig> {
+        self.pacing
+    }
+
+    /// Set the segment pacing configuration.
+    ///
+    /// With pacing, after a segment carrying data is sent, the next one is
+    /// held back until its share of the round-trip time has elapsed, and
+    /// [poll_at](crate::iface::Interface::poll_at) reports that deadline.
+    /// This avoids bursts of a whole window, which can overflow the queues
+    /// of a path with shallow buffers.
+    ///
+    /// By default, segments are not paced.
+    ///
+    /// # Panics
+    /// This function panics if the pacing rate is zero.
+    pub fn set_pacing(&mut self, pacing: Option<PacingConfig>) {
+        if let Some(pacing) = pacing {
+            assert!(pacing.rate_percent > 0);
+        }
+        self.pacing = pacing;
+        self.pacing_next_at = None;
+    }
+
     /// Enable or disable Nagle's Algorithm.
     ///
     /// Also known as "tinygram prevention". By default, it is enabled.
@@ -663,6 +1474,155 @@ impl<'a> Socket<'a> {
         self.nagle = enabled
     }
 
+    /// Return the loss detection algorithm.
+    ///
+    /// See also the [set_loss_detection](#method.set_loss_detection) method.
+    pub fn loss_detection(&self) -> LossDetection {
+        self.loss_detection
+    }
+
+    /// Set the loss detection algorithm.
+    ///
+    /// With [`LossDetection::RackTlp`], a segment is deemed lost once a
+    /// segment sent after it was selectively acknowledged and a quarter of
+    /// the round-trip time has passed since, instead of after a number of
+    /// duplicate ACKs. When the last segment of a burst isn't acknowledged in
+    /// about two round-trip times, it is sent again as a tail loss probe,
+    /// which recovers from losses at the end of a burst without waiting for
+    /// the retransmission timeout. Reordering is only detected if the remote
+    /// side supports selective ACK; otherwise, duplicate ACKs are still
+    /// counted.
+    ///
+    /// By default, [`LossDetection::Classic`] is used.
+    pub fn set_loss_detection(&mut self, loss_detection: LossDetection) {
+        self.loss_detection = loss_detection;
+        self.rack = RackTlp::default();
+    }
+
+    /// Return the number of duplicate ACKs triggering a fast retransmit.
+    ///
+    /// See also the [set_dup_ack_threshold](#method.set_dup_ack_threshold) method.
+    pub fn dup_ack_threshold(&self) -> u8 {
+        self.dup_ack_threshold
+    }
+
+    /// Set the number of duplicate ACKs triggering a fast retransmit.
+    ///
+    /// The default of 3 follows RFC 5681. On paths that reorder segments
+    /// heavily, a higher threshold avoids spurious retransmissions, at the cost
+    /// of recovering from actual losses later.
+    ///
+    /// # Panics
+    /// This function panics if `threshold` is 0.
+    pub fn set_dup_ack_threshold(&mut self, threshold: u8) {
+        assert!(
+            threshold > 0,
+            "the duplicate ACK threshold must be at least 1"
+        );
+        self.dup_ack_threshold = threshold
+    }
+
+    /// Enable or disable zeroing the buffers when the socket is reused.
+    ///
+    /// When enabled, every octet of the transmit and receive buffers is
+    /// overwritten with zero whenever the socket is reset, i.e. on
+    /// [listen](#method.listen), [connect](#method.connect), or after
+    /// the TIME-WAIT state expires. This ensures no data from a previous
+    /// connection lingers in memory. By default, it is disabled, since it
+    /// costs time proportional to the size of the buffers.
+    pub fn set_zero_on_reuse(&mut self, enabled: bool) {
+        self.zero_on_reuse = enabled
+    }
+
+    /// Enable or disable ignoring RSTs in the TIME-WAIT state.
+    ///
+    /// By default, an acceptable RST received in the TIME-WAIT state closes
+    /// the socket right away, as specified in RFC 793. This lets an old
+    /// duplicate RST cut the TIME-WAIT state short, so that a new incarnation
+    /// of the connection may receive stray segments of the old one; when
+    /// enabled, such RSTs are dropped instead, as recommended by [RFC 1337].
+    ///
+    /// [RFC 1337]: https://www.rfc-editor.org/rfc/rfc1337
+    pub fn set_time_wait_rst_ignored(&mut self, ignored: bool) {
+        self.time_wait_rst_ignored = ignored
+    }
+
+    /// Set the maximum number of challenge ACKs sent per second.
+    ///
+    /// Once a connection is synchronized, an RST or a SYN is only acted upon
+    /// if its sequence number is exactly the next one expected. Any other RST
+    /// or SYN, as well as a segment outside the receive window or with an
+    /// unacceptable acknowledgement, is answered with a challenge ACK, as
+    /// described in [RFC 5961]. This defeats blind attackers, which would
+    /// have to guess the exact sequence number to reset the connection, while
+    /// the remote endpoint answers the challenge ACK with an acceptable RST if
+    /// it lost the connection.
+    ///
+    /// Challenge ACKs are spaced out evenly to stay within the limit, which
+    /// is 1 per second by default. With a limit of 0, none are sent.
+    ///
+    /// [RFC 5961]: https://www.rfc-editor.org/rfc/rfc5961
+    pub fn set_challenge_ack_limit(&mut self, per_sec: u32) {
+        self.challenge_ack_limit = per_sec
+    }
+
+    /// Enable or disable congestion control.
+    ///
+    /// When enabled, the data in flight is limited by a congestion window
+    /// managed as described in [RFC 5681]: slow start, congestion avoidance,
+    /// and fast retransmit and recovery. When disabled, only the window
+    /// advertised by the remote endpoint limits the data in flight, and a
+    /// retransmission resends all of it at once. By default, it is enabled.
+    ///
+    /// [RFC 5681]: https://tools.ietf.org/html/rfc5681
+    pub fn set_congestion_control(&mut self, enabled: bool) {
+        self.congestion.enabled = enabled
+    }
+
+    /// Enable or disable slow-start restart after idle.
+    ///
+    /// When enabled, a connection which has not sent any data for longer than
+    /// the idle period (see [set_slow_start_restart_idle]) collapses its
+    /// congestion window back to the initial window before sending again, as
+    /// described in [RFC 5681 § 4.1]. This avoids bursting a stale, large
+    /// congestion window onto a path whose conditions may have changed.
+    /// By default, it is enabled.
+    ///
+    /// [set_slow_start_restart_idle]: #method.set_slow_start_restart_idle
+    /// [RFC 5681 § 4.1]: https://tools.ietf.org/html/rfc5681#section-4.1
+    pub fn set_slow_start_restart(&mut self, enabled: bool) {
+        self.slow_start_restart = enabled
+    }
+
+    /// Set the idle period after which slow start is restarted.
+    ///
+    /// If `None` (the default), the current retransmission timeout is used,
+    /// as recommended by RFC 5681.
+    pub fn set_slow_start_restart_idle(&mut self, duration: Option<Duration>) {
+        self.slow_start_restart_idle = duration
+    }
+
+    /// Return the initial congestion window, in segments.
+    ///
+    /// `None` means the window specified in RFC 5681 is used, which is
+    /// between two and four segments depending on the maximum segment size.
+    ///
+    /// See also the [set_initial_congestion_window](#method.set_initial_congestion_window)
+    /// method.
+    pub fn initial_congestion_window(&self) -> Option<usize> {
+        self.congestion.initial_segments
+    }
+
+    /// Set the initial congestion window, in segments.
+    ///
+    /// The window is used when the connection is established and when slow
+    /// start is restarted after an idle period. It is clamped to between one
+    /// and ten segments, the latter being the limit set by RFC 6928.
+    pub fn set_initial_congestion_window(&mut self, segments: usize) {
+        let segments = segments.clamp(1, MAX_INITIAL_WINDOW_SEGMENTS);
+        self.congestion.initial_segments = Some(segments);
+    }
+
     /// Return the keep-alive interval.
     ///
     /// See also the [set_keep_alive](#method.set_keep_alive) method.
@@ -722,47 +1682,204 @@ impl<'a> Socket<'a> {
         self.hop_limit = hop_limit
     }
 
-    /// Return the local endpoint, or None if not connected.
-    #[inline]
-    pub fn local_endpoint(&self) -> Option<IpEndpoint> {
-        Some(self.tuple?.local)
+    /// Return the Differentiated Services Code Point of outgoing packets.
+    ///
+    /// See also the [set_dscp](#method.set_dscp) method.
+    pub fn dscp(&self) -> u8 {
+        self.dscp
     }
 
-    /// Return the remote endpoint, or None if not connected.
-    #[inline]
-    pub fn remote_endpoint(&self) -> Option<IpEndpoint> {
-        Some(self.tuple?.remote)
+    /// Set the Differentiated Services Code Point of outgoing packets, used
+    /// as the upper six bits of the IPv4 Type of Service or IPv6 Traffic
+    /// Class field.
+    ///
+    /// By default, packets are not marked, i.e. use the value 0.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the value does not fit in six bits.
+    pub fn set_dscp(&mut self, dscp: u8) {
+        assert!(dscp < 64, "the DSCP value must fit in six bits");
+        self.dscp = dscp
     }
 
-    /// Return the connection state, in terms of the TCP state machine.
-    #[inline]
-    pub fn state(&self) -> State {
-        self.state
+    /// Return how the connection is torn down when the socket is removed from
+    /// its socket set.
+    ///
+    /// See also the [set_close_on_drop](#method.set_close_on_drop) method.
+    pub fn close_on_drop(&self) -> Option<CloseMode> {
+        self.close_on_drop
     }
 
-    fn reset(&mut self) {
-        let rx_cap_log2 =
-            mem::size_of::<usize>() * 8 - self.rx_buffer.capacity().leading_zeros() as usize;
-
+    /// Set how the connection is torn down when the socket is removed from its
+    /// socket set.
+    ///
+    /// If set, and the connection is synchronized, the socket set remembers a
+    /// RST or a FIN segment, depending on `mode`, which the interface sends on
+    /// its next poll. A FIN is only sent if none was sent yet. The removed
+    /// socket itself is left as is. With the TCP Authentication Option, the
+    /// segment is signed with the current key at that time. If `None`, the
+    /// default, nothing is sent, and the remote endpoint eventually times out.
+    pub fn set_close_on_drop(&mut self, mode: Option<CloseMode>) {
+        self.close_on_drop = mode
+    }
+
+    /// Return the segment tearing down the connection of the socket, when it
+    /// is removed from its socket set.
+    pub(crate) fn teardown(&mut self) -> Option<(IpRepr, TcpRepr<'static>)> {
+        let tuple = self.tuple?;
+        let control = match (self.close_on_drop?, self.state) {
+            (
+                CloseMode::Rst,
+                State::SynReceived
+                | State::Established
+                | State::FinWait1
+                | State::FinWait2
+                | State::CloseWait
+                | State::Closing
+                | State::LastAck,
+            ) => TcpControl::Rst,
+            (CloseMode::GracefulBestEffort, State::Established | State::CloseWait) => {
+                TcpControl::Fin
+            }
+            _ => return None,
+        };
+
+        let mut repr = TcpRepr {
+            src_port: tuple.local.port,
+            dst_port: tuple.remote.port,
+            control,
+            seq_number: self.remote_last_seq,
+            ack_number: Some(self.remote_seq_no + self.rx_buffer.len()),
+            window_len: self.scaled_window(),
+            window_scale: None,
+            max_seg_size: None,
+            sack_permitted: false,
+            sack_ranges: [None, None, None],
+            authentication: None,
+            accecn: None,
+            payload: &[],
+        };
+        let mut ip_repr = IpRepr::new(
+            tuple.local.addr,
+            tuple.remote.addr,
+            IpProtocol::Tcp,
+            repr.buffer_len(),
+            self.hop_limit.unwrap_or(64),
+        );
+        if let Some(ao) = &mut self.ao {
+            ao.sign(
+                self.isns,
+                self.local_seq_no,
+                self.remote_last_seq,
+                &mut ip_repr,
+                &mut repr,
+            );
+        }
+        Some((ip_repr, repr))
+    }
+
+    /// Return the local endpoint, or None if not connected.
+    #[inline]
+    pub fn local_endpoint(&self) -> Option<IpEndpoint> {
+        Some(self.tuple?.local)
+    }
+
+    /// Return the remote endpoint, or None if not connected.
+    #[inline]
+    pub fn remote_endpoint(&self) -> Option<IpEndpoint> {
+        Some(self.tuple?.remote)
+    }
+
+    /// Change the local address of the connection, keeping its sequence space
+    /// and every other part of its state.
+    ///
+    /// This allows a connection to survive a change of the local address, e.g.
+    /// when moving from one network to another. The following segments are
+    /// sent from `addr`, and only those sent to `addr` are accepted. This
+    /// only works if the remote endpoint, and any middlebox along the path,
+    /// tolerate the change; TCP itself has no notion of it, so most peers
+    /// will reset the connection or ignore the segments.
+    ///
+    /// Like [connect](#method.connect), this takes the context of the
+    /// interface, here to check that `addr` is assigned to it: the interface
+    /// drops the segments sent to any other address, so the connection would
+    /// stall after sending from it.
+    ///
+    /// Returns `Err(InvalidState)` if the socket is not connected, and
+    /// `Err(Unaddressable)` if `addr` is not assigned to the interface, or of
+    /// another IP version than the remote address.
+    pub fn rebind_local(&mut self, cx: &mut Context, addr: IpAddress) -> Result<(), ConnectError> {
+        let Some(tuple) = self.tuple.as_mut() else {
+            return Err(ConnectError::InvalidState);
+        };
+        if addr.version() != tuple.remote.addr.version() || !cx.has_ip_addr(addr) {
+            return Err(ConnectError::Unaddressable);
+        }
+
+        tuple.local.addr = addr;
+        // The traffic keys of the TCP Authentication Option are derived from
+        // the addresses of the connection.
+        if let Some(conn) = self.ao.as_mut().and_then(|ao| ao.conn.as_mut()) {
+            conn.traffic_keys = None;
+        }
+        Ok(())
+    }
+
+    /// Return the connection state, in terms of the TCP state machine.
+    #[inline]
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    fn reset(&mut self) {
+        let rx_cap_log2 =
+            mem::size_of::<usize>() * 8 - self.rx_buffer.capacity().leading_zeros() as usize;
+
         self.state = State::Closed;
         self.timer = Timer::new();
         self.rtte = RttEstimator::default();
+        self.delivery_rate = DeliveryRateEstimator::default();
+        self.congestion.reset(DEFAULT_MSS);
         self.assembler = Assembler::new();
-        self.tx_buffer.clear();
-        self.rx_buffer.clear();
+        if self.zero_on_reuse {
+            self.tx_buffer.reset();
+            self.rx_buffer.reset();
+        } else {
+            self.tx_buffer.clear();
+            self.rx_buffer.clear();
+        }
         self.rx_fin_received = false;
         self.listen_endpoint = IpListenEndpoint::default();
+        self.listen_isn = None;
         self.tuple = None;
+        self.isns = None;
         self.local_seq_no = TcpSeqNumber::default();
         self.remote_seq_no = TcpSeqNumber::default();
         self.remote_last_seq = TcpSeqNumber::default();
         self.remote_last_ack = None;
         self.remote_last_win = 0;
+        self.rx_window_zeroed = false;
+        self.last_activity = None;
+        self.flush_seq = None;
+        self.poke = false;
+        if let Some(ao) = &mut self.ao {
+            ao.current = 0;
+            ao.conn = None;
+        }
+        self.connect_retries = 0;
+        self.syn_retransmits = 0;
+        self.connect_retry_at = None;
         self.remote_win_len = 0;
         self.remote_win_scale = None;
         self.remote_win_shift = rx_cap_log2.saturating_sub(16) as u8;
         self.remote_mss = DEFAULT_MSS;
+        self.local_mss = DEFAULT_MSS;
         self.remote_last_ts = None;
+        self.local_last_ts = None;
+        self.pacing_next_at = None;
+        self.rack = RackTlp::default();
+        self.retransmits = 0;
         self.ack_delay_timer = AckDelayTimer::Idle;
         self.challenge_ack_timer = Instant::from_secs(0);
 
@@ -799,6 +1916,97 @@ impl<'a> Socket<'a> {
         Ok(())
     }
 
+    /// Start listening on the given endpoint, using the initial sequence
+    /// number returned by `isn` for the connection accepted.
+    ///
+    /// This lets a source of entropy such as a hardware RNG be used instead of
+    /// the PRNG of the interface, which is only as good as its seed. The
+    /// sequence number is used for a single connection: if the socket goes
+    /// back to the LISTEN state, the next one is drawn from the PRNG.
+    ///
+    /// Errors are the same as for [listen](#method.listen).
+    pub fn listen_with_isn<T, F>(&mut self, local_endpoint: T, isn: F) -> Result<(), ListenError>
+    where
+        T: Into<IpListenEndpoint>,
+        F: FnOnce() -> u32,
+    {
+        self.listen(local_endpoint)?;
+        self.listen_isn = Some(TcpSeqNumber(isn() as i32));
+        Ok(())
+    }
+
+    /// Return the accept filter.
+    ///
+    /// See also the [set_accept_filter](#method.set_accept_filter) method.
+    pub fn accept_filter(&self) -> Option<fn(IpEndpoint) -> bool> {
+        self.accept_filter
+    }
+
+    /// Set the accept filter.
+    ///
+    /// While listening, a SYN is only accepted if the filter returns `true`
+    /// for the remote endpoint it comes from; otherwise it is answered with a
+    /// RST, as if no socket were listening, or dropped silently if
+    /// [stealth listening](#method.set_stealth_listen) is enabled. `None`,
+    /// the default, accepts every SYN.
+    pub fn set_accept_filter(&mut self, filter: Option<fn(IpEndpoint) -> bool>) {
+        self.accept_filter = filter
+    }
+
+    /// Return whether stealth listening is enabled.
+    ///
+    /// See also the [set_stealth_listen](#method.set_stealth_listen) method.
+    pub fn stealth_listen(&self) -> bool {
+        self.stealth_listen
+    }
+
+    /// Enable or disable stealth listening.
+    ///
+    /// When enabled, SYNs rejected by the [accept
+    /// filter](#method.set_accept_filter) are dropped without any response, so
+    /// that to a rejected remote endpoint the port looks filtered rather than
+    /// closed. Stealth listening is disabled by default.
+    pub fn set_stealth_listen(&mut self, enabled: bool) {
+        self.stealth_listen = enabled
+    }
+
+    /// Return the KeyID outgoing segments are authenticated with, if the TCP
+    /// Authentication Option is used.
+    ///
+    /// See also the [set_ao_keys](#method.set_ao_keys) method.
+    pub fn ao_key_id(&self) -> Option<u8> {
+        let ao = self.ao.as_ref()?;
+        Some(ao.keys[ao.current].send_id)
+    }
+
+    /// Authenticate segments with the TCP Authentication Option (RFC 5925).
+    ///
+    /// Every outgoing segment carries a MAC computed by `mac` with one of
+    /// `keys`, starting with the first one, and incoming segments without a
+    /// valid MAC for the key matching their KeyID are dropped. The send and
+    /// receive traffic keys are derived by `kdf` from the master key and the
+    /// ISNs of the connection; SYN segments use zero as the ISN of the
+    /// remote end. Outgoing segments ask the remote end to use the key in
+    /// use locally; when the remote end asks for another one of `keys` with
+    /// its RNextKeyID, the socket switches to it. An empty slice of keys, the
+    /// default, disables authentication.
+    ///
+    /// The keys should be set before the connection is opened, since the
+    /// Sequence Number Extension is only tracked while they are.
+    pub fn set_ao_keys(&mut self, keys: &'a [AoKey<'a>], kdf: AoKdfFn, mac: AoMacFn) {
+        self.ao = if keys.is_empty() {
+            None
+        } else {
+            Some(AoState {
+                keys,
+                kdf,
+                mac,
+                current: 0,
+                conn: None,
+            })
+        }
+    }
+
     /// Connect to a given endpoint.
     ///
     /// The local port must be provided explicitly. Assuming `fn
@@ -849,9 +2057,49 @@ impl<'a> Socket<'a> {
         T: Into<IpEndpoint>,
         U: Into<IpListenEndpoint>,
     {
-        let remote_endpoint: IpEndpoint = remote_endpoint.into();
-        let local_endpoint: IpListenEndpoint = local_endpoint.into();
+        self.connect_impl(
+            cx,
+            remote_endpoint.into(),
+            local_endpoint.into(),
+            None::<fn() -> u32>,
+        )
+    }
+
+    /// Connect to a given endpoint, using the initial sequence number
+    /// returned by `isn` instead of one drawn from the PRNG of the interface.
+    ///
+    /// This lets a source of entropy such as a hardware RNG be used for every
+    /// connection. Retried connection attempts, see
+    /// [set_connect_retry](#method.set_connect_retry), still draw their
+    /// sequence number from the PRNG.
+    ///
+    /// Errors are the same as for [connect](#method.connect), in which case
+    /// `isn` is not called.
+    pub fn connect_with_isn<T, U, F>(
+        &mut self,
+        cx: &mut Context,
+        remote_endpoint: T,
+        local_endpoint: U,
+        isn: F,
+    ) -> Result<(), ConnectError>
+    where
+        T: Into<IpEndpoint>,
+        U: Into<IpListenEndpoint>,
+        F: FnOnce() -> u32,
+    {
+        self.connect_impl(cx, remote_endpoint.into(), local_endpoint.into(), Some(isn))
+    }
 
+    fn connect_impl<F>(
+        &mut self,
+        cx: &mut Context,
+        remote_endpoint: IpEndpoint,
+        local_endpoint: IpListenEndpoint,
+        isn: Option<F>,
+    ) -> Result<(), ConnectError>
+    where
+        F: FnOnce() -> u32,
+    {
         if self.is_open() {
             return Err(ConnectError::InvalidState);
         }
@@ -890,7 +2138,10 @@ impl<'a> Socket<'a> {
         // 将 socket 状态设置为 SynSent，表示已发送 SYN 包
         self.set_state(State::SynSent);
         // 生成一个随机的初始序列号
-        let seq = Self::random_seq_no(cx);
+        let seq = match isn {
+            Some(isn) => TcpSeqNumber(isn() as i32),
+            None => Self::random_seq_no(cx),
+        };
         // 设置本地和远程的序列号
         self.local_seq_no = seq;
         self.remote_last_seq = seq;
@@ -1081,6 +2332,21 @@ impl<'a> Socket<'a> {
         self.tx_buffer.capacity()
     }
 
+    /// Return the number of octets that can be enqueued right now, i.e. the
+    /// free space in the transmit buffer.
+    ///
+    /// A single [send_slice](#method.send_slice) call of at most this many
+    /// octets enqueues all of them. If the transmit half of the connection is
+    /// not open (see [may_send](#method.may_send)), this is zero.
+    #[inline]
+    pub fn send_capacity_available(&self) -> usize {
+        if !self.may_send() {
+            return 0;
+        }
+
+        self.tx_buffer.window()
+    }
+
     /// Check whether the receive half of the full-duplex connection buffer is
     /// open (see [may_recv](#method.may_recv)), and the receive buffer is
     /// not empty.
@@ -1147,6 +2413,59 @@ impl<'a> Socket<'a> {
         })
     }
 
+    /// Enqueue a sequence of octets to be sent, and fill it from the
+    /// concatenation of several slices.
+    ///
+    /// This function returns the amount of octets actually enqueued, which is
+    /// limited by the amount of free space in the transmit buffer; down to
+    /// zero. If the buffer fills up in the middle of a slice, only the
+    /// leading part of that slice is enqueued.
+    ///
+    /// See also [send_slice](#method.send_slice).
+    pub fn send_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, SendError> {
+        self.send_impl(|tx_buffer| {
+            let mut size = 0;
+            for buf in bufs {
+                let written = tx_buffer.enqueue_slice(buf);
+                size += written;
+                if written < buf.len() {
+                    break;
+                }
+            }
+            (size, size)
+        })
+    }
+
+    /// Send all the data currently in the transmit buffer as soon as possible.
+    ///
+    /// The buffered data is not held back by Nagle's Algorithm, and the PSH
+    /// flag is set on the segment carrying its last octet, so that the remote
+    /// endpoint delivers it promptly. Data enqueued afterwards is not affected.
+    ///
+    /// This function does nothing if the transmit buffer is empty.
+    pub fn flush(&mut self) {
+        if self.tx_buffer.is_empty() {
+            return;
+        }
+        self.flush_seq = Some(self.local_seq_no + self.tx_buffer.len());
+    }
+
+    /// Send an empty segment on the next poll, to poke the remote endpoint.
+    ///
+    /// The segment acknowledges the received data like any other, and has the
+    /// PSH flag set if the transmit half of the connection is open, but it
+    /// carries no data and takes no sequence space. Unlike a keep-alive probe,
+    /// it is sent right away and only once. If data or flags are sent in the
+    /// meantime, no separate segment is sent.
+    ///
+    /// This function does nothing if the connection is not synchronized.
+    pub fn poke(&mut self) {
+        match self.state {
+            State::Closed | State::Listen | State::SynSent | State::SynReceived => (),
+            _ => self.poke = true,
+        }
+    }
+
     fn recv_error_check(&mut self) -> Result<(), RecvError> {
         // We may have received some data inside the initial SYN, but until the
         // connection is fully open we must not dequeue any data, as it may be
@@ -1243,6 +2562,10 @@ impl<'a> Socket<'a> {
 
     /// Return the amount of octets queued in the transmit buffer.
     ///
+    /// Only application data is counted: the sequence space taken by the SYN
+    /// and FIN flags is not, so this drops to zero once all the data has been
+    /// acknowledged, whether or not the FIN has been.
+    ///
     /// Note that the Berkeley sockets interface does not have an equivalent of
     /// this API.
     pub fn send_queue(&self) -> usize {
@@ -1254,18 +2577,118 @@ impl<'a> Socket<'a> {
     /// because it includes all queued octets, and not only the octets that
     /// may be returned as a contiguous slice.
     ///
+    /// Like for [send_queue](#method.send_queue), a received SYN or FIN is
+    /// not counted.
+    ///
     /// Note that the Berkeley sockets interface does not have an equivalent of
     /// this API.
     pub fn recv_queue(&self) -> usize {
         self.rx_buffer.len()
     }
 
+    /// Return the amount of octets that can be received right now.
+    ///
+    /// Data is only ever received in order: octets that arrived out of order
+    /// are kept in the receive buffer, but are neither counted here nor
+    /// returned by [recv](#method.recv) until the holes before them are
+    /// filled. This is currently the same as
+    /// [recv_queue](#method.recv_queue).
+    pub fn contiguous_recv_available(&self) -> usize {
+        self.rx_buffer.len()
+    }
+
+    /// Return the amount of octets sent but not yet acknowledged, i.e. the
+    /// distance between SND.UNA and SND.NXT.
+    ///
+    /// This includes the sequence space taken by the SYN and FIN flags, and
+    /// drops to zero when a retransmission timeout rewinds SND.NXT.
+    pub fn bytes_in_flight(&self) -> usize {
+        if self.remote_last_seq > self.local_seq_no {
+            self.remote_last_seq - self.local_seq_no
+        } else {
+            0
+        }
+    }
+
+    /// Return the number of segments sent but not yet acknowledged.
+    ///
+    /// Segment boundaries are not recorded, so this is derived from
+    /// [bytes_in_flight](#method.bytes_in_flight) assuming that every segment
+    /// but the last one carries a full maximum segment size.
+    pub fn segments_in_flight(&self) -> usize {
+        let bytes = self.bytes_in_flight();
+        (bytes + self.remote_mss - 1) / self.remote_mss
+    }
+
+    /// Return the rate at which the remote endpoint acknowledges data, in
+    /// octets per second.
+    ///
+    /// The rate is measured over the last interval of at least one round-trip
+    /// time in which the transmit buffer never ran empty, and so estimates the
+    /// bandwidth available on the path as long as the application keeps it
+    /// busy. Returns `None` until a whole interval has been measured.
+    pub fn delivery_rate(&self) -> Option<u64> {
+        self.delivery_rate.rate
+    }
+
+    /// Return whether the socket ever advertised a zero receive window.
+    ///
+    /// The window drops to zero when the receive buffer fills up because the
+    /// application is not reading from it quickly enough, so this flag
+    /// distinguishes stalls caused by the application from stalls caused by
+    /// the network. It stays set until cleared with
+    /// [reset_rx_window_zeroed](#method.reset_rx_window_zeroed), or until the
+    /// socket is reused for another connection.
+    pub fn was_rx_window_zeroed(&self) -> bool {
+        self.rx_window_zeroed
+    }
+
+    /// Clear the flag returned by [was_rx_window_zeroed](#method.was_rx_window_zeroed).
+    pub fn reset_rx_window_zeroed(&mut self) {
+        self.rx_window_zeroed = false
+    }
+
+    /// Return the time application data was last sent or received.
+    ///
+    /// Only segments carrying data count as activity; pure acknowledgements,
+    /// window updates and keep-alive probes do not. Returns `None` if no data
+    /// was transferred since the socket was opened.
+    pub fn last_activity(&self) -> Option<Instant> {
+        self.last_activity
+    }
+
+    /// Return a snapshot of the state, tunables and counters of the socket.
+    ///
+    /// This only copies values the socket keeps anyway, and is cheap enough
+    /// to be called on every poll, e.g. for logging.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            state: self.state,
+            local_endpoint: self.local_endpoint(),
+            remote_endpoint: self.remote_endpoint(),
+            cwnd: self.congestion.window(),
+            ssthresh: self.congestion.slow_start_threshold(),
+            rtt: Duration::from_millis(self.rtte.rtt as u64),
+            rto: self.rtte.retransmission_timeout(),
+            mss: self.remote_mss,
+            bytes_in_flight: self.bytes_in_flight(),
+            retransmits: self.retransmits,
+            send_window: self.remote_win_len,
+            recv_window: self.rx_window(),
+            send_queue: self.send_queue(),
+            recv_queue: self.recv_queue(),
+        }
+    }
+
     fn set_state(&mut self, state: State) {
         if self.state != state {
             tcp_trace!("state={}=>{}", self.state, state);
         }
 
         self.state = state;
+        if state != State::SynSent {
+            self.connect_retry_at = None;
+        }
 
         #[cfg(feature = "async")]
         {
@@ -1290,6 +2713,8 @@ impl<'a> Socket<'a> {
             max_seg_size: None,
             sack_permitted: false,
             sack_ranges: [None, None, None],
+            authentication: None,
+            accecn: None,
             payload: &[],
         };
         let ip_reply_repr = IpRepr::new(
@@ -1318,6 +2743,163 @@ impl<'a> Socket<'a> {
         (ip_reply_repr, reply_repr)
     }
 
+    /// Compute the MAC of the TCP Authentication Option of a segment, given
+    /// its Sequence Number Extension and its header with the checksum and the
+    /// MAC zeroed.
+    fn ao_mac(
+        mac: AoMacFn,
+        traffic_key: &[u8],
+        sne: u32,
+        ip_repr: &IpRepr,
+        header: &[u8],
+        payload: &[u8],
+    ) -> [u8; TCP_AO_MAC_LEN] {
+        let sne = sne.to_be_bytes();
+        let segment_len = (header.len() + payload.len()) as u32;
+        let src_addr = ip_repr.src_addr();
+        let dst_addr = ip_repr.dst_addr();
+        let addr_len = src_addr.as_bytes().len();
+
+        let mut pseudo_header = [0; 40];
+        pseudo_header[..addr_len].copy_from_slice(src_addr.as_bytes());
+        pseudo_header[addr_len..2 * addr_len].copy_from_slice(dst_addr.as_bytes());
+        let pseudo_header = match ip_repr {
+            #[cfg(feature = "proto-ipv4")]
+            IpRepr::Ipv4(_) => {
+                pseudo_header[9] = IpProtocol::Tcp.into();
+                pseudo_header[10..12].copy_from_slice(&(segment_len as u16).to_be_bytes());
+                &pseudo_header[..12]
+            }
+            #[cfg(feature = "proto-ipv6")]
+            IpRepr::Ipv6(_) => {
+                pseudo_header[32..36].copy_from_slice(&segment_len.to_be_bytes());
+                pseudo_header[39] = IpProtocol::Tcp.into();
+                &pseudo_header[..]
+            }
+        };
+
+        mac(traffic_key, &[&sne, pseudo_header, header, payload])
+    }
+
+    /// Check the TCP Authentication Option of an incoming segment, and switch
+    /// to the key the remote end asks for.
+    ///
+    /// Returns `false` if the segment has to be dropped.
+    pub(crate) fn authenticate<T: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        ip_repr: &IpRepr,
+        packet: &TcpPacket<&T>,
+        repr: &TcpRepr,
+    ) -> bool {
+        let ao = match &mut self.ao {
+            Some(ao) => ao,
+            None => return true,
+        };
+        let authentication = match repr.authentication {
+            Some(authentication) => authentication,
+            None => {
+                tcp_trace!("segment without TCP-AO, dropping");
+                return false;
+            }
+        };
+        let index = match ao
+            .keys
+            .iter()
+            .position(|key| key.recv_id == authentication.key_id)
+        {
+            Some(index) => index,
+            None => {
+                tcp_trace!("unknown TCP-AO key {}, dropping", authentication.key_id);
+                return false;
+            }
+        };
+
+        // Zero the checksum and the MAC in a copy of the header.
+        let header_len = packet.header_len() as usize;
+        let mut header = [0; 60];
+        let header = &mut header[..header_len];
+        header.copy_from_slice(&packet.as_ref()[..header_len]);
+        TcpPacket::new_unchecked(&mut *header).set_checksum(0);
+        let mut offset = TCP_HEADER_LEN;
+        let mut options = packet.options();
+        while !options.is_empty() {
+            let (next_options, option) = match TcpOption::parse(options) {
+                Ok(res) => res,
+                Err(_) => return false,
+            };
+            match option {
+                TcpOption::EndOfList => break,
+                TcpOption::Authentication { mac, .. } => {
+                    header[offset + 4..offset + 4 + mac.len()].fill(0)
+                }
+                _ => (),
+            }
+            offset += options.len() - next_options.len();
+            options = next_options;
+        }
+
+        let key = ao.keys[index];
+        let local = IpEndpoint::new(ip_repr.dst_addr(), repr.dst_port);
+        let remote = IpEndpoint::new(ip_repr.src_addr(), repr.src_port);
+        let (traffic_key, sne) = match (repr.control, self.isns) {
+            // SYN segments carry the ISNs their traffic key is derived from,
+            // and are at the start of the sequence number space.
+            (TcpControl::Syn, _) => {
+                let local_isn = repr.ack_number.map_or(TcpSeqNumber(0), |ack| ack - 1);
+                let traffic_key = ao_traffic_key(
+                    ao.kdf,
+                    key.master_key,
+                    remote,
+                    local,
+                    repr.seq_number,
+                    local_isn,
+                );
+                (traffic_key, 0)
+            }
+            (_, Some(isns)) => {
+                let (_, recv_key) = ao.traffic_keys(index, local, remote, isns);
+                let conn = ao.connection(isns);
+                let rcv_nxt = self.remote_seq_no;
+                conn.rcv_sne = (rcv_nxt, ao_sne(rcv_nxt, conn.rcv_sne));
+                (recv_key, ao_sne(repr.seq_number, conn.rcv_sne))
+            }
+            // The remote ISN is not known yet.
+            (_, None) => {
+                let traffic_key = ao_traffic_key(
+                    ao.kdf,
+                    key.master_key,
+                    remote,
+                    local,
+                    TcpSeqNumber(0),
+                    self.local_seq_no,
+                );
+                (traffic_key, 0)
+            }
+        };
+        let mac = Self::ao_mac(ao.mac, &traffic_key, sne, ip_repr, header, repr.payload);
+        // Compare in constant time.
+        let diff = mac
+            .iter()
+            .zip(authentication.mac.iter())
+            .fold(0, |diff, (a, b)| diff | (a ^ b));
+        if diff != 0 {
+            tcp_trace!("bad TCP-AO MAC, dropping");
+            return false;
+        }
+
+        if ao.keys[ao.current].send_id != authentication.rnext_key_id {
+            if let Some(index) = ao
+                .keys
+                .iter()
+                .position(|key| key.send_id == authentication.rnext_key_id)
+            {
+                tcp_trace!("switching to TCP-AO key {}", authentication.rnext_key_id);
+                ao.current = index;
+            }
+        }
+        true
+    }
+
     fn ack_reply(&mut self, ip_repr: &IpRepr, repr: &TcpRepr) -> (IpRepr, TcpRepr<'static>) {
         let (mut ip_reply_repr, mut reply_repr) = Self::reply(ip_repr, repr);
 
@@ -1334,6 +2916,9 @@ impl<'a> Socket<'a> {
         // segments, is right-shifted by [advertised scale value] bits[...]
         reply_repr.window_len = self.scaled_window();
         self.remote_last_win = reply_repr.window_len;
+        if reply_repr.window_len == 0 {
+            self.rx_window_zeroed = true;
+        }
 
         // If the remote supports selective acknowledgement, add the option to the
         // outgoing segment.
@@ -1347,12 +2932,17 @@ impl<'a> Socket<'a> {
             // the header.
             reply_repr.sack_ranges[0] = None;
 
-            if let Some(last_seg_seq) = self.local_rx_last_seq.map(|s| s.0 as u32) {
+            // The assembler offsets are relative to the acknowledgement number. Turn them
+            // into sequence numbers before comparing them, so that a range straddling the
+            // wraparound of the sequence space is still found.
+            let ack_number = reply_repr.ack_number.unwrap_or_default();
+            if let Some(last_seg_seq) = self.local_rx_last_seq {
                 reply_repr.sack_ranges[0] = self
                     .assembler
-                    .iter_data(reply_repr.ack_number.map(|s| s.0 as usize).unwrap_or(0))
-                    .map(|(left, right)| (left as u32, right as u32))
-                    .find(|(left, right)| *left <= last_seg_seq && *right >= last_seg_seq);
+                    .iter_data(0)
+                    .map(|(left, right)| (ack_number + left, ack_number + right))
+                    .find(|(left, right)| *left <= last_seg_seq && *right >= last_seg_seq)
+                    .map(|(left, right)| (left.0 as u32, right.0 as u32));
             }
 
             if reply_repr.sack_ranges[0].is_none() {
@@ -1366,8 +2956,10 @@ impl<'a> Socket<'a> {
                 // most quickly advance the acknowledgement number.
                 reply_repr.sack_ranges[0] = self
                     .assembler
-                    .iter_data(reply_repr.ack_number.map(|s| s.0 as usize).unwrap_or(0))
-                    .map(|(left, right)| (left as u32, right as u32))
+                    .iter_data(0)
+                    .map(|(left, right)| {
+                        ((ack_number + left).0 as u32, (ack_number + right).0 as u32)
+                    })
                     .next();
             }
         }
@@ -1384,12 +2976,13 @@ impl<'a> Socket<'a> {
         ip_repr: &IpRepr,
         repr: &TcpRepr,
     ) -> Option<(IpRepr, TcpRepr<'static>)> {
-        if cx.now() < self.challenge_ack_timer {
+        if self.challenge_ack_limit == 0 || cx.now() < self.challenge_ack_timer {
             return None;
         }
 
-        // Rate-limit to 1 per second max.
-        self.challenge_ack_timer = cx.now() + Duration::from_secs(1);
+        // Rate-limit to the configured number per second.
+        self.challenge_ack_timer =
+            cx.now() + Duration::from_micros(1_000_000 / self.challenge_ack_limit as u64);
 
         return Some(self.ack_reply(ip_repr, repr));
     }
@@ -1428,6 +3021,25 @@ impl<'a> Socket<'a> {
         cx: &mut Context,
         ip_repr: &IpRepr,
         repr: &TcpRepr,
+    ) -> Option<(IpRepr, TcpRepr<'static>)> {
+        let (mut ip_reply_repr, mut reply_repr) = self.process_segment(cx, ip_repr, repr)?;
+        if let Some(ao) = &mut self.ao {
+            ao.sign(
+                self.isns,
+                self.local_seq_no,
+                self.remote_last_seq,
+                &mut ip_reply_repr,
+                &mut reply_repr,
+            );
+        }
+        Some((ip_reply_repr, reply_repr))
+    }
+
+    fn process_segment(
+        &mut self,
+        cx: &mut Context,
+        ip_repr: &IpRepr,
+        repr: &TcpRepr,
     ) -> Option<(IpRepr, TcpRepr<'static>)> {
         debug_assert!(self.accepts(cx, ip_repr, repr));
 
@@ -1444,6 +3056,30 @@ impl<'a> Socket<'a> {
         };
         let control_len = (sent_syn as usize) + (sent_fin as usize);
 
+        // A new SYN in the TIME-WAIT state may start a new incarnation of the
+        // connection if its sequence number is beyond the end of the old one,
+        // and the socket has a listen endpoint to return to (RFC 1122 § 4.2.2.13).
+        // Any other SYN is an old duplicate, which is acknowledged.
+        if self.state == State::TimeWait
+            && repr.control == TcpControl::Syn
+            && repr.ack_number.is_none()
+        {
+            if self.listen_endpoint.port != 0
+                && self.rx_buffer.is_empty()
+                && repr.seq_number > self.remote_seq_no
+            {
+                tcp_trace!("received SYN in TIME-WAIT, reopening connection");
+                let listen_endpoint = self.listen_endpoint;
+                self.reset();
+                self.listen_endpoint = listen_endpoint;
+                self.set_state(State::Listen);
+            } else {
+                net_debug!("old SYN in TIME-WAIT, will send an ACK");
+                self.timer.set_for_close(cx.now());
+                return Some(self.ack_reply(ip_repr, repr));
+            }
+        }
+
         // Reject unacceptable acknowledgements.
         match (self.state, repr.control, repr.ack_number) {
             // An RST received in response to initial SYN is acceptable if it acknowledges
@@ -1464,6 +3100,23 @@ impl<'a> Socket<'a> {
             (State::Listen, _, None) => (),
             // This case is handled in `accepts()`.
             (State::Listen, _, Some(_)) => unreachable!(),
+            // A SYN without an acknowledgement in the SYN-SENT state is a simultaneous open.
+            (State::SynSent, TcpControl::Syn, None) => (),
+            // Any SYN in a synchronized state may be spoofed, and is answered with a
+            // challenge ACK (RFC 5961 § 4.2).
+            (
+                State::Established
+                | State::FinWait1
+                | State::FinWait2
+                | State::CloseWait
+                | State::Closing
+                | State::LastAck,
+                TcpControl::Syn,
+                _,
+            ) => {
+                net_debug!("SYN in a synchronized state, will send challenge ACK");
+                return self.challenge_ack_reply(cx, ip_repr, repr);
+            }
             // Every packet after the initial SYN must be an acknowledgement.
             (_, _, None) => {
                 net_debug!("expecting an ACK");
@@ -1541,14 +3194,27 @@ impl<'a> Socket<'a> {
             }
         }
 
+        // Data beyond the receive buffer ceiling is not accepted, even if the
+        // remote end ignores the advertised window.
+        let rx_limit = match self.max_recv_buffer {
+            Some(max) => max.clamp(self.rx_buffer.len(), self.rx_buffer.capacity()),
+            None => self.rx_buffer.capacity(),
+        };
         let window_start = self.remote_seq_no + self.rx_buffer.len();
-        let window_end = self.remote_seq_no + self.rx_buffer.capacity();
+        let window_end = self.remote_seq_no + rx_limit;
         let segment_start = repr.seq_number;
         let segment_end = repr.seq_number + repr.segment_len();
 
         let (payload, payload_offset) = match self.state {
             // In LISTEN and SYN-SENT states, we have not yet synchronized with the remote end.
             State::Listen | State::SynSent => (&[][..], 0),
+            // In a simultaneous open, the SYN|ACK of the remote end repeats the SYN we have
+            // already acknowledged.
+            State::SynReceived
+                if repr.control == TcpControl::Syn && repr.seq_number + 1 == self.remote_seq_no =>
+            {
+                (&[][..], 0)
+            }
             _ => {
                 // https://www.rfc-editor.org/rfc/rfc9293.html#name-segment-acceptability-tests
                 let segment_in_window = match (
@@ -1683,6 +3349,36 @@ impl<'a> Socket<'a> {
                 return None;
             }
 
+            // RSTs in the TIME-WAIT state may be ignored to protect against
+            // TIME-WAIT assassination.
+            (State::TimeWait, TcpControl::Rst) if self.time_wait_rst_ignored => {
+                net_debug!("ignoring RST in TIME-WAIT");
+                return None;
+            }
+
+            // RSTs in a synchronized state must have the exact sequence number expected
+            // next, or they may be spoofed; the remote endpoint is challenged to send
+            // an exact RST instead (RFC 5961 § 3.2).
+            (
+                State::Established
+                | State::FinWait1
+                | State::FinWait2
+                | State::CloseWait
+                | State::Closing
+                | State::LastAck
+                | State::TimeWait,
+                TcpControl::Rst,
+            ) if repr.seq_number != window_start => {
+                net_debug!("RST not at the expected sequence number, will send challenge ACK");
+                return self.challenge_ack_reply(cx, ip_repr, repr);
+            }
+
+            // RSTs in response to our SYN may be followed by another attempt.
+            (State::SynSent, TcpControl::Rst) if self.schedule_connect_retry(cx) => {
+                tcp_trace!("received RST");
+                return None;
+            }
+
             // RSTs in any other state close the socket.
             (_, TcpControl::Rst) => {
                 tcp_trace!("received RST");
@@ -1693,6 +3389,16 @@ impl<'a> Socket<'a> {
 
             // SYN packets in the LISTEN state change it to SYN-RECEIVED.
             (State::Listen, TcpControl::Syn) => {
+                let remote = IpEndpoint::new(ip_repr.src_addr(), repr.src_port);
+                if matches!(self.accept_filter, Some(filter) if !filter(remote)) {
+                    if self.stealth_listen {
+                        tcp_trace!("SYN from {} rejected, dropping", remote);
+                        return None;
+                    }
+                    tcp_trace!("SYN from {} rejected, sending RST", remote);
+                    return Some(Self::rst_reply(ip_repr, repr));
+                }
+
                 tcp_trace!("received SYN");
                 if let Some(max_seg_size) = repr.max_seg_size {
                     if max_seg_size == 0 {
@@ -1706,10 +3412,15 @@ impl<'a> Socket<'a> {
                     local: IpEndpoint::new(ip_repr.dst_addr(), repr.dst_port),
                     remote: IpEndpoint::new(ip_repr.src_addr(), repr.src_port),
                 });
-                self.local_seq_no = Self::random_seq_no(cx);
+                self.local_seq_no = match self.listen_isn.take() {
+                    Some(isn) => isn,
+                    None => Self::random_seq_no(cx),
+                };
                 self.remote_seq_no = repr.seq_number + 1;
                 self.remote_last_seq = self.local_seq_no;
+                self.isns = Some((self.local_seq_no, repr.seq_number));
                 self.remote_has_sack = repr.sack_permitted;
+                self.congestion.reset(self.remote_mss);
                 self.remote_win_scale = repr.window_scale;
                 // Remote doesn't support window scaling, don't do it.
                 if self.remote_win_scale.is_none() {
@@ -1720,7 +3431,8 @@ impl<'a> Socket<'a> {
             }
 
             // ACK packets in the SYN-RECEIVED state change it to ESTABLISHED.
-            (State::SynReceived, TcpControl::None) => {
+            // So do SYN|ACK packets, which complete a simultaneous open.
+            (State::SynReceived, TcpControl::None) | (State::SynReceived, TcpControl::Syn) => {
                 self.set_state(State::Established);
                 self.timer.set_for_idle(cx.now(), self.keep_alive);
             }
@@ -1735,20 +3447,49 @@ impl<'a> Socket<'a> {
                 self.timer.set_for_idle(cx.now(), self.keep_alive);
             }
 
-            // SYN|ACK packets in the SYN-SENT state change it to ESTABLISHED.
-            (State::SynSent, TcpControl::Syn) => {
-                tcp_trace!("received SYN|ACK");
+            // SYN packets in the SYN-SENT state change it to SYN-RECEIVED; this is
+            // a simultaneous open (RFC 793 § 3.4, figure 8). Our SYN is sent again,
+            // this time with an acknowledgement.
+            (State::SynSent, TcpControl::Syn) if repr.ack_number.is_none() => {
+                tcp_trace!("received SYN (simultaneous open)");
                 if let Some(max_seg_size) = repr.max_seg_size {
                     if max_seg_size == 0 {
-                        tcp_trace!("received SYNACK with zero MSS, ignoring");
+                        tcp_trace!("received SYN with zero MSS, ignoring");
                         return None;
                     }
                     self.remote_mss = max_seg_size as usize;
                 }
 
                 self.remote_seq_no = repr.seq_number + 1;
-                self.remote_last_seq = self.local_seq_no + 1;
-                self.remote_last_ack = Some(repr.seq_number);
+                self.remote_last_seq = self.local_seq_no;
+                self.isns = Some((self.local_seq_no, repr.seq_number));
+                self.remote_has_sack = repr.sack_permitted;
+                self.congestion.reset(self.remote_mss);
+                self.remote_win_scale = repr.window_scale;
+                // Remote doesn't support window scaling, don't do it.
+                if self.remote_win_scale.is_none() {
+                    self.remote_win_shift = 0;
+                }
+                self.set_state(State::SynReceived);
+                self.timer.set_for_idle(cx.now(), self.keep_alive);
+            }
+
+            // SYN|ACK packets in the SYN-SENT state change it to ESTABLISHED.
+            (State::SynSent, TcpControl::Syn) => {
+                tcp_trace!("received SYN|ACK");
+                if let Some(max_seg_size) = repr.max_seg_size {
+                    if max_seg_size == 0 {
+                        tcp_trace!("received SYNACK with zero MSS, ignoring");
+                        return None;
+                    }
+                    self.remote_mss = max_seg_size as usize;
+                }
+
+                self.remote_seq_no = repr.seq_number + 1;
+                self.remote_last_seq = self.local_seq_no + 1;
+                self.remote_last_ack = Some(repr.seq_number);
+                self.isns = Some((self.local_seq_no, repr.seq_number));
+                self.congestion.reset(self.remote_mss);
                 self.remote_win_scale = repr.window_scale;
                 // Remote doesn't support window scaling, don't do it.
                 if self.remote_win_scale.is_none() {
@@ -1868,6 +3609,15 @@ impl<'a> Socket<'a> {
                 self.tx_buffer.len() - ack_len
             );
             self.tx_buffer.dequeue_allocated(ack_len);
+            self.congestion.on_ack(ack_len, self.remote_mss);
+            self.delivery_rate.on_ack(
+                cx.now(),
+                ack_len,
+                Duration::from_millis(self.rtte.rtt as u64),
+            );
+            if self.tx_buffer.is_empty() {
+                self.delivery_rate.on_idle();
+            }
 
             // There's new room available in tx_buffer, wake the waiting task if any.
             #[cfg(feature = "async")]
@@ -1880,12 +3630,13 @@ impl<'a> Socket<'a> {
 
             // Detect and react to duplicate ACKs by:
             // 1. Check if duplicate ACK and change self.local_rx_dup_acks accordingly
-            // 2. If exactly 3 duplicate ACKs received, set for fast retransmit
+            // 2. If exactly dup_ack_threshold duplicate ACKs received, set for fast
+            //    retransmit
             // 3. Update the last received ACK (self.local_rx_last_ack)
             match self.local_rx_last_ack {
                 // Duplicate ACK if payload empty and ACK doesn't move send window ->
                 // Increment duplicate ACK count and set for retransmit if we just received
-                // the third duplicate ACK
+                // as many duplicate ACKs as the threshold
                 Some(last_rx_ack)
                     if repr.payload.is_empty()
                         && last_rx_ack == ack_number
@@ -1894,6 +3645,7 @@ impl<'a> Socket<'a> {
                 {
                     // Increment duplicate ACK count
                     self.local_rx_dup_acks = self.local_rx_dup_acks.saturating_add(1);
+                    self.congestion.on_dup_ack(self.remote_mss);
 
                     net_debug!(
                         "received duplicate ACK for seq {} (duplicate nr {}{})",
@@ -1906,7 +3658,9 @@ impl<'a> Socket<'a> {
                         }
                     );
 
-                    if self.local_rx_dup_acks == 3 {
+                    if self.local_rx_dup_acks == self.dup_ack_threshold
+                        && !self.rack_detects_reordering()
+                    {
                         self.timer.set_for_fast_retransmit();
                         net_debug!("started fast retransmit");
                     }
@@ -1920,6 +3674,7 @@ impl<'a> Socket<'a> {
                     self.local_rx_last_ack = Some(ack_number);
                 }
             };
+            let ack_advanced = ack_number > self.local_seq_no;
             // We've processed everything in the incoming segment, so advance the local
             // sequence number past it.
             self.local_seq_no = ack_number;
@@ -1931,6 +3686,15 @@ impl<'a> Socket<'a> {
             if self.remote_last_seq < self.local_seq_no {
                 self.remote_last_seq = self.local_seq_no
             }
+
+            if self.loss_detection == LossDetection::RackTlp {
+                let delivered_later = repr
+                    .sack_ranges
+                    .iter()
+                    .flatten()
+                    .any(|&(_, right)| TcpSeqNumber(right as i32) > ack_number);
+                self.rack_on_ack(cx.now(), ack_advanced, delivered_later);
+            }
         }
 
         let payload_len = payload.len();
@@ -1961,6 +3725,7 @@ impl<'a> Socket<'a> {
         );
         let len_written = self.rx_buffer.write_unallocated(payload_offset, payload);
         debug_assert!(len_written == payload_len);
+        self.last_activity = Some(cx.now());
 
         if contig_len != 0 {
             // Enqueue the contiguous data octets in front of the buffer.
@@ -2019,6 +3784,56 @@ impl<'a> Socket<'a> {
         }
     }
 
+    /// Schedule a new connection attempt after the current one failed.
+    ///
+    /// Returns `false` if the attempt should not be retried, according to the
+    /// retry policy.
+    fn schedule_connect_retry(&mut self, cx: &mut Context) -> bool {
+        if self.connect_retry_at.is_some() {
+            return true;
+        }
+        let policy = match self.connect_retry {
+            Some(policy) if self.connect_retries < policy.max_retries => policy,
+            _ => return false,
+        };
+
+        let mut delay = policy.base;
+        for _ in 0..self.connect_retries {
+            if delay >= policy.max {
+                break;
+            }
+            delay *= 2;
+        }
+        delay = delay.min(policy.max);
+        if policy.jitter {
+            let half = delay.total_micros() / 2;
+            let jitter = cx.rand().rand_u32() as u64 % (half + 1);
+            delay = Duration::from_micros(delay.total_micros() - half + jitter);
+        }
+
+        self.connect_retries += 1;
+        self.connect_retry_at = Some(cx.now() + delay);
+        net_debug!(
+            "connection attempt failed, retry {} in {}",
+            self.connect_retries,
+            delay
+        );
+        true
+    }
+
+    /// Start a new connection attempt, once the retry delay has elapsed.
+    fn retry_connect(&mut self, cx: &mut Context) {
+        self.connect_retry_at = None;
+        self.syn_retransmits = 0;
+        self.timer = Timer::new();
+        self.rtte = RttEstimator::default();
+        self.remote_last_ts = None;
+
+        let seq = Self::random_seq_no(cx);
+        self.local_seq_no = seq;
+        self.remote_last_seq = seq;
+    }
+
     fn timed_out(&self, timestamp: Instant) -> bool {
         match (self.remote_last_ts, self.timeout) {
             (Some(remote_last_ts), Some(timeout)) => timestamp >= remote_last_ts + timeout,
@@ -2026,6 +3841,19 @@ impl<'a> Socket<'a> {
         }
     }
 
+    /// Return the largest segment we are able to send and receive, given the
+    /// MTU of the interface and the MSS clamp.
+    fn max_local_mss(&self, cx: &mut Context, ip_header_len: usize) -> usize {
+        let mut mss = cx.ip_mtu() - ip_header_len - TCP_HEADER_LEN;
+        if self.ao.is_some() {
+            mss -= TCP_AO_OPTION_LEN;
+        }
+        match self.mss_clamp {
+            Some(clamp) => mss.min(clamp),
+            None => mss,
+        }
+    }
+
     fn seq_to_transmit(&self, cx: &mut Context) -> bool {
         let ip_header_len = match self.tuple.unwrap().local.addr {
             #[cfg(feature = "proto-ipv4")]
@@ -2035,7 +3863,7 @@ impl<'a> Socket<'a> {
         };
 
         // Max segment size we're able to send due to MTU limitations.
-        let local_mss = cx.ip_mtu() - ip_header_len - TCP_HEADER_LEN;
+        let local_mss = self.max_local_mss(cx, ip_header_len);
 
         // The effective max segment size, taking into account our and remote's limits.
         let effective_mss = local_mss.min(self.remote_mss);
@@ -2050,7 +3878,7 @@ impl<'a> Socket<'a> {
 
         // max sequence number we can send.
         let max_send_seq =
-            self.local_seq_no + core::cmp::min(self.remote_win_len, self.tx_buffer.len());
+            self.local_seq_no + core::cmp::min(self.send_window(), self.tx_buffer.len());
 
         // Max amount of octets we can send.
         let max_send = if max_send_seq >= self.remote_last_seq {
@@ -2077,7 +3905,15 @@ impl<'a> Socket<'a> {
         // * There's no data in flight
         // * We can send a full packet
         // * We have all the data we'll ever send (we're closing send)
-        if self.nagle && data_in_flight && !can_send_full && !want_fin {
+        // Data that was flushed is sent regardless.
+        let flushing =
+            matches!(self.flush_seq, Some(flush_seq) if self.remote_last_seq < flush_seq);
+        if self.nagle && data_in_flight && !can_send_full && !want_fin && !flushing {
+            can_send = false;
+        }
+
+        // If we're pacing, we wait for the time slot of the next segment.
+        if self.pacing_delayed(cx.now()) {
             can_send = false;
         }
 
@@ -2091,6 +3927,80 @@ impl<'a> Socket<'a> {
         can_send || can_fin
     }
 
+    /// Return whether RACK-TLP detects reordering, which it does from
+    /// selective ACKs, in place of counting duplicate ACKs.
+    fn rack_detects_reordering(&self) -> bool {
+        self.loss_detection == LossDetection::RackTlp && self.remote_has_sack
+    }
+
+    /// Update the RACK-TLP state on an incoming ACK, see RFC 8985 § 6.2.
+    fn rack_on_ack(&mut self, timestamp: Instant, ack_advanced: bool, delivered_later: bool) {
+        if self.remote_last_seq == self.local_seq_no {
+            // Everything sent was acknowledged.
+            self.rack = RackTlp::default();
+            return;
+        }
+
+        if ack_advanced {
+            self.rack.una_sent_at = self.local_last_ts;
+            self.rack.lost_at = None;
+            self.rack.probe_at = Some(timestamp + self.probe_timeout());
+        }
+
+        if delivered_later && self.rack.lost_at.is_none() {
+            if let Some(una_sent_at) = self.rack.una_sent_at {
+                let rtt = Duration::from_millis(self.rtte.rtt as u64);
+                // Allow for a reordering window of a quarter of the round-trip time.
+                self.rack.lost_at = Some(una_sent_at + rtt + rtt / 4);
+            }
+        }
+    }
+
+    /// Return the time after which the last segment sent is probed, see
+    /// RFC 8985 § 7.2.
+    fn probe_timeout(&self) -> Duration {
+        let mut timeout = Duration::from_millis(self.rtte.rtt as u64) * 2;
+        if self.remote_last_seq - self.local_seq_no <= self.remote_mss {
+            // The ACK of a single segment in flight may be delayed.
+            timeout += TLP_MAX_ACK_DELAY;
+        }
+        timeout.min(self.rtte.retransmission_timeout())
+    }
+
+    fn pacing_delayed(&self, timestamp: Instant) -> bool {
+        match self.pacing_next_at {
+            Some(pacing_next_at) => timestamp < pacing_next_at,
+            None => false,
+        }
+    }
+
+    /// Return the time it takes to send a segment of `len` octets at the
+    /// pacing rate, i.e. `rate_percent` of the send window per round trip.
+    fn pacing_interval(&self, pacing: PacingConfig, len: usize) -> Duration {
+        let window = self.send_window().max(1) as u64;
+        let rtt_us = self.rtte.rtt as u64 * 1000;
+        Duration::from_micros(len as u64 * rtt_us * 100 / (window * pacing.rate_percent as u64))
+    }
+
+    /// Return the amount of octets we may have in flight, limited by both the
+    /// remote window and the congestion window.
+    fn send_window(&self) -> usize {
+        cmp::min(self.remote_win_len, self.congestion.window())
+    }
+
+    fn slow_start_restart_due(&self, timestamp: Instant) -> bool {
+        if !self.slow_start_restart || self.remote_last_seq != self.local_seq_no {
+            return false;
+        }
+        let idle = self
+            .slow_start_restart_idle
+            .unwrap_or_else(|| self.rtte.retransmission_timeout());
+        match self.local_last_ts {
+            Some(local_last_ts) => timestamp >= local_last_ts + idle,
+            None => false,
+        }
+    }
+
     fn delayed_ack_expired(&self, timestamp: Instant) -> bool {
         match self.ack_delay_timer {
             AckDelayTimer::Idle => true,
@@ -2126,6 +4036,14 @@ impl<'a> Socket<'a> {
             return Ok(());
         }
 
+        if let Some(retry_at) = self.connect_retry_at {
+            if cx.now() < retry_at {
+                return Ok(());
+            }
+            net_debug!("retrying connection");
+            self.retry_connect(cx);
+        }
+
         if self.remote_last_ts.is_none() {
             // We get here in exactly two cases:
             //  1) This socket just transitioned into SYN-SENT.
@@ -2137,8 +4055,27 @@ impl<'a> Socket<'a> {
             self.remote_last_ts = Some(cx.now());
         }
 
+        // If we've been idle for long enough, the congestion window no longer
+        // reflects the state of the path.
+        if self.slow_start_restart_due(cx.now()) {
+            self.congestion.restart(self.remote_mss);
+        }
+
+        // With a retry policy, a connection attempt also fails once the SYN
+        // has been retransmitted a few times without an answer.
+        let syn_unanswered = self.state == State::SynSent
+            && self.connect_retry.is_some()
+            && self.syn_retransmits >= CONNECT_SYN_RETRANSMITS
+            && self.timer.should_retransmit(cx.now()).is_some();
+
         // Check if any state needs to be changed because of a timer.
-        if self.timed_out(cx.now()) {
+        if (self.timed_out(cx.now()) || syn_unanswered)
+            && self.state == State::SynSent
+            && self.schedule_connect_retry(cx)
+        {
+            // The connection attempt failed, and another one will be made later.
+            return Ok(());
+        } else if self.timed_out(cx.now()) || syn_unanswered {
             // If a timeout expires, we should abort the connection.
             net_debug!("timeout exceeded");
             self.set_state(State::Closed);
@@ -2148,6 +4085,16 @@ impl<'a> Socket<'a> {
                 // ACK.
                 net_debug!("retransmitting at t+{}", retransmit_delta);
 
+                // Reduce the congestion window according to the amount of data
+                // that was in flight.
+                if self.timer == Timer::FastRetransmit {
+                    self.congestion
+                        .on_fast_retransmit(self.bytes_in_flight(), self.remote_mss);
+                } else {
+                    self.congestion
+                        .on_retransmit_timeout(self.bytes_in_flight(), self.remote_mss);
+                }
+
                 // Rewind "last sequence number sent", as if we never
                 // had sent them. This will cause all data in the queue
                 // to be sent again.
@@ -2161,6 +4108,31 @@ impl<'a> Socket<'a> {
 
                 // Inform RTTE, so that it can avoid bogus measurements.
                 self.rtte.on_retransmit();
+                self.retransmits = self.retransmits.saturating_add(1);
+                if self.state == State::SynSent {
+                    self.syn_retransmits = self.syn_retransmits.saturating_add(1);
+                }
+
+                // The retransmission supersedes any RACK-TLP recovery.
+                self.rack.lost_at = None;
+                self.rack.probe_at = None;
+            } else if matches!(self.rack.lost_at, Some(lost_at) if cx.now() >= lost_at) {
+                // A segment sent later was delivered, and the reordering window has
+                // passed, so the oldest unacknowledged segment is deemed lost.
+                net_debug!("RACK: retransmitting lost segment");
+                self.congestion
+                    .on_fast_retransmit(self.bytes_in_flight(), self.remote_mss);
+                self.remote_last_seq = self.local_seq_no;
+                self.rack.lost_at = None;
+                self.rtte.on_retransmit();
+                self.retransmits = self.retransmits.saturating_add(1);
+            } else if matches!(self.rack.probe_at, Some(probe_at) if cx.now() >= probe_at) {
+                // The last segment sent wasn't acknowledged in time; send it again, so
+                // that its ACK tells whether it, or the segments before, were lost.
+                net_debug!("sending tail loss probe");
+                let in_flight = self.remote_last_seq - self.local_seq_no;
+                self.remote_last_seq = self.remote_last_seq - in_flight.min(self.remote_mss);
+                self.rack.probe_at = None;
             }
         }
 
@@ -2174,6 +4146,9 @@ impl<'a> Socket<'a> {
         } else if self.window_to_update() && self.delayed_ack_expired(cx.now()) {
             // If we have window length increase to advertise, do it.
             tcp_trace!("outgoing segment will update window");
+        } else if self.poke {
+            // If we were asked to poke the remote endpoint, do it.
+            tcp_trace!("outgoing segment will poke");
         } else if self.state == State::Closed {
             // If we need to abort the connection, do it.
             tcp_trace!("outgoing segment will abort connection");
@@ -2215,6 +4190,8 @@ impl<'a> Socket<'a> {
             max_seg_size: None,
             sack_permitted: false,
             sack_ranges: [None, None, None],
+            authentication: None,
+            accecn: None,
             payload: &[],
         };
 
@@ -2233,7 +4210,7 @@ impl<'a> Socket<'a> {
             State::SynSent | State::SynReceived => {
                 repr.control = TcpControl::Syn;
                 // window len must NOT be scaled in SYNs.
-                repr.window_len = self.rx_buffer.window().min((1 << 16) - 1) as u16;
+                repr.window_len = self.rx_window().min((1 << 16) - 1) as u16;
                 if self.state == State::SynSent {
                     repr.ack_number = None;
                     repr.window_scale = Some(self.remote_win_shift);
@@ -2255,7 +4232,7 @@ impl<'a> Socket<'a> {
                 // from the transmit buffer.
 
                 // Right edge of window, ie the max sequence number we're allowed to send.
-                let win_right_edge = self.local_seq_no + self.remote_win_len;
+                let win_right_edge = self.local_seq_no + self.send_window();
 
                 // Max amount of octets we're allowed to send according to the remote window.
                 let win_limit = if win_right_edge >= self.remote_last_seq {
@@ -2272,10 +4249,10 @@ impl<'a> Socket<'a> {
                 // Maximum size we're allowed to send. This can be limited by 3 factors:
                 // 1. remote window
                 // 2. MSS the remote is willing to accept, probably determined by their MTU
-                // 3. MSS we can send, determined by our MTU.
+                // 3. MSS we can send, determined by our MTU and clamp.
                 let size = win_limit
                     .min(self.remote_mss)
-                    .min(cx.ip_mtu() - ip_repr.header_len() - TCP_HEADER_LEN);
+                    .min(self.max_local_mss(cx, ip_repr.header_len()));
 
                 let offset = self.remote_last_seq - self.local_seq_no;
                 repr.payload = self.tx_buffer.get_allocated(offset, size);
@@ -2292,6 +4269,12 @@ impl<'a> Socket<'a> {
                         }
                         _ => (),
                     }
+                } else if self.flush_seq == Some(self.remote_last_seq + repr.payload.len())
+                    && matches!(self.state, State::Established | State::CloseWait)
+                    && !repr.payload.is_empty()
+                {
+                    // The segment ends with the last octet flushed.
+                    repr.control = TcpControl::Psh
                 }
             }
 
@@ -2304,8 +4287,11 @@ impl<'a> Socket<'a> {
         // any packet that occupies sequence space will elicit an ACK, we only
         // need to send an explicit packet if we couldn't fill the sequence
         // space with anything.
+        if self.poke && repr.is_empty() && self.may_send() {
+            repr.control = TcpControl::Psh;
+        }
         let is_keep_alive;
-        if self.timer.should_keep_alive(cx.now()) && repr.is_empty() {
+        if self.timer.should_keep_alive(cx.now()) && repr.is_empty() && !self.poke {
             repr.seq_number = repr.seq_number - 1;
             repr.payload = b"\x00"; // RFC 1122 says we should do this
             is_keep_alive = true;
@@ -2338,7 +4324,8 @@ impl<'a> Socket<'a> {
 
         if repr.control == TcpControl::Syn {
             // Fill the MSS option. See RFC 6691 for an explanation of this calculation.
-            let max_segment_size = cx.ip_mtu() - ip_repr.header_len() - TCP_HEADER_LEN;
+            let max_segment_size = self.max_local_mss(cx, ip_repr.header_len());
+            self.local_mss = max_segment_size;
             repr.max_seg_size = Some(max_segment_size as u16);
         }
 
@@ -2350,6 +4337,15 @@ impl<'a> Socket<'a> {
         // to not waste time waiting for the retransmit timer on packets that we know
         // for sure will not be successfully transmitted.
         ip_repr.set_payload_len(repr.buffer_len());
+        if let Some(ao) = &mut self.ao {
+            ao.sign(
+                self.isns,
+                self.local_seq_no,
+                self.remote_last_seq,
+                &mut ip_repr,
+                &mut repr,
+            );
+        }
         emit(cx, (ip_repr, repr))?;
 
         // We've sent something, whether useful data or a keep-alive packet, so rewind
@@ -2367,6 +4363,7 @@ impl<'a> Socket<'a> {
             }
         }
         self.ack_delay_timer = AckDelayTimer::Idle;
+        self.poke = false;
 
         // Leave the rest of the state intact if sending a keep-alive packet, since
         // those carry a fake segment.
@@ -2376,12 +4373,35 @@ impl<'a> Socket<'a> {
 
         // We've sent a packet successfully, so we can update the internal state now.
         self.remote_last_seq = repr.seq_number + repr.segment_len();
+        if matches!(self.flush_seq, Some(flush_seq) if self.remote_last_seq >= flush_seq) {
+            self.flush_seq = None;
+        }
         self.remote_last_ack = repr.ack_number;
         self.remote_last_win = repr.window_len;
+        if repr.window_len == 0 && repr.control != TcpControl::Syn {
+            self.rx_window_zeroed = true;
+        }
 
+        // Whether the segment carries data or flags never sent before.
+        let sent_new = repr.segment_len() > 0
+            && self
+                .rtte
+                .max_seq_sent
+                .map_or(true, |max_seq_sent| self.remote_last_seq > max_seq_sent);
         if repr.segment_len() > 0 {
             self.rtte
                 .on_send(cx.now(), repr.seq_number + repr.segment_len());
+            self.local_last_ts = Some(cx.now());
+            if self.rack.una_sent_at.is_none() || repr.seq_number == self.local_seq_no {
+                self.rack.una_sent_at = Some(cx.now());
+            }
+        }
+        if !repr.payload.is_empty() {
+            self.last_activity = Some(cx.now());
+            if let Some(pacing) = self.pacing {
+                self.pacing_next_at =
+                    Some(cx.now() + self.pacing_interval(pacing, repr.payload.len()));
+            }
         }
 
         if !self.seq_to_transmit(cx) && repr.segment_len() > 0 {
@@ -2389,6 +4409,14 @@ impl<'a> Socket<'a> {
             // data or flag, to transmit, not just an ACK), wind up the retransmit timer.
             self.timer
                 .set_for_retransmit(cx.now(), self.rtte.retransmission_timeout());
+
+            // The last segment of a burst is probed if it isn't acknowledged in time.
+            if self.loss_detection == LossDetection::RackTlp
+                && sent_new
+                && repr.control != TcpControl::Syn
+            {
+                self.rack.probe_at = Some(cx.now() + self.probe_timeout());
+            }
         }
 
         if self.state == State::Closed {
@@ -2411,14 +4439,17 @@ impl<'a> Socket<'a> {
         if self.tuple.is_none() {
             // No one to talk to, nothing to transmit.
             PollAt::Ingress
+        } else if let Some(retry_at) = self.connect_retry_at {
+            // A failed connection attempt will be retried.
+            PollAt::Time(retry_at)
         } else if self.remote_last_ts.is_none() {
             // Socket stopped being quiet recently, we need to acquire a timestamp.
             PollAt::Now
         } else if self.state == State::Closed {
             // Socket was aborted, we have an RST packet to transmit.
             PollAt::Now
-        } else if self.seq_to_transmit(cx) {
-            // We have a data or flag packet to transmit.
+        } else if self.seq_to_transmit(cx) || self.poke {
+            // We have a data or flag packet, or a poke, to transmit.
             PollAt::Now
         } else {
             let want_ack = self.ack_to_transmit() || self.window_to_update();
@@ -2438,11 +4469,37 @@ impl<'a> Socket<'a> {
                 (..) => PollAt::Ingress,
             };
 
+            // If pacing held back the data we have to send, we need to poll when
+            // the time slot of the next segment comes.
+            let pacing_poll_at = match self.pacing_next_at {
+                Some(pacing_next_at)
+                    if pacing_next_at > cx.now()
+                        && self.remote_last_seq < self.local_seq_no + self.tx_buffer.len() =>
+                {
+                    PollAt::Time(pacing_next_at)
+                }
+                _ => PollAt::Ingress,
+            };
+
+            // RACK-TLP may retransmit a segment, or probe the last one, before
+            // the retransmission timeout.
+            let rack_poll_at = match (self.rack.lost_at, self.rack.probe_at) {
+                (Some(lost_at), Some(probe_at)) => PollAt::Time(lost_at.min(probe_at)),
+                (Some(at), None) | (None, Some(at)) => PollAt::Time(at),
+                (None, None) => PollAt::Ingress,
+            };
+
             // We wait for the earliest of our timers to fire.
-            *[self.timer.poll_at(), timeout_poll_at, delayed_ack_poll_at]
-                .iter()
-                .min()
-                .unwrap_or(&PollAt::Ingress)
+            *[
+                self.timer.poll_at(),
+                timeout_poll_at,
+                delayed_ack_poll_at,
+                pacing_poll_at,
+                rack_poll_at,
+            ]
+            .iter()
+            .min()
+            .unwrap_or(&PollAt::Ingress)
         }
     }
 }
@@ -2542,6 +4599,8 @@ mod test {
         max_seg_size: None,
         sack_permitted: false,
         sack_ranges: [None, None, None],
+        authentication: None,
+        accecn: None,
         payload: &[],
     };
     const _RECV_IP_TEMPL: IpRepr = IpReprIpvX(IpvXRepr {
@@ -2562,6 +4621,8 @@ mod test {
         max_seg_size: None,
         sack_permitted: false,
         sack_ranges: [None, None, None],
+        authentication: None,
+        accecn: None,
         payload: &[],
     };
 
@@ -2698,6 +4759,7 @@ mod test {
             assert_eq!(s1.remote_last_ack, s2.remote_last_ack, "remote_last_ack");
             assert_eq!(s1.remote_last_win, s2.remote_last_win, "remote_last_win");
             assert_eq!(s1.remote_win_len, s2.remote_win_len, "remote_win_len");
+            assert_eq!(s1.isns, s2.isns, "isns");
             assert_eq!(s1.timer, s2.timer, "timer");
         }};
     }
@@ -2723,6 +4785,7 @@ mod test {
         s.remote_seq_no = REMOTE_SEQ + 1;
         s.remote_last_seq = LOCAL_SEQ;
         s.remote_win_len = 256;
+        s.isns = Some((LOCAL_SEQ, REMOTE_SEQ));
         s
     }
 
@@ -3348,6 +5411,85 @@ mod test {
         assert_eq!(s.tuple, Some(TUPLE));
     }
 
+    #[test]
+    fn test_connect_with_isn() {
+        let mut s = socket();
+        s.socket
+            .connect_with_isn(&mut s.cx, REMOTE_END, LOCAL_END, || 0x1234_5678)
+            .unwrap();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: TcpSeqNumber(0x1234_5678),
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+
+        // Without a closure, the sequence number comes from the PRNG, which
+        // is replaced by a constant in tests.
+        let mut s = socket();
+        s.socket.connect(&mut s.cx, REMOTE_END, LOCAL_END).unwrap();
+        assert_eq!(s.local_seq_no, LOCAL_SEQ);
+    }
+
+    #[test]
+    fn test_listen_with_isn() {
+        let mut s = socket();
+        let mut calls = 0;
+        s.listen_with_isn(LISTEN_END, || {
+            calls += 1;
+            42
+        })
+        .unwrap();
+        assert_eq!(calls, 1);
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: TcpSeqNumber(42),
+                ack_number: Some(REMOTE_SEQ + 1),
+                max_seg_size: Some(BASE_MSS),
+                ..RECV_TEMPL
+            }]
+        );
+
+        // The sequence number is not reused for the next connection.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Listen);
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.local_seq_no, LOCAL_SEQ);
+    }
+
     #[test]
     fn test_connect_unspecified_local() {
         let mut s = socket();
@@ -3421,85 +5563,315 @@ mod test {
     }
 
     #[test]
-    fn test_syn_sent_syn_ack_not_incremented() {
-        let mut s = socket_syn_sent();
-        recv!(
-            s,
-            [TcpRepr {
-                control: TcpControl::Syn,
-                seq_number: LOCAL_SEQ,
-                ack_number: None,
-                max_seg_size: Some(BASE_MSS),
-                window_scale: Some(0),
-                sack_permitted: true,
-                ..RECV_TEMPL
-            }]
-        );
+    fn test_seq_number_wraparound() {
+        // Start both sequence spaces a few segments before they wrap around.
+        let local_isn = TcpSeqNumber(-200);
+        let remote_isn = TcpSeqNumber(-100);
+
+        let mut s = socket();
+        s.listen_with_isn(LISTEN_END, || local_isn.0 as u32)
+            .unwrap();
         send!(
             s,
             TcpRepr {
                 control: TcpControl::Syn,
-                seq_number: REMOTE_SEQ,
-                ack_number: Some(LOCAL_SEQ), // WRONG
-                max_seg_size: Some(BASE_MSS - 80),
-                window_scale: Some(0),
-                ..SEND_TEMPL
-            },
-            Some(TcpRepr {
-                control: TcpControl::Rst,
-                seq_number: LOCAL_SEQ,
+                seq_number: remote_isn,
                 ack_number: None,
-                window_len: 0,
-                ..RECV_TEMPL
-            })
-        );
-        assert_eq!(s.state, State::SynSent);
-    }
-
-    #[test]
-    fn test_syn_sent_rst() {
-        let mut s = socket_syn_sent();
-        send!(
-            s,
-            TcpRepr {
-                control: TcpControl::Rst,
-                seq_number: REMOTE_SEQ,
-                ack_number: Some(LOCAL_SEQ + 1),
+                sack_permitted: true,
                 ..SEND_TEMPL
             }
         );
-        assert_eq!(s.state, State::Closed);
-    }
-
-    #[test]
-    fn test_syn_sent_rst_no_ack() {
-        let mut s = socket_syn_sent();
-        send!(
+        recv!(
             s,
-            TcpRepr {
-                control: TcpControl::Rst,
-                seq_number: REMOTE_SEQ,
-                ack_number: None,
-                ..SEND_TEMPL
-            }
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: local_isn,
+                ack_number: Some(remote_isn + 1),
+                max_seg_size: Some(BASE_MSS),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
         );
-        assert_eq!(s.state, State::SynSent);
-    }
-
-    #[test]
-    fn test_syn_sent_rst_bad_ack() {
-        let mut s = socket_syn_sent();
         send!(
             s,
             TcpRepr {
-                control: TcpControl::Rst,
-                seq_number: REMOTE_SEQ,
-                ack_number: Some(TcpSeqNumber(1234)),
+                seq_number: remote_isn + 1,
+                ack_number: Some(local_isn + 1),
                 ..SEND_TEMPL
             }
         );
-        assert_eq!(s.state, State::SynSent);
-    }
+        assert_eq!(s.state, State::Established);
+
+        let mut local_seq = local_isn + 1;
+        let mut remote_seq = remote_isn + 1;
+        let mut buf = [0; 64];
+        for round in 0..8u8 {
+            let data = [round; 64];
+
+            s.send_slice(&data).unwrap();
+            recv!(
+                s,
+                [TcpRepr {
+                    seq_number: local_seq,
+                    ack_number: Some(remote_seq),
+                    payload: &data,
+                    ..RECV_TEMPL
+                }]
+            );
+            local_seq += data.len();
+
+            // The second half of the remote data arrives first, and is
+            // selectively acknowledged even when it straddles the wraparound.
+            send!(
+                s,
+                TcpRepr {
+                    seq_number: remote_seq + 32,
+                    ack_number: Some(local_seq),
+                    payload: &data[32..],
+                    ..SEND_TEMPL
+                },
+                Some(TcpRepr {
+                    seq_number: local_seq,
+                    ack_number: Some(remote_seq),
+                    sack_ranges: [
+                        Some(((remote_seq + 32).0 as u32, (remote_seq + 64).0 as u32)),
+                        None,
+                        None
+                    ],
+                    ..RECV_TEMPL
+                })
+            );
+            // Filling the hole is acknowledged right away.
+            send!(
+                s,
+                TcpRepr {
+                    seq_number: remote_seq,
+                    ack_number: Some(local_seq),
+                    payload: &data[..32],
+                    ..SEND_TEMPL
+                },
+                Some(TcpRepr {
+                    seq_number: local_seq,
+                    ack_number: Some(remote_seq + 64),
+                    window_len: 0,
+                    ..RECV_TEMPL
+                })
+            );
+            remote_seq += data.len();
+
+            assert_eq!(s.recv_slice(&mut buf), Ok(64));
+            assert_eq!(buf, data);
+            assert!(s.tx_buffer.is_empty());
+        }
+
+        // Both sides went past the wraparound.
+        assert!(local_seq > local_isn && local_seq.0 > 0);
+        assert!(remote_seq > remote_isn && remote_seq.0 > 0);
+        assert_eq!(s.state, State::Established);
+    }
+
+    #[test]
+    fn test_effective_mss() {
+        let mut s = socket();
+        s.set_mss_clamp(Some(1200));
+        s.socket
+            .connect(&mut s.cx, REMOTE_END, LOCAL_END.port)
+            .unwrap();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                max_seg_size: Some(1200),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+        assert_eq!(s.effective_mss(), None);
+
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(LOCAL_SEQ + 1),
+                max_seg_size: Some(1300),
+                window_scale: Some(0),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Established);
+        assert_eq!(s.effective_mss(), Some(1200));
+
+        // Without a clamp, the MTU and the remote side set the limit.
+        let mut s = socket_syn_sent();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(LOCAL_SEQ + 1),
+                max_seg_size: Some(BASE_MSS + 100),
+                window_scale: Some(0),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.effective_mss(), Some(BASE_MSS as usize));
+        s.abort();
+        assert_eq!(s.effective_mss(), None);
+    }
+
+    #[test]
+    fn test_syn_sent_simultaneous_open() {
+        let mut s = socket_syn_sent();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+        // The SYN of the remote end crosses ours.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS - 80),
+                window_scale: Some(0),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::SynReceived);
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: Some(REMOTE_SEQ + 1),
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                ..RECV_TEMPL
+            }]
+        );
+        // So does its SYN|ACK.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(LOCAL_SEQ + 1),
+                max_seg_size: Some(BASE_MSS - 80),
+                window_scale: Some(0),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Established);
+        assert_eq!(s.remote_mss, BASE_MSS as usize - 80);
+        recv_nothing!(s, time 1000);
+        sanity!(s, socket_established());
+    }
+
+    #[test]
+    fn test_syn_sent_syn_ack_not_incremented() {
+        let mut s = socket_syn_sent();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(LOCAL_SEQ), // WRONG
+                max_seg_size: Some(BASE_MSS - 80),
+                window_scale: Some(0),
+                ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                window_len: 0,
+                ..RECV_TEMPL
+            })
+        );
+        assert_eq!(s.state, State::SynSent);
+    }
+
+    #[test]
+    fn test_syn_sent_rst() {
+        let mut s = socket_syn_sent();
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Closed);
+    }
+
+    #[test]
+    fn test_syn_sent_rst_no_ack() {
+        let mut s = socket_syn_sent();
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::SynSent);
+    }
+
+    #[test]
+    fn test_syn_sent_rst_bad_ack() {
+        let mut s = socket_syn_sent();
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ,
+                ack_number: Some(TcpSeqNumber(1234)),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::SynSent);
+    }
 
     #[test]
     fn test_syn_sent_bad_ack() {
@@ -3930,6 +6302,40 @@ mod test {
         assert_eq!(s.tx_buffer.len(), 0);
     }
 
+    #[test]
+    fn test_established_send_vectored() {
+        let mut s = socket_established();
+        assert_eq!(s.send_vectored(&[b"abc", b"", b"def", b"gh"]), Ok(8));
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abcdefgh"[..],
+                ..RECV_TEMPL
+            }]
+        );
+    }
+
+    #[test]
+    fn test_established_send_vectored_partial() {
+        let mut s = socket_established_with_buffer_sizes(8, 64);
+        s.send_slice(b"abc").unwrap();
+        // The buffer fills up in the middle of the second slice.
+        assert_eq!(s.send_vectored(&[b"de", b"fghij", b"klm"]), Ok(5));
+        assert_eq!(s.tx_buffer.len(), 8);
+        assert_eq!(s.send_vectored(&[b"xyz"]), Ok(0));
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abcdefgh"[..],
+                ..RECV_TEMPL
+            }]
+        );
+    }
+
     #[test]
     fn test_established_send_no_ack_send() {
         let mut s = socket_established();
@@ -4266,12 +6672,79 @@ mod test {
     }
 
     #[test]
-    fn test_established_rst() {
+    fn test_queue_len_excludes_fin() {
         let mut s = socket_established();
-        send!(
+        s.send_slice(b"abcdef").unwrap();
+        s.close();
+        assert_eq!(s.send_queue(), 6);
+        recv!(
             s,
-            TcpRepr {
-                control: TcpControl::Rst,
+            [TcpRepr {
+                control: TcpControl::Fin,
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abcdef"[..],
+                ..RECV_TEMPL
+            }]
+        );
+        assert_eq!(s.send_queue(), 6);
+
+        // Part of the data is acknowledged.
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 3),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.send_queue(), 3);
+
+        // All of the data, but not the FIN.
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 6),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::FinWait1);
+        assert_eq!(s.send_queue(), 0);
+
+        // The remote closes with data, and acknowledges the FIN.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Fin,
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 6 + 1),
+                payload: &b"xyz"[..],
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::TimeWait);
+        assert_eq!(s.send_queue(), 0);
+        assert_eq!(s.recv_queue(), 3);
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 6 + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 3 + 1),
+                window_len: 61,
+                ..RECV_TEMPL
+            }]
+        );
+        assert_eq!(s.recv_queue(), 3);
+    }
+
+    #[test]
+    fn test_established_rst() {
+        let mut s = socket_established();
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
                 seq_number: REMOTE_SEQ + 1,
                 ack_number: Some(LOCAL_SEQ + 1),
                 ..SEND_TEMPL
@@ -4371,6 +6844,86 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_established_rst_in_window() {
+        let mut s = socket_established();
+        // An RST inside the receive window, but not at the expected sequence
+        // number, may be spoofed, and is answered with a challenge ACK.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1 + 10,
+                ack_number: None,
+                ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                ..RECV_TEMPL
+            })
+        );
+        assert_eq!(s.state, State::Established);
+
+        // The remote endpoint answers it with an exact RST.
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Closed);
+    }
+
+    #[test]
+    fn test_established_syn_challenge_ack() {
+        let mut s = socket_established();
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: None,
+                ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                ..RECV_TEMPL
+            })
+        );
+        assert_eq!(s.state, State::Established);
+    }
+
+    #[test]
+    fn test_challenge_ack_limit() {
+        let mut s = socket_established();
+        assert_eq!(s.challenge_ack_limit(), 1);
+        s.set_challenge_ack_limit(4);
+
+        let rst = TcpRepr {
+            control: TcpControl::Rst,
+            seq_number: REMOTE_SEQ + 1 + 10,
+            ack_number: None,
+            ..SEND_TEMPL
+        };
+        let challenge_ack = TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            ..RECV_TEMPL
+        };
+        send!(s, time 0, rst, Some(challenge_ack));
+        send!(s, time 100, rst, None);
+        send!(s, time 250, rst, Some(challenge_ack));
+
+        s.set_challenge_ack_limit(0);
+        send!(s, time 1000, rst, None);
+        assert_eq!(s.state, State::Established);
+    }
+
     // =========================================================================================//
     // Tests for the FIN-WAIT-1 state.
     // =========================================================================================//
@@ -4635,6 +7188,157 @@ mod test {
         assert_eq!(s.state, State::Closed);
     }
 
+    #[test]
+    fn test_time_wait_syn_reopen() {
+        let mut s = socket_time_wait(false);
+        s.listen_endpoint = LISTEN_END;
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 1),
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ + 100,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::SynReceived);
+        assert_eq!(s.tuple, Some(TUPLE));
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: Some(REMOTE_SEQ + 100 + 1),
+                max_seg_size: Some(BASE_MSS),
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 100 + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Established);
+    }
+
+    #[test]
+    fn test_time_wait_old_syn() {
+        let mut s = socket_time_wait(false);
+        send!(s, time 5_000, TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: REMOTE_SEQ,
+            ack_number: None,
+            ..SEND_TEMPL
+        }, Some(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 1,
+            ack_number: Some(REMOTE_SEQ + 1 + 1),
+            ..RECV_TEMPL
+        }));
+        assert_eq!(s.state, State::TimeWait);
+        assert_eq!(
+            s.timer,
+            Timer::Close {
+                expires_at: Instant::from_secs(5) + CLOSE_DELAY
+            }
+        );
+    }
+
+    #[test]
+    fn test_time_wait_stray_segment() {
+        let mut s = socket_time_wait(false);
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1 - 6,
+                ack_number: Some(LOCAL_SEQ + 1 + 1),
+                payload: &b"abcdef"[..],
+                ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 1),
+                ..RECV_TEMPL
+            })
+        );
+        assert_eq!(s.state, State::TimeWait);
+        assert!(s.rx_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_time_wait_rst() {
+        let mut s = socket_time_wait(false);
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1 + 1,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Closed);
+    }
+
+    #[test]
+    fn test_time_wait_rst_ignored() {
+        let mut s = socket_time_wait(false);
+        s.set_time_wait_rst_ignored(true);
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1 + 1,
+                ack_number: None,
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::TimeWait);
+    }
+
+    fn socket_reused(zero_on_reuse: bool) -> TestSocket {
+        let mut s = socket_established();
+        s.set_zero_on_reuse(zero_on_reuse);
+        s.send_slice(b"secret").unwrap();
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                payload: &b"hunter"[..],
+                ..SEND_TEMPL
+            }
+        );
+        s.abort();
+        s.listen(LOCAL_END).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_zero_on_reuse() {
+        let mut s = socket_reused(true);
+        assert_eq!(s.tx_buffer.get_unallocated(0, 64), &[0; 64][..]);
+        assert_eq!(s.rx_buffer.get_unallocated(0, 64), &[0; 64][..]);
+    }
+
+    #[test]
+    fn test_no_zero_on_reuse() {
+        let mut s = socket_reused(false);
+        assert_eq!(s.tx_buffer.get_unallocated(0, 6), b"secret");
+        assert_eq!(s.rx_buffer.get_unallocated(0, 6), b"hunter");
+    }
+
     // =========================================================================================//
     // Tests for the CLOSE-WAIT state.
     // =========================================================================================//
@@ -5178,6 +7882,7 @@ mod test {
     fn test_data_retransmit_bursts() {
         let mut s = socket_established();
         s.remote_mss = 6;
+        s.set_congestion_control(false);
         s.send_slice(b"abcdef012345").unwrap();
 
         recv!(s, time 0, Ok(TcpRepr {
@@ -5216,20 +7921,115 @@ mod test {
     }
 
     #[test]
-    fn test_data_retransmit_bursts_half_ack() {
+    fn test_pacing() {
         let mut s = socket_established();
         s.remote_mss = 6;
-        s.send_slice(b"abcdef012345").unwrap();
+        s.remote_win_len = 24;
+        s.rtte.rtt = 100;
+        s.set_pacing(Some(PacingConfig { rate_percent: 100 }));
+        s.send_slice(b"abcdef012345ghijkl").unwrap();
 
+        // A quarter of the window per round trip, so one segment every 25 ms.
         recv!(s, time 0, Ok(TcpRepr {
-            control:    TcpControl::None,
             seq_number: LOCAL_SEQ + 1,
             ack_number: Some(REMOTE_SEQ + 1),
             payload:    &b"abcdef"[..],
             ..RECV_TEMPL
-        }), exact);
-        recv!(s, time 0, Ok(TcpRepr {
-            control:    TcpControl::Psh,
+        }));
+        recv_nothing!(s, time 0);
+        s.cx.set_now(Instant::from_millis(0));
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(25))
+        );
+        recv_nothing!(s, time 24);
+        recv!(s, time 25, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"012345"[..],
+            ..RECV_TEMPL
+        }));
+        recv_nothing!(s, time 30);
+        recv!(s, time 50, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 12,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"ghijkl"[..],
+            ..RECV_TEMPL
+        }));
+
+        // Nothing is left to send, only the retransmission timer armed by the
+        // first segment is left to poll at.
+        s.cx.set_now(Instant::from_millis(50));
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(0) + s.rtte.retransmission_timeout())
+        );
+    }
+
+    #[test]
+    fn test_delivery_rate() {
+        // A path with a bottleneck of 100 octets per millisecond, and a
+        // round-trip time of 20 ms when its queue is empty.
+        const RATE: i64 = 100;
+        const RTT: i64 = 20;
+
+        let mut s = socket_established_with_buffer_sizes(4096, 64);
+        s.remote_mss = 500;
+        s.remote_win_len = 8192;
+        assert_eq!(s.delivery_rate(), None);
+
+        let mut link_free_at = 0;
+        let mut acks = std::collections::VecDeque::new();
+        for now in 0..2000 {
+            while let Some(&(at, ack_number)) = acks.front() {
+                if at > now {
+                    break;
+                }
+                acks.pop_front();
+                send!(s, time now, TcpRepr {
+                    seq_number: REMOTE_SEQ + 1,
+                    ack_number: Some(ack_number),
+                    window_len: 8192,
+                    ..SEND_TEMPL
+                });
+            }
+
+            s.send_slice(&[0; 4096]).unwrap();
+            s.cx.set_now(Instant::from_millis(now));
+            loop {
+                let mut sent = None;
+                let _ = s.socket.dispatch(&mut s.cx, |_, (_, repr)| {
+                    sent = Some((repr.seq_number + repr.payload.len(), repr.payload.len()));
+                    Ok::<(), ()>(())
+                });
+                let Some((end, len)) = sent else { break };
+                link_free_at = link_free_at.max(now) + len as i64 / RATE;
+                acks.push_back((link_free_at + RTT, end));
+            }
+        }
+
+        let rate = s.delivery_rate().unwrap();
+        assert!(
+            (95_000..=105_000).contains(&rate),
+            "delivery rate {rate} is not 100000 octets/s"
+        );
+    }
+
+    #[test]
+    fn test_data_retransmit_bursts_half_ack() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        s.send_slice(b"abcdef012345").unwrap();
+
+        recv!(s, time 0, Ok(TcpRepr {
+            control:    TcpControl::None,
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..RECV_TEMPL
+        }), exact);
+        recv!(s, time 0, Ok(TcpRepr {
+            control:    TcpControl::Psh,
             seq_number: LOCAL_SEQ + 1 + 6,
             ack_number: Some(REMOTE_SEQ + 1),
             payload:    &b"012345"[..],
@@ -5627,6 +8427,136 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_fast_recovery_inflates_window_on_dup_ack() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+
+        send!(s, time 0, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        });
+
+        let data = b"xxxxxxyyyyyywwwwwwzzzzzz";
+        s.send_slice(data).unwrap();
+        for (i, chunk) in data.chunks(6).enumerate() {
+            recv!(s, time 1000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    chunk,
+                ..RECV_TEMPL
+            }));
+        }
+
+        // The third duplicate ACK triggers the fast retransmit.
+        for _ in 0..3 {
+            send!(s, time 1050, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            });
+        }
+        recv!(s, time 1100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+        // Half of the flight, inflated by the three duplicate ACKs.
+        assert_eq!(s.congestion.ssthresh, 12);
+        assert_eq!(s.congestion.window(), 12 + 18);
+
+        // Each further duplicate ACK inflates the window by one segment.
+        for _ in 0..2 {
+            send!(s, time 1150, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            });
+        }
+        assert_eq!(s.congestion.window(), 12 + 18 + 12);
+
+        // New data being acknowledged deflates it back to the threshold.
+        send!(s, time 1200, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + (6 * 4)),
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.congestion.window(), 12);
+    }
+
+    #[test]
+    fn test_fast_retransmit_dup_ack_threshold() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        s.set_dup_ack_threshold(5);
+
+        s.send_slice(b"xxxxxxyyyyyywwwwwwzzzzzz").unwrap();
+        // This packet is lost
+        recv!(s, time 1000, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 1005, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"yyyyyy"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 1010, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + (6 * 2),
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"wwwwww"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 1015, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + (6 * 3),
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"zzzzzz"[..],
+            ..RECV_TEMPL
+        }));
+
+        // The later segments were reordered ahead of the lost one, and each
+        // got a duplicate ACK. Below the threshold, nothing is retransmitted.
+        send!(s, time 1050, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        });
+        for i in 0..4 {
+            send!(s, time 1050 + i, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            });
+        }
+        assert_eq!(s.local_rx_dup_acks, 4);
+        recv_nothing!(s, time 1060);
+
+        // The fifth duplicate ACK triggers a fast retransmit.
+        send!(s, time 1070, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        });
+        recv!(s, time 1100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "the duplicate ACK threshold must be at least 1")]
+    fn test_dup_ack_threshold_zero() {
+        let mut s = socket_established();
+        s.set_dup_ack_threshold(0);
+    }
+
     #[test]
     fn test_fast_retransmit_duplicate_detection_with_data() {
         let mut s = socket_established();
@@ -5889,6 +8819,128 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_rack_tlp_tail_loss_probe() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        s.rtte.rtt = 10;
+        s.set_loss_detection(LossDetection::RackTlp);
+
+        s.send_slice(b"xxxxxxyyyyyyzzzzzz").unwrap();
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"yyyyyy"[..],
+            ..RECV_TEMPL
+        }));
+        // This packet is lost
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6 * 2,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"zzzzzz"[..],
+            ..RECV_TEMPL
+        }));
+
+        send!(s, time 10, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 6),
+            ..SEND_TEMPL
+        });
+        send!(s, time 10, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 6 * 2),
+            ..SEND_TEMPL
+        });
+
+        // The probe is sent two round-trip times and the worst case ACK delay
+        // after the last ACK, well before the retransmission timeout.
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(230))
+        );
+        assert!(match s.timer {
+            Timer::Retransmit { expires_at, .. } => expires_at > Instant::from_millis(230),
+            _ => false,
+        });
+        recv_nothing!(s, time 229);
+        recv!(s, time 230, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6 * 2,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"zzzzzz"[..],
+            ..RECV_TEMPL
+        }));
+        recv_nothing!(s, time 230);
+
+        send!(s, time 240, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 6 * 3),
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.socket.poll_at(&mut s.cx), PollAt::Ingress);
+        recv_nothing!(s, time 1000);
+    }
+
+    #[test]
+    fn test_rack_tlp_reordering() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        s.remote_has_sack = true;
+        s.rtte.rtt = 100;
+        s.set_loss_detection(LossDetection::RackTlp);
+
+        s.send_slice(b"xxxxxxyyyyyyzzzzzz").unwrap();
+        // This packet is lost
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"yyyyyy"[..],
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6 * 2,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"zzzzzz"[..],
+            ..RECV_TEMPL
+        }));
+
+        // Later segments were delivered. A single selective ACK is enough, but
+        // the first segment might only be reordered, so it is given a quarter
+        // of the round-trip time more.
+        send!(s, time 50, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            sack_ranges: [
+                Some(((LOCAL_SEQ + 1 + 6).0 as u32, (LOCAL_SEQ + 1 + 6 * 3).0 as u32)),
+                None,
+                None,
+            ],
+            ..SEND_TEMPL
+        });
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(125))
+        );
+        recv_nothing!(s, time 124);
+        recv!(s, time 125, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"xxxxxx"[..],
+            ..RECV_TEMPL
+        }));
+    }
+
     #[test]
     fn test_fast_retransmit_zero_window() {
         let mut s = socket_established();
@@ -6137,16 +9189,39 @@ mod test {
     }
 
     #[test]
-    fn test_zero_window_ack_on_window_growth() {
+    fn test_rx_window_zeroed_flag() {
         let mut s = socket_established();
         s.rx_buffer = SocketBuffer::new(vec![0; 6]);
         s.assembler = Assembler::new();
+        assert!(!s.was_rx_window_zeroed());
+
         send!(
             s,
             TcpRepr {
                 seq_number: REMOTE_SEQ + 1,
                 ack_number: Some(LOCAL_SEQ + 1),
-                payload: &b"abcdef"[..],
+                payload: &b"abc"[..],
+                ..SEND_TEMPL
+            }
+        );
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 3),
+                window_len: 3,
+                ..RECV_TEMPL
+            }]
+        );
+        assert!(!s.was_rx_window_zeroed());
+
+        // The application stops reading, and the buffer fills up.
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1 + 3,
+                ack_number: Some(LOCAL_SEQ + 1),
+                payload: &b"def"[..],
                 ..SEND_TEMPL
             }
         );
@@ -6159,9 +9234,273 @@ mod test {
                 ..RECV_TEMPL
             }]
         );
-        recv_nothing!(s, time 0);
-        s.recv(|buffer| {
-            assert_eq!(&buffer[..3], b"abc");
+        assert!(s.was_rx_window_zeroed());
+
+        // The flag is sticky, even once the application catches up.
+        s.recv(|buffer| (buffer.len(), ())).unwrap();
+        assert!(s.was_rx_window_zeroed());
+
+        s.reset_rx_window_zeroed();
+        assert!(!s.was_rx_window_zeroed());
+    }
+
+    #[test]
+    fn test_send_capacity_available() {
+        let s = socket_syn_sent();
+        assert_eq!(s.send_capacity_available(), 0);
+
+        let mut s = socket_established();
+        assert_eq!(s.send_capacity_available(), 64);
+
+        s.send_slice(&[0xaa; 40]).unwrap();
+        assert_eq!(s.send_capacity_available(), 24);
+        // The data stays in the buffer until it is acknowledged.
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &[0xaa; 40],
+                ..RECV_TEMPL
+            }]
+        );
+        assert_eq!(s.send_capacity_available(), 24);
+
+        // The buffer wraps around once part of the data is acknowledged.
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 30),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.send_capacity_available(), 54);
+        assert_eq!(s.send_slice(&[0xbb; 54]), Ok(54));
+        assert_eq!(s.send_capacity_available(), 0);
+        assert!(!s.can_send());
+
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 40),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.send_capacity_available(), 10);
+
+        s.close();
+        assert_eq!(s.send_capacity_available(), 0);
+    }
+
+    #[test]
+    fn test_last_activity() {
+        let mut s = socket_established();
+        assert_eq!(s.last_activity(), None);
+
+        send!(s, time 1000, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.last_activity(), Some(Instant::from_millis(1000)));
+
+        // A pure ACK is not activity.
+        recv!(s, time 1100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+        assert_eq!(s.last_activity(), Some(Instant::from_millis(1000)));
+
+        s.send_slice(b"xyz").unwrap();
+        recv!(s, time 2000, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            payload:    &b"xyz"[..],
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+        assert_eq!(s.last_activity(), Some(Instant::from_millis(2000)));
+        send!(s, time 2100, TcpRepr {
+            seq_number: REMOTE_SEQ + 1 + 6,
+            ack_number: Some(LOCAL_SEQ + 1 + 3),
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.last_activity(), Some(Instant::from_millis(2000)));
+
+        // Neither is a keep-alive probe.
+        s.set_keep_alive(Some(Duration::from_millis(1000)));
+        recv!(s, time 3000, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 2,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            payload:    &[0],
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+        assert_eq!(s.last_activity(), Some(Instant::from_millis(2000)));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        let stats = s.stats();
+        assert_eq!(stats.state, State::Established);
+        assert_eq!(stats.local_endpoint, Some(LOCAL_END));
+        assert_eq!(stats.remote_endpoint, Some(REMOTE_END));
+        assert_eq!(stats.mss, 6);
+        assert_eq!(stats.bytes_in_flight, 0);
+        assert_eq!(stats.retransmits, 0);
+        assert_eq!(stats.ssthresh, None);
+
+        send!(s, time 0, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..SEND_TEMPL
+        });
+        s.send_slice(b"abcdef012345").unwrap();
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            payload:    &b"abcdef"[..],
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            payload:    &b"012345"[..],
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+
+        let stats = s.stats();
+        assert_eq!(stats.bytes_in_flight, 12);
+        assert!(stats.bytes_in_flight <= stats.cwnd);
+        assert!(stats.bytes_in_flight <= stats.send_window);
+        assert_eq!(stats.send_queue, 12);
+        assert_eq!(stats.recv_queue, 6);
+        assert_eq!(stats.recv_window, 58);
+        assert!(stats.rtt <= stats.rto);
+
+        // The retransmission timeout expires.
+        recv!(s, time 1000, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1 + 6),
+            payload:    &b"abcdef"[..],
+            window_len: 58,
+            ..RECV_TEMPL
+        }));
+        let stats = s.stats();
+        assert_eq!(stats.retransmits, 1);
+        assert_eq!(stats.bytes_in_flight, 6);
+        assert_eq!(stats.ssthresh, Some(12));
+        assert_eq!(stats.cwnd, 6);
+
+        send!(s, time 1050, TcpRepr {
+            seq_number: REMOTE_SEQ + 1 + 6,
+            ack_number: Some(LOCAL_SEQ + 1 + 6),
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.recv_slice(&mut [0; 6]), Ok(6));
+        let stats = s.stats();
+        assert_eq!(stats.bytes_in_flight, 0);
+        assert_eq!(stats.send_queue, 6);
+        assert_eq!(stats.recv_queue, 0);
+        assert_eq!(stats.recv_window, 64);
+    }
+
+    #[test]
+    fn test_max_recv_buffer() {
+        let mut s = socket_established();
+        s.set_ack_delay(None);
+        s.set_max_recv_buffer(16);
+
+        let mut max_window = 0;
+        for i in 0..8 {
+            send!(
+                s,
+                TcpRepr {
+                    seq_number: REMOTE_SEQ + 1 + i * 8,
+                    ack_number: Some(LOCAL_SEQ + 1),
+                    payload: &[0x5a; 8],
+                    ..SEND_TEMPL
+                }
+            );
+            recv(&mut s, Instant::from_millis(0), |repr| {
+                let repr = repr.unwrap();
+                assert_eq!(repr.ack_number, Some(REMOTE_SEQ + 1 + (i + 1) * 8));
+                assert_eq!(repr.window_len, 8);
+            });
+
+            // The application keeps up, so the window opens up again, but only
+            // up to the ceiling.
+            assert_eq!(s.recv_slice(&mut [0; 8]), Ok(8));
+            recv(&mut s, Instant::from_millis(0), |repr| {
+                max_window = max_window.max(repr.unwrap().window_len);
+            });
+        }
+        assert_eq!(max_window, 16);
+        assert!(s.rx_buffer.capacity() > 16);
+    }
+
+    #[test]
+    fn test_max_recv_buffer_ignored_window() {
+        let mut s = socket_established();
+        s.set_ack_delay(None);
+        s.set_max_recv_buffer(16);
+
+        // The remote end sends past the advertised window; only the data up
+        // to the ceiling is accepted.
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                payload: &[0x5a; 24],
+                ..SEND_TEMPL
+            }
+        );
+        recv(&mut s, Instant::from_millis(0), |repr| {
+            let repr = repr.unwrap();
+            assert_eq!(repr.ack_number, Some(REMOTE_SEQ + 1 + 16));
+            assert_eq!(repr.window_len, 0);
+        });
+        assert_eq!(s.recv_queue(), 16);
+        assert!(s.rx_buffer.capacity() > 16);
+    }
+
+    #[test]
+    fn test_zero_window_ack_on_window_growth() {
+        let mut s = socket_established();
+        s.rx_buffer = SocketBuffer::new(vec![0; 6]);
+        s.assembler = Assembler::new();
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                payload: &b"abcdef"[..],
+                ..SEND_TEMPL
+            }
+        );
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 6),
+                window_len: 0,
+                ..RECV_TEMPL
+            }]
+        );
+        recv_nothing!(s, time 0);
+        s.recv(|buffer| {
+            assert_eq!(&buffer[..3], b"abc");
             (3, ())
         })
         .unwrap();
@@ -6332,69 +9671,233 @@ mod test {
     }
 
     #[test]
-    fn test_established_timeout() {
-        let mut s = socket_established();
-        s.set_timeout(Some(Duration::from_millis(1000)));
-        recv_nothing!(s, time 250);
-        assert_eq!(
-            s.socket.poll_at(&mut s.cx),
-            PollAt::Time(Instant::from_millis(1250))
-        );
-        s.send_slice(b"abcdef").unwrap();
-        assert_eq!(s.socket.poll_at(&mut s.cx), PollAt::Now);
-        recv!(s, time 255, Ok(TcpRepr {
-            seq_number: LOCAL_SEQ + 1,
-            ack_number: Some(REMOTE_SEQ + 1),
-            payload:    &b"abcdef"[..],
-            ..RECV_TEMPL
+    fn test_connect_retry_backoff() {
+        let mut s = socket();
+        s.local_seq_no = LOCAL_SEQ;
+        s.socket
+            .connect(&mut s.cx, REMOTE_END, LOCAL_END.port)
+            .unwrap();
+        s.set_timeout(Some(Duration::from_millis(100)));
+        s.set_connect_retry(Some(ConnectRetryPolicy {
+            base: Duration::from_millis(1000),
+            max: Duration::from_millis(1500),
+            jitter: false,
+            max_retries: 3,
         }));
+
+        let syn = TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: LOCAL_SEQ,
+            ack_number: None,
+            max_seg_size: Some(BASE_MSS),
+            window_scale: Some(0),
+            sack_permitted: true,
+            ..RECV_TEMPL
+        };
+
+        // The SYN-ACKs to the first two attempts are lost.
+        recv!(s, time 0, Ok(syn));
+        recv_nothing!(s, time 100);
+        assert_eq!(s.state, State::SynSent);
         assert_eq!(
             s.socket.poll_at(&mut s.cx),
-            PollAt::Time(Instant::from_millis(955))
+            PollAt::Time(Instant::from_millis(1100))
         );
-        recv!(s, time 955, Ok(TcpRepr {
-            seq_number: LOCAL_SEQ + 1,
-            ack_number: Some(REMOTE_SEQ + 1),
-            payload:    &b"abcdef"[..],
-            ..RECV_TEMPL
-        }));
+        recv_nothing!(s, time 1099);
+        recv!(s, time 1100, Ok(syn));
+
+        recv_nothing!(s, time 1200);
         assert_eq!(
             s.socket.poll_at(&mut s.cx),
-            PollAt::Time(Instant::from_millis(1255))
+            PollAt::Time(Instant::from_millis(2700))
         );
-        recv!(s, time 1255, Ok(TcpRepr {
-            control:    TcpControl::Rst,
-            seq_number: LOCAL_SEQ + 1 + 6,
+        recv!(s, time 2700, Ok(syn));
+
+        send!(s, time 2750, TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: REMOTE_SEQ,
+            ack_number: Some(LOCAL_SEQ + 1),
+            max_seg_size: Some(BASE_MSS),
+            window_scale: Some(0),
+            ..SEND_TEMPL
+        });
+        recv!(s, time 2750, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
             ack_number: Some(REMOTE_SEQ + 1),
             ..RECV_TEMPL
         }));
-        assert_eq!(s.state, State::Closed);
+        assert_eq!(s.state, State::Established);
     }
 
     #[test]
-    fn test_established_keep_alive_timeout() {
-        let mut s = socket_established();
-        s.set_keep_alive(Some(Duration::from_millis(50)));
-        s.set_timeout(Some(Duration::from_millis(100)));
-        recv!(s, time 100, Ok(TcpRepr {
+    fn test_connect_retry_syn_unanswered() {
+        let mut s = socket();
+        s.local_seq_no = LOCAL_SEQ;
+        s.socket
+            .connect(&mut s.cx, REMOTE_END, LOCAL_END.port)
+            .unwrap();
+        assert_eq!(s.timeout(), None);
+        s.set_connect_retry(Some(ConnectRetryPolicy {
+            base: Duration::from_millis(1000),
+            max: Duration::from_millis(1000),
+            jitter: false,
+            max_retries: 1,
+        }));
+
+        let syn = TcpRepr {
+            control: TcpControl::Syn,
             seq_number: LOCAL_SEQ,
-            ack_number: Some(REMOTE_SEQ + 1),
-            payload:    &[0],
+            ack_number: None,
+            max_seg_size: Some(BASE_MSS),
+            window_scale: Some(0),
+            sack_permitted: true,
             ..RECV_TEMPL
-        }));
-        recv_nothing!(s, time 100);
+        };
+        recv!(s, time 0, Ok(syn));
+
+        // The SYN is retransmitted until the attempt fails, without a timeout.
+        let mut syns = 1;
+        let failed_at = loop {
+            let at = match s.socket.poll_at(&mut s.cx) {
+                PollAt::Time(at) => at,
+                poll_at => panic!("unexpected {poll_at:?}"),
+            };
+            s.cx.set_now(at);
+            let mut sent = false;
+            s.socket
+                .dispatch(&mut s.cx, |_, (_, repr)| {
+                    assert_eq!(repr, syn);
+                    sent = true;
+                    Ok::<_, ()>(())
+                })
+                .unwrap();
+            if !sent {
+                break at;
+            }
+            syns += 1;
+        };
+        assert_eq!(syns, 1 + CONNECT_SYN_RETRANSMITS);
+        assert_eq!(s.state, State::SynSent);
         assert_eq!(
             s.socket.poll_at(&mut s.cx),
-            PollAt::Time(Instant::from_millis(150))
+            PollAt::Time(failed_at + Duration::from_millis(1000))
         );
-        send!(s, time 105, TcpRepr {
-            seq_number: REMOTE_SEQ + 1,
+
+        let retry_at = (failed_at + Duration::from_millis(1000)).total_millis();
+        recv!(s, time retry_at, Ok(syn));
+        send!(s, time retry_at, TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: REMOTE_SEQ,
             ack_number: Some(LOCAL_SEQ + 1),
+            max_seg_size: Some(BASE_MSS),
+            window_scale: Some(0),
             ..SEND_TEMPL
         });
-        assert_eq!(
-            s.socket.poll_at(&mut s.cx),
-            PollAt::Time(Instant::from_millis(155))
+        assert_eq!(s.state, State::Established);
+    }
+
+    #[test]
+    fn test_connect_retry_exhausted() {
+        let mut s = socket_syn_sent();
+        s.set_connect_retry(Some(ConnectRetryPolicy {
+            base: Duration::from_millis(1000),
+            max: Duration::from_millis(8000),
+            jitter: true,
+            max_retries: 1,
+        }));
+
+        let rst = TcpRepr {
+            control: TcpControl::Rst,
+            seq_number: REMOTE_SEQ,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        };
+        send!(s, time 0, rst);
+        assert_eq!(s.state, State::SynSent);
+        let retry_at = match s.socket.poll_at(&mut s.cx) {
+            PollAt::Time(retry_at) => retry_at,
+            poll_at => panic!("unexpected {poll_at:?}"),
+        };
+        assert!(retry_at >= Instant::from_millis(500));
+        assert!(retry_at <= Instant::from_millis(1000));
+
+        recv!(s, time 1000, Ok(TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: LOCAL_SEQ,
+            ack_number: None,
+            max_seg_size: Some(BASE_MSS),
+            window_scale: Some(0),
+            sack_permitted: true,
+            ..RECV_TEMPL
+        }));
+        send!(s, time 1000, rst);
+        assert_eq!(s.state, State::Closed);
+    }
+
+    #[test]
+    fn test_established_timeout() {
+        let mut s = socket_established();
+        s.set_timeout(Some(Duration::from_millis(1000)));
+        recv_nothing!(s, time 250);
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(1250))
+        );
+        s.send_slice(b"abcdef").unwrap();
+        assert_eq!(s.socket.poll_at(&mut s.cx), PollAt::Now);
+        recv!(s, time 255, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..RECV_TEMPL
+        }));
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(955))
+        );
+        recv!(s, time 955, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..RECV_TEMPL
+        }));
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(1255))
+        );
+        recv!(s, time 1255, Ok(TcpRepr {
+            control:    TcpControl::Rst,
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            ..RECV_TEMPL
+        }));
+        assert_eq!(s.state, State::Closed);
+    }
+
+    #[test]
+    fn test_established_keep_alive_timeout() {
+        let mut s = socket_established();
+        s.set_keep_alive(Some(Duration::from_millis(50)));
+        s.set_timeout(Some(Duration::from_millis(100)));
+        recv!(s, time 100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &[0],
+            ..RECV_TEMPL
+        }));
+        recv_nothing!(s, time 100);
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(150))
+        );
+        send!(s, time 105, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        });
+        assert_eq!(
+            s.socket.poll_at(&mut s.cx),
+            PollAt::Time(Instant::from_millis(155))
         );
         recv!(s, time 155, Ok(TcpRepr {
             seq_number: LOCAL_SEQ,
@@ -6550,6 +10053,71 @@ mod test {
         }));
     }
 
+    #[test]
+    fn test_poke() {
+        let mut s = socket_established();
+        s.set_keep_alive(Some(Duration::from_millis(100)));
+        recv!(s, time 0, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &[0],
+            ..RECV_TEMPL
+        }));
+
+        // A poke is an empty segment, sent right away, instead of a keep-alive.
+        s.poke();
+        assert_eq!(s.socket.poll_at(&mut s.cx), PollAt::Now);
+        recv!(s, time 100, Ok(TcpRepr {
+            control:    TcpControl::Psh,
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            ..RECV_TEMPL
+        }), exact);
+        recv_nothing!(s, time 100);
+
+        // The data stream is unaffected.
+        s.send_slice(b"abc").unwrap();
+        recv!(s, time 100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abc"[..],
+            ..RECV_TEMPL
+        }));
+        send!(s, time 100, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 3),
+            ..SEND_TEMPL
+        });
+
+        // A poke is not sent separately from data.
+        s.send_slice(b"def").unwrap();
+        s.poke();
+        recv!(s, time 100, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 3,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"def"[..],
+            ..RECV_TEMPL
+        }));
+        recv_nothing!(s, time 100);
+
+        // Nothing is sent before the connection is synchronized.
+        let mut s = socket_syn_sent();
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: None,
+                max_seg_size: Some(BASE_MSS),
+                window_scale: Some(0),
+                sack_permitted: true,
+                ..RECV_TEMPL
+            }]
+        );
+        s.poke();
+        recv_nothing!(s);
+    }
+
     // =========================================================================================//
     // Tests for time-to-live configuration.
     // =========================================================================================//
@@ -6628,6 +10196,55 @@ mod test {
         .unwrap();
     }
 
+    #[test]
+    fn test_recv_in_order_only() {
+        let mut s = socket_established();
+        s.set_ack_delay(None);
+        for (offset, payload) in [(3, &b"def"[..]), (6, &b"ghi"[..])] {
+            send!(
+                s,
+                TcpRepr {
+                    seq_number: REMOTE_SEQ + 1 + offset,
+                    ack_number: Some(LOCAL_SEQ + 1),
+                    payload,
+                    ..SEND_TEMPL
+                },
+                Some(TcpRepr {
+                    seq_number: LOCAL_SEQ + 1,
+                    ack_number: Some(REMOTE_SEQ + 1),
+                    ..RECV_TEMPL
+                })
+            );
+        }
+
+        // The later octets are buffered, but nothing can be read past the hole.
+        assert_eq!(s.contiguous_recv_available(), 0);
+        assert!(!s.can_recv());
+        assert_eq!(s.recv_slice(&mut [0; 9]), Ok(0));
+        assert_eq!(s.peek(9), Ok(&b""[..]));
+
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                payload: &b"abc"[..],
+                ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 9),
+                window_len: 55,
+                ..RECV_TEMPL
+            })
+        );
+        assert_eq!(s.contiguous_recv_available(), 9);
+        let mut data = [0; 9];
+        assert_eq!(s.recv_slice(&mut data), Ok(9));
+        assert_eq!(&data, b"abcdefghi");
+        assert_eq!(s.contiguous_recv_available(), 0);
+    }
+
     #[test]
     fn test_buffer_wraparound_rx() {
         let mut s = socket_established();
@@ -6800,6 +10417,7 @@ mod test {
             (0, ())
         })
         .unwrap();
+        // An RST past the expected sequence number is challenged.
         send!(
             s,
             TcpRepr {
@@ -6807,6 +10425,20 @@ mod test {
                 seq_number: REMOTE_SEQ + 1 + 9,
                 ack_number: Some(LOCAL_SEQ + 1),
                 ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 3),
+                ..RECV_TEMPL
+            })
+        );
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1 + 3,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
             }
         );
         // Error must be `Illegal` even if we've received a FIN,
@@ -6870,6 +10502,7 @@ mod test {
                 ..RECV_TEMPL
             })
         );
+        // An RST past the expected sequence number is challenged.
         send!(
             s,
             TcpRepr {
@@ -6877,6 +10510,21 @@ mod test {
                 seq_number: REMOTE_SEQ + 1 + 9,
                 ack_number: Some(LOCAL_SEQ + 1),
                 ..SEND_TEMPL
+            },
+            Some(TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1 + 3),
+                window_len: 61,
+                ..RECV_TEMPL
+            })
+        );
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Rst,
+                seq_number: REMOTE_SEQ + 1 + 3,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
             }
         );
         s.recv(|data| {
@@ -7076,6 +10724,56 @@ mod test {
     // Tests for Nagle's Algorithm
     // =========================================================================================//
 
+    #[test]
+    fn test_flush() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+
+        // Flushing with nothing to send does nothing.
+        s.flush();
+        assert_eq!(s.flush_seq, None);
+        recv_nothing!(s);
+
+        s.send_slice(b"abcdef").unwrap();
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abcdef"[..],
+                ..RECV_TEMPL
+            }]
+        );
+
+        // With data in flight, Nagle's Algorithm holds back the small write...
+        s.send_slice(b"abcdefghi").unwrap();
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 6,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abcdef"[..],
+                ..RECV_TEMPL
+            }]
+        );
+        recv_nothing!(s);
+
+        // ...until it is flushed.
+        s.flush();
+        recv!(s, time 0, Ok(TcpRepr {
+            control:    TcpControl::Psh,
+            seq_number: LOCAL_SEQ + 1 + 6 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"ghi"[..],
+            ..RECV_TEMPL
+        }), exact);
+        assert_eq!(s.flush_seq, None);
+
+        // Later writes are subject to Nagle's Algorithm again.
+        s.send_slice(b"xyz").unwrap();
+        recv_nothing!(s);
+    }
+
     #[test]
     fn test_nagle() {
         let mut s = socket_established();
@@ -7162,20 +10860,363 @@ mod test {
     }
 
     // =========================================================================================//
-    // Tests for packet filtering.
+    // Tests for congestion control
     // =========================================================================================//
 
-    #[test]
-    fn test_doesnt_accept_wrong_port() {
-        let mut s = socket_established();
-        s.rx_buffer = SocketBuffer::new(vec![0; 6]);
-        s.assembler = Assembler::new();
+    fn socket_established_grown_cwnd() -> TestSocket {
+        let mut s = socket_established_with_buffer_sizes(128, 64);
+        s.remote_mss = 6;
+        s.congestion = CongestionController::new(6);
+        assert_eq!(s.congestion.window(), 24);
 
-        let tcp_repr = TcpRepr {
-            seq_number: REMOTE_SEQ + 1,
-            ack_number: Some(LOCAL_SEQ + 1),
-            dst_port: LOCAL_PORT + 1,
-            ..SEND_TEMPL
+        s.send_slice(&[0xaa; 24]).unwrap();
+        for i in 0..4 {
+            recv!(s, time 0, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xaa; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+        // The initial window is exhausted.
+        s.send_slice(&[0xbb; 6]).unwrap();
+        recv_nothing!(s, time 0);
+
+        // Acknowledge every segment separately, growing the window by one
+        // segment each time.
+        for i in 1..=4 {
+            send!(s, time 10, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 6 * i),
+                ..SEND_TEMPL
+            });
+        }
+        assert_eq!(s.congestion.window(), 48);
+
+        recv!(s, time 10, Ok(TcpRepr {
+            seq_number: LOCAL_SEQ + 1 + 24,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &[0xbb; 6][..],
+            ..RECV_TEMPL
+        }));
+        send!(s, time 20, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 30),
+            ..SEND_TEMPL
+        });
+        assert_eq!(s.congestion.window(), 54);
+        s
+    }
+
+    #[test]
+    fn test_congestion_avoidance() {
+        let mut s = socket_established_grown_cwnd();
+        s.congestion.ssthresh = 48;
+
+        s.send_slice(&[0xcc; 54]).unwrap();
+        for i in 0..9 {
+            recv!(s, time 30, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+
+        // Above the threshold, the window only grows by one segment once a
+        // whole window has been acknowledged.
+        for i in 1..=9 {
+            assert_eq!(s.congestion.window(), 54);
+            send!(s, time 40, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 30 + 6 * i),
+                ..SEND_TEMPL
+            });
+        }
+        assert_eq!(s.congestion.window(), 60);
+    }
+
+    #[test]
+    fn test_retransmit_timeout_collapses_window() {
+        let mut s = socket_established();
+        s.remote_mss = 6;
+        s.send_slice(b"abcdef012345").unwrap();
+
+        recv!(s, time 0, Ok(TcpRepr {
+            control:    TcpControl::None,
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..RECV_TEMPL
+        }), exact);
+        recv!(s, time 0, Ok(TcpRepr {
+            control:    TcpControl::Psh,
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"012345"[..],
+            ..RECV_TEMPL
+        }), exact);
+        recv_nothing!(s, time 50);
+
+        // After the timeout, the window collapses to a single segment.
+        recv!(s, time 1000, Ok(TcpRepr {
+            control:    TcpControl::None,
+            seq_number: LOCAL_SEQ + 1,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"abcdef"[..],
+            ..RECV_TEMPL
+        }), exact);
+        assert_eq!(s.congestion.window(), 6);
+        assert_eq!(s.congestion.ssthresh, 12);
+        recv_nothing!(s, time 1050);
+
+        send!(s, time 1100, TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 6),
+            ..SEND_TEMPL
+        });
+        recv!(s, time 1100, Ok(TcpRepr {
+            control:    TcpControl::Psh,
+            seq_number: LOCAL_SEQ + 1 + 6,
+            ack_number: Some(REMOTE_SEQ + 1),
+            payload:    &b"012345"[..],
+            ..RECV_TEMPL
+        }), exact);
+        recv_nothing!(s, time 1150);
+    }
+
+    #[test]
+    fn test_congestion_control_disabled() {
+        let mut s = socket_established_grown_cwnd();
+        assert!(s.congestion_control());
+        s.set_congestion_control(false);
+        assert!(!s.congestion_control());
+        assert_eq!(s.stats().cwnd, usize::MAX);
+
+        // Only the remote window limits the data in flight.
+        s.send_slice(&[0xcc; 64]).unwrap();
+        for i in 0..10 {
+            recv!(s, time 5000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+    }
+
+    #[test]
+    fn test_slow_start_restart_after_idle() {
+        let mut s = socket_established_grown_cwnd();
+        assert!(s.slow_start_restart());
+
+        // Idle for well over the retransmission timeout, then send a burst.
+        s.send_slice(&[0xcc; 48]).unwrap();
+        for i in 0..4 {
+            recv!(s, time 5000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+        recv_nothing!(s, time 5000);
+        assert_eq!(s.congestion.window(), 24);
+    }
+
+    #[test]
+    fn test_slow_start_restart_before_idle() {
+        let mut s = socket_established_grown_cwnd();
+        s.set_slow_start_restart_idle(Some(Duration::from_secs(10)));
+
+        s.send_slice(&[0xcc; 48]).unwrap();
+        for i in 0..8 {
+            recv!(s, time 5000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+        recv_nothing!(s, time 5000);
+        assert_eq!(s.congestion.window(), 54);
+    }
+
+    #[test]
+    fn test_slow_start_restart_disabled() {
+        let mut s = socket_established_grown_cwnd();
+        s.set_slow_start_restart(false);
+
+        s.send_slice(&[0xcc; 48]).unwrap();
+        for i in 0..8 {
+            recv!(s, time 5000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+        recv_nothing!(s, time 5000);
+        assert_eq!(s.congestion.window(), 54);
+    }
+
+    fn socket_established_via_listen(initial_window: Option<usize>) -> TestSocket {
+        let mut s = socket_with_buffer_sizes(128, 64);
+        if let Some(segments) = initial_window {
+            s.set_initial_congestion_window(segments);
+        }
+        s.state = State::Listen;
+        s.listen_endpoint = LISTEN_END;
+
+        send!(
+            s,
+            TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: REMOTE_SEQ,
+                ack_number: None,
+                max_seg_size: Some(6),
+                ..SEND_TEMPL
+            }
+        );
+        recv!(
+            s,
+            [TcpRepr {
+                control: TcpControl::Syn,
+                seq_number: LOCAL_SEQ,
+                ack_number: Some(REMOTE_SEQ + 1),
+                max_seg_size: Some(BASE_MSS),
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1),
+                ..SEND_TEMPL
+            }
+        );
+        assert_eq!(s.state, State::Established);
+        s
+    }
+
+    fn first_burst_segments(mut s: TestSocket) -> usize {
+        s.send_slice(&[0xaa; 120]).unwrap();
+        let mut segments = 0;
+        while s.bytes_in_flight() < 120 {
+            recv!(s, time 0, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 6 * segments,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xaa; 6][..],
+                ..RECV_TEMPL
+            }));
+            segments += 1;
+            if s.bytes_in_flight() == s.congestion.window() {
+                break;
+            }
+        }
+        // Wait for an ACK before sending any more.
+        recv_nothing!(s, time 0);
+        segments
+    }
+
+    #[test]
+    fn test_initial_congestion_window_default() {
+        let s = socket_established_via_listen(None);
+        assert_eq!(s.initial_congestion_window(), None);
+        assert_eq!(first_burst_segments(s), 4);
+    }
+
+    #[test]
+    fn test_initial_congestion_window() {
+        let s = socket_established_via_listen(Some(10));
+        assert_eq!(s.initial_congestion_window(), Some(10));
+        assert_eq!(first_burst_segments(s), 10);
+    }
+
+    #[test]
+    fn test_initial_congestion_window_clamped() {
+        let mut s = socket();
+        s.set_initial_congestion_window(0);
+        assert_eq!(s.initial_congestion_window(), Some(1));
+        s.set_initial_congestion_window(100);
+        assert_eq!(s.initial_congestion_window(), Some(10));
+    }
+
+    #[test]
+    fn test_initial_congestion_window_restart() {
+        let mut s = socket_established_grown_cwnd();
+        s.set_initial_congestion_window(2);
+
+        // Idle for well over the retransmission timeout.
+        s.send_slice(&[0xcc; 48]).unwrap();
+        for i in 0..2 {
+            recv!(s, time 5000, Ok(TcpRepr {
+                seq_number: LOCAL_SEQ + 1 + 30 + 6 * i,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload:    &[0xcc; 6][..],
+                ..RECV_TEMPL
+            }));
+        }
+        recv_nothing!(s, time 5000);
+        assert_eq!(s.congestion.window(), 12);
+    }
+
+    #[test]
+    fn test_in_flight() {
+        let mut s = socket_established_with_buffer_sizes(128, 64);
+        s.remote_mss = 6;
+        s.congestion = CongestionController::new(6);
+        assert_eq!(s.bytes_in_flight(), 0);
+        assert_eq!(s.segments_in_flight(), 0);
+
+        s.send_slice(&[0xaa; 96]).unwrap();
+        let mut sent = 0;
+        let mut acked = 0;
+        while acked < 96 {
+            // Send as much as the congestion window allows.
+            while sent < 96 && s.send_window() > s.bytes_in_flight() {
+                recv!(s, time 0, Ok(TcpRepr {
+                    seq_number: LOCAL_SEQ + 1 + sent,
+                    ack_number: Some(REMOTE_SEQ + 1),
+                    payload:    &[0xaa; 6][..],
+                    ..RECV_TEMPL
+                }));
+                sent += 6;
+                assert_eq!(s.bytes_in_flight(), sent - acked);
+                assert_eq!(s.segments_in_flight(), (sent - acked) / 6);
+                assert!(s.bytes_in_flight() <= s.congestion.window());
+            }
+            recv_nothing!(s, time 0);
+
+            // Acknowledge two segments at once.
+            acked += 12;
+            send!(s, time 0, TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + acked),
+                ..SEND_TEMPL
+            });
+            assert_eq!(s.bytes_in_flight(), sent - acked);
+        }
+        assert_eq!(s.bytes_in_flight(), 0);
+        assert_eq!(s.segments_in_flight(), 0);
+    }
+
+    // =========================================================================================//
+    // Tests for packet filtering.
+    // =========================================================================================//
+
+    #[test]
+    fn test_doesnt_accept_wrong_port() {
+        let mut s = socket_established();
+        s.rx_buffer = SocketBuffer::new(vec![0; 6]);
+        s.assembler = Assembler::new();
+
+        let tcp_repr = TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            dst_port: LOCAL_PORT + 1,
+            ..SEND_TEMPL
         };
         assert!(!s.socket.accepts(&mut s.cx, &SEND_IP_TEMPL, &tcp_repr));
 
@@ -7227,6 +11268,502 @@ mod test {
         assert!(!s.socket.accepts(&mut s.cx, &ip_repr_wrong_dst, &tcp_repr));
     }
 
+    #[test]
+    fn test_rebind_local() {
+        let mut s = socket();
+        assert_eq!(
+            s.socket.rebind_local(&mut s.cx, OTHER_ADDR.into()),
+            Err(ConnectError::InvalidState)
+        );
+
+        let mut s = socket_established();
+        assert_eq!(
+            s.socket
+                .rebind_local(&mut s.cx, IpvXAddress::UNSPECIFIED.into()),
+            Err(ConnectError::Unaddressable)
+        );
+        // The address must be assigned to the interface.
+        assert_eq!(
+            s.socket.rebind_local(&mut s.cx, OTHER_ADDR.into()),
+            Err(ConnectError::Unaddressable)
+        );
+        s.cx.add_ip_addr(crate::wire::IpCidr::new(OTHER_ADDR.into(), 24));
+        s.send_slice(b"abc").unwrap();
+        recv!(
+            s,
+            [TcpRepr {
+                seq_number: LOCAL_SEQ + 1,
+                ack_number: Some(REMOTE_SEQ + 1),
+                payload: &b"abc"[..],
+                ..RECV_TEMPL
+            }]
+        );
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 3),
+                ..SEND_TEMPL
+            }
+        );
+
+        assert_eq!(s.socket.rebind_local(&mut s.cx, OTHER_ADDR.into()), Ok(()));
+        assert_eq!(
+            s.local_endpoint(),
+            Some(IpEndpoint::new(OTHER_ADDR.into(), LOCAL_PORT))
+        );
+
+        // Segments are sent from the new address, in the same sequence space.
+        s.send_slice(b"def").unwrap();
+        let mut sent = 0;
+        s.socket
+            .dispatch(&mut s.cx, |_, (ip_repr, tcp_repr)| {
+                assert_eq!(ip_repr.src_addr(), OTHER_ADDR.into());
+                assert_eq!(ip_repr.dst_addr(), REMOTE_ADDR.into());
+                assert_eq!(tcp_repr.seq_number, LOCAL_SEQ + 1 + 3);
+                assert_eq!(tcp_repr.payload, b"def");
+                sent += 1;
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(sent, 1);
+
+        // Segments are accepted on the new address only.
+        let tcp_repr = TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1 + 6),
+            payload: &b"xyz"[..],
+            ..SEND_TEMPL
+        };
+        let ip_repr = |dst_addr| {
+            IpReprIpvX(IpvXRepr {
+                src_addr: REMOTE_ADDR,
+                dst_addr,
+                next_header: IpProtocol::Tcp,
+                payload_len: tcp_repr.buffer_len(),
+                hop_limit: 64,
+            })
+        };
+        assert!(!s.socket.accepts(&mut s.cx, &ip_repr(LOCAL_ADDR), &tcp_repr));
+        assert!(s.socket.accepts(&mut s.cx, &ip_repr(OTHER_ADDR), &tcp_repr));
+        s.socket.process(&mut s.cx, &ip_repr(OTHER_ADDR), &tcp_repr);
+        assert_eq!(s.state, State::Established);
+        assert!(s.tx_buffer.is_empty());
+        assert_eq!(
+            s.recv(|data| (data.len(), data.to_owned())),
+            Ok(b"xyz".to_vec())
+        );
+    }
+
+    // =========================================================================================//
+    // Tests for the TCP Authentication Option.
+    // =========================================================================================//
+
+    static AO_KEYS: [AoKey<'static>; 2] = [
+        AoKey {
+            send_id: 1,
+            recv_id: 2,
+            master_key: b"first key",
+        },
+        AoKey {
+            send_id: 3,
+            recv_id: 4,
+            master_key: b"second key",
+        },
+    ];
+
+    /// A KDF that is easy to compute, not a secure one.
+    fn ao_test_kdf(master_key: &[u8], context: &[u8]) -> [u8; AO_TRAFFIC_KEY_LEN] {
+        let mut key = [0u8; AO_TRAFFIC_KEY_LEN];
+        for (i, byte) in master_key.iter().chain(context).enumerate() {
+            key[i % AO_TRAFFIC_KEY_LEN] = key[i % AO_TRAFFIC_KEY_LEN].rotate_left(1) ^ byte;
+        }
+        key
+    }
+
+    /// A MAC that is easy to compute, not a secure one.
+    fn ao_test_mac(traffic_key: &[u8], message: &[&[u8]]) -> [u8; TCP_AO_MAC_LEN] {
+        let mut mac = [0u8; TCP_AO_MAC_LEN];
+        let bytes = traffic_key
+            .iter()
+            .chain(message.iter().flat_map(|m| m.iter()));
+        for (i, byte) in bytes.enumerate() {
+            mac[i % TCP_AO_MAC_LEN] = mac[i % TCP_AO_MAC_LEN].rotate_left(1) ^ byte;
+        }
+        mac
+    }
+
+    /// Return the traffic keys of `key` from the local end to the remote end,
+    /// and back, for the given local and remote ISNs.
+    fn ao_traffic_keys(
+        key: &AoKey,
+        local_isn: TcpSeqNumber,
+        remote_isn: TcpSeqNumber,
+    ) -> ([u8; AO_TRAFFIC_KEY_LEN], [u8; AO_TRAFFIC_KEY_LEN]) {
+        (
+            ao_traffic_key(
+                ao_test_kdf,
+                key.master_key,
+                LOCAL_END,
+                REMOTE_END,
+                local_isn,
+                remote_isn,
+            ),
+            ao_traffic_key(
+                ao_test_kdf,
+                key.master_key,
+                REMOTE_END,
+                LOCAL_END,
+                remote_isn,
+                local_isn,
+            ),
+        )
+    }
+
+    /// Emit a segment from the remote end, authenticated with `traffic_key`.
+    fn ao_segment(
+        repr: &TcpRepr,
+        key_id: u8,
+        rnext_key_id: u8,
+        traffic_key: &[u8],
+        sne: u32,
+    ) -> Vec<u8> {
+        let mut repr = TcpRepr {
+            authentication: Some(TcpAuthentication {
+                key_id,
+                rnext_key_id,
+                mac: [0; TCP_AO_MAC_LEN],
+            }),
+            ..*repr
+        };
+        let ip_repr = IpReprIpvX(IpvXRepr {
+            src_addr: REMOTE_ADDR,
+            dst_addr: LOCAL_ADDR,
+            next_header: IpProtocol::Tcp,
+            payload_len: repr.buffer_len(),
+            hop_limit: 64,
+        });
+
+        let mut bytes = vec![0; repr.buffer_len()];
+        repr.emit(
+            &mut TcpPacket::new_unchecked(&mut bytes),
+            &REMOTE_ADDR.into(),
+            &LOCAL_ADDR.into(),
+            &ChecksumCapabilities::ignored(),
+        );
+        let (header, payload) = bytes.split_at(repr.header_len());
+        let mac = Socket::ao_mac(ao_test_mac, traffic_key, sne, &ip_repr, header, payload);
+        repr.authentication.as_mut().unwrap().mac = mac;
+        repr.emit(
+            &mut TcpPacket::new_unchecked(&mut bytes),
+            &REMOTE_ADDR.into(),
+            &LOCAL_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        );
+        bytes
+    }
+
+    /// Compute the MAC of a segment from the local end, authenticated with
+    /// `traffic_key`.
+    fn ao_local_mac(repr: &TcpRepr, traffic_key: &[u8], sne: u32) -> [u8; TCP_AO_MAC_LEN] {
+        ao_local_mac_from(LOCAL_ADDR, repr, traffic_key, sne)
+    }
+
+    /// Compute the MAC of a segment from `src_addr`, authenticated with
+    /// `traffic_key`.
+    fn ao_local_mac_from(
+        src_addr: IpvXAddress,
+        repr: &TcpRepr,
+        traffic_key: &[u8],
+        sne: u32,
+    ) -> [u8; TCP_AO_MAC_LEN] {
+        let mut header = vec![0; repr.header_len()];
+        TcpRepr {
+            authentication: repr.authentication.map(|authentication| TcpAuthentication {
+                mac: [0; TCP_AO_MAC_LEN],
+                ..authentication
+            }),
+            payload: &[],
+            ..*repr
+        }
+        .emit(
+            &mut TcpPacket::new_unchecked(&mut header),
+            &src_addr.into(),
+            &REMOTE_ADDR.into(),
+            &ChecksumCapabilities::ignored(),
+        );
+        let ip_repr = IpReprIpvX(IpvXRepr {
+            src_addr,
+            dst_addr: REMOTE_ADDR,
+            next_header: IpProtocol::Tcp,
+            payload_len: repr.buffer_len(),
+            hop_limit: 64,
+        });
+        Socket::ao_mac(
+            ao_test_mac,
+            traffic_key,
+            sne,
+            &ip_repr,
+            &header,
+            repr.payload,
+        )
+    }
+
+    /// Parse a segment from the remote end, and return whether it is
+    /// authenticated, and the immediate reply to it.
+    fn ao_send(s: &mut TestSocket, bytes: &[u8]) -> (bool, Option<TcpRepr<'static>>) {
+        let packet = TcpPacket::new_checked(bytes).unwrap();
+        let repr = TcpRepr::parse(
+            &packet,
+            &REMOTE_ADDR.into(),
+            &LOCAL_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        )
+        .unwrap();
+        let ip_repr = IpReprIpvX(IpvXRepr {
+            src_addr: REMOTE_ADDR,
+            dst_addr: LOCAL_ADDR,
+            next_header: IpProtocol::Tcp,
+            payload_len: bytes.len(),
+            hop_limit: 64,
+        });
+        if !s.socket.authenticate(&ip_repr, &packet, &repr) {
+            return (false, None);
+        }
+        let reply = s.socket.process(&mut s.cx, &ip_repr, &repr);
+        (true, reply.map(|(_, repr)| repr))
+    }
+
+    #[test]
+    fn test_ao_sign() {
+        let mut s = socket_established();
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+        assert_eq!(s.ao_key_id(), Some(1));
+        s.send_slice(b"abcdef").unwrap();
+
+        let (send_key, recv_key) = ao_traffic_keys(&AO_KEYS[0], LOCAL_SEQ, REMOTE_SEQ);
+        assert_ne!(send_key, recv_key);
+        recv(&mut s, Instant::from_millis(0), |result| {
+            let repr = result.unwrap();
+            let authentication = repr.authentication.unwrap();
+            assert_eq!(authentication.key_id, 1);
+            assert_eq!(authentication.rnext_key_id, 2);
+            assert_eq!(repr.payload, b"abcdef");
+            // The MAC is computed over the header with the MAC zeroed.
+            assert_eq!(authentication.mac, ao_local_mac(&repr, &send_key, 0));
+        });
+    }
+
+    #[test]
+    fn test_ao_sign_teardown() {
+        let mut s = socket_established();
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+        s.set_close_on_drop(Some(CloseMode::Rst));
+
+        let (ip_repr, repr) = s.socket.teardown().unwrap();
+        assert_eq!(repr.control, TcpControl::Rst);
+        assert_eq!(ip_repr.payload_len(), repr.buffer_len());
+        let authentication = repr.authentication.unwrap();
+        assert_eq!(authentication.key_id, 1);
+
+        let (send_key, _) = ao_traffic_keys(&AO_KEYS[0], LOCAL_SEQ, REMOTE_SEQ);
+        assert_eq!(authentication.mac, ao_local_mac(&repr, &send_key, 0));
+    }
+
+    #[test]
+    fn test_ao_rebind_local() {
+        let mut s = socket_established();
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+        s.send_slice(b"abc").unwrap();
+        recv(&mut s, Instant::from_millis(0), |result| {
+            assert!(result.unwrap().authentication.is_some());
+        });
+        send!(
+            s,
+            TcpRepr {
+                seq_number: REMOTE_SEQ + 1,
+                ack_number: Some(LOCAL_SEQ + 1 + 3),
+                ..SEND_TEMPL
+            }
+        );
+
+        // The traffic keys derived from the old address are dropped.
+        s.cx.add_ip_addr(crate::wire::IpCidr::new(OTHER_ADDR.into(), 24));
+        assert_eq!(s.socket.rebind_local(&mut s.cx, OTHER_ADDR.into()), Ok(()));
+        assert_eq!(s.ao.unwrap().conn.unwrap().traffic_keys, None);
+
+        let send_key = ao_traffic_key(
+            ao_test_kdf,
+            AO_KEYS[0].master_key,
+            IpEndpoint::new(OTHER_ADDR.into(), LOCAL_PORT),
+            REMOTE_END,
+            LOCAL_SEQ,
+            REMOTE_SEQ,
+        );
+        s.send_slice(b"def").unwrap();
+        let mut sent = 0;
+        s.socket
+            .dispatch(&mut s.cx, |_, (_, tcp_repr)| {
+                let authentication = tcp_repr.authentication.unwrap();
+                assert_eq!(
+                    authentication.mac,
+                    ao_local_mac_from(OTHER_ADDR, &tcp_repr, &send_key, 0)
+                );
+                sent += 1;
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn test_ao_verify() {
+        let mut s = socket_established();
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+
+        let repr = TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            payload: &b"abcdef"[..],
+            ..SEND_TEMPL
+        };
+        let (send_key, recv_key) = ao_traffic_keys(&AO_KEYS[0], LOCAL_SEQ, REMOTE_SEQ);
+        let (_, other_recv_key) = ao_traffic_keys(&AO_KEYS[1], LOCAL_SEQ, REMOTE_SEQ);
+
+        // Segments without the option, with an unknown key, with the traffic
+        // key of another key or direction, with another Sequence Number
+        // Extension or with a bad MAC are dropped.
+        let mut bytes = vec![0; repr.buffer_len()];
+        repr.emit(
+            &mut TcpPacket::new_unchecked(&mut bytes),
+            &REMOTE_ADDR.into(),
+            &LOCAL_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        );
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let bytes = ao_segment(&repr, 5, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let bytes = ao_segment(&repr, 2, 1, &other_recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let bytes = ao_segment(&repr, 2, 1, &send_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 1);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let mut bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        *bytes.last_mut().unwrap() ^= 1;
+        let mut packet = TcpPacket::new_unchecked(&mut bytes);
+        packet.fill_checksum(&REMOTE_ADDR.into(), &LOCAL_ADDR.into());
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        assert_eq!(s.rx_buffer.len(), 0);
+
+        // A valid segment is accepted, and acknowledged with the key the
+        // remote end asks for.
+        let bytes = ao_segment(&repr, 2, 3, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (true, None));
+        assert_eq!(s.rx_buffer.len(), 6);
+        assert_eq!(s.ao_key_id(), Some(3));
+        let (other_send_key, _) = ao_traffic_keys(&AO_KEYS[1], LOCAL_SEQ, REMOTE_SEQ);
+        recv(&mut s, Instant::from_millis(0), |result| {
+            let repr = result.unwrap();
+            assert_eq!(repr.ack_number, Some(REMOTE_SEQ + 1 + 6));
+            let authentication = repr.authentication.unwrap();
+            assert_eq!(authentication.key_id, 3);
+            assert_eq!(authentication.rnext_key_id, 4);
+            assert_eq!(authentication.mac, ao_local_mac(&repr, &other_send_key, 0));
+        });
+    }
+
+    #[test]
+    fn test_ao_handshake() {
+        let mut s = socket_listen();
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+
+        // The SYN is authenticated with a local ISN of zero.
+        let repr = TcpRepr {
+            control: TcpControl::Syn,
+            seq_number: REMOTE_SEQ,
+            ack_number: None,
+            ..SEND_TEMPL
+        };
+        let (_, recv_key) = ao_traffic_keys(&AO_KEYS[0], LOCAL_SEQ, REMOTE_SEQ);
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let (_, recv_key) = ao_traffic_keys(&AO_KEYS[0], TcpSeqNumber(0), REMOTE_SEQ);
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (true, None));
+        assert_eq!(s.state, State::SynReceived);
+
+        // The SYN|ACK and the segments after it with both ISNs.
+        let (send_key, recv_key) = ao_traffic_keys(&AO_KEYS[0], LOCAL_SEQ, REMOTE_SEQ);
+        recv(&mut s, Instant::from_millis(0), |result| {
+            let repr = result.unwrap();
+            assert_eq!(repr.control, TcpControl::Syn);
+            assert_eq!(repr.seq_number, LOCAL_SEQ);
+            let authentication = repr.authentication.unwrap();
+            assert_eq!(authentication.mac, ao_local_mac(&repr, &send_key, 0));
+        });
+        let repr = TcpRepr {
+            seq_number: REMOTE_SEQ + 1,
+            ack_number: Some(LOCAL_SEQ + 1),
+            ..SEND_TEMPL
+        };
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (true, None));
+        assert_eq!(s.state, State::Established);
+    }
+
+    #[test]
+    fn test_ao_sne() {
+        // Both sequence numbers are about to wrap around.
+        let local_isn = TcpSeqNumber(-4);
+        let remote_isn = TcpSeqNumber(-5);
+        let mut s = socket_established();
+        s.isns = Some((local_isn, remote_isn));
+        s.local_seq_no = local_isn + 1;
+        s.remote_last_seq = local_isn + 1;
+        s.remote_seq_no = remote_isn + 1;
+        s.remote_last_ack = Some(remote_isn + 1);
+        s.set_ao_keys(&AO_KEYS, ao_test_kdf, ao_test_mac);
+        let (send_key, recv_key) = ao_traffic_keys(&AO_KEYS[0], local_isn, remote_isn);
+
+        // Segments up to the wraparound have an extension of zero.
+        let repr = TcpRepr {
+            seq_number: remote_isn + 1,
+            ack_number: Some(local_isn + 1),
+            payload: &b"abcdef"[..],
+            ..SEND_TEMPL
+        };
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (true, None));
+        s.send_slice(b"abcdef").unwrap();
+        recv(&mut s, Instant::from_millis(0), |result| {
+            let repr = result.unwrap();
+            assert_eq!(repr.seq_number, local_isn + 1);
+            let authentication = repr.authentication.unwrap();
+            assert_eq!(authentication.mac, ao_local_mac(&repr, &send_key, 0));
+        });
+
+        // Segments after it have an extension of one.
+        let repr = TcpRepr {
+            seq_number: remote_isn + 1 + 6,
+            ack_number: Some(local_isn + 1 + 6),
+            payload: &b"ghijkl"[..],
+            ..SEND_TEMPL
+        };
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 0);
+        assert_eq!(ao_send(&mut s, &bytes), (false, None));
+        let bytes = ao_segment(&repr, 2, 1, &recv_key, 1);
+        assert_eq!(ao_send(&mut s, &bytes), (true, None));
+        assert_eq!(s.rx_buffer.len(), 12);
+        s.send_slice(b"ghijkl").unwrap();
+        recv(&mut s, Instant::from_millis(0), |result| {
+            let repr = result.unwrap();
+            assert_eq!(repr.seq_number, local_isn + 1 + 6);
+            let authentication = repr.authentication.unwrap();
+            assert_eq!(authentication.mac, ao_local_mac(&repr, &send_key, 1));
+        });
+    }
+
     // =========================================================================================//
     // Timer tests
     // =========================================================================================//
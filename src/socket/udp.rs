@@ -118,6 +118,11 @@ pub struct Socket<'a> {
     /// The time-to-live (IPv4) or hop limit (IPv6) value used in outgoing
     /// packets.
     hop_limit: Option<u8>,
+    /// The Differentiated Services Code Point of outgoing packets.
+    dscp: u8,
+    /// Whether multicast datagrams sent from this socket are delivered back
+    /// to local sockets, if set.
+    multicast_loop: Option<bool>,
     #[cfg(feature = "async")]
     rx_waker: WakerRegistration,
     #[cfg(feature = "async")]
@@ -132,6 +137,8 @@ impl<'a> Socket<'a> {
             rx_buffer,
             tx_buffer,
             hop_limit: None,
+            dscp: 0,
+            multicast_loop: None,
             #[cfg(feature = "async")]
             rx_waker: WakerRegistration::new(),
             #[cfg(feature = "async")]
@@ -212,6 +219,46 @@ impl<'a> Socket<'a> {
         self.hop_limit = hop_limit
     }
 
+    /// Return the Differentiated Services Code Point of outgoing packets.
+    ///
+    /// See also the [set_dscp](#method.set_dscp) method.
+    pub fn dscp(&self) -> u8 {
+        self.dscp
+    }
+
+    /// Set the Differentiated Services Code Point of outgoing packets, used
+    /// as the upper six bits of the IPv4 Type of Service or IPv6 Traffic
+    /// Class field.
+    ///
+    /// By default, packets are not marked, i.e. use the value 0.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the value does not fit in six bits.
+    pub fn set_dscp(&mut self, dscp: u8) {
+        assert!(dscp < 64, "the DSCP value must fit in six bits");
+        self.dscp = dscp
+    }
+
+    /// Return whether multicast datagrams sent from this socket are delivered
+    /// back to local sockets joined to the group, if set.
+    ///
+    /// See also the [set_multicast_loop](#method.set_multicast_loop) method.
+    pub fn multicast_loop(&self) -> Option<bool> {
+        self.multicast_loop
+    }
+
+    /// Set whether multicast datagrams sent from this socket are delivered
+    /// back to local sockets joined to the group on the same interface, like
+    /// `IP_MULTICAST_LOOP`.
+    ///
+    /// A socket without an explicitly set value uses the interface
+    /// [default](crate::iface::Interface::set_multicast_loop), which enables
+    /// looping.
+    pub fn set_multicast_loop(&mut self, multicast_loop: bool) {
+        self.multicast_loop = Some(multicast_loop)
+    }
+
     /// Bind the socket to the given endpoint.
     ///
     /// This function returns `Err(Error::Illegal)` if the socket was open
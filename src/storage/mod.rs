@@ -21,6 +21,12 @@ pub trait Resettable {
     fn reset(&mut self);
 }
 
+impl Resettable for u8 {
+    fn reset(&mut self) {
+        *self = 0;
+    }
+}
+
 /// Error returned when enqueuing into a full buffer.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -51,6 +51,23 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
         }
     }
 
+    /// Create a ring buffer with owned storage of the given capacity, and
+    /// enqueue the elements of `iter` into it.
+    ///
+    /// Elements that do not fit are dropped, as with [`Extend::extend`].
+    #[cfg(feature = "alloc")]
+    pub fn from_iter_with_capacity<I>(capacity: usize, iter: I) -> RingBuffer<'a, T>
+    where
+        T: Default,
+        I: IntoIterator<Item = T>,
+    {
+        let mut storage = alloc::vec::Vec::new();
+        storage.resize_with(capacity, T::default);
+        let mut ring = RingBuffer::new(storage);
+        ring.extend(iter);
+        ring
+    }
+
     /// Clear the ring buffer.
     /// 清空环形缓冲区，重置读取位置和长度
     pub fn clear(&mut self) {
@@ -78,6 +95,31 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
         }
     }
 
+    /// Change the capacity of the ring buffer, preserving the queued elements
+    /// in order.
+    ///
+    /// Afterwards, the queued elements are at the start of the storage, and new
+    /// elements are set to their default value. Returns `Err(Full)`, and does
+    /// nothing, if the buffer holds more than `new_capacity` elements, or if
+    /// its storage is borrowed and cannot be reallocated.
+    #[cfg(feature = "alloc")]
+    pub fn resize(&mut self, new_capacity: usize) -> Result<(), Full>
+    where
+        T: Default,
+    {
+        if new_capacity < self.length {
+            return Err(Full);
+        }
+        let ManagedSlice::Owned(ref mut storage) = self.storage else {
+            return Err(Full);
+        };
+
+        storage.rotate_left(self.read_at);
+        storage.resize_with(new_capacity, T::default);
+        self.read_at = 0;
+        Ok(())
+    }
+
     /// Return the current number of elements in the ring buffer.
     ///
     /// 返回环形缓冲区中当前的元素数量
@@ -112,6 +154,23 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
         self.window() == 0
     }
 
+    /// Return the index in the storage of the first allocated element.
+    ///
+    /// This is mostly useful to reason about wraparound, e.g. in tests.
+    pub fn read_at(&self) -> usize {
+        self.read_at
+    }
+
+    /// Return the index in the storage of the allocated element at the given
+    /// offset past the first one, or `None` if there is no such element.
+    pub fn physical_index(&self, logical_offset: usize) -> Option<usize> {
+        if logical_offset < self.length {
+            Some(self.get_idx_unchecked(logical_offset))
+        } else {
+            None
+        }
+    }
+
     /// Shorthand for `(self.read + idx) % self.capacity()` with an
     /// additional check to ensure that the capacity is not zero.
     ///
@@ -275,6 +334,23 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
         size_1 + size_2
     }
 
+    /// Enqueue all the elements of the given slice into the buffer, or none of
+    /// them.
+    ///
+    /// Unlike [`enqueue_slice`](Self::enqueue_slice), returns `Err(Full)`,
+    /// and leaves the buffer untouched, if the slice does not fit as a whole.
+    pub fn try_enqueue_slice(&mut self, data: &[T]) -> Result<(), Full>
+    where
+        T: Copy,
+    {
+        if self.window() < data.len() {
+            return Err(Full);
+        }
+        let size = self.enqueue_slice(data);
+        debug_assert_eq!(size, data.len());
+        Ok(())
+    }
+
     /// Call `f` with the largest contiguous slice of allocated buffer elements,
     /// and dequeue the amount of elements returned by `f`.
     ///
@@ -346,6 +422,46 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
     }
 }
 
+/// This is the "overwriting" ring buffer interface: when the buffer is full,
+/// the oldest elements are silently discarded to make room for new ones,
+/// which suits logs or telemetry where only the latest elements matter.
+impl<'a, T: 'a> RingBuffer<'a, T> {
+    /// Enqueue a single element into the buffer, and return a reference to it.
+    ///
+    /// Unlike [`enqueue_one`](Self::enqueue_one), if the buffer is full, the
+    /// oldest element is dequeued and discarded to make room, without any
+    /// indication. Returns `None`, and does nothing, if the buffer has no
+    /// capacity.
+    pub fn enqueue_one_overwrite(&mut self) -> Option<&mut T> {
+        if self.capacity() == 0 {
+            return None;
+        }
+        if self.is_full() {
+            self.dequeue_allocated(1);
+        }
+
+        let index = self.get_idx_unchecked(self.length);
+        self.length += 1;
+        Some(&mut self.storage[index])
+    }
+
+    /// Enqueue a slice of elements into the buffer, and return the amount of
+    /// elements enqueued.
+    ///
+    /// Unlike [`enqueue_slice`](Self::enqueue_slice), as many of the oldest
+    /// elements as needed are dequeued and discarded to make room for the
+    /// slice, without any indication. If the slice is longer than the capacity
+    /// of the buffer, only its last elements are enqueued.
+    pub fn enqueue_slice_overwrite(&mut self, data: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let data = &data[data.len().saturating_sub(self.capacity())..];
+        self.dequeue_allocated(data.len().saturating_sub(self.window()));
+        self.enqueue_slice(data)
+    }
+}
+
 /// This is the "random access" ring buffer interface: it operates with element
 /// slices, and allows to access elements of the buffer that are not adjacent to
 /// its head or tail.
@@ -412,11 +528,11 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
     /// up to the given size.
     #[must_use]
     pub fn get_allocated(&self, offset: usize, mut size: usize) -> &[T] {
-        let start_at = self.get_idx(offset);
         // We can't read past the end of the allocated data.
-        if offset > self.length {
-            return &mut [];
+        if offset >= self.length {
+            return &[];
         }
+        let start_at = self.get_idx(offset);
         // We can't read more than we have allocated.
         let clamped_length = self.length - offset;
         if size > clamped_length {
@@ -462,6 +578,45 @@ impl<'a, T: 'a> RingBuffer<'a, T> {
         self.length -= count;
         self.read_at = self.get_idx(count);
     }
+
+    /// Return an iterator over the allocated buffer elements, from the first
+    /// one to the last one, without dequeueing them.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let start_at = self.get_idx(0);
+        let until_end = cmp::min(self.length, self.capacity() - start_at);
+        let wrapped = self.length - until_end;
+        self.storage[start_at..start_at + until_end]
+            .iter()
+            .chain(self.storage[..wrapped].iter())
+    }
+
+    /// Return an iterator over the allocated buffer elements that allows
+    /// modifying them, from the first one to the last one, without dequeueing
+    /// them.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        let start_at = self.get_idx(0);
+        let until_end = cmp::min(self.length, self.capacity() - start_at);
+        let wrapped = self.length - until_end;
+        let (head, tail) = self.storage.split_at_mut(start_at);
+        tail[..until_end]
+            .iter_mut()
+            .chain(head[..wrapped].iter_mut())
+    }
+
+    /// Rearrange the storage so that the allocated buffer elements are
+    /// contiguous, and return them, without dequeueing them.
+    ///
+    /// If the elements wrap around the end of the storage, they are rotated
+    /// in place to its start; otherwise, nothing is moved.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.length == 0 {
+            self.read_at = 0;
+        } else if self.read_at + self.length > self.capacity() {
+            self.storage.rotate_left(self.read_at);
+            self.read_at = 0;
+        }
+        &mut self.storage[self.read_at..self.read_at + self.length]
+    }
 }
 
 impl<'a, T: 'a> From<ManagedSlice<'a, T>> for RingBuffer<'a, T> {
@@ -470,6 +625,32 @@ impl<'a, T: 'a> From<ManagedSlice<'a, T>> for RingBuffer<'a, T> {
     }
 }
 
+/// Extending enqueues elements one at a time until the buffer is full; the
+/// remaining elements of the iterator are dropped and lost.
+impl<'a, T: 'a> Extend<T> for RingBuffer<'a, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            match self.enqueue_one() {
+                Ok(slot) => *slot = item,
+                Err(Full) => break,
+            }
+        }
+    }
+}
+
+/// Cloning always copies the elements into owned storage, even if the
+/// original borrows its storage, so that the clone is independent of it.
+#[cfg(feature = "alloc")]
+impl<'a, T: Clone + 'a> Clone for RingBuffer<'a, T> {
+    fn clone(&self) -> Self {
+        RingBuffer {
+            storage: ManagedSlice::Owned(self.storage.to_vec()),
+            read_at: self.read_at,
+            length: self.length,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -652,6 +833,24 @@ mod test {
         assert_eq!(&ring.storage[..], b"abcdefghijkl");
     }
 
+    #[test]
+    fn test_buffer_try_enqueue_slice() {
+        let mut ring = RingBuffer::new(vec![b'.'; 8]);
+        assert_eq!(ring.try_enqueue_slice(b"abcdef"), Ok(()));
+        assert_eq!(ring.try_enqueue_slice(b"ghi"), Err(Full));
+        assert_eq!(ring.len(), 6);
+        assert_eq!(&ring.storage[..], b"abcdef..");
+
+        // The slice wraps around the end of the storage.
+        assert_eq!(ring.dequeue_many(4), b"abcd");
+        assert_eq!(ring.try_enqueue_slice(b"ghijkl"), Ok(()));
+        assert!(ring.is_full());
+        assert_eq!(&ring.storage[..], b"ijklefgh");
+        assert_eq!(ring.try_enqueue_slice(b"m"), Err(Full));
+        assert_eq!(ring.try_enqueue_slice(b""), Ok(()));
+        assert_eq!(ring.len(), 8);
+    }
+
     #[test]
     fn test_buffer_dequeue_many_with() {
         let mut ring = RingBuffer::new(vec![b'.'; 12]);
@@ -775,6 +974,17 @@ mod test {
         assert_eq!(&ring.storage[..], b"ABCDEFGHIJKL");
     }
 
+    #[test]
+    fn test_buffer_reset() {
+        let mut ring = RingBuffer::new(vec![b'.'; 12]);
+        assert_eq!(ring.enqueue_slice(b"abcdef"), 6);
+        assert_eq!(ring.dequeue_many(2), b"ab");
+
+        ring.reset();
+        assert_eq!(ring.len(), 0);
+        assert_eq!(&ring.storage[..], &[0; 12]);
+    }
+
     #[test]
     fn test_buffer_write_unallocated() {
         let mut ring = RingBuffer::new(vec![b'.'; 12]);
@@ -833,6 +1043,222 @@ mod test {
         assert_eq!(&data[..], b"mno\x00\x00\x00");
     }
 
+    #[test]
+    fn test_buffer_iter() {
+        let mut ring = RingBuffer::new(vec![b'.'; 8]);
+        assert_eq!(ring.iter().count(), 0);
+
+        assert_eq!(ring.enqueue_slice(b"abcdef"), 6);
+        assert!(ring.iter().copied().eq(b"abcdef".iter().copied()));
+
+        // Wrap around the end of the storage.
+        assert_eq!(ring.dequeue_many(4), b"abcd");
+        assert_eq!(ring.enqueue_slice(b"ghijk"), 5);
+        assert_eq!(ring.get_allocated(0, 7), b"efgh");
+        assert!(ring.iter().copied().eq(b"efghijk".iter().copied()));
+
+        for elem in ring.iter_mut() {
+            *elem = elem.to_ascii_uppercase();
+        }
+        assert!(ring.iter().copied().eq(b"EFGHIJK".iter().copied()));
+        assert_eq!(ring.len(), 7);
+
+        // Only the allocated elements are visited.
+        let mut data = [0; 8];
+        assert_eq!(ring.dequeue_slice(&mut data[..]), 7);
+        assert_eq!(&data[..7], b"EFGHIJK");
+        assert_eq!(ring.iter().count(), 0);
+        assert_eq!(ring.iter_mut().count(), 0);
+    }
+
+    #[test]
+    fn test_buffer_get_allocated_past_end() {
+        let mut ring = RingBuffer::new(vec![b'.'; 6]);
+        for k in 0..8 {
+            assert_eq!(ring.get_allocated(0, k), b"");
+            assert_eq!(ring.get_allocated(1, k), b"");
+        }
+
+        assert_eq!(ring.enqueue_slice(b"abcd"), 4);
+        assert_eq!(ring.dequeue_many(3), b"abc");
+        assert_eq!(ring.enqueue_slice(b"efghi"), 5);
+        assert!(ring.is_full());
+        for k in 0..8 {
+            assert_eq!(ring.get_allocated(ring.len(), k), b"");
+            assert_eq!(ring.get_allocated(ring.len() + 1, k), b"");
+        }
+
+        assert_eq!(ring.dequeue_many(3), b"def");
+        for k in 0..8 {
+            assert_eq!(ring.get_allocated(ring.len(), k), b"");
+        }
+        assert_eq!(ring.get_allocated(2, 8), b"i");
+    }
+
+    #[test]
+    fn test_buffer_physical_index() {
+        let mut ring = RingBuffer::new(vec![b'.'; 6]);
+        assert_eq!(ring.read_at(), 0);
+        assert_eq!(ring.physical_index(0), None);
+
+        assert_eq!(ring.enqueue_slice(b"abcd"), 4);
+        assert_eq!(ring.dequeue_many(3), b"abc");
+        assert_eq!(ring.enqueue_slice(b"efgh"), 4);
+        assert_eq!(ring.read_at(), 3);
+        let indices: Vec<_> = (0..6).map(|offset| ring.physical_index(offset)).collect();
+        assert_eq!(indices, [Some(3), Some(4), Some(5), Some(0), Some(1), None]);
+        assert_eq!(ring.storage[ring.physical_index(3).unwrap()], b'g');
+
+        let no_capacity: RingBuffer<u8> = RingBuffer::new(vec![]);
+        assert_eq!(no_capacity.read_at(), 0);
+        assert_eq!(no_capacity.physical_index(0), None);
+    }
+
+    #[test]
+    fn test_buffer_extend() {
+        let mut ring = RingBuffer::new(vec![0; 5]);
+        assert_eq!(ring.enqueue_slice(&[1, 2, 3]), 3);
+        assert_eq!(ring.dequeue_many(2), &[1, 2]);
+
+        // The elements wrap around, and those that do not fit are dropped.
+        ring.extend(4..10);
+        assert!(ring.is_full());
+        assert!(ring.iter().copied().eq(3..8));
+
+        ring.extend(10..12);
+        assert!(ring.iter().copied().eq(3..8));
+
+        let mut ring = RingBuffer::from_iter_with_capacity(4, b"ab".iter().copied());
+        assert_eq!(ring.capacity(), 4);
+        assert_eq!(ring.dequeue_many(4), b"ab");
+
+        let mut ring = RingBuffer::from_iter_with_capacity(4, b"abcdef".iter().copied());
+        assert!(ring.is_full());
+        assert_eq!(ring.dequeue_many(4), b"abcd");
+    }
+
+    #[test]
+    fn test_buffer_clone() {
+        let mut storage = [b'.'; 6];
+        let mut ring = RingBuffer::new(&mut storage[..]);
+        assert_eq!(ring.enqueue_slice(b"abcd"), 4);
+        assert_eq!(ring.dequeue_many(3), b"abc");
+        assert_eq!(ring.enqueue_slice(b"efgh"), 4);
+
+        let mut clone = ring.clone();
+        assert!(matches!(clone.storage, ManagedSlice::Owned(_)));
+        assert_eq!(clone.read_at, ring.read_at);
+        assert_eq!(clone.len(), ring.len());
+        assert!(clone.iter().eq(ring.iter()));
+
+        assert_eq!(clone.dequeue_many(5), b"def");
+        assert_eq!(clone.dequeue_many(5), b"gh");
+        assert!(clone.is_empty());
+        assert_eq!(ring.dequeue_many(5), b"def");
+        assert_eq!(ring.dequeue_many(5), b"gh");
+
+        // The clone no longer shares storage with the original.
+        assert_eq!(ring.enqueue_slice(b"xyz"), 3);
+        assert_eq!(clone.enqueue_slice(b"123"), 3);
+        assert_eq!(ring.dequeue_many(3), b"xyz");
+        assert_eq!(clone.dequeue_many(3), b"123");
+    }
+
+    #[test]
+    fn test_buffer_make_contiguous() {
+        let mut ring = RingBuffer::new(vec![b'.'; 8]);
+        assert_eq!(ring.make_contiguous(), b"");
+
+        // Already contiguous elements are left in place.
+        assert_eq!(ring.enqueue_slice(b"abcdef"), 6);
+        assert_eq!(ring.dequeue_many(2), b"ab");
+        assert_eq!(ring.make_contiguous(), b"cdef");
+        assert_eq!(ring.read_at, 2);
+
+        // Wrap around the end of the storage.
+        assert_eq!(ring.enqueue_slice(b"ghij"), 4);
+        assert!(ring.read_at + ring.len() > ring.capacity());
+        assert_eq!(ring.get_allocated(0, 8), b"cdefgh");
+        assert_eq!(ring.make_contiguous(), b"cdefghij");
+        assert_eq!(ring.read_at, 0);
+        assert_eq!(ring.get_allocated(0, 8), b"cdefghij");
+
+        ring.make_contiguous()[0] = b'C';
+        assert_eq!(ring.dequeue_many(3), b"Cde");
+        assert_eq!(ring.enqueue_slice(b"klmn"), 3);
+        assert_eq!(ring.make_contiguous(), b"fghijklm");
+        assert_eq!(ring.dequeue_many(8), b"fghijklm");
+        assert_eq!(ring.make_contiguous(), b"");
+
+        let mut no_capacity: RingBuffer<u8> = RingBuffer::new(vec![]);
+        assert_eq!(no_capacity.make_contiguous(), b"");
+    }
+
+    #[test]
+    fn test_buffer_resize() {
+        let mut ring = RingBuffer::new(vec![b'.'; 6]);
+        assert_eq!(ring.enqueue_slice(b"abcd"), 4);
+        assert_eq!(ring.dequeue_many(3), b"abc");
+        assert_eq!(ring.enqueue_slice(b"efghi"), 5);
+        assert_eq!(ring.get_allocated(0, 6), b"def");
+
+        assert_eq!(ring.resize(10), Ok(()));
+        assert_eq!(ring.capacity(), 10);
+        assert_eq!(ring.len(), 6);
+        assert_eq!(ring.get_allocated(0, 10), b"defghi");
+        assert_eq!(ring.enqueue_slice(b"jklmn"), 4);
+        assert!(ring.iter().copied().eq(b"defghijklm".iter().copied()));
+
+        assert_eq!(ring.dequeue_many(5), b"defgh");
+        assert_eq!(ring.resize(4), Err(Full));
+        assert_eq!(ring.capacity(), 10);
+        assert_eq!(ring.resize(5), Ok(()));
+        assert_eq!(ring.capacity(), 5);
+        assert!(ring.is_full());
+        assert_eq!(ring.dequeue_many(5), b"ijklm");
+
+        let mut storage = [0; 4];
+        let mut ring = RingBuffer::new(&mut storage[..]);
+        assert_eq!(ring.resize(8), Err(Full));
+        assert_eq!(ring.capacity(), 4);
+    }
+
+    #[test]
+    fn test_buffer_enqueue_one_overwrite() {
+        let mut ring = RingBuffer::new(vec![0; 3]);
+        for i in 1..=3 {
+            *ring.enqueue_one_overwrite().unwrap() = i;
+        }
+        assert!(ring.is_full());
+
+        // The oldest elements make room for the new ones, across the end of
+        // the storage.
+        for i in 4..=7 {
+            *ring.enqueue_one_overwrite().unwrap() = i;
+            assert_eq!(ring.len(), 3);
+        }
+        assert_eq!(ring.dequeue_one(), Ok(&mut 5));
+        assert_eq!(ring.dequeue_one(), Ok(&mut 6));
+        assert_eq!(ring.dequeue_one(), Ok(&mut 7));
+        assert_eq!(ring.dequeue_one(), Err(Empty));
+    }
+
+    #[test]
+    fn test_buffer_enqueue_slice_overwrite() {
+        let mut ring = RingBuffer::new(vec![b'.'; 6]);
+        assert_eq!(ring.enqueue_slice_overwrite(b"abcd"), 4);
+        assert_eq!(ring.enqueue_slice_overwrite(b"efg"), 3);
+        assert_eq!(ring.len(), 6);
+        let mut data = [0; 6];
+        assert_eq!(ring.read_allocated(0, &mut data), 6);
+        assert_eq!(&data, b"bcdefg");
+
+        // Only the end of a slice larger than the buffer is kept.
+        assert_eq!(ring.enqueue_slice_overwrite(b"hijklmnop"), 6);
+        assert_eq!(ring.read_allocated(0, &mut data), 6);
+        assert_eq!(&data, b"klmnop");
+    }
+
     #[test]
     fn test_buffer_with_no_capacity() {
         let mut no_capacity: RingBuffer<u8> = RingBuffer::new(vec![]);
@@ -845,6 +1271,11 @@ mod test {
         assert_eq!(no_capacity.enqueue_many(0), &[]);
         assert_eq!(no_capacity.enqueue_one(), Err(Full));
         assert_eq!(no_capacity.contiguous_window(), 0);
+        assert_eq!(no_capacity.iter().count(), 0);
+        assert_eq!(no_capacity.iter_mut().count(), 0);
+        assert_eq!(no_capacity.enqueue_one_overwrite(), None);
+        assert_eq!(no_capacity.enqueue_slice_overwrite(b"abc"), 0);
+        assert!(no_capacity.is_empty());
     }
 
     /// Use the buffer a bit. Then empty it and put in an item of
@@ -0,0 +1,802 @@
+// See https://tools.ietf.org/html/rfc7252 for the CoAP specification.
+
+use byteorder::{ByteOrder, NetworkEndian};
+use core::fmt;
+
+use super::{Error, Result};
+
+/// The default UDP port for CoAP.
+pub const DEFAULT_PORT: u16 = 5683;
+
+/// The only CoAP version defined by RFC 7252.
+pub const VERSION: u8 = 1;
+
+/// The maximum length of a token.
+pub const MAX_TOKEN_LEN: usize = 8;
+
+const PAYLOAD_MARKER: u8 = 0xff;
+
+enum_with_unknown! {
+    /// The type of a CoAP message.
+    pub enum MessageType(u8) {
+        Confirmable = 0,
+        NonConfirmable = 1,
+        Acknowledgement = 2,
+        Reset = 3,
+    }
+}
+
+enum_with_unknown! {
+    /// The code of a CoAP message: its request method or response code.
+    pub enum Code(u8) {
+        Empty = 0x00,
+        Get = 0x01,
+        Post = 0x02,
+        Put = 0x03,
+        Delete = 0x04,
+        Created = 0x41,
+        Deleted = 0x42,
+        Valid = 0x43,
+        Changed = 0x44,
+        Content = 0x45,
+        BadRequest = 0x80,
+        Unauthorized = 0x81,
+        BadOption = 0x82,
+        Forbidden = 0x83,
+        NotFound = 0x84,
+        MethodNotAllowed = 0x85,
+        InternalServerError = 0xa0,
+        NotImplemented = 0xa1,
+        ServiceUnavailable = 0xa3,
+    }
+}
+
+impl Code {
+    /// Return the class of the code, i.e. the `c` in `c.dd`.
+    pub fn class(&self) -> u8 {
+        u8::from(*self) >> 5
+    }
+
+    /// Return the detail of the code, i.e. the `dd` in `c.dd`.
+    pub fn detail(&self) -> u8 {
+        u8::from(*self) & 0x1f
+    }
+
+    /// Return whether the code is a request method.
+    pub fn is_request(&self) -> bool {
+        self.class() == 0 && *self != Code::Empty
+    }
+
+    /// Return whether the code is a response code.
+    pub fn is_response(&self) -> bool {
+        matches!(self.class(), 2 | 4 | 5)
+    }
+}
+
+enum_with_unknown! {
+    /// The number of a CoAP option.
+    pub enum OptionNumber(u16) {
+        IfMatch = 1,
+        UriHost = 3,
+        ETag = 4,
+        IfNoneMatch = 5,
+        Observe = 6,
+        UriPort = 7,
+        LocationPath = 8,
+        UriPath = 11,
+        ContentFormat = 12,
+        MaxAge = 14,
+        UriQuery = 15,
+        Accept = 17,
+        LocationQuery = 20,
+        Block2 = 23,
+        Block1 = 27,
+        Size2 = 28,
+        ProxyUri = 35,
+        ProxyScheme = 39,
+        Size1 = 60,
+    }
+}
+
+/// A representation of a single CoAP option.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CoapOption<'a> {
+    pub number: OptionNumber,
+    pub value: &'a [u8],
+}
+
+/// Decode the extended form of an option delta or length nibble.
+fn read_extended(nibble: u8, data: &mut &[u8]) -> Result<u16> {
+    match nibble {
+        0..=12 => Ok(nibble as u16),
+        13 => {
+            let (&ext, rest) = data.split_first().ok_or(Error)?;
+            *data = rest;
+            Ok(ext as u16 + 13)
+        }
+        14 => {
+            if data.len() < 2 {
+                return Err(Error);
+            }
+            let ext = NetworkEndian::read_u16(&data[..2]);
+            *data = &data[2..];
+            ext.checked_add(269).ok_or(Error)
+        }
+        _ => Err(Error),
+    }
+}
+
+/// Return the nibble and the length of the extended form encoding `value`.
+const fn extended_form(value: u16) -> (u8, usize) {
+    match value {
+        0..=12 => (value as u8, 0),
+        13..=268 => (13, 1),
+        _ => (14, 2),
+    }
+}
+
+/// Write the extended form of an option delta or length, returning the
+/// number of octets written.
+fn write_extended(value: u16, data: &mut [u8]) -> usize {
+    match extended_form(value) {
+        (13, len) => {
+            data[0] = (value - 13) as u8;
+            len
+        }
+        (14, len) => {
+            NetworkEndian::write_u16(&mut data[..2], value - 269);
+            len
+        }
+        (_, len) => len,
+    }
+}
+
+/// An iterator over the options of a CoAP message.
+///
+/// Option numbers are reconstructed from the deltas in the message. If the
+/// options are malformed, an error is returned once and iteration stops.
+#[derive(Debug, Clone)]
+pub struct OptionsIterator<'a> {
+    data: &'a [u8],
+    number: u16,
+}
+
+impl<'a> OptionsIterator<'a> {
+    /// Create an iterator over the options starting at the beginning of
+    /// `data`, and ending at the end of `data` or at the payload marker.
+    pub fn new(data: &'a [u8]) -> OptionsIterator<'a> {
+        OptionsIterator { data, number: 0 }
+    }
+
+    /// Return the data following the options that have been iterated over,
+    /// starting with the payload marker if there is one.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    fn next_option(&mut self) -> Result<CoapOption<'a>> {
+        let (&header, mut data) = self.data.split_first().ok_or(Error)?;
+        let delta = read_extended(header >> 4, &mut data)?;
+        let len = read_extended(header & 0x0f, &mut data)? as usize;
+        if data.len() < len {
+            return Err(Error);
+        }
+
+        self.number = self.number.checked_add(delta).ok_or(Error)?;
+        self.data = &data[len..];
+        Ok(CoapOption {
+            number: OptionNumber::from(self.number),
+            value: &data[..len],
+        })
+    }
+}
+
+/// An iterator over a list of options in order of option number, keeping
+/// the order of options with the same number.
+#[derive(Debug, Clone)]
+struct SortedOptions<'a> {
+    options: &'a [CoapOption<'a>],
+    last: Option<(u16, usize)>,
+}
+
+impl<'a> Iterator for SortedOptions<'a> {
+    type Item = CoapOption<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.last;
+        let (key, option) = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| ((u16::from(option.number), index), option))
+            .filter(|&(key, _)| last.map_or(true, |last| key > last))
+            .min_by_key(|&(key, _)| key)?;
+        self.last = Some(key);
+        Some(*option)
+    }
+}
+
+/// The options of a CoAP message representation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Options<'a> {
+    /// A list of options, in any order. They are emitted sorted by option
+    /// number; options with the same number keep their relative order.
+    List(&'a [CoapOption<'a>]),
+    /// Options in their wire format, as found in a parsed message.
+    Encoded(&'a [u8]),
+}
+
+impl<'a> Options<'a> {
+    /// Return an iterator over the options, in the order they are emitted.
+    ///
+    /// Iteration stops at the first malformed option.
+    pub fn iter(&self) -> impl Iterator<Item = CoapOption<'a>> {
+        let (list, encoded): (&'a [CoapOption<'a>], &'a [u8]) = match *self {
+            Options::List(list) => (list, &[]),
+            Options::Encoded(encoded) => (&[], encoded),
+        };
+        let sorted = SortedOptions {
+            options: list,
+            last: None,
+        };
+        sorted.chain(OptionsIterator::new(encoded).map_while(Result::ok))
+    }
+}
+
+impl<'a> Iterator for OptionsIterator<'a> {
+    type Item = Result<CoapOption<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.data.first() {
+            None | Some(&PAYLOAD_MARKER) => None,
+            Some(_) => {
+                let option = self.next_option();
+                if option.is_err() {
+                    self.data = &[];
+                }
+                Some(option)
+            }
+        }
+    }
+}
+
+/// A read/write wrapper around a CoAP message buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+mod field {
+    #![allow(non_snake_case)]
+
+    use crate::wire::field::*;
+
+    pub const VER_T_TKL: usize = 0;
+    pub const CODE: usize = 1;
+    pub const MESSAGE_ID: Field = 2..4;
+
+    pub const fn TOKEN(token_len: u8) -> Field {
+        MESSAGE_ID.end..MESSAGE_ID.end + token_len as usize
+    }
+}
+
+/// The length of the fixed CoAP header.
+pub const HEADER_LEN: usize = field::MESSAGE_ID.end;
+
+impl<T: AsRef<[u8]>> Packet<T> {
+    /// Imbue a raw octet buffer with CoAP message structure.
+    pub const fn new_unchecked(buffer: T) -> Packet<T> {
+        Packet { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Packet<T>> {
+        let packet = Self::new_unchecked(buffer);
+        packet.check_len()?;
+        Ok(packet)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is too short, if the token length
+    /// is reserved, or if the options are malformed.
+    ///
+    /// The result of this check is invalidated by calling [set_token_len].
+    ///
+    /// [set_token_len]: #method.set_token_len
+    pub fn check_len(&self) -> Result<()> {
+        let len = self.buffer.as_ref().len();
+        if len < HEADER_LEN {
+            return Err(Error);
+        }
+        let token_len = self.token_len();
+        if token_len as usize > MAX_TOKEN_LEN || len < field::TOKEN(token_len).end {
+            return Err(Error);
+        }
+
+        let mut options = self.options();
+        for option in &mut options {
+            option?;
+        }
+        // A payload marker followed by an empty payload is a format error.
+        if options.remaining() == [PAYLOAD_MARKER] {
+            return Err(Error);
+        }
+        Ok(())
+    }
+
+    /// Consume the packet, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the version field.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::VER_T_TKL] >> 6
+    }
+
+    /// Return the message type field.
+    #[inline]
+    pub fn msg_type(&self) -> MessageType {
+        let data = self.buffer.as_ref();
+        MessageType::from((data[field::VER_T_TKL] >> 4) & 0x03)
+    }
+
+    /// Return the token length field.
+    #[inline]
+    pub fn token_len(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::VER_T_TKL] & 0x0f
+    }
+
+    /// Return the code field.
+    #[inline]
+    pub fn code(&self) -> Code {
+        let data = self.buffer.as_ref();
+        Code::from(data[field::CODE])
+    }
+
+    /// Return the message ID field.
+    #[inline]
+    pub fn message_id(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::MESSAGE_ID])
+    }
+
+    /// Return the token.
+    #[inline]
+    pub fn token(&self) -> &[u8] {
+        let data = self.buffer.as_ref();
+        &data[field::TOKEN(self.token_len())]
+    }
+
+    /// Return an iterator over the options.
+    pub fn options(&self) -> OptionsIterator<'_> {
+        let data = self.buffer.as_ref();
+        OptionsIterator::new(&data[field::TOKEN(self.token_len()).end..])
+    }
+
+    /// Return the payload, which follows the payload marker if there is one.
+    pub fn payload(&self) -> &[u8] {
+        let mut options = self.options();
+        for _ in &mut options {}
+        match options.remaining() {
+            [PAYLOAD_MARKER, payload @ ..] => payload,
+            _ => &[],
+        }
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
+    /// Set the version field.
+    #[inline]
+    pub fn set_version(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::VER_T_TKL] = (data[field::VER_T_TKL] & 0x3f) | (value << 6);
+    }
+
+    /// Set the message type field.
+    #[inline]
+    pub fn set_msg_type(&mut self, value: MessageType) {
+        let data = self.buffer.as_mut();
+        data[field::VER_T_TKL] = (data[field::VER_T_TKL] & 0xcf) | ((u8::from(value) & 0x03) << 4);
+    }
+
+    /// Set the token length field.
+    #[inline]
+    pub fn set_token_len(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::VER_T_TKL] = (data[field::VER_T_TKL] & 0xf0) | (value & 0x0f);
+    }
+
+    /// Set the code field.
+    #[inline]
+    pub fn set_code(&mut self, value: Code) {
+        let data = self.buffer.as_mut();
+        data[field::CODE] = value.into();
+    }
+
+    /// Set the message ID field.
+    #[inline]
+    pub fn set_message_id(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::MESSAGE_ID], value)
+    }
+
+    /// Return a mutable pointer to the token.
+    #[inline]
+    pub fn token_mut(&mut self) -> &mut [u8] {
+        let token = field::TOKEN(self.token_len());
+        let data = self.buffer.as_mut();
+        &mut data[token]
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Packet<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// A high-level representation of a CoAP message.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Repr<'a> {
+    pub msg_type: MessageType,
+    pub code: Code,
+    pub message_id: u16,
+    pub token: &'a [u8],
+    pub options: Options<'a>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Repr<'a> {
+    /// Parse a CoAP message and return a high-level representation.
+    pub fn parse<T>(packet: &Packet<&'a T>) -> Result<Repr<'a>>
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        packet.check_len()?;
+
+        if packet.version() != VERSION {
+            return Err(Error);
+        }
+        // An empty message must not contain anything but the header.
+        if packet.code() == Code::Empty && packet.buffer.as_ref().len() != HEADER_LEN {
+            return Err(Error);
+        }
+
+        let data = packet.buffer.as_ref();
+        let token = &data[field::TOKEN(packet.token_len())];
+        let options_data = &data[token.len() + HEADER_LEN..];
+        let mut options = OptionsIterator::new(options_data);
+        for _ in &mut options {}
+        let options_len = options_data.len() - options.remaining().len();
+        let payload = match options.remaining() {
+            [PAYLOAD_MARKER, payload @ ..] => payload,
+            _ => &[],
+        };
+
+        Ok(Repr {
+            msg_type: packet.msg_type(),
+            code: packet.code(),
+            message_id: packet.message_id(),
+            token,
+            options: Options::Encoded(&options_data[..options_len]),
+            payload,
+        })
+    }
+
+    /// Return the length of a message that will be emitted from this
+    /// high-level representation.
+    pub fn buffer_len(&self) -> usize {
+        let mut len = HEADER_LEN + self.token.len();
+
+        let mut number = 0;
+        for option in self.options.iter() {
+            let option_number = u16::from(option.number);
+            let (_, delta_len) = extended_form(option_number - number);
+            let (_, value_len) = extended_form(option.value.len() as u16);
+            len += 1 + delta_len + value_len + option.value.len();
+            number = option_number;
+        }
+
+        if !self.payload.is_empty() {
+            len += 1 + self.payload.len();
+        }
+        len
+    }
+
+    /// Emit a high-level representation into a CoAP message.
+    ///
+    /// # Panics
+    /// This function panics if the token is longer than eight octets.
+    pub fn emit<T>(&self, packet: &mut Packet<&mut T>)
+    where
+        T: AsRef<[u8]> + AsMut<[u8]> + ?Sized,
+    {
+        assert!(self.token.len() <= MAX_TOKEN_LEN);
+
+        packet.set_version(VERSION);
+        packet.set_msg_type(self.msg_type);
+        packet.set_token_len(self.token.len() as u8);
+        packet.set_code(self.code);
+        packet.set_message_id(self.message_id);
+        packet.token_mut().copy_from_slice(self.token);
+
+        let data = &mut packet.buffer.as_mut()[HEADER_LEN + self.token.len()..];
+        let mut offset = 0;
+        let mut number = 0;
+        for option in self.options.iter() {
+            let option_number = u16::from(option.number);
+            let delta = option_number - number;
+            let value_len = option.value.len() as u16;
+
+            let (delta_nibble, _) = extended_form(delta);
+            let (len_nibble, _) = extended_form(value_len);
+            data[offset] = (delta_nibble << 4) | len_nibble;
+            offset += 1;
+            offset += write_extended(delta, &mut data[offset..]);
+            offset += write_extended(value_len, &mut data[offset..]);
+            data[offset..offset + option.value.len()].copy_from_slice(option.value);
+            offset += option.value.len();
+            number = option_number;
+        }
+
+        if !self.payload.is_empty() {
+            data[offset] = PAYLOAD_MARKER;
+            offset += 1;
+            data[offset..offset + self.payload.len()].copy_from_slice(self.payload);
+        }
+    }
+}
+
+impl<'a> fmt::Display for Repr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CoAP {:?} {}.{:02} mid={} tkl={} len={}",
+            self.msg_type,
+            self.code.class(),
+            self.code.detail(),
+            self.message_id,
+            self.token.len(),
+            self.payload.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // CON GET, message ID 0x1234, token 0xcafe, Uri-Path "sensors", Uri-Path "temperature".
+    static GET_BYTES: [u8; 26] = [
+        0x42, 0x01, 0x12, 0x34, 0xca, 0xfe, 0xb7, b's', b'e', b'n', b's', b'o', b'r', b's', 0x0b,
+        b't', b'e', b'm', b'p', b'e', b'r', b'a', b't', b'u', b'r', b'e',
+    ];
+
+    // ACK 2.05 Content, message ID 0x1234, token 0xcafe, Content-Format 0, payload "22.5".
+    static CONTENT_BYTES: [u8; 12] = [
+        0x62, 0x45, 0x12, 0x34, 0xca, 0xfe, 0xc0, 0xff, b'2', b'2', b'.', b'5',
+    ];
+
+    const GET_OPTIONS: [CoapOption<'static>; 2] = [
+        CoapOption {
+            number: OptionNumber::UriPath,
+            value: b"sensors",
+        },
+        CoapOption {
+            number: OptionNumber::UriPath,
+            value: b"temperature",
+        },
+    ];
+
+    const CONTENT_OPTIONS: [CoapOption<'static>; 1] = [CoapOption {
+        number: OptionNumber::ContentFormat,
+        value: b"",
+    }];
+
+    fn get_repr() -> Repr<'static> {
+        Repr {
+            msg_type: MessageType::Confirmable,
+            code: Code::Get,
+            message_id: 0x1234,
+            token: &[0xca, 0xfe],
+            options: Options::List(&GET_OPTIONS),
+            payload: &[],
+        }
+    }
+
+    fn content_repr() -> Repr<'static> {
+        Repr {
+            msg_type: MessageType::Acknowledgement,
+            code: Code::Content,
+            message_id: 0x1234,
+            token: &[0xca, 0xfe],
+            options: Options::List(&CONTENT_OPTIONS),
+            payload: b"22.5",
+        }
+    }
+
+    #[test]
+    fn test_deconstruct_get() {
+        let packet = Packet::new_checked(&GET_BYTES[..]).unwrap();
+        assert_eq!(packet.version(), 1);
+        assert_eq!(packet.msg_type(), MessageType::Confirmable);
+        assert_eq!(packet.token_len(), 2);
+        assert_eq!(packet.code(), Code::Get);
+        assert!(packet.code().is_request());
+        assert_eq!(packet.message_id(), 0x1234);
+        assert_eq!(packet.token(), &[0xca, 0xfe]);
+
+        let mut options = packet.options();
+        assert_eq!(options.next(), Some(Ok(GET_OPTIONS[0])));
+        // The second Uri-Path option has a delta of zero.
+        assert_eq!(options.remaining()[0] >> 4, 0);
+        assert_eq!(options.next(), Some(Ok(GET_OPTIONS[1])));
+        assert_eq!(options.next(), None);
+        assert_eq!(packet.payload(), b"");
+    }
+
+    #[test]
+    fn test_get_roundtrip() {
+        let repr = get_repr();
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+        assert_eq!(&bytes[..], &GET_BYTES[..]);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        let parsed = Repr::parse(&packet).unwrap();
+        assert_eq!(
+            parsed,
+            Repr {
+                options: Options::Encoded(&GET_BYTES[6..]),
+                ..repr
+            }
+        );
+        assert!(parsed.options.iter().eq(GET_OPTIONS));
+        assert!(packet.options().map(Result::unwrap).eq(GET_OPTIONS));
+
+        // A parsed representation emits the same message.
+        assert_eq!(parsed.buffer_len(), GET_BYTES.len());
+        let mut bytes = vec![0xa5; parsed.buffer_len()];
+        parsed.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+        assert_eq!(&bytes[..], &GET_BYTES[..]);
+    }
+
+    #[test]
+    fn test_content_roundtrip() {
+        let repr = content_repr();
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+        assert_eq!(&bytes[..], &CONTENT_BYTES[..]);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert!(packet.code().is_response());
+        assert_eq!((packet.code().class(), packet.code().detail()), (2, 5));
+
+        let mut options = packet.options();
+        // Content-Format has a delta of 12 and an empty value.
+        assert_eq!(options.remaining()[0], 0xc0);
+        assert_eq!(options.next(), Some(Ok(CONTENT_OPTIONS[0])));
+        assert_eq!(options.next(), None);
+        // The payload starts right after the payload marker.
+        assert_eq!(options.remaining(), b"\xff22.5");
+        assert_eq!(packet.payload(), b"22.5");
+
+        let parsed = Repr::parse(&packet).unwrap();
+        assert_eq!(parsed.payload, b"22.5");
+        assert!(parsed.options.iter().eq(CONTENT_OPTIONS));
+
+        let mut bytes = vec![0xa5; parsed.buffer_len()];
+        parsed.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+        assert_eq!(&bytes[..], &CONTENT_BYTES[..]);
+    }
+
+    #[test]
+    fn test_unsorted_options() {
+        let options = [
+            CONTENT_OPTIONS[0],
+            GET_OPTIONS[0],
+            CoapOption {
+                number: OptionNumber::UriHost,
+                value: b"host",
+            },
+            GET_OPTIONS[1],
+        ];
+        let repr = Repr {
+            options: Options::List(&options),
+            ..content_repr()
+        };
+        assert!(repr.options.iter().eq([
+            options[2],
+            GET_OPTIONS[0],
+            GET_OPTIONS[1],
+            CONTENT_OPTIONS[0]
+        ]));
+
+        let len = repr.buffer_len();
+        let mut bytes = vec![0xa5; len + 1];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes[..len]));
+        // Nothing is written beyond the computed length.
+        assert_eq!(bytes[len], 0xa5);
+
+        let packet = Packet::new_checked(&bytes[..len]).unwrap();
+        assert!(packet.options().map(Result::unwrap).eq(repr.options.iter()));
+        assert_eq!(packet.payload(), b"22.5");
+    }
+
+    #[test]
+    fn test_extended_options() {
+        let value = [0x5a; 300];
+        let options = [
+            CoapOption {
+                number: OptionNumber::Size1,
+                value: &value[..13],
+            },
+            CoapOption {
+                number: OptionNumber::Unknown(2000),
+                value: &value[..],
+            },
+        ];
+        let repr = Repr {
+            msg_type: MessageType::NonConfirmable,
+            code: Code::Post,
+            message_id: 1,
+            token: &[],
+            options: Options::List(&options),
+            payload: b"x",
+        };
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+
+        // Size1 (60) uses a one-octet delta and length: 13 + 47, 13 + 0.
+        assert_eq!(&bytes[4..7], &[0xdd, 47, 0]);
+        // 2000 - 60 uses a two-octet delta, 300 a two-octet length.
+        let second = 7 + 13;
+        assert_eq!(bytes[second], 0xee);
+        assert_eq!(
+            NetworkEndian::read_u16(&bytes[second + 1..second + 3]),
+            2000 - 60 - 269
+        );
+        assert_eq!(
+            NetworkEndian::read_u16(&bytes[second + 3..second + 5]),
+            300 - 269
+        );
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert!(packet.options().map(Result::unwrap).eq(options));
+        assert_eq!(packet.payload(), b"x");
+    }
+
+    #[test]
+    fn test_malformed() {
+        // Truncated header.
+        assert_eq!(Packet::new_checked(&GET_BYTES[..3]), Err(Error));
+        // Truncated option value.
+        assert_eq!(Packet::new_checked(&GET_BYTES[..20]), Err(Error));
+        // Reserved token length.
+        let mut bytes = GET_BYTES;
+        bytes[0] = 0x49;
+        assert_eq!(Packet::new_checked(&bytes[..]), Err(Error));
+        // Reserved option delta.
+        let mut bytes = GET_BYTES;
+        bytes[6] = 0xf7;
+        assert_eq!(Packet::new_checked(&bytes[..]), Err(Error));
+        // Payload marker followed by no payload.
+        assert_eq!(Packet::new_checked(&CONTENT_BYTES[..8]), Err(Error));
+        // Empty message with a token.
+        let mut bytes = GET_BYTES;
+        bytes[1] = 0x00;
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Err(Error));
+    }
+}
@@ -110,6 +110,62 @@ pub struct DhcpOption<'a> {
     pub data: &'a [u8],
 }
 
+/// The data of a relay agent information option (RFC 3046), made of
+/// sub-options such as the agent circuit ID and the agent remote ID.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RelayAgentInformation<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RelayAgentInformation<'a> {
+    /// Wrap the data of a relay agent information option, checking that the
+    /// lengths of its sub-options are consistent with it.
+    pub fn new_checked(data: &'a [u8]) -> Result<Self> {
+        let mut rest = data;
+        while !rest.is_empty() {
+            if rest.len() < 2 || rest.len() < 2 + rest[1] as usize {
+                return Err(Error);
+            }
+            rest = &rest[2 + rest[1] as usize..];
+        }
+        Ok(RelayAgentInformation { data })
+    }
+
+    /// Return the data of the option.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return an iterator over the `(type, value)` sub-options.
+    pub fn sub_options(&self) -> impl Iterator<Item = (u8, &'a [u8])> {
+        let mut rest = self.data;
+        iter::from_fn(move || {
+            let (&kind, tail) = rest.split_first()?;
+            let (&len, tail) = tail.split_first()?;
+            let (value, tail) = tail.split_at(len as usize);
+            rest = tail;
+            Some((kind, value))
+        })
+    }
+
+    /// Return the agent circuit ID sub-option, if present.
+    pub fn circuit_id(&self) -> Option<&'a [u8]> {
+        self.sub_option(field::RELAY_AGENT_SUBOPT_CIRCUIT_ID)
+    }
+
+    /// Return the agent remote ID sub-option, if present.
+    pub fn remote_id(&self) -> Option<&'a [u8]> {
+        self.sub_option(field::RELAY_AGENT_SUBOPT_REMOTE_ID)
+    }
+
+    fn sub_option(&self, kind: u8) -> Option<&'a [u8]> {
+        self.sub_options()
+            .find(|(sub_kind, _)| *sub_kind == kind)
+            .map(|(_, value)| value)
+    }
+}
+
 /// A read/write wrapper around a Dynamic Host Configuration Protocol packet
 /// buffer.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -230,6 +286,11 @@ pub(crate) mod field {
     pub const OPT_REBINDING_TIME_VALUE: u8 = 59;
     pub const OPT_VENDOR_CLASS_ID: u8 = 60;
     pub const OPT_CLIENT_ID: u8 = 61;
+
+    // Relay Agent Information Option, RFC 3046
+    pub const OPT_RELAY_AGENT_INFORMATION: u8 = 82;
+    pub const RELAY_AGENT_SUBOPT_CIRCUIT_ID: u8 = 1;
+    pub const RELAY_AGENT_SUBOPT_REMOTE_ID: u8 = 2;
 }
 
 impl<T: AsRef<[u8]>> Packet<T> {
@@ -656,6 +717,16 @@ pub struct Repr<'a> {
     /// in all subsequent messages, to ensure that all servers
     /// correctly identify the client.
     pub client_identifier: Option<EthernetAddress>,
+    /// The "client identifier" option, as opaque data starting with its type
+    /// octet.
+    ///
+    /// When emitting, this is used instead of `client_identifier` if set. When
+    /// parsing, this is set if the identifier is not an Ethernet address.
+    pub opaque_client_identifier: Option<&'a [u8]>,
+    /// The "vendor class identifier" option. It is used by clients to
+    /// identify their vendor and configuration, such as the hardware or
+    /// software they run.
+    pub vendor_class_identifier: Option<&'a [u8]>,
     /// The "server identifier" option. It is used both to identify a DHCP
     /// server in a DHCP message and as a destination address from clients
     /// to servers.
@@ -675,6 +746,8 @@ pub struct Repr<'a> {
     /// The DHCP IP rebind duration (T2 interval), in seconds, if specified in
     /// the packet.
     pub rebind_duration: Option<u32>,
+    /// The "relay agent information" option, added by DHCP relay agents.
+    pub relay_agent_information: Option<RelayAgentInformation<'a>>,
     /// When returned from [`Repr::parse`], this field will be `None`.
     /// However, when calling [`Repr::emit`], this field should contain only
     /// additional DHCP options not known to smoltcp.
@@ -691,9 +764,14 @@ impl<'a> Repr<'a> {
         if self.requested_ip.is_some() {
             len += 6;
         }
-        if self.client_identifier.is_some() {
+        if let Some(id) = self.opaque_client_identifier {
+            len += id.len() + 2;
+        } else if self.client_identifier.is_some() {
             len += 9;
         }
+        if let Some(id) = self.vendor_class_identifier {
+            len += id.len() + 2;
+        }
         if self.server_identifier.is_some() {
             len += 6;
         }
@@ -716,6 +794,9 @@ impl<'a> Repr<'a> {
         if let Some(list) = self.parameter_request_list {
             len += list.len() + 2;
         }
+        if let Some(info) = self.relay_agent_information {
+            len += info.as_bytes().len() + 2;
+        }
         for opt in self.additional_options {
             len += 2 + opt.data.len()
         }
@@ -753,6 +834,8 @@ impl<'a> Repr<'a> {
         let mut message_type = Err(Error);
         let mut requested_ip = None;
         let mut client_identifier = None;
+        let mut opaque_client_identifier = None;
+        let mut vendor_class_identifier = None;
         let mut server_identifier = None;
         let mut router = None;
         let mut subnet_mask = None;
@@ -762,6 +845,7 @@ impl<'a> Repr<'a> {
         let mut lease_duration = None;
         let mut renew_duration = None;
         let mut rebind_duration = None;
+        let mut relay_agent_information = None;
 
         for option in packet.options() {
             let data = option.data;
@@ -775,13 +859,17 @@ impl<'a> Repr<'a> {
                 (field::OPT_REQUESTED_IP, 4) => {
                     requested_ip = Some(Ipv4Address::from_bytes(data));
                 }
-                (field::OPT_CLIENT_ID, 7) => {
-                    let hardware_type = Hardware::from(u16::from(data[0]));
-                    if hardware_type != Hardware::Ethernet {
-                        return Err(Error);
-                    }
+                (field::OPT_CLIENT_ID, 7)
+                    if Hardware::from(u16::from(data[0])) == Hardware::Ethernet =>
+                {
                     client_identifier = Some(EthernetAddress::from_bytes(&data[1..]));
                 }
+                (field::OPT_CLIENT_ID, 1..) => {
+                    opaque_client_identifier = Some(data);
+                }
+                (field::OPT_VENDOR_CLASS_ID, 1..) => {
+                    vendor_class_identifier = Some(data);
+                }
                 (field::OPT_SERVER_IDENTIFIER, 4) => {
                     server_identifier = Some(Ipv4Address::from_bytes(data));
                 }
@@ -817,6 +905,9 @@ impl<'a> Repr<'a> {
                     }
                     dns_servers = Some(servers);
                 }
+                (field::OPT_RELAY_AGENT_INFORMATION, _) => {
+                    relay_agent_information = Some(RelayAgentInformation::new_checked(data)?);
+                }
                 _ => {}
             }
         }
@@ -837,12 +928,15 @@ impl<'a> Repr<'a> {
             router,
             subnet_mask,
             client_identifier,
+            opaque_client_identifier,
+            vendor_class_identifier,
             parameter_request_list,
             dns_servers,
             max_size,
             lease_duration,
             renew_duration,
             rebind_duration,
+            relay_agent_information,
             message_type: message_type?,
             additional_options: &[],
         })
@@ -882,7 +976,12 @@ impl<'a> Repr<'a> {
                 data: &[self.message_type.into()],
             })?;
 
-            if let Some(val) = &self.client_identifier {
+            if let Some(data) = self.opaque_client_identifier {
+                options.emit(DhcpOption {
+                    kind: field::OPT_CLIENT_ID,
+                    data,
+                })?;
+            } else if let Some(val) = &self.client_identifier {
                 let mut data = [0; 7];
                 data[0] = u16::from(Hardware::Ethernet) as u8;
                 data[1..].copy_from_slice(val.as_bytes());
@@ -893,6 +992,13 @@ impl<'a> Repr<'a> {
                 })?;
             }
 
+            if let Some(data) = self.vendor_class_identifier {
+                options.emit(DhcpOption {
+                    kind: field::OPT_VENDOR_CLASS_ID,
+                    data,
+                })?;
+            }
+
             if let Some(val) = &self.server_identifier {
                 options.emit(DhcpOption {
                     kind: field::OPT_SERVER_IDENTIFIER,
@@ -955,6 +1061,13 @@ impl<'a> Repr<'a> {
                 })?;
             }
 
+            if let Some(info) = self.relay_agent_information {
+                options.emit(DhcpOption {
+                    kind: field::OPT_RELAY_AGENT_INFORMATION,
+                    data: info.as_bytes(),
+                })?;
+            }
+
             for option in self.additional_options {
                 options.emit(*option)?;
             }
@@ -1179,6 +1292,8 @@ mod test {
             broadcast: false,
             requested_ip: None,
             client_identifier: Some(CLIENT_MAC),
+            opaque_client_identifier: None,
+            vendor_class_identifier: None,
             server_identifier: None,
             parameter_request_list: None,
             dns_servers: None,
@@ -1186,6 +1301,7 @@ mod test {
             renew_duration: None,
             rebind_duration: None,
             lease_duration: Some(0xffff_ffff), // Infinite lease
+            relay_agent_information: None,
             additional_options: &[],
         }
     }
@@ -1209,9 +1325,12 @@ mod test {
             lease_duration: None,
             requested_ip: Some(IP_NULL),
             client_identifier: Some(CLIENT_MAC),
+            opaque_client_identifier: None,
+            vendor_class_identifier: None,
             server_identifier: None,
             parameter_request_list: Some(&[1, 3, 6, 42]),
             dns_servers: None,
+            relay_agent_information: None,
             additional_options: &[],
         }
     }
@@ -1237,6 +1356,90 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_emit_discover_client_id_vendor_class() {
+        let mut repr = discover_repr();
+        repr.opaque_client_identifier = Some(b"\x00device-1");
+        repr.vendor_class_identifier = Some(b"smoltcp");
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(&mut packet).unwrap();
+
+        let packet = Packet::new_unchecked(&bytes);
+        let repr_parsed = Repr::parse(&packet).unwrap();
+        assert_eq!(repr_parsed.client_identifier, None);
+        assert_eq!(
+            repr_parsed.opaque_client_identifier,
+            Some(&b"\x00device-1"[..])
+        );
+        assert_eq!(repr_parsed.vendor_class_identifier, Some(&b"smoltcp"[..]));
+
+        // Without them, the hardware address is the client identifier, and no
+        // vendor class is sent.
+        let packet = Packet::new_unchecked(DISCOVER_BYTES);
+        let repr_parsed = Repr::parse(&packet).unwrap();
+        assert_eq!(repr_parsed.client_identifier, Some(CLIENT_MAC));
+        assert_eq!(repr_parsed.opaque_client_identifier, None);
+        assert_eq!(repr_parsed.vendor_class_identifier, None);
+        assert!(packet
+            .options()
+            .all(|option| option.kind != field::OPT_VENDOR_CLASS_ID));
+    }
+
+    #[test]
+    fn test_parse_relay_agent_information() {
+        let mut repr = discover_repr();
+        let option = DhcpOption {
+            kind: field::OPT_RELAY_AGENT_INFORMATION,
+            data: b"\x01\x04eth0\x02\x06\x00\x11\x22\x33\x44\x55",
+        };
+        let options = [option];
+        repr.additional_options = &options;
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(&mut packet).unwrap();
+
+        let packet = Packet::new_unchecked(&bytes);
+        let repr_parsed = Repr::parse(&packet).unwrap();
+        let info = repr_parsed.relay_agent_information.unwrap();
+        assert!(info.sub_options().eq([
+            (field::RELAY_AGENT_SUBOPT_CIRCUIT_ID, &b"eth0"[..]),
+            (
+                field::RELAY_AGENT_SUBOPT_REMOTE_ID,
+                &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55][..]
+            ),
+        ]));
+        assert_eq!(info.circuit_id(), Some(&b"eth0"[..]));
+        assert_eq!(
+            info.remote_id(),
+            Some(&[0x00, 0x11, 0x22, 0x33, 0x44, 0x55][..])
+        );
+
+        // Emitting the parsed option produces the same option.
+        let mut repr = discover_repr();
+        repr.relay_agent_information = Some(info);
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(&mut packet).unwrap();
+        let packet = Packet::new_unchecked(&bytes);
+        assert!(packet.options().any(|opt| opt == option));
+
+        // A sub-option running past the end of the option is an error.
+        for data in [&b"\x01\x05eth0"[..], &b"\x01\x04eth0\x02"[..]] {
+            let options = [DhcpOption {
+                kind: field::OPT_RELAY_AGENT_INFORMATION,
+                data,
+            }];
+            let mut repr = discover_repr();
+            repr.additional_options = &options;
+            let mut bytes = vec![0xa5; repr.buffer_len()];
+            let mut packet = Packet::new_unchecked(&mut bytes);
+            repr.emit(&mut packet).unwrap();
+            let packet = Packet::new_unchecked(&bytes);
+            assert_eq!(Repr::parse(&packet), Err(Error));
+        }
+    }
+
     #[test]
     fn test_emit_offer() {
         let repr = offer_repr();
@@ -43,6 +43,7 @@ enum_with_unknown! {
         Cname = 0x0005,
         Soa   = 0x0006,
         Aaaa  = 0x001c,
+        Opt   = 0x0029,
     }
 }
 
@@ -59,6 +60,14 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// EDNS0 flags, carried in the TTL field of the OPT record.
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct EdnsFlags: u16 {
+        const DNSSEC_OK = 0b1000_0000_0000_0000;
+    }
+}
+
 mod field {
     use crate::wire::field::*;
 
@@ -367,6 +376,7 @@ pub enum RecordData<'a> {
     #[cfg(feature = "proto-ipv6")]
     Aaaa(Ipv6Address),
     Cname(&'a [u8]),
+    Opt(Edns0<'a>),
     Other(Type, &'a [u8]),
 }
 
@@ -384,21 +394,73 @@ impl<'a> Record<'a> {
         let len = NetworkEndian::read_u16(&rest[8..10]) as usize;
         let rest = &rest[10..];
 
-        if class != CLASS_IN {
+        let data = rest.get(..len).ok_or(Error)?;
+        let rest = &rest[len..];
+
+        // The OPT pseudo-record reuses the class and TTL fields.
+        let data = if type_ == Type::Opt {
+            RecordData::Opt(Edns0::parse(class, ttl, data))
+        } else if class != CLASS_IN {
             return Err(Error);
+        } else {
+            RecordData::parse(type_, data)?
+        };
+
+        Ok((rest, Record { name, ttl, data }))
+    }
+}
+
+/// An EDNS0 OPT pseudo-record, see [RFC 6891 § 6].
+///
+/// [RFC 6891 § 6]: https://www.rfc-editor.org/rfc/rfc6891#section-6
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Edns0<'a> {
+    /// The largest UDP payload the sender can reassemble, from the CLASS field.
+    pub udp_payload_size: u16,
+    /// The upper 8 bits of the 12-bit response code.
+    pub extended_rcode: u8,
+    /// The EDNS version, 0 for EDNS0.
+    pub version: u8,
+    pub flags: EdnsFlags,
+    /// The raw options, as a sequence of (code, length, data) entries.
+    pub options: &'a [u8],
+}
+
+impl<'a> Edns0<'a> {
+    fn parse(class: u16, ttl: u32, options: &'a [u8]) -> Edns0<'a> {
+        Edns0 {
+            udp_payload_size: class,
+            extended_rcode: (ttl >> 24) as u8,
+            version: (ttl >> 16) as u8,
+            flags: EdnsFlags::from_bits_truncate(ttl as u16),
+            options,
         }
+    }
 
-        let data = rest.get(..len).ok_or(Error)?;
-        let rest = &rest[len..];
+    /// Return the full 12-bit response code, given the 4-bit response code
+    /// from the header.
+    pub fn rcode(&self, header_rcode: Rcode) -> u16 {
+        ((self.extended_rcode as u16) << 4) | (u8::from(header_rcode) & 0x0f) as u16
+    }
 
-        Ok((
-            rest,
-            Record {
-                name,
-                ttl,
-                data: RecordData::parse(type_, data)?,
-            },
-        ))
+    /// Return the length of a record that will be emitted from this high-level
+    /// representation.
+    pub const fn buffer_len(&self) -> usize {
+        // Root name, type, class, TTL and data length.
+        11 + self.options.len()
+    }
+
+    /// Emit a high-level representation into an OPT record.
+    pub fn emit(&self, packet: &mut [u8]) {
+        packet[0] = 0;
+        NetworkEndian::write_u16(&mut packet[1..3], Type::Opt.into());
+        NetworkEndian::write_u16(&mut packet[3..5], self.udp_payload_size);
+        packet[5] = self.extended_rcode;
+        packet[6] = self.version;
+        NetworkEndian::write_u16(&mut packet[7..9], self.flags.bits());
+        NetworkEndian::write_u16(&mut packet[9..11], self.options.len() as u16);
+        packet[11..11 + self.options.len()].copy_from_slice(self.options);
     }
 }
 
@@ -412,13 +474,19 @@ pub struct Repr<'a> {
     pub opcode: Opcode,
     pub flags: Flags,
     pub question: Question<'a>,
+    /// The OPT record to append to the additional section, if any.
+    pub edns0: Option<Edns0<'a>>,
 }
 
 impl<'a> Repr<'a> {
     /// Return the length of a packet that will be emitted from this high-level
     /// representation.
     pub const fn buffer_len(&self) -> usize {
-        field::HEADER_END + self.question.buffer_len()
+        let edns0_len = match &self.edns0 {
+            Some(edns0) => edns0.buffer_len(),
+            None => 0,
+        };
+        field::HEADER_END + self.question.buffer_len() + edns0_len
     }
 
     /// Emit a high-level representation into a DNS packet.
@@ -432,8 +500,11 @@ impl<'a> Repr<'a> {
         packet.set_question_count(1);
         packet.set_answer_record_count(0);
         packet.set_authority_record_count(0);
-        packet.set_additional_record_count(0);
-        self.question.emit(packet.payload_mut())
+        packet.set_additional_record_count(self.edns0.is_some() as u16);
+        self.question.emit(packet.payload_mut());
+        if let Some(edns0) = &self.edns0 {
+            edns0.emit(&mut packet.payload_mut()[self.question.buffer_len()..]);
+        }
     }
 }
 
@@ -783,6 +854,7 @@ mod test {
                 name,
                 type_: Type::A,
             },
+            edns0: None,
         };
 
         let mut buf = Vec::new();
@@ -796,4 +868,65 @@ mod test {
         ];
         assert_eq!(&buf, want);
     }
+
+    #[test]
+    fn test_emit_edns0() {
+        let name = &[
+            0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+        ];
+
+        let repr = Repr {
+            transaction_id: 0x5184,
+            flags: Flags::RECURSION_DESIRED,
+            opcode: Opcode::Query,
+            question: Question {
+                name,
+                type_: Type::A,
+            },
+            edns0: Some(Edns0 {
+                udp_payload_size: 4096,
+                extended_rcode: 0,
+                version: 0,
+                flags: EdnsFlags::empty(),
+                options: &[],
+            }),
+        };
+
+        let mut buf = vec![0; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut buf));
+
+        let want = &[
+            0x51, 0x84, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x06, 0x67,
+            0x6f, 0x6f, 0x67, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00, 0x00, 0x01, 0x00, 0x01,
+            0x00, 0x00, 0x29, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(&buf, want);
+
+        let p = Parsed::parse(&buf).unwrap();
+        assert_eq!(p.additionals[0].data, RecordData::Opt(repr.edns0.unwrap()));
+    }
+
+    #[test]
+    fn test_parse_edns0() {
+        let p = Parsed::parse(&[
+            0x51, 0x84, 0x81, 0x80, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x06, 0x67,
+            0x6f, 0x6f, 0x67, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00, 0x00, 0x01, 0x00, 0x01,
+            0x00, 0x00, 0x29, 0x04, 0xd0, 0x01, 0x00, 0x80, 0x00, 0x00, 0x04, 0x00, 0x0a, 0x00,
+            0x00,
+        ])
+        .unwrap();
+
+        assert_eq!(p.packet.additional_record_count(), 1);
+        let edns0 = match p.additionals[0].data {
+            RecordData::Opt(edns0) => edns0,
+            _ => panic!("expected an OPT record"),
+        };
+        assert_eq!(edns0.udp_payload_size, 1232);
+        assert_eq!(edns0.extended_rcode, 1);
+        assert_eq!(edns0.version, 0);
+        assert_eq!(edns0.flags, EdnsFlags::DNSSEC_OK);
+        assert_eq!(edns0.options, &[0x00, 0x0a, 0x00, 0x00]);
+        // BADVERS
+        assert_eq!(edns0.rcode(p.packet.rcode()), 16);
+    }
 }
@@ -9,7 +9,10 @@ enum_with_unknown! {
     pub enum EtherType(u16) {
         Ipv4 = 0x0800,
         Arp  = 0x0806,
-        Ipv6 = 0x86DD
+        Vlan = 0x8100,
+        Ipv6 = 0x86DD,
+        ServiceVlan = 0x88A8,
+        Lldp = 0x88CC
     }
 }
 
@@ -19,6 +22,9 @@ impl fmt::Display for EtherType {
             EtherType::Ipv4 => write!(f, "IPv4"),
             EtherType::Ipv6 => write!(f, "IPv6"),
             EtherType::Arp => write!(f, "ARP"),
+            EtherType::Vlan => write!(f, "802.1Q"),
+            EtherType::ServiceVlan => write!(f, "802.1ad"),
+            EtherType::Lldp => write!(f, "LLDP"),
             EtherType::Unknown(id) => write!(f, "0x{id:04x}"),
         }
     }
@@ -49,13 +55,14 @@ impl Address {
     }
 
     /// Query whether the address is an unicast address.
-    pub fn is_unicast(&self) -> bool {
+    pub const fn is_unicast(&self) -> bool {
         !(self.is_broadcast() || self.is_multicast())
     }
 
     /// Query whether this address is the broadcast address.
-    pub fn is_broadcast(&self) -> bool {
-        *self == Self::BROADCAST
+    pub const fn is_broadcast(&self) -> bool {
+        let [a, b, c, d, e, f] = self.0;
+        a & b & c & d & e & f == 0xff
     }
 
     /// Query whether the "multicast" bit in the OUI is set.
@@ -67,6 +74,25 @@ impl Address {
     pub const fn is_local(&self) -> bool {
         self.0[0] & 0x02 != 0
     }
+
+    /// Convert the address to a modified Extended Unique Identifier (EUI-64),
+    /// as described in RFC 4291 Appendix A.
+    pub fn as_eui_64(&self) -> [u8; 8] {
+        let b = self.0;
+        [b[0] ^ 0x02, b[1], b[2], 0xff, 0xfe, b[3], b[4], b[5]]
+    }
+
+    /// Convert the address to a link-local IPv6 address using the EUI-64
+    /// format from RFC 2464.
+    #[cfg(feature = "proto-ipv6")]
+    pub fn as_link_local_address(&self) -> super::Ipv6Address {
+        let mut bytes = [0; 16];
+        bytes[0] = 0xfe;
+        bytes[1] = 0x80;
+        bytes[8..].copy_from_slice(&self.as_eui_64());
+
+        super::Ipv6Address::from_bytes(&bytes)
+    }
 }
 
 impl fmt::Display for Address {
@@ -80,6 +106,44 @@ impl fmt::Display for Address {
     }
 }
 
+/// An IEEE 802.1Q VLAN tag, without its tag protocol identifier.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VlanTag {
+    /// Priority code point.
+    pub pcp: u8,
+    /// Drop eligible indicator.
+    pub dei: bool,
+    /// VLAN identifier.
+    pub vid: u16,
+}
+
+impl VlanTag {
+    /// Construct a VLAN tag from a tag control information field.
+    pub const fn from_tci(tci: u16) -> VlanTag {
+        VlanTag {
+            pcp: (tci >> 13) as u8,
+            dei: tci & 0x1000 != 0,
+            vid: tci & 0x0fff,
+        }
+    }
+
+    /// Return the tag control information field of this tag.
+    pub const fn tci(&self) -> u16 {
+        ((self.pcp as u16 & 0x07) << 13) | ((self.dei as u16) << 12) | (self.vid & 0x0fff)
+    }
+}
+
+impl fmt::Display for VlanTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "vid={} pcp={}", self.vid, self.pcp)?;
+        if self.dei {
+            write!(f, " dei")?;
+        }
+        Ok(())
+    }
+}
+
 /// A read/write wrapper around an Ethernet II frame buffer.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -94,11 +158,18 @@ mod field {
     pub const SOURCE: Field = 6..12;
     pub const ETHERTYPE: Field = 12..14;
     pub const PAYLOAD: Rest = 14..;
+
+    pub const OUTER_TCI: Field = 14..16;
+    pub const INNER_TPID: Field = 16..18;
+    pub const INNER_TCI: Field = 18..20;
 }
 
 /// The Ethernet header length
 pub const HEADER_LEN: usize = field::PAYLOAD.start;
 
+/// The length of a VLAN tag.
+pub const VLAN_TAG_LEN: usize = 4;
+
 impl<T: AsRef<[u8]>> Frame<T> {
     /// Imbue a raw octet buffer with Ethernet frame structure.
     pub const fn new_unchecked(buffer: T) -> Frame<T> {
@@ -117,9 +188,12 @@ impl<T: AsRef<[u8]>> Frame<T> {
 
     /// Ensure that no accessor method will panic if called.
     /// Returns `Err(Error)` if the buffer is too short.
+    ///
+    /// The buffer must also hold the VLAN tags announced by the EtherType
+    /// field.
     pub fn check_len(&self) -> Result<()> {
         let len = self.buffer.as_ref().len();
-        if len < HEADER_LEN {
+        if len < HEADER_LEN || len < self.tagged_header_len() {
             Err(Error)
         } else {
             Ok(())
@@ -163,6 +237,80 @@ impl<T: AsRef<[u8]>> Frame<T> {
         let raw = NetworkEndian::read_u16(&data[field::ETHERTYPE]);
         EtherType::from(raw)
     }
+
+    /// Return the number of VLAN tags in the frame.
+    ///
+    /// A frame may carry a single 802.1Q customer tag, or an 802.1ad service
+    /// tag followed by a customer tag. Returns `Err(Error)` for any other
+    /// stack of tags.
+    pub fn vlan_tag_count(&self) -> Result<usize> {
+        let data = self.buffer.as_ref();
+        let count = match self.ethertype() {
+            EtherType::Vlan => 1,
+            EtherType::ServiceVlan => 2,
+            _ => return Ok(0),
+        };
+        let header_len = HEADER_LEN + count * VLAN_TAG_LEN;
+
+        let inner_ethertype = match count {
+            1 => NetworkEndian::read_u16(&data[field::INNER_TPID]),
+            _ => {
+                if NetworkEndian::read_u16(&data[field::INNER_TPID]) != EtherType::Vlan.into() {
+                    return Err(Error);
+                }
+                NetworkEndian::read_u16(&data[header_len - 2..header_len])
+            }
+        };
+        match EtherType::from(inner_ethertype) {
+            EtherType::Vlan | EtherType::ServiceVlan => Err(Error),
+            _ => Ok(count),
+        }
+    }
+
+    /// Return the outermost VLAN tag, if the frame is tagged.
+    #[inline]
+    pub fn outer_vlan_tag(&self) -> Option<VlanTag> {
+        let data = self.buffer.as_ref();
+        match self.ethertype() {
+            EtherType::Vlan | EtherType::ServiceVlan => Some(VlanTag::from_tci(
+                NetworkEndian::read_u16(&data[field::OUTER_TCI]),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Return the inner VLAN tag, if the frame is double-tagged.
+    #[inline]
+    pub fn inner_vlan_tag(&self) -> Option<VlanTag> {
+        let data = self.buffer.as_ref();
+        match self.ethertype() {
+            EtherType::ServiceVlan => Some(VlanTag::from_tci(NetworkEndian::read_u16(
+                &data[field::INNER_TCI],
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Return the EtherType of the payload, following the VLAN tags.
+    ///
+    /// The tags are not validated; see [vlan_tag_count].
+    ///
+    /// [vlan_tag_count]: #method.vlan_tag_count
+    #[inline]
+    pub fn inner_ethertype(&self) -> EtherType {
+        let data = self.buffer.as_ref();
+        let end = self.tagged_header_len();
+        EtherType::from(NetworkEndian::read_u16(&data[end - 2..end]))
+    }
+
+    /// Return the length of the header, including the VLAN tags.
+    fn tagged_header_len(&self) -> usize {
+        match self.ethertype() {
+            EtherType::Vlan => HEADER_LEN + VLAN_TAG_LEN,
+            EtherType::ServiceVlan => HEADER_LEN + 2 * VLAN_TAG_LEN,
+            _ => HEADER_LEN,
+        }
+    }
 }
 
 impl<'a, T: AsRef<[u8]> + ?Sized> Frame<&'a T> {
@@ -172,6 +320,13 @@ impl<'a, T: AsRef<[u8]> + ?Sized> Frame<&'a T> {
         let data = self.buffer.as_ref();
         &data[field::PAYLOAD]
     }
+
+    /// Return a pointer to the payload following the VLAN tags.
+    #[inline]
+    pub fn tagged_payload(&self) -> &'a [u8] {
+        let data = self.buffer.as_ref();
+        &data[self.tagged_header_len()..]
+    }
 }
 
 impl<T: AsRef<[u8]> + AsMut<[u8]>> Frame<T> {
@@ -202,6 +357,43 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Frame<T> {
         let data = self.buffer.as_mut();
         &mut data[field::PAYLOAD]
     }
+
+    /// Set the VLAN tags and the EtherType of the payload.
+    ///
+    /// A single tag is emitted as an 802.1Q customer tag; with two tags, the
+    /// outer one is emitted as an 802.1ad service tag.
+    ///
+    /// # Panics
+    /// This function panics if `inner` is set but `outer` is not, or if the
+    /// buffer is too short to hold the tags.
+    pub fn set_vlan_tags(
+        &mut self,
+        outer: Option<VlanTag>,
+        inner: Option<VlanTag>,
+        ethertype: EtherType,
+    ) {
+        let data = self.buffer.as_mut();
+        match (outer, inner) {
+            (None, None) => NetworkEndian::write_u16(&mut data[field::ETHERTYPE], ethertype.into()),
+            (Some(outer), None) => {
+                NetworkEndian::write_u16(&mut data[field::ETHERTYPE], EtherType::Vlan.into());
+                NetworkEndian::write_u16(&mut data[field::OUTER_TCI], outer.tci());
+                NetworkEndian::write_u16(&mut data[field::INNER_TPID], ethertype.into());
+            }
+            (Some(outer), Some(inner)) => {
+                NetworkEndian::write_u16(
+                    &mut data[field::ETHERTYPE],
+                    EtherType::ServiceVlan.into(),
+                );
+                NetworkEndian::write_u16(&mut data[field::OUTER_TCI], outer.tci());
+                NetworkEndian::write_u16(&mut data[field::INNER_TPID], EtherType::Vlan.into());
+                NetworkEndian::write_u16(&mut data[field::INNER_TCI], inner.tci());
+                let end = field::INNER_TCI.end;
+                NetworkEndian::write_u16(&mut data[end..end + 2], ethertype.into());
+            }
+            (None, Some(_)) => panic!("inner VLAN tag without an outer one"),
+        }
+    }
 }
 
 impl<T: AsRef<[u8]>> AsRef<[u8]> for Frame<T> {
@@ -257,7 +449,7 @@ impl<T: AsRef<[u8]>> PrettyPrint for Frame<T> {
     }
 }
 
-/// A high-level representation of an Internet Protocol version 4 packet header.
+/// A high-level representation of an Ethernet II frame header.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Repr {
@@ -291,6 +483,53 @@ impl Repr {
     }
 }
 
+/// A high-level representation of the VLAN tags following an Ethernet II
+/// frame header.
+///
+/// A single tag is an 802.1Q customer tag; with an inner tag, the outer one
+/// is an 802.1ad service tag.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VlanRepr {
+    /// The outermost VLAN tag.
+    pub outer_tag: VlanTag,
+    /// The inner VLAN tag of a double-tagged frame.
+    pub inner_tag: Option<VlanTag>,
+    /// The EtherType of the payload, following the VLAN tags.
+    pub ethertype: EtherType,
+}
+
+impl VlanRepr {
+    /// Parse the VLAN tags of an Ethernet II frame and return a high-level
+    /// representation, or `None` if the frame is untagged.
+    pub fn parse<T: AsRef<[u8]> + ?Sized>(frame: &Frame<&T>) -> Result<Option<VlanRepr>> {
+        frame.check_len()?;
+        frame.vlan_tag_count()?;
+        Ok(frame.outer_vlan_tag().map(|outer_tag| VlanRepr {
+            outer_tag,
+            inner_tag: frame.inner_vlan_tag(),
+            ethertype: frame.inner_ethertype(),
+        }))
+    }
+
+    /// Return the length of the tags that will be emitted from this high-level
+    /// representation, not including the Ethernet II header.
+    pub const fn buffer_len(&self) -> usize {
+        match self.inner_tag {
+            None => VLAN_TAG_LEN,
+            Some(_) => 2 * VLAN_TAG_LEN,
+        }
+    }
+
+    /// Emit a high-level representation into an Ethernet II frame.
+    ///
+    /// This overwrites the EtherType field, and so must be done after
+    /// emitting the frame header with [Repr::emit].
+    pub fn emit<T: AsRef<[u8]> + AsMut<[u8]>>(&self, frame: &mut Frame<T>) {
+        frame.set_vlan_tags(Some(self.outer_tag), self.inner_tag, self.ethertype);
+    }
+}
+
 #[cfg(test)]
 mod test {
     // Tests that are valid with any combination of
@@ -304,6 +543,42 @@ mod test {
         assert!(Address::BROADCAST.is_multicast());
         assert!(Address::BROADCAST.is_local());
     }
+
+    #[test]
+    fn test_multicast() {
+        let addr = Address([0x01, 0x00, 0x5e, 0x00, 0x00, 0x01]);
+        assert!(addr.is_multicast());
+        assert!(!addr.is_broadcast());
+        assert!(!addr.is_unicast());
+        assert!(!addr.is_local());
+
+        // Any octet other than 0xff makes a multicast address not broadcast.
+        let addr = Address([0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+        assert!(addr.is_multicast());
+        assert!(!addr.is_broadcast());
+    }
+
+    #[test]
+    fn test_local_unicast() {
+        let addr = Address([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        assert!(addr.is_unicast());
+        assert!(addr.is_local());
+        assert!(!addr.is_multicast());
+        assert!(!addr.is_broadcast());
+
+        let addr = Address([0x00, 0x1b, 0x21, 0x0a, 0x0b, 0x0c]);
+        assert!(addr.is_unicast());
+        assert!(!addr.is_local());
+    }
+
+    #[test]
+    fn test_eui_64() {
+        let addr = Address([0x00, 0x1b, 0x21, 0x0a, 0x0b, 0x0c]);
+        assert_eq!(
+            addr.as_eui_64(),
+            [0x02, 0x1b, 0x21, 0xff, 0xfe, 0x0a, 0x0b, 0x0c]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -342,6 +617,123 @@ mod test_ipv4 {
         assert_eq!(frame.payload(), &PAYLOAD_BYTES[..]);
     }
 
+    static QINQ_FRAME_BYTES: [u8; 42] = [
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x88, 0xa8, 0xa0,
+        0x64, 0x81, 0x00, 0x30, 0x0a, 0x08, 0x00, 0x45, 0x00, 0x00, 0x14, 0x00, 0x00, 0x40, 0x00,
+        0x40, 0x3b, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x01, 0x0a, 0x00, 0x00, 0x02,
+    ];
+
+    fn qinq_repr() -> (Repr, VlanRepr) {
+        let repr = Repr {
+            src_addr: Address([0x11, 0x12, 0x13, 0x14, 0x15, 0x16]),
+            dst_addr: Address([0x01, 0x02, 0x03, 0x04, 0x05, 0x06]),
+            ethertype: EtherType::ServiceVlan,
+        };
+        let vlan_repr = VlanRepr {
+            outer_tag: VlanTag {
+                pcp: 5,
+                dei: false,
+                vid: 100,
+            },
+            inner_tag: Some(VlanTag {
+                pcp: 1,
+                dei: true,
+                vid: 10,
+            }),
+            ethertype: EtherType::Ipv4,
+        };
+        (repr, vlan_repr)
+    }
+
+    #[test]
+    fn test_parse_qinq() {
+        let frame = Frame::new_checked(&QINQ_FRAME_BYTES[..]).unwrap();
+        assert_eq!(frame.ethertype(), EtherType::ServiceVlan);
+        assert_eq!(frame.vlan_tag_count(), Ok(2));
+        assert_eq!(frame.outer_vlan_tag().unwrap().vid, 100);
+        assert_eq!(frame.inner_vlan_tag().unwrap().vid, 10);
+        assert_eq!(frame.inner_ethertype(), EtherType::Ipv4);
+        assert_eq!(frame.tagged_payload(), &QINQ_FRAME_BYTES[22..]);
+        let (repr, vlan_repr) = qinq_repr();
+        assert_eq!(Repr::parse(&frame), Ok(repr));
+        assert_eq!(VlanRepr::parse(&frame), Ok(Some(vlan_repr)));
+
+        let ipv4_packet = crate::wire::Ipv4Packet::new_checked(frame.tagged_payload()).unwrap();
+        assert_eq!(
+            ipv4_packet.dst_addr(),
+            crate::wire::Ipv4Address([10, 0, 0, 2])
+        );
+    }
+
+    #[test]
+    fn test_emit_qinq() {
+        let (repr, vlan_repr) = qinq_repr();
+        assert_eq!(repr.buffer_len() + vlan_repr.buffer_len(), 22);
+
+        let mut bytes = vec![0xa5; QINQ_FRAME_BYTES.len()];
+        let mut frame = Frame::new_unchecked(&mut bytes);
+        repr.emit(&mut frame);
+        vlan_repr.emit(&mut frame);
+        bytes[22..].copy_from_slice(&QINQ_FRAME_BYTES[22..]);
+        // The service tag comes first, then the customer tag.
+        assert_eq!(&bytes[12..14], &[0x88, 0xa8]);
+        assert_eq!(&bytes[16..18], &[0x81, 0x00]);
+        assert_eq!(&bytes[..], &QINQ_FRAME_BYTES[..]);
+    }
+
+    #[test]
+    fn test_single_vlan_tag() {
+        let (repr, vlan_repr) = qinq_repr();
+        let vlan_repr = VlanRepr {
+            inner_tag: None,
+            ..vlan_repr
+        };
+        let mut bytes = vec![0xa5; repr.buffer_len() + vlan_repr.buffer_len()];
+        let mut frame = Frame::new_unchecked(&mut bytes);
+        repr.emit(&mut frame);
+        vlan_repr.emit(&mut frame);
+        assert_eq!(&bytes[12..18], &[0x81, 0x00, 0xa0, 0x64, 0x08, 0x00]);
+
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(frame.vlan_tag_count(), Ok(1));
+        assert_eq!(VlanRepr::parse(&frame), Ok(Some(vlan_repr)));
+
+        // Untagged frames have no VLAN representation.
+        let frame = Frame::new_checked(&FRAME_BYTES[..]).unwrap();
+        assert_eq!(VlanRepr::parse(&frame), Ok(None));
+    }
+
+    #[test]
+    fn test_invalid_vlan_tags() {
+        // A service tag must be followed by a customer tag.
+        let mut bytes = QINQ_FRAME_BYTES;
+        bytes[16..18].copy_from_slice(&[0x88, 0xa8]);
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(frame.vlan_tag_count(), Err(Error));
+        assert_eq!(VlanRepr::parse(&frame), Err(Error));
+
+        // No more than two tags.
+        let mut bytes = QINQ_FRAME_BYTES;
+        bytes[20..22].copy_from_slice(&[0x81, 0x00]);
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(frame.vlan_tag_count(), Err(Error));
+
+        // Truncated tags.
+        assert_eq!(
+            Frame::new_checked(&QINQ_FRAME_BYTES[..21]).err(),
+            Some(Error)
+        );
+        assert!(Frame::new_checked(&QINQ_FRAME_BYTES[..22]).is_ok());
+        let mut bytes = [0; 17];
+        bytes.copy_from_slice(&QINQ_FRAME_BYTES[..17]);
+        bytes[12..14].copy_from_slice(&[0x81, 0x00]);
+        assert_eq!(Frame::new_checked(&bytes[..]).err(), Some(Error));
+        assert_eq!(
+            VlanRepr::parse(&Frame::new_unchecked(&bytes[..])),
+            Err(Error)
+        );
+    }
+
     #[test]
     fn test_construct() {
         let mut bytes = vec![0xa5; 64];
@@ -0,0 +1,291 @@
+//! Fragmentation of whole IP datagrams.
+//!
+//! These helpers split a datagram that does not fit a link into fragments
+//! that do, e.g. to test reassembly or to forward datagrams between links of
+//! different MTUs. The interface has its own, allocation-free, fragmenter for
+//! the packets it emits.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "proto-ipv4")]
+use super::Ipv4Packet;
+#[cfg(feature = "proto-ipv6")]
+use super::IPV6_HEADER_LEN;
+use super::{Error, Result};
+#[cfg(feature = "proto-ipv6")]
+use super::{IpProtocol, Ipv6ExtHeader, Ipv6FragmentHeader, Ipv6FragmentRepr, Ipv6Packet};
+
+/// The length of the data of every fragment but the last must be a multiple
+/// of this.
+const ALIGNMENT: usize = 8;
+
+/// The length of an IPv6 Fragment header, including its first two octets.
+#[cfg(feature = "proto-ipv6")]
+const IPV6_FRAGMENT_HEADER_LEN: usize = 8;
+
+/// Split an IPv4 datagram into fragments of at most `mtu` octets, identified
+/// by `ident`.
+///
+/// Every fragment carries a copy of the header of the datagram, options
+/// included, and all of them but the last have the More Fragments flag set.
+/// A datagram that is itself a fragment is split further, keeping its offset
+/// and its flag. A datagram that fits is returned as is.
+///
+/// Returns `Err(Error)` if the datagram is malformed, if its Don't Fragment
+/// flag is set, or if `mtu` leaves no room for eight octets of data.
+#[cfg(feature = "proto-ipv4")]
+pub fn fragment_ipv4(datagram: &[u8], mtu: usize, ident: u16) -> Result<Vec<Vec<u8>>> {
+    let packet = Ipv4Packet::new_checked(datagram)?;
+    if packet.version() != 4 {
+        return Err(Error);
+    }
+    let header_len = packet.header_len() as usize;
+    let total_len = packet.total_len() as usize;
+    if total_len <= mtu {
+        return Ok(Vec::from([datagram[..total_len].to_vec()]));
+    }
+    if packet.dont_frag() || mtu < header_len + ALIGNMENT {
+        return Err(Error);
+    }
+
+    let header = &datagram[..header_len];
+    let data_len = (mtu - header_len) / ALIGNMENT * ALIGNMENT;
+    let mut offset = packet.frag_offset() as usize;
+    let more_frags = packet.more_frags();
+
+    let mut fragments = Vec::new();
+    let mut chunks = datagram[header_len..total_len].chunks(data_len).peekable();
+    while let Some(chunk) = chunks.next() {
+        let mut fragment = Vec::with_capacity(header_len + chunk.len());
+        fragment.extend_from_slice(header);
+        fragment.extend_from_slice(chunk);
+
+        let mut packet = Ipv4Packet::new_unchecked(&mut fragment[..]);
+        packet.set_total_len((header_len + chunk.len()) as u16);
+        packet.set_ident(ident);
+        packet.set_more_frags(more_frags || chunks.peek().is_some());
+        packet.set_frag_offset(offset as u16);
+        packet.fill_checksum();
+
+        fragments.push(fragment);
+        offset += chunk.len();
+    }
+    Ok(fragments)
+}
+
+/// Split an IPv6 datagram into fragments of at most `mtu` octets, identified
+/// by `ident`.
+///
+/// A Fragment header is inserted in every fragment after the unfragmentable
+/// part of the datagram, that is its IPv6 header followed by any Hop-by-Hop
+/// Options or Routing header, and the Destination Options header before the
+/// latter. See [RFC 8200 § 4.5]. A datagram that fits is returned as is.
+///
+/// Returns `Err(Error)` if the datagram is malformed or already a fragment,
+/// or if `mtu` leaves no room for eight octets of data.
+///
+/// [RFC 8200 § 4.5]: https://www.rfc-editor.org/rfc/rfc8200#section-4.5
+#[cfg(feature = "proto-ipv6")]
+pub fn fragment_ipv6(datagram: &[u8], mtu: usize, ident: u32) -> Result<Vec<Vec<u8>>> {
+    let packet = Ipv6Packet::new_checked(datagram)?;
+    if packet.version() != 6 {
+        return Err(Error);
+    }
+    let total_len = packet.total_len();
+    if total_len <= mtu {
+        return Ok(Vec::from([datagram[..total_len].to_vec()]));
+    }
+
+    // Find the end of the unfragmentable part, and the Next Header field that
+    // will point to the Fragment header.
+    let mut next_header_at = 6;
+    let mut unfragmentable_len = IPV6_HEADER_LEN;
+    let mut offset = IPV6_HEADER_LEN;
+    let mut next_header = packet.next_header();
+    loop {
+        match next_header {
+            IpProtocol::HopByHop | IpProtocol::Ipv6Route | IpProtocol::Ipv6Opts => (),
+            IpProtocol::Ipv6Frag => return Err(Error),
+            _ => break,
+        }
+        let header = Ipv6ExtHeader::new_checked(&datagram[offset..total_len])?;
+        let header_len = (header.header_len() as usize + 1) * 8;
+        if next_header != IpProtocol::Ipv6Opts {
+            next_header_at = offset;
+            unfragmentable_len = offset + header_len;
+        }
+        next_header = header.next_header();
+        offset += header_len;
+    }
+
+    if mtu < unfragmentable_len + IPV6_FRAGMENT_HEADER_LEN + ALIGNMENT {
+        return Err(Error);
+    }
+    let unfragmentable = &datagram[..unfragmentable_len];
+    let data_len = (mtu - unfragmentable_len - IPV6_FRAGMENT_HEADER_LEN) / ALIGNMENT * ALIGNMENT;
+    let first_fragmentable = datagram[next_header_at];
+
+    let mut fragments = Vec::new();
+    let mut offset = 0;
+    let mut chunks = datagram[unfragmentable_len..total_len]
+        .chunks(data_len)
+        .peekable();
+    while let Some(chunk) = chunks.next() {
+        let len = unfragmentable_len + IPV6_FRAGMENT_HEADER_LEN + chunk.len();
+        let mut fragment = Vec::with_capacity(len);
+        fragment.extend_from_slice(unfragmentable);
+        fragment.resize(unfragmentable_len + IPV6_FRAGMENT_HEADER_LEN, 0);
+        fragment.extend_from_slice(chunk);
+        fragment[next_header_at] = IpProtocol::Ipv6Frag.into();
+
+        let mut header = Ipv6ExtHeader::new_unchecked(&mut fragment[unfragmentable_len..]);
+        header.set_next_header(first_fragmentable.into());
+        header.set_header_len(0);
+        Ipv6FragmentRepr {
+            frag_offset: (offset / ALIGNMENT) as u16,
+            more_frags: chunks.peek().is_some(),
+            ident,
+        }
+        .emit(&mut Ipv6FragmentHeader::new_unchecked(header.payload_mut()));
+
+        let mut packet = Ipv6Packet::new_unchecked(&mut fragment[..]);
+        packet.set_payload_len((len - IPV6_HEADER_LEN) as u16);
+
+        fragments.push(fragment);
+        offset += chunk.len();
+    }
+    Ok(fragments)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wire::IpProtocol;
+
+    const PAYLOAD_LEN: usize = 2000;
+    const MTU: usize = 576;
+
+    fn payload() -> Vec<u8> {
+        (0..PAYLOAD_LEN).map(|i| i as u8).collect()
+    }
+
+    #[cfg(feature = "proto-ipv4")]
+    fn ipv4_datagram() -> Vec<u8> {
+        use crate::phy::ChecksumCapabilities;
+        use crate::wire::{Ipv4Address, Ipv4Repr};
+
+        let repr = Ipv4Repr {
+            src_addr: Ipv4Address::new(192, 0, 2, 1),
+            dst_addr: Ipv4Address::new(192, 0, 2, 2),
+            next_header: IpProtocol::Udp,
+            payload_len: PAYLOAD_LEN,
+            hop_limit: 64,
+        };
+        let mut datagram = vec![0; repr.buffer_len() + PAYLOAD_LEN];
+        let mut packet = Ipv4Packet::new_unchecked(&mut datagram[..]);
+        repr.emit(&mut packet, &ChecksumCapabilities::default());
+        packet.set_dont_frag(false);
+        packet.payload_mut().copy_from_slice(&payload());
+        packet.fill_checksum();
+        datagram
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv4")]
+    fn test_fragment_ipv4() {
+        let fragments = fragment_ipv4(&ipv4_datagram(), MTU, 0x1234).unwrap();
+        assert_eq!(fragments.len(), 4);
+
+        let mut reassembled = vec![0; PAYLOAD_LEN];
+        for (i, fragment) in fragments.iter().enumerate() {
+            assert!(fragment.len() <= MTU);
+            let packet = Ipv4Packet::new_checked(&fragment[..]).unwrap();
+            assert!(packet.verify_checksum());
+            assert_eq!(packet.ident(), 0x1234);
+            assert_eq!(packet.frag_offset() as usize, i * 552);
+            assert_eq!(packet.more_frags(), i < 3);
+            if packet.more_frags() {
+                assert_eq!(packet.payload().len() % 8, 0);
+            }
+            let offset = packet.frag_offset() as usize;
+            reassembled[offset..offset + packet.payload().len()].copy_from_slice(packet.payload());
+        }
+        assert_eq!(reassembled, payload());
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv4")]
+    fn test_fragment_ipv4_unfragmented() {
+        let datagram = ipv4_datagram();
+        assert_eq!(
+            fragment_ipv4(&datagram, 2020, 0x1234),
+            Ok(vec![datagram.clone()])
+        );
+
+        let mut datagram = datagram;
+        Ipv4Packet::new_unchecked(&mut datagram[..]).set_dont_frag(true);
+        assert_eq!(fragment_ipv4(&datagram, MTU, 0x1234), Err(Error));
+        assert_eq!(fragment_ipv4(&datagram[..10], MTU, 0x1234), Err(Error));
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv6")]
+    fn test_fragment_ipv6() {
+        use crate::wire::{Ipv6Address, Ipv6Repr};
+
+        // A Hop-by-Hop Options header, with a PadN option, stays unfragmentable.
+        const HOP_BY_HOP: [u8; 8] = [0x11, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let repr = Ipv6Repr {
+            src_addr: Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+            dst_addr: Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2),
+            next_header: IpProtocol::HopByHop,
+            payload_len: HOP_BY_HOP.len() + PAYLOAD_LEN,
+            hop_limit: 64,
+        };
+        let mut datagram = vec![0; repr.buffer_len() + repr.payload_len];
+        let mut packet = Ipv6Packet::new_unchecked(&mut datagram[..]);
+        repr.emit(&mut packet);
+        let payload_mut = packet.payload_mut();
+        payload_mut[..8].copy_from_slice(&HOP_BY_HOP);
+        payload_mut[8..].copy_from_slice(&payload());
+
+        let fragments = fragment_ipv6(&datagram, MTU, 0x12345678).unwrap();
+        assert_eq!(fragments.len(), 4);
+
+        let mut reassembled = vec![0; PAYLOAD_LEN];
+        for (i, fragment) in fragments.iter().enumerate() {
+            assert!(fragment.len() <= MTU);
+            let packet = Ipv6Packet::new_checked(&fragment[..]).unwrap();
+            assert_eq!(packet.src_addr(), repr.src_addr);
+            assert_eq!(packet.next_header(), IpProtocol::HopByHop);
+            assert_eq!(packet.payload()[0], u8::from(IpProtocol::Ipv6Frag));
+            assert_eq!(packet.payload()[1..8], HOP_BY_HOP[1..]);
+
+            let header = Ipv6ExtHeader::new_checked(&packet.payload()[8..]).unwrap();
+            assert_eq!(header.next_header(), IpProtocol::Udp);
+            let frag = Ipv6FragmentRepr::parse(
+                &Ipv6FragmentHeader::new_checked(header.payload()).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(frag.ident, 0x12345678);
+            assert_eq!(frag.frag_offset as usize * 8, i * 520);
+            assert_eq!(frag.more_frags, i < 3);
+
+            let data = &packet.payload()[16..];
+            if frag.more_frags {
+                assert_eq!(data.len() % 8, 0);
+            }
+            let offset = frag.frag_offset as usize * 8;
+            reassembled[offset..offset + data.len()].copy_from_slice(data);
+        }
+        assert_eq!(reassembled, payload());
+
+        // Fragments fit, and are not fragmented again.
+        assert_eq!(
+            fragment_ipv6(&fragments[0], MTU, 1),
+            Ok(vec![fragments[0].clone()])
+        );
+        assert_eq!(fragment_ipv6(&fragments[0], 256, 1), Err(Error));
+        assert_eq!(fragment_ipv6(&datagram, 56, 1), Err(Error));
+    }
+}
@@ -0,0 +1,81 @@
+//! Framing of messages carried over a TCP stream.
+//!
+//! A TCP stream has no message boundaries, so many application protocols
+//! prefix every message with its length. [`LengthPrefixed`] splits the
+//! received octets, as returned by e.g. `tcp::Socket::peek`, into such
+//! frames.
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// Frames prefixed with their length, as a big-endian integer.
+///
+/// The length does not include the prefix itself.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LengthPrefixed;
+
+impl LengthPrefixed {
+    /// Return the first frame of `buf`, whose length prefix is
+    /// `prefix_width` octets wide.
+    ///
+    /// Returns the frame without its prefix, and the number of octets of
+    /// `buf` it spans, prefix included; the next frame starts right after
+    /// them. Returns `None` if `buf` does not hold the whole frame yet.
+    ///
+    /// # Panics
+    /// This function panics if `prefix_width` is not between 1 and 8.
+    pub fn next_frame(buf: &[u8], prefix_width: usize) -> Option<(&[u8], usize)> {
+        assert!((1..=8).contains(&prefix_width));
+
+        let prefix = buf.get(..prefix_width)?;
+        let len = usize::try_from(NetworkEndian::read_uint(prefix, prefix_width)).ok()?;
+        let consumed = prefix_width.checked_add(len)?;
+        let frame = buf.get(prefix_width..consumed)?;
+        Some((frame, consumed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_two_octet_prefix() {
+        let buf = [0x00, 0x03, b'a', b'b', b'c'];
+        assert_eq!(LengthPrefixed::next_frame(&buf, 2), Some((&b"abc"[..], 5)));
+        assert_eq!(
+            LengthPrefixed::next_frame(&[0x00, 0x00], 2),
+            Some((&b""[..], 2))
+        );
+    }
+
+    #[test]
+    fn test_four_octet_prefix() {
+        let buf = [0x00, 0x00, 0x00, 0x02, b'h', b'i', 0xff];
+        assert_eq!(LengthPrefixed::next_frame(&buf, 4), Some((&b"hi"[..], 6)));
+    }
+
+    #[test]
+    fn test_incomplete() {
+        assert_eq!(LengthPrefixed::next_frame(&[], 2), None);
+        assert_eq!(LengthPrefixed::next_frame(&[0x00], 2), None);
+        assert_eq!(
+            LengthPrefixed::next_frame(&[0x00, 0x03, b'a', b'b'], 2),
+            None
+        );
+        assert_eq!(
+            LengthPrefixed::next_frame(&[0x00, 0x00, 0x01, 0x00, b'a'], 4),
+            None
+        );
+        assert_eq!(LengthPrefixed::next_frame(&[0xff; 8], 8), None);
+    }
+
+    #[test]
+    fn test_concatenated_frames() {
+        let buf = [0x00, 0x02, b'a', b'b', 0x00, 0x01, b'c', 0x00];
+        let (frame, consumed) = LengthPrefixed::next_frame(&buf, 2).unwrap();
+        assert_eq!((frame, consumed), (&b"ab"[..], 4));
+        let (frame, consumed) = LengthPrefixed::next_frame(&buf[4..], 2).unwrap();
+        assert_eq!((frame, consumed), (&b"c"[..], 3));
+        assert_eq!(LengthPrefixed::next_frame(&buf[7..], 2), None);
+    }
+}
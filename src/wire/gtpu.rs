@@ -0,0 +1,331 @@
+//! Parsing of GTP-U, the GPRS Tunnelling Protocol carrying user plane traffic
+//! between the nodes of a 4G or 5G mobile core.
+//!
+//! Only the header is interpreted, to find the tunnel a packet belongs to and
+//! the user packet it encapsulates. See [3GPP TS 29.281], section 5.
+//!
+//! [3GPP TS 29.281]: https://www.3gpp.org/DynaReport/29281.htm
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{Error, Result};
+
+/// The UDP port GTP-U messages are sent to.
+pub const PORT: u16 = 2152;
+
+/// The length of the mandatory part of the GTP-U header.
+pub const HEADER_LEN: usize = field::TEID.end;
+
+/// The version of GTP described by this module.
+pub const VERSION: u8 = 1;
+
+enum_with_unknown! {
+    /// The type of a GTP-U message.
+    pub enum MessageType(u8) {
+        EchoRequest = 1,
+        EchoResponse = 2,
+        ErrorIndication = 26,
+        SupportedExtensionHeadersNotification = 31,
+        EndMarker = 254,
+        GPdu = 255,
+    }
+}
+
+/// A read/write wrapper around a GTP-U packet buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |Ver  |P|R|E|S|N| Message Type  |            Length             |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                 Tunnel Endpoint Identifier                    |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |        Sequence Number        | N-PDU Number  | Next Ext Type |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// The last word is only present if one of the E, S or N flags is set. Each
+// extension header starts with its length in words, and ends with the type
+// of the next one.
+mod field {
+    use crate::wire::field::*;
+
+    pub const FLAGS: usize = 0;
+    pub const MESSAGE_TYPE: usize = 1;
+    pub const LENGTH: Field = 2..4;
+    pub const TEID: Field = 4..8;
+    pub const SEQUENCE: Field = 8..10;
+    pub const N_PDU: usize = 10;
+    pub const NEXT_EXT_TYPE: usize = 11;
+    pub const OPTIONAL: Field = 8..12;
+}
+
+const FLAG_PT: u8 = 0x10;
+const FLAG_E: u8 = 0x04;
+const FLAG_S: u8 = 0x02;
+const FLAG_PN: u8 = 0x01;
+
+impl<T: AsRef<[u8]>> Packet<T> {
+    /// Imbue a raw octet buffer with GTP-U packet structure.
+    pub const fn new_unchecked(buffer: T) -> Packet<T> {
+        Packet { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Packet<T>> {
+        let packet = Self::new_unchecked(buffer);
+        packet.check_len()?;
+        Ok(packet)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is shorter than the length field
+    /// says, or if the optional fields and extension headers do not fit in
+    /// that length.
+    pub fn check_len(&self) -> Result<()> {
+        let data = self.buffer.as_ref();
+        if data.len() < HEADER_LEN {
+            return Err(Error);
+        }
+        let total_len = HEADER_LEN + self.length() as usize;
+        if data.len() < total_len {
+            return Err(Error);
+        }
+        if !self.has_optional_fields() {
+            return Ok(());
+        }
+        if total_len < field::OPTIONAL.end {
+            return Err(Error);
+        }
+
+        let mut offset = field::OPTIONAL.end;
+        let mut next_type = data[field::NEXT_EXT_TYPE];
+        while self.has_extension_header() && next_type != 0 {
+            let ext_len = match data.get(offset) {
+                Some(&words) if offset < total_len => words as usize * 4,
+                _ => return Err(Error),
+            };
+            if ext_len == 0 || offset + ext_len > total_len {
+                return Err(Error);
+            }
+            next_type = data[offset + ext_len - 1];
+            offset += ext_len;
+        }
+        Ok(())
+    }
+
+    /// Consume the packet, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the version field.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.buffer.as_ref()[field::FLAGS] >> 5
+    }
+
+    /// Return the protocol type flag, set for GTP and clear for GTP'.
+    #[inline]
+    pub fn protocol_type(&self) -> bool {
+        self.buffer.as_ref()[field::FLAGS] & FLAG_PT != 0
+    }
+
+    /// Return the extension header flag.
+    #[inline]
+    pub fn has_extension_header(&self) -> bool {
+        self.buffer.as_ref()[field::FLAGS] & FLAG_E != 0
+    }
+
+    /// Return the sequence number flag.
+    #[inline]
+    pub fn has_sequence_number(&self) -> bool {
+        self.buffer.as_ref()[field::FLAGS] & FLAG_S != 0
+    }
+
+    /// Return the N-PDU number flag.
+    #[inline]
+    pub fn has_n_pdu_number(&self) -> bool {
+        self.buffer.as_ref()[field::FLAGS] & FLAG_PN != 0
+    }
+
+    /// Return whether the header has the optional sequence number, N-PDU
+    /// number and next extension header type fields.
+    ///
+    /// They are present, though possibly meaningless, if any of the E, S or
+    /// PN flags is set.
+    #[inline]
+    pub fn has_optional_fields(&self) -> bool {
+        self.buffer.as_ref()[field::FLAGS] & (FLAG_E | FLAG_S | FLAG_PN) != 0
+    }
+
+    /// Return the message type field.
+    #[inline]
+    pub fn message_type(&self) -> MessageType {
+        MessageType::from(self.buffer.as_ref()[field::MESSAGE_TYPE])
+    }
+
+    /// Return the length field, which counts the octets following the
+    /// mandatory part of the header.
+    #[inline]
+    pub fn length(&self) -> u16 {
+        NetworkEndian::read_u16(&self.buffer.as_ref()[field::LENGTH])
+    }
+
+    /// Return the tunnel endpoint identifier field.
+    #[inline]
+    pub fn teid(&self) -> u32 {
+        NetworkEndian::read_u32(&self.buffer.as_ref()[field::TEID])
+    }
+
+    /// Return the sequence number, if the S flag is set.
+    #[inline]
+    pub fn sequence_number(&self) -> Option<u16> {
+        self.has_sequence_number()
+            .then(|| NetworkEndian::read_u16(&self.buffer.as_ref()[field::SEQUENCE]))
+    }
+
+    /// Return the N-PDU number, if the PN flag is set.
+    #[inline]
+    pub fn n_pdu_number(&self) -> Option<u8> {
+        self.has_n_pdu_number()
+            .then(|| self.buffer.as_ref()[field::N_PDU])
+    }
+
+    /// Return the type of the first extension header, if the E flag is set
+    /// and there is one.
+    #[inline]
+    pub fn next_extension_header_type(&self) -> Option<u8> {
+        if !self.has_extension_header() {
+            return None;
+        }
+        match self.buffer.as_ref()[field::NEXT_EXT_TYPE] {
+            0 => None,
+            next_type => Some(next_type),
+        }
+    }
+
+    /// Return the length of the whole header, including the optional fields
+    /// and the extension headers.
+    pub fn header_len(&self) -> usize {
+        if !self.has_optional_fields() {
+            return HEADER_LEN;
+        }
+
+        let data = self.buffer.as_ref();
+        let mut offset = field::OPTIONAL.end;
+        let mut next_type = self.next_extension_header_type().unwrap_or(0);
+        while next_type != 0 {
+            let ext_len = data[offset] as usize * 4;
+            next_type = data[offset + ext_len - 1];
+            offset += ext_len;
+        }
+        offset
+    }
+}
+
+impl<'a, T: AsRef<[u8]> + ?Sized> Packet<&'a T> {
+    /// Return a pointer to the encapsulated packet.
+    ///
+    /// Any octets following the length given in the header are left out.
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        let header_len = self.header_len();
+        let total_len = HEADER_LEN + self.length() as usize;
+        &self.buffer.as_ref()[header_len..total_len]
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Packet<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static PAYLOAD_BYTES: [u8; 4] = [0x45, 0x00, 0x00, 0x14];
+
+    static GPDU_BYTES: [u8; 14] = [
+        0x30, 0xff, 0x00, 0x04, 0x12, 0x34, 0x56, 0x78, 0x45, 0x00, 0x00, 0x14, 0xa5, 0xa5,
+    ];
+
+    static GPDU_SEQ_BYTES: [u8; 16] = [
+        0x32, 0xff, 0x00, 0x08, 0x12, 0x34, 0x56, 0x78, 0x00, 0x2a, 0x00, 0x00, 0x45, 0x00, 0x00,
+        0x14,
+    ];
+
+    // With a PDU session container extension header.
+    static GPDU_EXT_BYTES: [u8; 20] = [
+        0x34, 0xff, 0x00, 0x0c, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x85, 0x01, 0x00, 0x09,
+        0x00, 0x45, 0x00, 0x00, 0x14,
+    ];
+
+    #[test]
+    fn test_gpdu() {
+        let packet = Packet::new_checked(&GPDU_BYTES[..]).unwrap();
+        assert_eq!(packet.version(), VERSION);
+        assert!(packet.protocol_type());
+        assert_eq!(packet.message_type(), MessageType::GPdu);
+        assert_eq!(packet.length(), 4);
+        assert_eq!(packet.teid(), 0x12345678);
+        assert!(!packet.has_optional_fields());
+        assert_eq!(packet.sequence_number(), None);
+        assert_eq!(packet.n_pdu_number(), None);
+        assert_eq!(packet.next_extension_header_type(), None);
+        assert_eq!(packet.header_len(), HEADER_LEN);
+        assert_eq!(packet.payload(), &PAYLOAD_BYTES[..]);
+    }
+
+    #[test]
+    fn test_gpdu_sequence_number() {
+        let packet = Packet::new_checked(&GPDU_SEQ_BYTES[..]).unwrap();
+        assert_eq!(packet.message_type(), MessageType::GPdu);
+        assert_eq!(packet.teid(), 0x12345678);
+        assert_eq!(packet.sequence_number(), Some(42));
+        assert_eq!(packet.n_pdu_number(), None);
+        assert_eq!(packet.next_extension_header_type(), None);
+        assert_eq!(packet.header_len(), 12);
+        assert_eq!(packet.payload(), &PAYLOAD_BYTES[..]);
+    }
+
+    #[test]
+    fn test_gpdu_extension_header() {
+        let packet = Packet::new_checked(&GPDU_EXT_BYTES[..]).unwrap();
+        assert_eq!(packet.teid(), 0x12345678);
+        assert_eq!(packet.sequence_number(), None);
+        assert_eq!(packet.next_extension_header_type(), Some(0x85));
+        assert_eq!(packet.header_len(), 16);
+        assert_eq!(packet.payload(), &PAYLOAD_BYTES[..]);
+    }
+
+    #[test]
+    fn test_check_len() {
+        // Shorter than the mandatory header.
+        assert_eq!(Packet::new_checked(&GPDU_BYTES[..7]), Err(Error));
+        // Shorter than the length field says.
+        assert_eq!(Packet::new_checked(&GPDU_SEQ_BYTES[..15]), Err(Error));
+
+        // Optional fields that do not fit in the length.
+        let mut bytes = GPDU_BYTES;
+        bytes[0] |= FLAG_S;
+        bytes[3] = 2;
+        assert_eq!(Packet::new_checked(&bytes[..]), Err(Error));
+
+        // An extension header of zero length, or overrunning the packet.
+        let mut bytes = GPDU_EXT_BYTES;
+        bytes[12] = 0;
+        assert_eq!(Packet::new_checked(&bytes[..]), Err(Error));
+        bytes[12] = 3;
+        assert_eq!(Packet::new_checked(&bytes[..]), Err(Error));
+    }
+}
@@ -5,7 +5,7 @@ use byteorder::{ByteOrder, NetworkEndian};
 use super::{Error, Result};
 use crate::{
     phy::ChecksumCapabilities,
-    wire::{ip::checksum, Ipv4Packet, Ipv4Repr},
+    wire::{ip::checksum, Ipv4Packet, Ipv4Repr, IPV4_HEADER_LEN},
 };
 
 enum_with_unknown! {
@@ -182,6 +182,8 @@ mod field {
     pub const ECHO_IDENT: Field = 4..6;
     pub const ECHO_SEQNO: Field = 6..8;
 
+    pub const PARAM_PROBLEM_POINTER: usize = 4;
+
     pub const HEADER_END: usize = 8;
 }
 
@@ -264,6 +266,17 @@ impl<T: AsRef<[u8]>> Packet<T> {
         NetworkEndian::read_u16(&data[field::ECHO_SEQNO])
     }
 
+    /// Return the pointer field (for parameter problem packets).
+    ///
+    /// # Panics
+    /// This function may panic if this packet is not a parameter problem
+    /// packet.
+    #[inline]
+    pub fn param_problem_pointer(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::PARAM_PROBLEM_POINTER]
+    }
+
     /// Return the header length.
     /// The result depends on the value of the message type field.
     pub fn header_len(&self) -> usize {
@@ -342,6 +355,17 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
         NetworkEndian::write_u16(&mut data[field::ECHO_SEQNO], value)
     }
 
+    /// Set the pointer field (for parameter problem packets).
+    ///
+    /// # Panics
+    /// This function may panic if this packet is not a parameter problem
+    /// packet.
+    #[inline]
+    pub fn set_param_problem_pointer(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::PARAM_PROBLEM_POINTER] = value
+    }
+
     /// Compute and fill in the header checksum.
     pub fn fill_checksum(&mut self) {
         self.set_checksum(0);
@@ -395,9 +419,38 @@ pub enum Repr<'a> {
         header: Ipv4Repr,
         data: &'a [u8],
     },
+    ParamProblem {
+        reason: ParamProblem,
+        /// The offset of the octet where the problem was found, in the
+        /// offending packet.
+        pointer: u8,
+        /// The offending datagram, quoted verbatim from the start of its
+        /// header, so that `pointer` can refer to its options.
+        data: &'a [u8],
+    },
 }
 
+/// The number of octets of the offending datagram's payload quoted in error
+/// messages, as required by RFC 792.
+const ERROR_QUOTE_LEN: usize = 8;
+
 impl<'a> Repr<'a> {
+    /// Build a Parameter Problem message about the octet at `pointer` in the
+    /// header of the offending datagram.
+    ///
+    /// The message quotes the offending header, options included, and the
+    /// first eight octets of its payload, as required by RFC 792; `offending`
+    /// may be longer, and is truncated.
+    pub fn parameter_problem(pointer: u8, offending: &'a [u8]) -> Repr<'a> {
+        let header_len = Ipv4Packet::new_unchecked(offending).header_len() as usize;
+        let data = &offending[..cmp::min(offending.len(), header_len + ERROR_QUOTE_LEN)];
+        Repr::ParamProblem {
+            reason: ParamProblem::AtPointer,
+            pointer,
+            data,
+        }
+    }
+
     /// Parse an Internet Control Message Protocol version 4 packet and return
     /// a high-level representation.
     pub fn parse<T>(
@@ -471,6 +524,27 @@ impl<'a> Repr<'a> {
                 })
             }
 
+            (Message::ParamProblem, code) => {
+                // The quoted header keeps the total length of the offending
+                // datagram, so only check that the header itself is present.
+                let data = packet.data();
+                if data.is_empty() {
+                    return Err(Error);
+                }
+                let header_len = Ipv4Packet::new_unchecked(data).header_len() as usize;
+                // RFC 792 requires exactly eight bytes to be returned.
+                // We allow more, since there isn't a reason not to, but require at least eight.
+                if header_len < IPV4_HEADER_LEN || data.len() < header_len + ERROR_QUOTE_LEN {
+                    return Err(Error);
+                }
+
+                Ok(Repr::ParamProblem {
+                    reason: ParamProblem::from(code),
+                    pointer: packet.param_problem_pointer(),
+                    data,
+                })
+            }
+
             _ => Err(Error),
         }
     }
@@ -486,6 +560,7 @@ impl<'a> Repr<'a> {
             | &Repr::TimeExceeded { header, data, .. } => {
                 field::UNUSED.end + header.buffer_len() + data.len()
             }
+            &Repr::ParamProblem { data, .. } => field::UNUSED.end + data.len(),
         }
     }
 
@@ -550,6 +625,18 @@ impl<'a> Repr<'a> {
                 let payload = &mut ip_packet.into_inner()[header.buffer_len()..];
                 payload.copy_from_slice(data)
             }
+
+            Repr::ParamProblem {
+                reason,
+                pointer,
+                data,
+            } => {
+                packet.set_msg_type(Message::ParamProblem);
+                packet.set_msg_code(reason.into());
+                packet.set_param_problem_pointer(pointer);
+                packet.buffer.as_mut()[field::PARAM_PROBLEM_POINTER + 1..field::UNUSED.end].fill(0);
+                packet.data_mut()[..data.len()].copy_from_slice(data)
+            }
         }
 
         if checksum_caps.icmpv4.tx() {
@@ -614,6 +701,11 @@ impl<'a> fmt::Display for Repr<'a> {
             Repr::TimeExceeded { reason, .. } => {
                 write!(f, "ICMPv4 time exceeded ({reason})")
             }
+            Repr::ParamProblem {
+                reason, pointer, ..
+            } => {
+                write!(f, "ICMPv4 parameter problem ({reason:?}) pointer={pointer}")
+            }
         }
     }
 }
@@ -633,7 +725,7 @@ impl<T: AsRef<[u8]>> PrettyPrint for Packet<T> {
         write!(f, "{indent}{packet}")?;
 
         match packet.msg_type() {
-            Message::DstUnreachable | Message::TimeExceeded => {
+            Message::DstUnreachable | Message::TimeExceeded | Message::ParamProblem => {
                 indent.increase(f)?;
                 super::Ipv4Packet::<&[u8]>::pretty_print(&packet.data(), f, indent)
             }
@@ -708,4 +800,63 @@ mod test {
         assert_eq!(Packet::new_checked(&bytes[..4]), Err(Error));
         assert!(Packet::new_checked(&bytes[..]).is_ok());
     }
+
+    #[test]
+    fn test_parameter_problem() {
+        // An offending header carrying a Loose Source Route option, followed
+        // by twelve octets of payload.
+        let mut offending = vec![0u8; 28 + 12];
+        let mut ip_packet = Ipv4Packet::new_unchecked(&mut offending[..]);
+        Ipv4Repr {
+            src_addr: crate::wire::Ipv4Address([192, 168, 1, 2]),
+            dst_addr: crate::wire::Ipv4Address([192, 168, 1, 1]),
+            next_header: crate::wire::IpProtocol::Udp,
+            payload_len: 12,
+            hop_limit: 64,
+        }
+        .emit(&mut ip_packet, &ChecksumCapabilities::default());
+        ip_packet.set_header_len(28);
+        ip_packet.set_total_len(28 + 12);
+        offending[20..28].copy_from_slice(&[0x83, 0x07, 0x08, 192, 168, 1, 3, 0x00]);
+        for (i, b) in offending[28..].iter_mut().enumerate() {
+            *b = 0x11 * (i as u8 + 1);
+        }
+
+        let repr = Repr::parameter_problem(20, &offending);
+        assert_eq!(repr.buffer_len(), 8 + 28 + 8);
+
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        repr.emit(&mut packet, &ChecksumCapabilities::default());
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert!(packet.verify_checksum());
+        assert_eq!(packet.msg_type(), Message::ParamProblem);
+        assert_eq!(packet.msg_code(), 0);
+        assert_eq!(packet.param_problem_pointer(), 20);
+        assert_eq!(&bytes[5..8], &[0, 0, 0]);
+        assert_eq!(packet.data(), &offending[..36]);
+        assert_eq!(packet.data()[20], 0x83);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(
+            Repr::parse(&packet, &ChecksumCapabilities::default()),
+            Ok(repr)
+        );
+    }
+
+    #[test]
+    fn test_parameter_problem_short_quote() {
+        let mut bytes = [0u8; 8 + 20 + 4];
+        bytes[0] = 0x0c;
+        bytes[4] = 9;
+        bytes[8] = 0x45;
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        packet.fill_checksum();
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(
+            Repr::parse(&packet, &ChecksumCapabilities::default()),
+            Err(Error)
+        );
+    }
 }
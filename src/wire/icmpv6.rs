@@ -614,6 +614,27 @@ pub enum Repr<'a> {
 }
 
 impl<'a> Repr<'a> {
+    /// Build a Parameter Problem message about the octet at `pointer` in the
+    /// offending packet.
+    ///
+    /// The message quotes the offending header and as much of its `payload`
+    /// as fits in the minimum MTU, as required by RFC 4443.
+    pub fn parameter_problem(
+        reason: ParamProblem,
+        pointer: u32,
+        offending: Ipv6Repr,
+        payload: &'a [u8],
+    ) -> Repr<'a> {
+        let max_len = MAX_ERROR_PACKET_LEN - field::POINTER.end - IPV6_HEADER_LEN;
+        let data = &payload[..cmp::min(payload.len(), max_len)];
+        Repr::ParamProblem {
+            reason,
+            pointer,
+            header: offending,
+            data,
+        }
+    }
+
     /// Parse an Internet Control Message Protocol version 6 packet and return
     /// a high-level representation.
     pub fn parse<T>(
@@ -1096,4 +1117,43 @@ mod test {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_parameter_problem() {
+        let offending = Ipv6Repr {
+            src_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 2),
+            dst_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+            next_header: IpProtocol::Unknown(0x0c),
+            payload_len: 1400,
+            hop_limit: 64,
+        };
+        let payload = [0x5a; 1400];
+        let repr =
+            Repr::parameter_problem(ParamProblem::UnrecognizedNxtHdr, 6, offending, &payload);
+        // The message fits in the minimum MTU, along with its IPv6 header.
+        assert_eq!(repr.buffer_len(), IPV6_MIN_MTU - IPV6_HEADER_LEN);
+
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        repr.emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &ChecksumCapabilities::default(),
+        );
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert!(packet.verify_checksum(&MOCK_IP_ADDR_1, &MOCK_IP_ADDR_2));
+        assert_eq!(packet.msg_type(), Message::ParamProblem);
+        assert_eq!(packet.msg_code(), 1);
+        assert_eq!(packet.param_problem_ptr(), 6);
+
+        let quoted = Ipv6Packet::new_unchecked(packet.payload());
+        assert_eq!(quoted.payload_len(), 1400);
+        assert_eq!(quoted.src_addr(), offending.src_addr);
+        assert_eq!(quoted.next_header(), offending.next_header);
+        let quoted_payload = &packet.payload()[IPV6_HEADER_LEN..];
+        assert_eq!(quoted_payload.len(), 1192);
+        assert_eq!(quoted_payload, &payload[..1192]);
+    }
 }
@@ -0,0 +1,428 @@
+//! IEEE 802.11 data frames, as captured in monitor mode.
+//!
+//! Only the MAC header of data frames is understood, which is enough to find
+//! the endpoints of a frame and, for unprotected frames, the LLC/SNAP header
+//! carrying the EtherType of the payload. Unlike most other protocols, the
+//! multi-octet fields of 802.11 are little-endian.
+
+use byteorder::{ByteOrder, LittleEndian, NetworkEndian};
+
+use super::{Error, EthernetAddress, EthernetProtocol, Result};
+
+enum_with_unknown! {
+    /// IEEE 802.11 frame type.
+    pub enum FrameType(u8) {
+        Management = 0,
+        Control    = 1,
+        Data       = 2,
+        Extension  = 3
+    }
+}
+
+/// The LLC header and the RFC 1042 SNAP organization code preceding the
+/// EtherType of an encapsulated packet.
+pub const LLC_SNAP_HEADER: [u8; 6] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00];
+
+/// The LLC header and the 802.1H SNAP organization code, used instead of the
+/// RFC 1042 one for a few EtherTypes.
+pub const LLC_SNAP_BRIDGE_TUNNEL_HEADER: [u8; 6] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0xf8];
+
+/// A read/write wrapper around an IEEE 802.11 data frame buffer.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Frame<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+mod field {
+    use crate::wire::field::*;
+
+    pub const FRAME_CONTROL: Field = 0..2;
+    pub const DURATION: Field = 2..4;
+    pub const ADDR1: Field = 4..10;
+    pub const ADDR2: Field = 10..16;
+    pub const ADDR3: Field = 16..22;
+    pub const SEQUENCE_CONTROL: Field = 22..24;
+    pub const ADDR4: Field = 24..30;
+
+    pub const SNAP_ETHERTYPE: Field = 6..8;
+}
+
+/// The length of a data frame header with three addresses and no QoS control.
+pub const HEADER_LEN: usize = field::SEQUENCE_CONTROL.end;
+
+/// The length of an LLC/SNAP header, including the EtherType.
+pub const SNAP_HEADER_LEN: usize = field::SNAP_ETHERTYPE.end;
+
+const TO_DS: u16 = 0x0100;
+const FROM_DS: u16 = 0x0200;
+const PROTECTED: u16 = 0x4000;
+const ORDER: u16 = 0x8000;
+
+/// The subtype bit of QoS data frames, which carry a QoS control field.
+const SUBTYPE_QOS: u8 = 0x8;
+
+const QOS_CONTROL_LEN: usize = 2;
+const HT_CONTROL_LEN: usize = 4;
+
+impl<T: AsRef<[u8]>> Frame<T> {
+    /// Imbue a raw octet buffer with IEEE 802.11 data frame structure.
+    pub const fn new_unchecked(buffer: T) -> Frame<T> {
+        Frame { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Frame<T>> {
+        let frame = Self::new_unchecked(buffer);
+        frame.check_len()?;
+        Ok(frame)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is too short, or if the frame is not
+    /// a data frame of protocol version 0.
+    pub fn check_len(&self) -> Result<()> {
+        let len = self.buffer.as_ref().len();
+        if len < field::FRAME_CONTROL.end {
+            return Err(Error);
+        }
+        if self.version() != 0 || self.frame_type() != FrameType::Data {
+            return Err(Error);
+        }
+        if len < self.header_len() {
+            return Err(Error);
+        }
+        Ok(())
+    }
+
+    /// Consumes the frame, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    #[inline]
+    fn frame_control(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        LittleEndian::read_u16(&data[field::FRAME_CONTROL])
+    }
+
+    /// Return the protocol version field.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        (self.frame_control() & 0x3) as u8
+    }
+
+    /// Return the frame type field.
+    #[inline]
+    pub fn frame_type(&self) -> FrameType {
+        FrameType::from(((self.frame_control() >> 2) & 0x3) as u8)
+    }
+
+    /// Return the frame subtype field.
+    #[inline]
+    pub fn subtype(&self) -> u8 {
+        ((self.frame_control() >> 4) & 0xf) as u8
+    }
+
+    /// Return the To DS flag, set on frames sent to the distribution system.
+    #[inline]
+    pub fn to_ds(&self) -> bool {
+        self.frame_control() & TO_DS != 0
+    }
+
+    /// Return the From DS flag, set on frames coming from the distribution
+    /// system.
+    #[inline]
+    pub fn from_ds(&self) -> bool {
+        self.frame_control() & FROM_DS != 0
+    }
+
+    /// Return the Protected Frame flag, set if the frame body is encrypted.
+    #[inline]
+    pub fn protected(&self) -> bool {
+        self.frame_control() & PROTECTED != 0
+    }
+
+    /// Return whether the frame is a QoS data frame.
+    #[inline]
+    pub fn is_qos(&self) -> bool {
+        self.subtype() & SUBTYPE_QOS != 0
+    }
+
+    /// Return the duration field.
+    #[inline]
+    pub fn duration(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        LittleEndian::read_u16(&data[field::DURATION])
+    }
+
+    /// Return the first address field, the receiver of the frame.
+    #[inline]
+    pub fn addr1(&self) -> EthernetAddress {
+        let data = self.buffer.as_ref();
+        EthernetAddress::from_bytes(&data[field::ADDR1])
+    }
+
+    /// Return the second address field, the transmitter of the frame.
+    #[inline]
+    pub fn addr2(&self) -> EthernetAddress {
+        let data = self.buffer.as_ref();
+        EthernetAddress::from_bytes(&data[field::ADDR2])
+    }
+
+    /// Return the third address field.
+    #[inline]
+    pub fn addr3(&self) -> EthernetAddress {
+        let data = self.buffer.as_ref();
+        EthernetAddress::from_bytes(&data[field::ADDR3])
+    }
+
+    /// Return the fourth address field, only present if both the To DS and
+    /// From DS flags are set.
+    #[inline]
+    pub fn addr4(&self) -> Option<EthernetAddress> {
+        let data = self.buffer.as_ref();
+        if self.to_ds() && self.from_ds() {
+            Some(EthernetAddress::from_bytes(&data[field::ADDR4]))
+        } else {
+            None
+        }
+    }
+
+    /// Return the address of the final recipient of the frame.
+    #[inline]
+    pub fn dst_addr(&self) -> EthernetAddress {
+        if self.to_ds() {
+            self.addr3()
+        } else {
+            self.addr1()
+        }
+    }
+
+    /// Return the address of the original sender of the frame.
+    #[inline]
+    pub fn src_addr(&self) -> EthernetAddress {
+        if let Some(addr) = self.addr4() {
+            addr
+        } else if self.from_ds() {
+            self.addr3()
+        } else {
+            self.addr2()
+        }
+    }
+
+    /// Return the sequence number field.
+    #[inline]
+    pub fn sequence_number(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        LittleEndian::read_u16(&data[field::SEQUENCE_CONTROL]) >> 4
+    }
+
+    /// Return the fragment number field.
+    #[inline]
+    pub fn fragment_number(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        (LittleEndian::read_u16(&data[field::SEQUENCE_CONTROL]) & 0xf) as u8
+    }
+
+    /// Return the length of the MAC header, which depends on the number of
+    /// addresses and on the presence of the QoS and HT control fields.
+    pub fn header_len(&self) -> usize {
+        let mut len = HEADER_LEN;
+        if self.to_ds() && self.from_ds() {
+            len += field::ADDR4.len();
+        }
+        if self.is_qos() {
+            len += QOS_CONTROL_LEN;
+            if self.frame_control() & ORDER != 0 {
+                len += HT_CONTROL_LEN;
+            }
+        }
+        len
+    }
+
+    /// Return the EtherType of the packet encapsulated in the frame body.
+    ///
+    /// Returns `None` if the frame body is encrypted or does not start with
+    /// an LLC/SNAP header.
+    pub fn snap_ethertype(&self) -> Option<EthernetProtocol> {
+        let data = self.buffer.as_ref();
+        let body = &data[self.header_len()..];
+        if self.protected() || body.len() < SNAP_HEADER_LEN {
+            return None;
+        }
+        let header = &body[..LLC_SNAP_HEADER.len()];
+        if header != LLC_SNAP_HEADER && header != LLC_SNAP_BRIDGE_TUNNEL_HEADER {
+            return None;
+        }
+        Some(EthernetProtocol::from(NetworkEndian::read_u16(
+            &body[field::SNAP_ETHERTYPE],
+        )))
+    }
+}
+
+impl<'a, T: AsRef<[u8]> + ?Sized> Frame<&'a T> {
+    /// Return a pointer to the frame body.
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        let data = self.buffer.as_ref();
+        &data[self.header_len()..]
+    }
+
+    /// Return a pointer to the packet following the LLC/SNAP header.
+    ///
+    /// Returns `None` if [snap_ethertype] does.
+    ///
+    /// [snap_ethertype]: #method.snap_ethertype
+    #[inline]
+    pub fn snap_payload(&self) -> Option<&'a [u8]> {
+        self.snap_ethertype()?;
+        Some(&self.payload()[SNAP_HEADER_LEN..])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A data frame from an access point to a station, carrying an IPv4 packet.
+    #[rustfmt::skip]
+    static FROM_AP_FRAME_BYTES: [u8; 36] = [
+        // Frame control: data, From DS
+        0x08, 0x02,
+        // Duration
+        0x2c, 0x00,
+        // Address 1: destination
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x01,
+        // Address 2: BSSID
+        0x02, 0x00, 0x00, 0x00, 0x00, 0xaa,
+        // Address 3: source
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x02,
+        // Sequence control: sequence 0x123, fragment 0
+        0x30, 0x12,
+        // LLC/SNAP header, IPv4
+        0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00, 0x08, 0x00,
+        // Start of the IPv4 packet
+        0x45, 0x00, 0x00, 0x14,
+    ];
+
+    // A QoS data frame between two access points, carrying an IPv6 packet.
+    #[rustfmt::skip]
+    static WDS_QOS_FRAME_BYTES: [u8; 44] = [
+        // Frame control: QoS data, To DS and From DS
+        0x88, 0x03,
+        // Duration
+        0x00, 0x00,
+        // Address 1: receiver
+        0x02, 0x00, 0x00, 0x00, 0x00, 0xbb,
+        // Address 2: transmitter
+        0x02, 0x00, 0x00, 0x00, 0x00, 0xaa,
+        // Address 3: destination
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x01,
+        // Sequence control: sequence 1, fragment 2
+        0x12, 0x00,
+        // Address 4: source
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x02,
+        // QoS control
+        0x00, 0x00,
+        // LLC/SNAP header, IPv6
+        0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00, 0x86, 0xdd,
+        // Start of the IPv6 packet
+        0x60, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_deconstruct_from_ap() {
+        let frame = Frame::new_checked(&FROM_AP_FRAME_BYTES[..]).unwrap();
+        assert_eq!(frame.frame_type(), FrameType::Data);
+        assert_eq!(frame.subtype(), 0);
+        assert!(!frame.to_ds());
+        assert!(frame.from_ds());
+        assert!(!frame.protected());
+        assert_eq!(frame.duration(), 44);
+        assert_eq!(
+            frame.addr1(),
+            EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x01])
+        );
+        assert_eq!(
+            frame.addr2(),
+            EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0xaa])
+        );
+        assert_eq!(
+            frame.addr3(),
+            EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x02])
+        );
+        assert_eq!(frame.addr4(), None);
+        assert_eq!(frame.dst_addr(), frame.addr1());
+        assert_eq!(frame.src_addr(), frame.addr3());
+        assert_eq!(frame.sequence_number(), 0x123);
+        assert_eq!(frame.fragment_number(), 0);
+        assert_eq!(frame.header_len(), 24);
+        assert_eq!(frame.snap_ethertype(), Some(EthernetProtocol::Ipv4));
+        assert_eq!(frame.snap_payload(), Some(&[0x45, 0x00, 0x00, 0x14][..]));
+    }
+
+    #[test]
+    fn test_deconstruct_wds_qos() {
+        let frame = Frame::new_checked(&WDS_QOS_FRAME_BYTES[..]).unwrap();
+        assert!(frame.is_qos());
+        assert!(frame.to_ds());
+        assert!(frame.from_ds());
+        assert_eq!(
+            frame.addr4(),
+            Some(EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x02]))
+        );
+        assert_eq!(
+            frame.dst_addr(),
+            EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x01])
+        );
+        assert_eq!(
+            frame.src_addr(),
+            EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x02])
+        );
+        assert_eq!(frame.sequence_number(), 1);
+        assert_eq!(frame.fragment_number(), 2);
+        assert_eq!(frame.header_len(), 32);
+        assert_eq!(frame.snap_ethertype(), Some(EthernetProtocol::Ipv6));
+        assert_eq!(frame.snap_payload(), Some(&[0x60, 0x00, 0x00, 0x00][..]));
+    }
+
+    #[test]
+    fn test_no_snap() {
+        let mut bytes = FROM_AP_FRAME_BYTES;
+        // Protected frames are encrypted.
+        bytes[1] |= 0x40;
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(frame.snap_ethertype(), None);
+        assert_eq!(frame.snap_payload(), None);
+
+        let mut bytes = FROM_AP_FRAME_BYTES;
+        bytes[24] = 0x42;
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(frame.snap_ethertype(), None);
+
+        let frame = Frame::new_checked(&FROM_AP_FRAME_BYTES[..30]).unwrap();
+        assert_eq!(frame.snap_ethertype(), None);
+    }
+
+    #[test]
+    fn test_check_len() {
+        assert_eq!(
+            Frame::new_checked(&FROM_AP_FRAME_BYTES[..23]).err(),
+            Some(Error)
+        );
+        assert_eq!(
+            Frame::new_checked(&WDS_QOS_FRAME_BYTES[..31]).err(),
+            Some(Error)
+        );
+        assert_eq!(Frame::new_checked(&[0x08][..]).err(), Some(Error));
+
+        // A beacon is a management frame.
+        let mut bytes = FROM_AP_FRAME_BYTES;
+        bytes[0] = 0x80;
+        assert_eq!(Frame::new_checked(&bytes[..]).err(), Some(Error));
+    }
+}
@@ -54,12 +54,33 @@ enum_with_unknown! {
         Udp       = 0x11,
         Ipv6Route = 0x2b,
         Ipv6Frag  = 0x2c,
+        IpSecEsp  = 0x32,
+        IpSecAh   = 0x33,
         Icmpv6    = 0x3a,
         Ipv6NoNxt = 0x3b,
         Ipv6Opts  = 0x3c
     }
 }
 
+impl Protocol {
+    /// Return whether this protocol number identifies an IPv6 extension header,
+    /// as opposed to an upper-layer protocol.
+    ///
+    /// Walkers of the IPv6 header chain can use this to know when to stop.
+    /// Note that `Ipv6NoNxt` is not an extension header: it terminates the chain.
+    pub fn is_ipv6_extension_header(&self) -> bool {
+        matches!(
+            self,
+            Protocol::HopByHop
+                | Protocol::Ipv6Route
+                | Protocol::Ipv6Frag
+                | Protocol::IpSecEsp
+                | Protocol::IpSecAh
+                | Protocol::Ipv6Opts
+        )
+    }
+}
+
 impl fmt::Display for Protocol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -70,6 +91,8 @@ impl fmt::Display for Protocol {
             Protocol::Udp => write!(f, "UDP"),
             Protocol::Ipv6Route => write!(f, "IPv6-Route"),
             Protocol::Ipv6Frag => write!(f, "IPv6-Frag"),
+            Protocol::IpSecEsp => write!(f, "IPsec-ESP"),
+            Protocol::IpSecAh => write!(f, "IPsec-AH"),
             Protocol::Icmpv6 => write!(f, "ICMPv6"),
             Protocol::Ipv6NoNxt => write!(f, "IPv6-NoNxt"),
             Protocol::Ipv6Opts => write!(f, "IPv6-Opts"),
@@ -153,6 +176,26 @@ impl Address {
         }
     }
 
+    /// Query whether the address is a loopback address.
+    pub fn is_loopback(&self) -> bool {
+        match self {
+            #[cfg(feature = "proto-ipv4")]
+            Address::Ipv4(addr) => addr.is_loopback(),
+            #[cfg(feature = "proto-ipv6")]
+            Address::Ipv6(addr) => addr.is_loopback(),
+        }
+    }
+
+    /// Query whether the address is a link-local address.
+    pub fn is_link_local(&self) -> bool {
+        match self {
+            #[cfg(feature = "proto-ipv4")]
+            Address::Ipv4(addr) => addr.is_link_local(),
+            #[cfg(feature = "proto-ipv6")]
+            Address::Ipv6(addr) => addr.is_link_local(),
+        }
+    }
+
     /// Query whether the address falls into the "unspecified" range.
     pub fn is_unspecified(&self) -> bool {
         match self {
@@ -918,6 +961,23 @@ pub(crate) mod test {
     #[cfg(feature = "proto-ipv4")]
     use crate::wire::{Ipv4Address, Ipv4Repr};
 
+    #[test]
+    fn test_protocol_is_ipv6_extension_header() {
+        assert!(Protocol::HopByHop.is_ipv6_extension_header());
+        assert!(Protocol::Ipv6Route.is_ipv6_extension_header());
+        assert!(Protocol::Ipv6Frag.is_ipv6_extension_header());
+        assert!(Protocol::IpSecEsp.is_ipv6_extension_header());
+        assert!(Protocol::IpSecAh.is_ipv6_extension_header());
+        assert!(Protocol::Ipv6Opts.is_ipv6_extension_header());
+        assert!(Protocol::from(60).is_ipv6_extension_header());
+
+        assert!(!Protocol::Tcp.is_ipv6_extension_header());
+        assert!(!Protocol::Udp.is_ipv6_extension_header());
+        assert!(!Protocol::Icmpv6.is_ipv6_extension_header());
+        assert!(!Protocol::Ipv6NoNxt.is_ipv6_extension_header());
+        assert!(!Protocol::Unknown(0xfd).is_ipv6_extension_header());
+    }
+
     #[test]
     #[cfg(feature = "proto-ipv4")]
     fn to_prefix_len_ipv4() {
@@ -28,6 +28,23 @@ pub const MIN_MTU: usize = 576;
 /// [RFC 8200 § 2]: https://www.rfc-editor.org/rfc/rfc791#section-3.2
 pub const ADDR_SIZE: usize = 4;
 
+enum_with_unknown! {
+    /// IPv4 option type, including the copied flag and the option class.
+    pub enum OptionType(u8) {
+        EndOfList         = 0x00,
+        NoOperation       = 0x01,
+        RecordRoute       = 0x07,
+        LooseSourceRoute  = 0x83,
+        StrictSourceRoute = 0x89,
+        RouterAlert       = 0x94
+    }
+}
+
+/// The length of the Router Alert option, see [RFC 2113].
+///
+/// [RFC 2113]: https://www.rfc-editor.org/rfc/rfc2113
+pub const ROUTER_ALERT_LEN: usize = 4;
+
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Key {
@@ -463,6 +480,53 @@ impl<T: AsRef<[u8]>> Packet<T> {
         checksum::data(&data[..self.header_len() as usize]) == !0
     }
 
+    /// Return the options of the header.
+    #[inline]
+    pub fn options(&self) -> &[u8] {
+        let data = self.buffer.as_ref();
+        let end = (self.header_len() as usize).max(field::DST_ADDR.end);
+        &data[field::DST_ADDR.end..end]
+    }
+
+    /// Return the offset, within the header, of the first option of the given
+    /// type, if any.
+    ///
+    /// The search stops at the end of the option list, or at the first
+    /// malformed option.
+    pub fn option_offset(&self, option_type: OptionType) -> Option<usize> {
+        let options = self.options();
+        let mut offset = 0;
+        while offset < options.len() {
+            let current = OptionType::from(options[offset]);
+            if current == option_type {
+                return Some(field::DST_ADDR.end + offset);
+            }
+            match current {
+                OptionType::EndOfList => return None,
+                OptionType::NoOperation => offset += 1,
+                _ => match options.get(offset + 1) {
+                    Some(&len) if len >= 2 => offset += len as usize,
+                    _ => return None,
+                },
+            }
+        }
+        None
+    }
+
+    /// Return the value of the Router Alert option, if present.
+    ///
+    /// A value of 0 means that routers must examine the packet.
+    pub fn router_alert(&self) -> Option<u16> {
+        let offset = self.option_offset(OptionType::RouterAlert)?;
+        let data = self.buffer.as_ref();
+        match data.get(offset + 1..offset + ROUTER_ALERT_LEN) {
+            Some(&[len, ref value @ ..]) if len as usize == ROUTER_ALERT_LEN => {
+                Some(NetworkEndian::read_u16(value))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the key for identifying the packet.
     pub fn get_key(&self) -> Key {
         Key {
@@ -596,6 +660,23 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
         data[field::DST_ADDR].copy_from_slice(value.as_bytes())
     }
 
+    /// Append a Router Alert option with the given value to a header without
+    /// options, and extend the header and total length accordingly.
+    ///
+    /// The payload starts [ROUTER_ALERT_LEN] octets later afterwards, so it
+    /// has to be emitted after the option. The checksum is not updated.
+    pub fn set_router_alert(&mut self, value: u16) {
+        let header_len = self.header_len() + ROUTER_ALERT_LEN as u8;
+        let total_len = self.total_len() + ROUTER_ALERT_LEN as u16;
+        let data = self.buffer.as_mut();
+        let option = &mut data[field::DST_ADDR.end..field::DST_ADDR.end + ROUTER_ALERT_LEN];
+        option[0] = OptionType::RouterAlert.into();
+        option[1] = ROUTER_ALERT_LEN as u8;
+        NetworkEndian::write_u16(&mut option[2..], value);
+        self.set_header_len(header_len);
+        self.set_total_len(total_len);
+    }
+
     /// Compute and fill in the header checksum.
     pub fn fill_checksum(&mut self) {
         self.set_checksum(0);
@@ -938,6 +1019,28 @@ mod test {
         assert_eq!(&*packet.into_inner(), &REPR_PACKET_BYTES[..]);
     }
 
+    #[test]
+    fn test_router_alert() {
+        let repr = packet_repr();
+        let mut bytes = vec![0xa5; repr.buffer_len() + ROUTER_ALERT_LEN + REPR_PAYLOAD_BYTES.len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(&mut packet, &ChecksumCapabilities::default());
+        assert_eq!(packet.router_alert(), None);
+        packet.set_router_alert(0);
+        packet.fill_checksum();
+        packet.payload_mut().copy_from_slice(&REPR_PAYLOAD_BYTES);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(packet.header_len(), 24);
+        assert_eq!(packet.total_len(), 28);
+        assert_eq!(&packet.as_ref()[20..24], &[0x94, 0x04, 0x00, 0x00]);
+        assert_eq!(packet.router_alert(), Some(0));
+        assert!(packet.verify_checksum());
+        assert_eq!(packet.payload(), &REPR_PAYLOAD_BYTES[..]);
+        let parsed = Repr::parse(&packet, &ChecksumCapabilities::default()).unwrap();
+        assert_eq!(parsed, packet_repr());
+    }
+
     #[test]
     fn test_unspecified() {
         assert!(Address::UNSPECIFIED.is_unspecified());
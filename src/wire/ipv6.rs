@@ -5,8 +5,8 @@ use core::fmt;
 use byteorder::{ByteOrder, NetworkEndian};
 
 pub use super::IpProtocol as Protocol;
-use super::{Error, Result};
-use crate::wire::ip::pretty_print_ip_payload;
+use super::{Error, Ipv6ExtHeaderIterator, Result};
+use crate::wire::ip::{checksum, pretty_print_ip_payload};
 #[cfg(feature = "proto-ipv4")]
 use crate::wire::ipv4;
 
@@ -196,6 +196,18 @@ impl Address {
         }
     }
 
+    #[cfg(feature = "proto-ipv4")]
+    /// Return the IPv4 address embedded in a [6to4] address from `2002::/16`.
+    ///
+    /// [6to4]: https://www.rfc-editor.org/rfc/rfc3056#section-2
+    pub fn sixto4_embedded_v4(&self) -> Option<ipv4::Address> {
+        if self.0[0..2] == [0x20, 0x02] {
+            Some(ipv4::Address::from_bytes(&self.0[2..6]))
+        } else {
+            None
+        }
+    }
+
     /// Helper function used to mask an address given a prefix.
     ///
     /// # Panics
@@ -630,6 +642,35 @@ impl<'a, T: AsRef<[u8]> + ?Sized> Packet<&'a T> {
         let range = self.header_len()..self.total_len();
         &data[range]
     }
+
+    /// Return an iterator over the extension header chain of the packet.
+    ///
+    /// See [Ipv6ExtHeaderIterator] for details.
+    pub fn extension_headers(&self) -> Ipv6ExtHeaderIterator<'a> {
+        Ipv6ExtHeaderIterator::new(self.next_header(), self.payload())
+    }
+
+    /// Skip the extension header chain, and return the upper-layer protocol
+    /// together with its data.
+    ///
+    /// Returns `Err(Error)` if the header chain is truncated.
+    pub fn upper_layer(&self) -> Result<(Protocol, &'a [u8])> {
+        self.extension_headers().upper_layer()
+    }
+
+    /// Compute the pseudo-header checksum of the upper-layer protocol,
+    /// skipping the extension header chain.
+    ///
+    /// Returns `Err(Error)` if the header chain is truncated.
+    pub fn upper_layer_pseudo_header_checksum(&self) -> Result<u16> {
+        let (next_header, data) = self.upper_layer()?;
+        Ok(checksum::pseudo_header(
+            &self.src_addr().into(),
+            &self.dst_addr().into(),
+            next_header,
+            data.len() as u32,
+        ))
+    }
 }
 
 impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
@@ -780,6 +821,26 @@ impl Repr {
         packet.set_src_addr(self.src_addr);
         packet.set_dst_addr(self.dst_addr);
     }
+
+    /// Compute the pseudo-header checksum of the upper-layer protocol of a
+    /// packet with this header.
+    ///
+    /// `next_header` and `upper_len` must describe the upper-layer protocol
+    /// found after the extension headers, if any. In particular, `upper_len`
+    /// is the length of the upper-layer header and data only, and is shorter
+    /// than `payload_len` when extension headers are present.
+    pub fn upper_layer_pseudo_header_checksum(
+        &self,
+        next_header: Protocol,
+        upper_len: usize,
+    ) -> u16 {
+        checksum::pseudo_header(
+            &self.src_addr.into(),
+            &self.dst_addr.into(),
+            next_header,
+            upper_len as u32,
+        )
+    }
 }
 
 impl fmt::Display for Repr {
@@ -833,7 +894,7 @@ impl<T: AsRef<[u8]>> PrettyPrint for Packet<T> {
 
 #[cfg(test)]
 mod test {
-    use super::{Address, Cidr, Error, Packet, Protocol, Repr};
+    use super::{checksum, Address, Cidr, Error, Packet, Protocol, Repr};
     #[cfg(feature = "proto-ipv4")]
     use crate::wire::ipv4::Address as Ipv4Address;
     use crate::wire::pretty_print::PrettyPrinter;
@@ -1253,6 +1314,98 @@ mod test {
         assert_eq!(Packet::new_checked(&bytes).unwrap_err(), Error);
     }
 
+    // A packet carrying a Hop-by-Hop header, a Fragment header and a TCP payload.
+    static EXT_HEADER_PACKET_BYTES: [u8; 60] = [
+        0x60, 0x00, 0x00, 0x00, 0x00, 0x14, 0x00, 0x40, 0xfe, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff, 0x02, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x2c, 0x00, 0x01, 0x04, 0x00,
+        0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x01, 0x12, 0x34, 0x56, 0x78, 0xaa, 0xbb, 0xcc, 0xdd,
+    ];
+
+    #[test]
+    fn test_extension_headers() {
+        let packet = Packet::new_checked(&EXT_HEADER_PACKET_BYTES[..]).unwrap();
+        let mut headers = packet.extension_headers();
+        assert_eq!(
+            headers.next(),
+            Some(Ok((Protocol::HopByHop, &EXT_HEADER_PACKET_BYTES[40..48])))
+        );
+        assert_eq!(
+            headers.next(),
+            Some(Ok((Protocol::Ipv6Frag, &EXT_HEADER_PACKET_BYTES[48..56])))
+        );
+        assert_eq!(headers.next(), None);
+
+        assert_eq!(
+            packet.upper_layer(),
+            Ok((Protocol::Tcp, &EXT_HEADER_PACKET_BYTES[56..]))
+        );
+    }
+
+    #[test]
+    fn test_extension_headers_none() {
+        let packet = Packet::new_unchecked(&REPR_PACKET_BYTES[..]);
+        assert_eq!(packet.extension_headers().next(), None);
+        assert_eq!(
+            packet.upper_layer(),
+            Ok((Protocol::Udp, &REPR_PAYLOAD_BYTES[..]))
+        );
+    }
+
+    #[test]
+    fn test_extension_headers_truncated() {
+        let mut bytes = vec![];
+        bytes.extend(&EXT_HEADER_PACKET_BYTES[..52]);
+        Packet::new_unchecked(&mut bytes).set_payload_len(12);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        let mut headers = packet.extension_headers();
+        assert!(matches!(headers.next(), Some(Ok((Protocol::HopByHop, _)))));
+        assert_eq!(headers.next(), Some(Err(Error)));
+        assert_eq!(headers.next(), None);
+
+        assert_eq!(packet.upper_layer(), Err(Error));
+    }
+
+    #[test]
+    fn test_upper_layer_pseudo_header_checksum() {
+        let mut bytes = vec![0; 60];
+        let repr = Repr {
+            next_header: Protocol::HopByHop,
+            payload_len: 20,
+            ..packet_repr()
+        };
+        repr.emit(&mut Packet::new_unchecked(&mut bytes));
+        // Hop-by-Hop header with a PadN option, followed by UDP.
+        bytes[40..48].copy_from_slice(&[0x11, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00]);
+        bytes[48..].copy_from_slice(&REPR_PAYLOAD_BYTES[..]);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        let pseudo_header = packet.upper_layer_pseudo_header_checksum().unwrap();
+        assert_eq!(
+            pseudo_header,
+            repr.upper_layer_pseudo_header_checksum(Protocol::Udp, REPR_PAYLOAD_BYTES.len())
+        );
+        assert_ne!(
+            pseudo_header,
+            repr.upper_layer_pseudo_header_checksum(Protocol::Udp, repr.payload_len)
+        );
+
+        // The UDP checksum verifies against the pseudo-header.
+        let sum = checksum::combine(&[pseudo_header, checksum::data(&bytes[48..])]);
+        assert_eq!(sum, !0);
+    }
+
+    #[test]
+    fn test_upper_layer_pseudo_header_checksum_truncated() {
+        let mut bytes = vec![];
+        bytes.extend(&EXT_HEADER_PACKET_BYTES[..52]);
+        Packet::new_unchecked(&mut bytes).set_payload_len(12);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(packet.upper_layer_pseudo_header_checksum(), Err(Error));
+    }
+
     #[test]
     fn test_repr_parse_valid() {
         let packet = Packet::new_unchecked(&REPR_PACKET_BYTES[..]);
@@ -1,6 +1,9 @@
 #![allow(unused)]
 
-use super::{Error, IpProtocol, Result};
+use super::{
+    Error, IpProtocol, Ipv6FragmentHeader, Ipv6FragmentRepr, Ipv6OptionsIterator,
+    Ipv6RawOptionsIterator, Result,
+};
 
 mod field {
     #![allow(non_snake_case)]
@@ -142,16 +145,139 @@ impl<'a> Repr<'a> {
         })
     }
 
-    /// Return the length, in bytes, of a header that will be emitted from this
-    /// high-level representation.
+    /// Return an iterator over the options of a Hop-by-Hop or Destination
+    /// Options header.
+    pub fn options(&self) -> Ipv6OptionsIterator<'a> {
+        Ipv6OptionsIterator::new(self.data)
+    }
+
+    /// Return an iterator over the type and data of the options of a
+    /// Hop-by-Hop or Destination Options header.
+    pub fn raw_options(&self) -> Ipv6RawOptionsIterator<'a> {
+        Ipv6RawOptionsIterator::new(self.data)
+    }
+
+    /// Parse the data of a Fragment header.
+    ///
+    /// Returns `Err(Error)` if the header length field does not declare the
+    /// fixed size of a Fragment header, 8 octets.
+    pub fn fragment(&self) -> Result<Ipv6FragmentRepr> {
+        if self.length != 0 {
+            return Err(Error);
+        }
+        Ipv6FragmentRepr::parse(&Ipv6FragmentHeader::new_checked(self.data)?)
+    }
+
+    /// Return the length, in bytes, of the header as declared by the header
+    /// length field, including the Next Header and length fields.
     pub const fn header_len(&self) -> usize {
-        2
+        field::PAYLOAD(self.length).end
+    }
+
+    /// Return the length, in bytes, of a header that will be emitted from this
+    /// high-level representation, including its data, padded to a multiple of
+    /// 8 octets.
+    pub const fn buffer_len(&self) -> usize {
+        (field::PAYLOAD(0).start + self.data.len() + 7) / 8 * 8
     }
 
     /// Emit a high-level representation into an IPv6 Extension Header.
+    ///
+    /// The data is followed by zeroes up to the next 8-octet boundary.
+    ///
+    /// # Panics
+    /// This function panics if the header length field does not match the
+    /// length of the data, or if the buffer is shorter than
+    /// [buffer_len](#method.buffer_len).
     pub fn emit<T: AsRef<[u8]> + AsMut<[u8]> + ?Sized>(&self, header: &mut Header<&mut T>) {
+        assert_eq!(
+            self.header_len(),
+            self.buffer_len(),
+            "the header length field does not match the extension header data"
+        );
+        assert!(
+            header.buffer.as_ref().len() >= self.buffer_len(),
+            "the buffer is too short for the extension header"
+        );
         header.set_next_header(self.next_header);
         header.set_header_len(self.length);
+        let payload = header.payload_mut();
+        payload[..self.data.len()].copy_from_slice(self.data);
+        payload[self.data.len()..].fill(0);
+    }
+}
+
+/// An iterator over the extension headers of an IPv6 packet.
+///
+/// Each item is the protocol number identifying the extension header and the
+/// octets of the whole header, including the Next Header and length fields.
+/// The iteration ends at the first upper-layer protocol, or at an
+/// Encapsulating Security Payload header, since everything following it is
+/// encrypted.
+#[derive(Debug, Clone)]
+pub struct Ipv6ExtHeaderIterator<'a> {
+    next_header: IpProtocol,
+    data: &'a [u8],
+    hit_error: bool,
+}
+
+impl<'a> Ipv6ExtHeaderIterator<'a> {
+    /// Create a new `Ipv6ExtHeaderIterator`, used to iterate over the
+    /// extension header chain starting with a header of type `next_header`
+    /// contained in `data` (e.g. the payload of an IPv6 packet).
+    pub fn new(next_header: IpProtocol, data: &'a [u8]) -> Ipv6ExtHeaderIterator<'a> {
+        Ipv6ExtHeaderIterator {
+            next_header,
+            data,
+            hit_error: false,
+        }
+    }
+
+    /// Walk the rest of the header chain and return the first protocol that is
+    /// not a walkable extension header, together with its data.
+    ///
+    /// Returns `Err(Error)` if the header chain is truncated.
+    pub fn upper_layer(mut self) -> Result<(IpProtocol, &'a [u8])> {
+        for header in &mut self {
+            header?;
+        }
+        Ok((self.next_header, self.data))
+    }
+}
+
+impl<'a> Iterator for Ipv6ExtHeaderIterator<'a> {
+    type Item = Result<(IpProtocol, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.hit_error
+            || !self.next_header.is_ipv6_extension_header()
+            || self.next_header == IpProtocol::IpSecEsp
+        {
+            return None;
+        }
+
+        if self.data.len() < field::MIN_HEADER_SIZE {
+            self.hit_error = true;
+            return Some(Err(Error));
+        }
+
+        let len = match self.next_header {
+            // The Fragment header has a fixed size; its second octet is reserved.
+            IpProtocol::Ipv6Frag => 8,
+            // The Authentication Header length is in 4-octet units, minus 2.
+            IpProtocol::IpSecAh => (self.data[field::LENGTH] as usize + 2) * 4,
+            _ => field::PAYLOAD(self.data[field::LENGTH]).end,
+        };
+        if self.data.len() < len {
+            self.hit_error = true;
+            return Some(Err(Error));
+        }
+
+        let protocol = self.next_header;
+        let (header, rest) = self.data.split_at(len);
+        self.next_header = IpProtocol::from(header[field::NXT_HDR]);
+        self.data = rest;
+        Some(Ok((protocol, header)))
     }
 }
 
@@ -167,6 +293,11 @@ mod test {
         0x06, 0x1, 0x1, 0x0C, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
     ];
 
+    // The Fragment headers of the two fragments of a 1500-byte ICMPv6 Echo
+    // Request sent over a 1500-byte MTU link.
+    static FRAGMENT_FIRST: [u8; 8] = [0x3a, 0x00, 0x00, 0x01, 0xa1, 0xb2, 0xc3, 0xd4];
+    static FRAGMENT_LAST: [u8; 8] = [0x3a, 0x00, 0x05, 0xa8, 0xa1, 0xb2, 0xc3, 0xd4];
+
     #[test]
     fn test_check_len() {
         // zero byte buffer
@@ -195,7 +326,6 @@ mod test {
         let header: [u8; 8] = [0x06, 0x2, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
         assert_eq!(Err(Error), Header::new_unchecked(&header).check_len());
     }
-
     #[test]
     fn test_header_deconstruct() {
         let header = Header::new_unchecked(&REPR_PACKET_PAD4);
@@ -208,7 +338,6 @@ mod test {
         assert_eq!(header.header_len(), 1);
         assert_eq!(header.payload(), &REPR_PACKET_PAD12[2..]);
     }
-
     #[test]
     fn test_overlong() {
         let mut bytes = vec![];
@@ -237,7 +366,6 @@ mod test {
             REPR_PACKET_PAD12[2..].len()
         );
     }
-
     #[test]
     fn test_header_len_overflow() {
         let mut bytes = vec![];
@@ -254,7 +382,6 @@ mod test {
 
         assert_eq!(Header::new_checked(&bytes).unwrap_err(), Error);
     }
-
     #[test]
     fn test_repr_parse_valid() {
         let header = Header::new_unchecked(&REPR_PACKET_PAD4);
@@ -279,7 +406,6 @@ mod test {
             }
         );
     }
-
     #[test]
     fn test_repr_emit() {
         let repr = Repr {
@@ -287,19 +413,134 @@ mod test {
             length: 0,
             data: &REPR_PACKET_PAD4[2..],
         };
-        let mut bytes = [0u8; 2];
+        let mut bytes = [0xffu8; 8];
         let mut header = Header::new_unchecked(&mut bytes);
         repr.emit(&mut header);
-        assert_eq!(header.into_inner(), &REPR_PACKET_PAD4[..2]);
+        assert_eq!(header.into_inner(), &REPR_PACKET_PAD4[..]);
 
         let repr = Repr {
             next_header: IpProtocol::Tcp,
             length: 1,
             data: &REPR_PACKET_PAD12[2..],
         };
-        let mut bytes = [0u8; 2];
+        let mut bytes = [0xffu8; 16];
         let mut header = Header::new_unchecked(&mut bytes);
         repr.emit(&mut header);
-        assert_eq!(header.into_inner(), &REPR_PACKET_PAD12[..2]);
+        assert_eq!(header.into_inner(), &REPR_PACKET_PAD12[..]);
+    }
+    #[test]
+    fn test_repr_buffer_len() {
+        let repr = Repr::parse(&Header::new_unchecked(&REPR_PACKET_PAD4)).unwrap();
+        assert_eq!(repr.header_len(), 8);
+        assert_eq!(repr.buffer_len(), 8);
+
+        let repr = Repr::parse(&Header::new_unchecked(&REPR_PACKET_PAD12)).unwrap();
+        assert_eq!(repr.header_len(), 16);
+        assert_eq!(repr.buffer_len(), 16);
+    }
+    #[test]
+    fn test_repr_roundtrip() {
+        let header = Header::new_checked(&REPR_PACKET_PAD12[..]).unwrap();
+        let repr = Repr::parse(&header).unwrap();
+
+        let mut bytes = [0u8; 16];
+        repr.emit(&mut Header::new_unchecked(&mut bytes));
+        assert_eq!(bytes, REPR_PACKET_PAD12);
+    }
+    #[test]
+    fn test_repr_emit_padding() {
+        // A PadN option of option data length 2, followed by two Pad1 options
+        // filling the header up to 8 octets.
+        let repr = Repr {
+            next_header: IpProtocol::Tcp,
+            length: 0,
+            data: &[0x1, 0x2, 0x0, 0x0],
+        };
+        let mut bytes = [0xffu8; 8];
+        repr.emit(&mut Header::new_unchecked(&mut bytes));
+        assert_eq!(bytes, [0x06, 0x0, 0x1, 0x2, 0x0, 0x0, 0x0, 0x0]);
+    }
+    #[test]
+    #[should_panic(expected = "the buffer is too short for the extension header")]
+    fn test_repr_emit_short_buffer() {
+        let repr = Repr::parse(&Header::new_unchecked(&REPR_PACKET_PAD12)).unwrap();
+        let mut bytes = [0u8; 8];
+        repr.emit(&mut Header::new_unchecked(&mut bytes));
+    }
+    #[test]
+    #[should_panic(expected = "the header length field does not match the extension header data")]
+    fn test_repr_emit_length_mismatch() {
+        let repr = Repr {
+            next_header: IpProtocol::Tcp,
+            length: 1,
+            data: &REPR_PACKET_PAD4[2..],
+        };
+        let mut bytes = [0u8; 16];
+        repr.emit(&mut Header::new_unchecked(&mut bytes));
+    }
+    #[test]
+    fn test_repr_options() {
+        use crate::wire::{Ipv6OptionRepr, Ipv6OptionType};
+
+        // A Hop-by-Hop header with a Router Alert option, padded with Pad1
+        // options.
+        let bytes = [0x3a, 0x0, 0x00, 0x05, 0x02, 0x00, 0x00, 0x00];
+        let header = Header::new_unchecked(&bytes[..]);
+        let repr = Repr::parse(&header).unwrap();
+        assert!(repr.options().map(Result::unwrap).eq([
+            Ipv6OptionRepr::Pad1,
+            Ipv6OptionRepr::RouterAlert(0),
+            Ipv6OptionRepr::Pad1
+        ]));
+        assert!(repr.raw_options().map(Result::unwrap).eq([
+            (Ipv6OptionType::Pad1, &[][..]),
+            (Ipv6OptionType::RouterAlert, &[0x00, 0x00][..]),
+            (Ipv6OptionType::Pad1, &[][..])
+        ]));
+
+        // An option running past the end of the header is an error, after
+        // which the iteration stops.
+        let repr = Repr {
+            next_header: IpProtocol::Icmpv6,
+            length: 0,
+            data: &bytes[2..6],
+        };
+        let mut options = repr.options();
+        assert_eq!(options.next(), Some(Ok(Ipv6OptionRepr::Pad1)));
+        assert_eq!(options.next(), Some(Err(Error)));
+        assert_eq!(options.next(), None);
+    }
+    #[test]
+    fn test_fragment() {
+        for (bytes, frag_offset, more_frags) in
+            [(&FRAGMENT_FIRST, 0, true), (&FRAGMENT_LAST, 181, false)]
+        {
+            let header = Header::new_checked(&bytes[..]).unwrap();
+            let repr = Repr::parse(&header).unwrap();
+            assert_eq!(repr.next_header, IpProtocol::Icmpv6);
+            let frag = repr.fragment().unwrap();
+            assert_eq!(
+                frag,
+                Ipv6FragmentRepr {
+                    frag_offset,
+                    more_frags,
+                    ident: 0xa1b2c3d4,
+                }
+            );
+
+            let mut buf = [0xffu8; 8];
+            let mut header = Header::new_unchecked(&mut buf);
+            header.set_next_header(IpProtocol::Icmpv6);
+            header.set_header_len(0);
+            frag.emit(&mut Ipv6FragmentHeader::new_unchecked(header.payload_mut()));
+            assert_eq!(&buf, bytes);
+        }
+
+        // A Fragment header declaring more than its fixed size.
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&FRAGMENT_FIRST);
+        bytes[1] = 1;
+        let header = Header::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&header).unwrap().fragment(), Err(Error));
     }
 }
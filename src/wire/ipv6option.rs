@@ -11,6 +11,14 @@ enum_with_unknown! {
         Pad1 = 0,
         /// Multiple bytes of padding
         PadN = 1,
+        /// Router Alert
+        ///
+        /// See https://tools.ietf.org/html/rfc2711 for details.
+        RouterAlert = 0x05,
+        /// Jumbo Payload
+        ///
+        /// See https://tools.ietf.org/html/rfc2675 for details.
+        JumboPayload = 0xc2,
         /// RPL Option
         Rpl  = 0x63,
     }
@@ -21,6 +29,8 @@ impl fmt::Display for Type {
         match *self {
             Type::Pad1 => write!(f, "Pad1"),
             Type::PadN => write!(f, "PadN"),
+            Type::RouterAlert => write!(f, "RouterAlert"),
+            Type::JumboPayload => write!(f, "JumboPayload"),
             Type::Rpl => write!(f, "RPL"),
             Type::Unknown(id) => write!(f, "{id}"),
         }
@@ -227,6 +237,10 @@ impl<'a, T: AsRef<[u8]> + ?Sized> fmt::Display for Ipv6Option<&'a T> {
 pub enum Repr<'a> {
     Pad1,
     PadN(u8),
+    /// A Router Alert option, with the value identifying the kind of message.
+    RouterAlert(u16),
+    /// A Jumbo Payload option, with the length of the payload of the jumbogram.
+    JumboPayload(u32),
     #[cfg(feature = "proto-rpl")]
     Rpl(RplHopByHopRepr),
     Unknown {
@@ -246,6 +260,24 @@ impl<'a> Repr<'a> {
         match opt.option_type() {
             Type::Pad1 => Ok(Repr::Pad1),
             Type::PadN => Ok(Repr::PadN(opt.data_len())),
+            // Options with an unexpected length are handled like unknown
+            // ones, according to their type.
+            Type::RouterAlert => match *opt.data() {
+                [hi, lo] => Ok(Repr::RouterAlert(u16::from_be_bytes([hi, lo]))),
+                _ => Ok(Repr::Unknown {
+                    type_: Type::RouterAlert,
+                    length: opt.data_len(),
+                    data: opt.data(),
+                }),
+            },
+            Type::JumboPayload => match *opt.data() {
+                [a, b, c, d] => Ok(Repr::JumboPayload(u32::from_be_bytes([a, b, c, d]))),
+                _ => Ok(Repr::Unknown {
+                    type_: Type::JumboPayload,
+                    length: opt.data_len(),
+                    data: opt.data(),
+                }),
+            },
 
             #[cfg(feature = "proto-rpl")]
             Type::Rpl => Ok(Repr::Rpl(RplHopByHopRepr::parse(
@@ -272,6 +304,8 @@ impl<'a> Repr<'a> {
         match *self {
             Repr::Pad1 => 1,
             Repr::PadN(length) => field::DATA(length).end,
+            Repr::RouterAlert(_) => field::DATA(2).end,
+            Repr::JumboPayload(_) => field::DATA(4).end,
             #[cfg(feature = "proto-rpl")]
             Repr::Rpl(opt) => field::DATA(opt.buffer_len() as u8).end,
             Repr::Unknown { length, .. } => field::DATA(length).end,
@@ -290,6 +324,16 @@ impl<'a> Repr<'a> {
                     *x = 0
                 }
             }
+            Repr::RouterAlert(value) => {
+                opt.set_option_type(Type::RouterAlert);
+                opt.set_data_len(2);
+                opt.data_mut().copy_from_slice(&value.to_be_bytes());
+            }
+            Repr::JumboPayload(length) => {
+                opt.set_option_type(Type::JumboPayload);
+                opt.set_data_len(4);
+                opt.data_mut().copy_from_slice(&length.to_be_bytes());
+            }
             #[cfg(feature = "proto-rpl")]
             Repr::Rpl(rpl) => {
                 opt.set_option_type(Type::Rpl);
@@ -367,12 +411,63 @@ impl<'a> Iterator for Ipv6OptionsIterator<'a> {
     }
 }
 
+/// An iterator over the options of an IPv6 Extension Header, yielding the
+/// type and the data of each option without interpreting them.
+///
+/// A Pad1 option has no data. If an option runs past the end of the data,
+/// an error is returned once and iteration stops.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ipv6RawOptionsIterator<'a> {
+    data: &'a [u8],
+    hit_error: bool,
+}
+
+impl<'a> Ipv6RawOptionsIterator<'a> {
+    /// Create a new `Ipv6RawOptionsIterator` over the options contained in
+    /// an IPv6 Extension Header.
+    pub fn new(data: &'a [u8]) -> Ipv6RawOptionsIterator<'a> {
+        Ipv6RawOptionsIterator {
+            data,
+            hit_error: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Ipv6RawOptionsIterator<'a> {
+    type Item = Result<(Type, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() || self.hit_error {
+            return None;
+        }
+
+        match Ipv6Option::new_checked(self.data) {
+            Ok(opt) if opt.option_type() == Type::Pad1 => {
+                self.data = &self.data[1..];
+                Some(Ok((Type::Pad1, &[])))
+            }
+            Ok(opt) => {
+                let data = opt.data();
+                self.data = &self.data[field::DATA(opt.data_len()).end..];
+                Some(Ok((opt.option_type(), data)))
+            }
+            Err(e) => {
+                self.hit_error = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 impl<'a> fmt::Display for Repr<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "IPv6 Option ")?;
         match *self {
             Repr::Pad1 => write!(f, "{} ", Type::Pad1),
             Repr::PadN(len) => write!(f, "{} length={} ", Type::PadN, len),
+            Repr::RouterAlert(value) => write!(f, "{} value={} ", Type::RouterAlert, value),
+            Repr::JumboPayload(length) => write!(f, "{} length={} ", Type::JumboPayload, length),
             #[cfg(feature = "proto-rpl")]
             Repr::Rpl(rpl) => write!(f, "{} {rpl}", Type::Rpl),
             Repr::Unknown { type_, length, .. } => write!(f, "{type_} length={length} "),
@@ -583,6 +678,70 @@ mod test {
         assert_eq!(failure_type, FailureType::DiscardSendUnicast);
     }
 
+    #[test]
+    fn test_router_alert_jumbo_payload() {
+        let bytes = [0x05, 0x02, 0x00, 0x00];
+        let opt = Ipv6Option::new_checked(&bytes).unwrap();
+        let repr = Repr::parse(&opt).unwrap();
+        assert_eq!(repr, Repr::RouterAlert(0));
+        assert_eq!(repr.buffer_len(), 4);
+        let mut emitted = [0xffu8; 4];
+        repr.emit(&mut Ipv6Option::new_unchecked(&mut emitted));
+        assert_eq!(emitted, bytes);
+
+        let bytes = [0xc2, 0x04, 0x00, 0x01, 0x00, 0x00];
+        let opt = Ipv6Option::new_checked(&bytes).unwrap();
+        let repr = Repr::parse(&opt).unwrap();
+        assert_eq!(repr, Repr::JumboPayload(0x10000));
+        assert_eq!(repr.buffer_len(), 6);
+        let mut emitted = [0xffu8; 6];
+        repr.emit(&mut Ipv6Option::new_unchecked(&mut emitted));
+        assert_eq!(emitted, bytes);
+
+        // With another length, they are handled like unknown options.
+        let opt = Ipv6Option::new_checked(&[0x05, 0x01, 0x00][..]).unwrap();
+        assert_eq!(
+            Repr::parse(&opt),
+            Ok(Repr::Unknown {
+                type_: Type::RouterAlert,
+                length: 1,
+                data: &[0x00],
+            })
+        );
+        let opt = Ipv6Option::new_checked(&[0xc2, 0x02, 0x00, 0x00][..]).unwrap();
+        assert_eq!(
+            Repr::parse(&opt),
+            Ok(Repr::Unknown {
+                type_: Type::JumboPayload,
+                length: 2,
+                data: &[0x00, 0x00],
+            })
+        );
+    }
+
+    #[test]
+    fn test_raw_options_iter() {
+        let options = [
+            0x00, 0x01, 0x01, 0x00, 0x05, 0x02, 0x00, 0x01, 0x00, 0x11, 0x03, 0x00,
+        ];
+
+        let mut iterator = Ipv6RawOptionsIterator::new(&options);
+        assert_eq!(iterator.next(), Some(Ok((Type::Pad1, &[][..]))));
+        assert_eq!(iterator.next(), Some(Ok((Type::PadN, &[0x00][..]))));
+        assert_eq!(
+            iterator.next(),
+            Some(Ok((Type::RouterAlert, &[0x00, 0x01][..])))
+        );
+        assert_eq!(iterator.next(), Some(Ok((Type::Pad1, &[][..]))));
+        // The last option runs past the end of the data.
+        assert_eq!(iterator.next(), Some(Err(Error)));
+        assert_eq!(iterator.next(), None);
+
+        let mut iterator = Ipv6RawOptionsIterator::new(&options[..9]);
+        assert_eq!(iterator.nth(3), Some(Ok((Type::Pad1, &[][..]))));
+        assert_eq!(iterator.next(), None);
+    }
+
     #[test]
     fn test_options_iter() {
         let options = [
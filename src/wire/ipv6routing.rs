@@ -20,6 +20,10 @@ enum_with_unknown! {
         ///
         /// See https://tools.ietf.org/html/rfc6554 for details.
         Rpl = 3,
+        /// Segment Routing Header (SRH)
+        ///
+        /// See https://tools.ietf.org/html/rfc8754 for details.
+        Srh = 4,
         /// RFC3692-style Experiment 1
         ///
         /// See https://tools.ietf.org/html/rfc4727 for details.
@@ -40,6 +44,7 @@ impl fmt::Display for Type {
             Type::Nimrod => write!(f, "Nimrod"),
             Type::Type2 => write!(f, "Type2"),
             Type::Rpl => write!(f, "Rpl"),
+            Type::Srh => write!(f, "Srh"),
             Type::Experiment1 => write!(f, "Experiment1"),
             Type::Experiment2 => write!(f, "Experiment2"),
             Type::Reserved => write!(f, "Reserved"),
@@ -83,6 +88,25 @@ mod field {
     pub const TYPE: usize = 0;
     // 8-bit unsigned integer. The number of route segments remaining.
     pub const SEG_LEFT: usize = 1;
+    // Variable length field containing the type-specific data.
+    pub const DATA: usize = 2;
+
+    // The Type 0 Routing Header has the following format:
+    //
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |  Next Header  |  Hdr Ext Len  | Routing Type=0| Segments Left |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |                            Reserved                           |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |                                                               |
+    // .                                                               .
+    // .                        Addresses[1..n]                        .
+    // .                                                               .
+    // |                                                               |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    //
+    // The addresses start at `ADDRESSES`, like in the RPL Source Routing
+    // Header.
 
     // The Type 2 Routing Header has the following format:
     //
@@ -123,6 +147,39 @@ mod field {
     pub const PAD: usize = 3;
     // Variable length field containing addresses
     pub const ADDRESSES: usize = 6;
+
+    // The Segment Routing Header has the following format:
+    //
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // | Next Header   |  Hdr Ext Len  | Routing Type  | Segments Left |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |  Last Entry   |     Flags     |              Tag              |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |                                                               |
+    // |            Segment List[0] (128-bit IPv6 address)             |
+    // |                                                               |
+    // |                                                               |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // .                               ...                             .
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // |                                                               |
+    // |            Segment List[n] (128-bit IPv6 address)             |
+    // |                                                               |
+    // |                                                               |
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    // //                                                             //
+    // //         Optional Type Length Value objects (variable)       //
+    // //                                                             //
+    // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    // 8-bit index of the last element of the segment list.
+    pub const LAST_ENTRY: usize = 2;
+    // 8-bit field containing the flags.
+    pub const SRH_FLAGS: usize = 3;
+    // 16-bit field tagging the packet as part of a class or group.
+    pub const TAG: Field = 4..6;
+    // Variable length field containing the segment list, followed by TLVs.
+    pub const SEGMENT_LIST: usize = 6;
 }
 
 /// Core getter methods relevant to any routing type.
@@ -156,7 +213,8 @@ impl<T: AsRef<[u8]>> Header<T> {
 
         match self.routing_type() {
             Type::Type2 if len < field::HOME_ADDRESS.end => return Err(Error),
-            Type::Rpl if len < field::ADDRESSES => return Err(Error),
+            Type::Type0 | Type::Rpl if len < field::ADDRESSES => return Err(Error),
+            Type::Srh if len < field::SEGMENT_LIST => return Err(Error),
             _ => (),
         }
 
@@ -181,6 +239,13 @@ impl<T: AsRef<[u8]>> Header<T> {
         let data = self.buffer.as_ref();
         data[field::SEG_LEFT]
     }
+
+    /// Return the type-specific data, following the segments left field.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        let data = self.buffer.as_ref();
+        &data[field::DATA..]
+    }
 }
 
 /// Getter methods for the Type 2 Routing Header routing type.
@@ -196,6 +261,49 @@ impl<T: AsRef<[u8]>> Header<T> {
     }
 }
 
+/// Getter methods for the Segment Routing Header routing type.
+impl<T: AsRef<[u8]>> Header<T> {
+    /// Return the index of the last element of the segment list.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn last_entry(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::LAST_ENTRY]
+    }
+
+    /// Return the flags of the Segment Routing Header.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn srh_flags(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::SRH_FLAGS]
+    }
+
+    /// Return the tag of the Segment Routing Header.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn tag(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        u16::from_be_bytes([data[field::TAG.start], data[field::TAG.start + 1]])
+    }
+
+    /// Return the segment list and the TLVs following it, in bytes.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn segment_list_and_tlvs(&self) -> &[u8] {
+        let data = self.buffer.as_ref();
+        &data[field::SEGMENT_LIST..]
+    }
+}
+
 /// Getter methods for the RPL Source Routing Header routing type.
 impl<T: AsRef<[u8]>> Header<T> {
     /// Return the number of prefix octets elided from addresses[1..n-1].
@@ -256,6 +364,13 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
         data[field::SEG_LEFT] = value;
     }
 
+    /// Set the type-specific data.
+    #[inline]
+    pub fn set_data(&mut self, value: &[u8]) {
+        let data = self.buffer.as_mut();
+        data[field::DATA..].copy_from_slice(value);
+    }
+
     /// Initialize reserved fields to 0.
     ///
     /// # Panics
@@ -266,17 +381,17 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
         let data = self.buffer.as_mut();
 
         match routing_type {
-            Type::Type2 => {
+            Type::Type0 | Type::Type2 => {
+                data[2] = 0;
+                data[3] = 0;
                 data[4] = 0;
                 data[5] = 0;
-                data[6] = 0;
-                data[7] = 0;
             }
             Type::Rpl => {
                 // Retain the higher order 4 bits of the padding field
                 data[field::PAD] &= 0xF0;
-                data[6] = 0;
-                data[7] = 0;
+                data[4] = 0;
+                data[5] = 0;
             }
 
             _ => panic!("Unrecognized routing type when clearing reserved fields."),
@@ -284,7 +399,7 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
     }
 }
 
-/// Setter methods for the RPL Source Routing Header routing type.
+/// Setter methods for the Type 2 Routing Header routing type.
 impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
     /// Set the Ipv6 Home Address
     ///
@@ -297,6 +412,52 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
     }
 }
 
+/// Setter methods for the Segment Routing Header routing type.
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
+    /// Set the index of the last element of the segment list.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn set_last_entry(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::LAST_ENTRY] = value;
+    }
+
+    /// Set the flags of the Segment Routing Header.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn set_srh_flags(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::SRH_FLAGS] = value;
+    }
+
+    /// Set the tag of the Segment Routing Header.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type.
+    pub fn set_tag(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        data[field::TAG].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Set the segment list, followed by the TLVs.
+    ///
+    /// # Panics
+    /// This function may panic if this header is not the Segment Routing
+    /// Header routing type, or if the buffer is not exactly as long as the
+    /// segment list and the TLVs.
+    pub fn set_segment_list_and_tlvs(&mut self, segments: &[u8], tlvs: &[u8]) {
+        let data = self.buffer.as_mut();
+        let (segment_list, tlv_data) = data[field::SEGMENT_LIST..].split_at_mut(segments.len());
+        segment_list.copy_from_slice(segments);
+        tlv_data.copy_from_slice(tlvs);
+    }
+}
+
 /// Setter methods for the RPL Source Routing Header routing type.
 impl<T: AsRef<[u8]> + AsMut<[u8]>> Header<T> {
     /// Set the number of prefix octets elided from addresses[1..n-1].
@@ -361,6 +522,12 @@ impl<'a, T: AsRef<[u8]> + ?Sized> fmt::Display for Header<&'a T> {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum Repr<'a> {
+    Type0 {
+        /// Number of route segments remaining.
+        segments_left: u8,
+        /// Vector of addresses, numbered 1 to `n`.
+        addresses: &'a [u8],
+    },
     Type2 {
         /// Number of route segments remaining.
         segments_left: u8,
@@ -381,6 +548,28 @@ pub enum Repr<'a> {
         /// Vector of addresses, numbered 1 to `n`.
         addresses: &'a [u8],
     },
+    Srh {
+        /// Number of route segments remaining.
+        segments_left: u8,
+        /// Flags of the Segment Routing Header.
+        flags: u8,
+        /// Tag of the packet, tying it to a class or group.
+        tag: u16,
+        /// Segment list, from the last segment of the path to the first one.
+        segments: &'a [u8],
+        /// Type Length Value objects following the segment list.
+        tlvs: &'a [u8],
+    },
+    /// A routing header of a type this representation does not interpret,
+    /// so that the caller can decide whether to drop the packet.
+    Unknown {
+        /// The routing type.
+        routing_type: Type,
+        /// Number of route segments remaining.
+        segments_left: u8,
+        /// The type-specific data.
+        data: &'a [u8],
+    },
 }
 
 impl<'a> Repr<'a> {
@@ -389,7 +578,19 @@ impl<'a> Repr<'a> {
     where
         T: AsRef<[u8]> + ?Sized,
     {
+        const ADDR_LEN: usize = 16;
+
         match header.routing_type() {
+            Type::Type0 => {
+                let addresses = header.addresses();
+                if addresses.len() % ADDR_LEN != 0 {
+                    return Err(Error);
+                }
+                Ok(Repr::Type0 {
+                    segments_left: header.segments_left(),
+                    addresses,
+                })
+            }
             Type::Type2 => Ok(Repr::Type2 {
                 segments_left: header.segments_left(),
                 home_address: header.home_address(),
@@ -401,24 +602,85 @@ impl<'a> Repr<'a> {
                 pad: header.pad(),
                 addresses: header.addresses(),
             }),
+            Type::Srh => {
+                let segments_len = (header.last_entry() as usize + 1) * ADDR_LEN;
+                let data = header.segment_list_and_tlvs();
+                if data.len() < segments_len {
+                    return Err(Error);
+                }
+                let (segments, tlvs) = data.split_at(segments_len);
+                Ok(Repr::Srh {
+                    segments_left: header.segments_left(),
+                    flags: header.srh_flags(),
+                    tag: header.tag(),
+                    segments,
+                    tlvs,
+                })
+            }
+
+            routing_type => Ok(Repr::Unknown {
+                routing_type,
+                segments_left: header.segments_left(),
+                data: header.data(),
+            }),
+        }
+    }
 
-            _ => Err(Error),
+    /// Return the number of route segments remaining.
+    pub const fn segments_left(&self) -> u8 {
+        match *self {
+            Repr::Type0 { segments_left, .. }
+            | Repr::Type2 { segments_left, .. }
+            | Repr::Rpl { segments_left, .. }
+            | Repr::Srh { segments_left, .. }
+            | Repr::Unknown { segments_left, .. } => segments_left,
         }
     }
 
+    /// Return an iterator over the addresses of a Type 0 Routing Header, or
+    /// the segment list of a Segment Routing Header.
+    ///
+    /// For other routing types, whose addresses are not stored in full, the
+    /// iterator is empty.
+    pub fn addresses(&self) -> impl Iterator<Item = Address> + 'a {
+        let data = match *self {
+            Repr::Type0 { addresses, .. } => addresses,
+            Repr::Srh { segments, .. } => segments,
+            _ => &[],
+        };
+        data.chunks_exact(16).map(Address::from_bytes)
+    }
+
     /// Return the length, in bytes, of a header that will be emitted from this
     /// high-level representation.
     pub const fn buffer_len(&self) -> usize {
         match self {
             // Routing Type + Segments Left + Reserved + Home Address
             Repr::Type2 { home_address, .. } => 2 + 4 + home_address.as_bytes().len(),
-            Repr::Rpl { addresses, .. } => 2 + 4 + addresses.len(),
+            Repr::Type0 { addresses, .. } | Repr::Rpl { addresses, .. } => 2 + 4 + addresses.len(),
+            // Routing Type + Segments Left + Last Entry + Flags + Tag + Segments + TLVs
+            Repr::Srh { segments, tlvs, .. } => 2 + 4 + segments.len() + tlvs.len(),
+            Repr::Unknown { data, .. } => 2 + data.len(),
         }
     }
 
     /// Emit a high-level representation into an IPv6 Routing Header.
+    ///
+    /// # Panics
+    /// This function panics if the segment list of a Segment Routing Header is
+    /// empty, holds more than 256 addresses, or its length is not a multiple
+    /// of 16 octets.
     pub fn emit<T: AsRef<[u8]> + AsMut<[u8]> + ?Sized>(&self, header: &mut Header<&mut T>) {
         match *self {
+            Repr::Type0 {
+                segments_left,
+                addresses,
+            } => {
+                header.set_routing_type(Type::Type0);
+                header.set_segments_left(segments_left);
+                header.clear_reserved();
+                header.set_addresses(addresses);
+            }
             Repr::Type2 {
                 segments_left,
                 home_address,
@@ -443,6 +705,34 @@ impl<'a> Repr<'a> {
                 header.clear_reserved();
                 header.set_addresses(addresses);
             }
+            Repr::Srh {
+                segments_left,
+                flags,
+                tag,
+                segments,
+                tlvs,
+            } => {
+                assert!(
+                    !segments.is_empty() && segments.len() % 16 == 0 && segments.len() <= 256 * 16,
+                    "the segment list must hold between 1 and 256 addresses"
+                );
+                let last_entry = (segments.len() / 16 - 1) as u8;
+                header.set_routing_type(Type::Srh);
+                header.set_segments_left(segments_left);
+                header.set_last_entry(last_entry);
+                header.set_srh_flags(flags);
+                header.set_tag(tag);
+                header.set_segment_list_and_tlvs(segments, tlvs);
+            }
+            Repr::Unknown {
+                routing_type,
+                segments_left,
+                data,
+            } => {
+                header.set_routing_type(routing_type);
+                header.set_segments_left(segments_left);
+                header.set_data(data);
+            }
         }
     }
 }
@@ -450,6 +740,14 @@ impl<'a> Repr<'a> {
 impl<'a> fmt::Display for Repr<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            Repr::Type0 { segments_left, .. } => {
+                write!(
+                    f,
+                    "IPv6 Routing type={} seg_left={}",
+                    Type::Type0,
+                    segments_left
+                )
+            }
             Repr::Type2 {
                 segments_left,
                 home_address,
@@ -479,6 +777,32 @@ impl<'a> fmt::Display for Repr<'a> {
                     pad
                 )
             }
+            Repr::Srh {
+                segments_left,
+                flags,
+                tag,
+                ..
+            } => {
+                write!(
+                    f,
+                    "IPv6 Routing type={} seg_left={} flags={:#04x} tag={}",
+                    Type::Srh,
+                    segments_left,
+                    flags,
+                    tag
+                )
+            }
+            Repr::Unknown {
+                routing_type,
+                segments_left,
+                ..
+            } => {
+                write!(
+                    f,
+                    "IPv6 Routing type={} seg_left={}",
+                    routing_type, segments_left
+                )
+            }
         }
     }
 }
@@ -532,6 +856,51 @@ mod test {
         addresses: &[0x2, 0x3, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0],
     };
 
+    // A Type 0 Routing Header with two addresses
+    static BYTES_TYPE0: [u8; 38] = [
+        0x0, 0x1, 0x0, 0x0, 0x0, 0x0, 0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x2, 0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x3,
+    ];
+
+    // A representation of a Type 0 Routing Header
+    static REPR_TYPE0: Repr = Repr::Type0 {
+        segments_left: 1,
+        addresses: &[
+            0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2, 0xfd,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x3,
+        ],
+    };
+
+    // A Segment Routing Header with two segments and a Pad1 TLV
+    static BYTES_SRH: [u8; 46] = [
+        0x4, 0x1, 0x1, 0x0, 0x12, 0x34, 0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x0, 0x0, 0x3, 0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        0x0, 0x0, 0x2, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+    ];
+
+    // A representation of a Segment Routing Header
+    static REPR_SRH: Repr = Repr::Srh {
+        segments_left: 1,
+        flags: 0,
+        tag: 0x1234,
+        segments: &[
+            0xfd, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x3, 0xfd,
+            0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2,
+        ],
+        tlvs: &[0x0; 8],
+    };
+
+    // A routing header of an unassigned routing type
+    static BYTES_UNKNOWN: [u8; 6] = [0x80, 0x0, 0x1, 0x2, 0x3, 0x4];
+
+    // A representation of a routing header of an unassigned routing type
+    static REPR_UNKNOWN: Repr = Repr::Unknown {
+        routing_type: Type::Unknown(0x80),
+        segments_left: 0,
+        data: &[0x1, 0x2, 0x3, 0x4],
+    };
+
     #[test]
     fn test_check_len() {
         // less than min header size
@@ -590,6 +959,46 @@ mod test {
         let header = Header::new_checked(&BYTES_SRH_ELIDED[..]).unwrap();
         let repr = Repr::parse(&header).unwrap();
         assert_eq!(repr, REPR_SRH_ELIDED);
+
+        let header = Header::new_checked(&BYTES_TYPE0[..]).unwrap();
+        let repr = Repr::parse(&header).unwrap();
+        assert_eq!(repr, REPR_TYPE0);
+
+        let header = Header::new_checked(&BYTES_SRH[..]).unwrap();
+        let repr = Repr::parse(&header).unwrap();
+        assert_eq!(repr, REPR_SRH);
+
+        let header = Header::new_checked(&BYTES_UNKNOWN[..]).unwrap();
+        let repr = Repr::parse(&header).unwrap();
+        assert_eq!(repr, REPR_UNKNOWN);
+    }
+
+    #[test]
+    fn test_repr_parse_invalid() {
+        // Type 0 addresses must be complete.
+        let header = Header::new_checked(&BYTES_TYPE0[..37]).unwrap();
+        assert_eq!(Repr::parse(&header), Err(Error));
+
+        // The segment list must fit in the Segment Routing Header.
+        let mut bytes = BYTES_SRH;
+        bytes[2] = 2;
+        let header = Header::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&header), Err(Error));
+
+        assert_eq!(Header::new_checked(&BYTES_SRH[..5]).map(|_| ()), Err(Error));
+    }
+
+    #[test]
+    fn test_repr_addresses() {
+        let addr_2 = Address::new(0xfd00, 0, 0, 0, 0, 0, 0, 2);
+        let addr_3 = Address::new(0xfd00, 0, 0, 0, 0, 0, 0, 3);
+        assert!(REPR_TYPE0.addresses().eq([addr_2, addr_3]));
+        assert!(REPR_SRH.addresses().eq([addr_3, addr_2]));
+        assert_eq!(REPR_SRH_FULL.addresses().count(), 0);
+        assert_eq!(REPR_UNKNOWN.addresses().count(), 0);
+
+        assert_eq!(REPR_TYPE0.segments_left(), 1);
+        assert_eq!(REPR_UNKNOWN.segments_left(), 0);
     }
 
     #[test]
@@ -608,6 +1017,35 @@ mod test {
         let mut header = Header::new_unchecked(&mut bytes[..]);
         REPR_SRH_ELIDED.emit(&mut header);
         assert_eq!(header.into_inner(), &BYTES_SRH_ELIDED[..]);
+
+        let mut bytes = [0xffu8; 38];
+        let mut header = Header::new_unchecked(&mut bytes[..]);
+        REPR_TYPE0.emit(&mut header);
+        assert_eq!(header.into_inner(), &BYTES_TYPE0[..]);
+
+        let mut bytes = [0u8; 46];
+        let mut header = Header::new_unchecked(&mut bytes[..]);
+        REPR_SRH.emit(&mut header);
+        assert_eq!(header.into_inner(), &BYTES_SRH[..]);
+
+        let mut bytes = [0u8; 6];
+        let mut header = Header::new_unchecked(&mut bytes[..]);
+        REPR_UNKNOWN.emit(&mut header);
+        assert_eq!(header.into_inner(), &BYTES_UNKNOWN[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the segment list must hold between 1 and 256 addresses")]
+    fn test_repr_emit_srh_partial_segment() {
+        let repr = Repr::Srh {
+            segments_left: 0,
+            flags: 0,
+            tag: 0,
+            segments: &[0; 20],
+            tlvs: &[],
+        };
+        let mut bytes = [0u8; 26];
+        repr.emit(&mut Header::new_unchecked(&mut bytes[..]));
     }
 
     #[test]
@@ -615,5 +1053,8 @@ mod test {
         assert_eq!(REPR_TYPE2.buffer_len(), 22);
         assert_eq!(REPR_SRH_FULL.buffer_len(), 38);
         assert_eq!(REPR_SRH_ELIDED.buffer_len(), 14);
+        assert_eq!(REPR_TYPE0.buffer_len(), 38);
+        assert_eq!(REPR_SRH.buffer_len(), 46);
+        assert_eq!(REPR_UNKNOWN.buffer_len(), 6);
     }
 }
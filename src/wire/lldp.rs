@@ -0,0 +1,495 @@
+//! Link Layer Discovery Protocol data units, as specified in IEEE 802.1AB.
+//!
+//! An LLDPDU is carried directly in an Ethernet frame with the
+//! [`EtherType::Lldp`](super::EthernetProtocol::Lldp) ethertype. It is a
+//! sequence of TLVs, starting with the mandatory Chassis ID, Port ID and Time
+//! To Live TLVs, and terminated by an End Of LLDPDU TLV.
+
+use byteorder::{ByteOrder, NetworkEndian};
+use core::fmt;
+
+use super::{Error, EthernetAddress, Result};
+
+/// The "nearest bridge" destination address of LLDPDUs.
+pub const MULTICAST_ADDRESS: EthernetAddress =
+    EthernetAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e]);
+
+enum_with_unknown! {
+    /// TLV type.
+    pub enum TlvType(u8) {
+        /// End Of LLDPDU.
+        End                     = 0,
+        /// Chassis ID.
+        ChassisId               = 1,
+        /// Port ID.
+        PortId                  = 2,
+        /// Time To Live.
+        Ttl                     = 3,
+        /// Port Description.
+        PortDescription         = 4,
+        /// System Name.
+        SystemName              = 5,
+        /// System Description.
+        SystemDescription       = 6,
+        /// System Capabilities.
+        SystemCapabilities      = 7,
+        /// Management Address.
+        ManagementAddress       = 8,
+        /// Organizationally Specific TLV.
+        OrganizationallySpecific = 127
+    }
+}
+
+/// Length of a TLV header.
+pub const TLV_HEADER_LEN: usize = 2;
+
+/// The maximum length of a TLV value.
+pub const TLV_MAX_LEN: usize = 0x1ff;
+
+/// A TLV of an LLDPDU.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Tlv<'a> {
+    pub tlv_type: TlvType,
+    pub value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Parse a TLV from the start of a sequence of octets.
+    ///
+    /// Returns `Err(Error)` if the buffer is too short for the TLV header or
+    /// its value.
+    pub fn parse(data: &'a [u8]) -> Result<Tlv<'a>> {
+        if data.len() < TLV_HEADER_LEN {
+            return Err(Error);
+        }
+        let header = NetworkEndian::read_u16(&data[..TLV_HEADER_LEN]);
+        let tlv_type = TlvType::from((header >> 9) as u8);
+        let len = (header & 0x1ff) as usize;
+        let value = data
+            .get(TLV_HEADER_LEN..TLV_HEADER_LEN + len)
+            .ok_or(Error)?;
+        Ok(Tlv { tlv_type, value })
+    }
+
+    /// Return the length of the TLV, including its header.
+    pub const fn buffer_len(&self) -> usize {
+        TLV_HEADER_LEN + self.value.len()
+    }
+
+    /// Emit the TLV into the start of a sequence of octets.
+    ///
+    /// # Panics
+    /// This function panics if the value is longer than [`TLV_MAX_LEN`], or if
+    /// the buffer is shorter than the TLV.
+    pub fn emit(&self, data: &mut [u8]) {
+        assert!(self.value.len() <= TLV_MAX_LEN);
+        let header = (u8::from(self.tlv_type) as u16) << 9 | self.value.len() as u16;
+        NetworkEndian::write_u16(&mut data[..TLV_HEADER_LEN], header);
+        data[TLV_HEADER_LEN..self.buffer_len()].copy_from_slice(self.value);
+    }
+}
+
+/// An iterator over the TLVs of an LLDPDU.
+///
+/// The iterator stops at the End Of LLDPDU TLV, which is not returned, or at
+/// the end of the buffer. A truncated TLV is returned as an error, after
+/// which the iteration stops.
+#[derive(Debug, Clone)]
+pub struct TlvIterator<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TlvIterator<'a> {
+    /// Create an iterator over a sequence of TLVs.
+    pub const fn new(data: &'a [u8]) -> TlvIterator<'a> {
+        TlvIterator { data }
+    }
+}
+
+impl<'a> Iterator for TlvIterator<'a> {
+    type Item = Result<Tlv<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        match Tlv::parse(self.data) {
+            Ok(tlv) if tlv.tlv_type == TlvType::End => {
+                self.data = &[];
+                None
+            }
+            Ok(tlv) => {
+                self.data = &self.data[tlv.buffer_len()..];
+                Some(Ok(tlv))
+            }
+            Err(e) => {
+                self.data = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A read/write wrapper around an LLDPDU buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Frame<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+impl<T: AsRef<[u8]>> Frame<T> {
+    /// Imbue a raw octet buffer with LLDPDU structure.
+    pub const fn new_unchecked(buffer: T) -> Frame<T> {
+        Frame { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Frame<T>> {
+        let frame = Self::new_unchecked(buffer);
+        frame.check_len()?;
+        Ok(frame)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if a TLV is truncated, or if the LLDPDU does not
+    /// start with the Chassis ID, Port ID and Time To Live TLVs.
+    ///
+    /// Octets following the End Of LLDPDU TLV, such as Ethernet padding, are
+    /// ignored.
+    pub fn check_len(&self) -> Result<()> {
+        let mut tlvs = self.tlvs();
+        for tlv_type in [TlvType::ChassisId, TlvType::PortId, TlvType::Ttl] {
+            // Identifiers are made of a subtype and at least one octet, and
+            // the Time To Live is two octets long.
+            let tlv = tlvs.next().ok_or(Error)??;
+            if tlv.tlv_type != tlv_type || tlv.value.len() < 2 {
+                return Err(Error);
+            }
+        }
+        for tlv in tlvs {
+            tlv?;
+        }
+        Ok(())
+    }
+
+    /// Consume the frame, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return an iterator over the TLVs of the LLDPDU.
+    pub fn tlvs(&self) -> TlvIterator<'_> {
+        TlvIterator::new(self.buffer.as_ref())
+    }
+
+    /// Return the value of the first TLV of the given type, if any.
+    pub fn tlv_value(&self, tlv_type: TlvType) -> Option<&[u8]> {
+        self.tlvs()
+            .filter_map(|tlv| tlv.ok())
+            .find(|tlv| tlv.tlv_type == tlv_type)
+            .map(|tlv| tlv.value)
+    }
+
+    /// Return the value of the TLV at the given index.
+    ///
+    /// # Panics
+    /// This function panics if there is no such TLV.
+    fn nth_value(&self, index: usize) -> &[u8] {
+        self.tlvs().nth(index).unwrap().unwrap().value
+    }
+
+    /// Return the Chassis ID subtype.
+    #[inline]
+    pub fn chassis_id_subtype(&self) -> u8 {
+        self.nth_value(0)[0]
+    }
+
+    /// Return the Chassis ID.
+    #[inline]
+    pub fn chassis_id(&self) -> &[u8] {
+        &self.nth_value(0)[1..]
+    }
+
+    /// Return the Port ID subtype.
+    #[inline]
+    pub fn port_id_subtype(&self) -> u8 {
+        self.nth_value(1)[0]
+    }
+
+    /// Return the Port ID.
+    #[inline]
+    pub fn port_id(&self) -> &[u8] {
+        &self.nth_value(1)[1..]
+    }
+
+    /// Return the Time To Live, in seconds.
+    #[inline]
+    pub fn ttl(&self) -> u16 {
+        NetworkEndian::read_u16(self.nth_value(2))
+    }
+
+    /// Return the System Name, if present.
+    #[inline]
+    pub fn system_name(&self) -> Option<&[u8]> {
+        self.tlv_value(TlvType::SystemName)
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Frame<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// A high-level representation of an LLDPDU.
+///
+/// Only the mandatory TLVs and the textual optional TLVs are represented;
+/// the other TLVs can be read with [`Frame::tlvs`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Repr<'a> {
+    pub chassis_id_subtype: u8,
+    pub chassis_id: &'a [u8],
+    pub port_id_subtype: u8,
+    pub port_id: &'a [u8],
+    /// Time To Live, in seconds.
+    pub ttl: u16,
+    pub port_description: Option<&'a [u8]>,
+    pub system_name: Option<&'a [u8]>,
+    pub system_description: Option<&'a [u8]>,
+}
+
+impl<'a> Repr<'a> {
+    /// Parse an LLDPDU and return a high-level representation.
+    pub fn parse<T: AsRef<[u8]> + ?Sized>(frame: &Frame<&'a T>) -> Result<Repr<'a>> {
+        frame.check_len()?;
+        let data = frame.buffer.as_ref();
+        let mut repr = Repr {
+            chassis_id_subtype: 0,
+            chassis_id: &[],
+            port_id_subtype: 0,
+            port_id: &[],
+            ttl: 0,
+            port_description: None,
+            system_name: None,
+            system_description: None,
+        };
+        for tlv in TlvIterator::new(data) {
+            let tlv = tlv?;
+            match tlv.tlv_type {
+                TlvType::ChassisId if repr.chassis_id.is_empty() => {
+                    repr.chassis_id_subtype = tlv.value[0];
+                    repr.chassis_id = &tlv.value[1..];
+                }
+                TlvType::PortId if repr.port_id.is_empty() => {
+                    repr.port_id_subtype = tlv.value[0];
+                    repr.port_id = &tlv.value[1..];
+                }
+                // Only the first Time To Live TLV is checked by `check_len`.
+                TlvType::Ttl if tlv.value.len() < 2 => return Err(Error),
+                TlvType::Ttl => repr.ttl = NetworkEndian::read_u16(tlv.value),
+                TlvType::PortDescription => repr.port_description = Some(tlv.value),
+                TlvType::SystemName => repr.system_name = Some(tlv.value),
+                TlvType::SystemDescription => repr.system_description = Some(tlv.value),
+                _ => (),
+            }
+        }
+        Ok(repr)
+    }
+
+    fn id_tlv_len(id: &[u8]) -> usize {
+        TLV_HEADER_LEN + 1 + id.len()
+    }
+
+    fn optional_tlv_len(value: Option<&[u8]>) -> usize {
+        value.map_or(0, |value| TLV_HEADER_LEN + value.len())
+    }
+
+    /// Return the length of the LLDPDU that will be emitted from this
+    /// high-level representation, including the End Of LLDPDU TLV.
+    pub fn buffer_len(&self) -> usize {
+        Self::id_tlv_len(self.chassis_id)
+            + Self::id_tlv_len(self.port_id)
+            + TLV_HEADER_LEN
+            + 2
+            + Self::optional_tlv_len(self.port_description)
+            + Self::optional_tlv_len(self.system_name)
+            + Self::optional_tlv_len(self.system_description)
+            + TLV_HEADER_LEN
+    }
+
+    /// Emit a high-level representation into an LLDPDU.
+    ///
+    /// # Panics
+    /// This function panics if a TLV value is longer than [`TLV_MAX_LEN`], or
+    /// if the buffer is shorter than [`buffer_len`](Self::buffer_len).
+    pub fn emit<T: AsRef<[u8]> + AsMut<[u8]>>(&self, frame: &mut Frame<T>) {
+        fn emit_id(data: &mut [u8], tlv_type: TlvType, subtype: u8, id: &[u8]) -> usize {
+            let header = (u8::from(tlv_type) as u16) << 9 | (1 + id.len()) as u16;
+            NetworkEndian::write_u16(&mut data[..TLV_HEADER_LEN], header);
+            data[TLV_HEADER_LEN] = subtype;
+            data[TLV_HEADER_LEN + 1..TLV_HEADER_LEN + 1 + id.len()].copy_from_slice(id);
+            TLV_HEADER_LEN + 1 + id.len()
+        }
+
+        let data = frame.buffer.as_mut();
+        let mut offset = emit_id(
+            data,
+            TlvType::ChassisId,
+            self.chassis_id_subtype,
+            self.chassis_id,
+        );
+        offset += emit_id(
+            &mut data[offset..],
+            TlvType::PortId,
+            self.port_id_subtype,
+            self.port_id,
+        );
+
+        let mut ttl = [0; 2];
+        NetworkEndian::write_u16(&mut ttl, self.ttl);
+        let tlvs = [
+            (TlvType::Ttl, Some(&ttl[..])),
+            (TlvType::PortDescription, self.port_description),
+            (TlvType::SystemName, self.system_name),
+            (TlvType::SystemDescription, self.system_description),
+            (TlvType::End, Some(&[][..])),
+        ];
+        for (tlv_type, value) in tlvs {
+            if let Some(value) = value {
+                let tlv = Tlv { tlv_type, value };
+                tlv.emit(&mut data[offset..]);
+                offset += tlv.buffer_len();
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Display for Repr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LLDP chassis={:?} port={:?} ttl={}",
+            self.chassis_id, self.port_id, self.ttl
+        )?;
+        if let Some(name) = self.system_name {
+            write!(f, " name={name:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[rustfmt::skip]
+    static FRAME_BYTES: [u8; 46] = [
+        // Chassis ID, MAC address subtype
+        0x02, 0x07, 0x04, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55,
+        // Port ID, interface name subtype
+        0x04, 0x05, 0x05, 0x65, 0x74, 0x68, 0x30,
+        // Time To Live
+        0x06, 0x02, 0x00, 0x78,
+        // System Name
+        0x0a, 0x06, 0x73, 0x77, 0x69, 0x74, 0x63, 0x68,
+        // Organizationally Specific
+        0xfe, 0x06, 0x00, 0x80, 0xc2, 0x01, 0x00, 0x01,
+        // End Of LLDPDU
+        0x00, 0x00,
+        // Padding
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    ];
+
+    fn frame_repr() -> Repr<'static> {
+        Repr {
+            chassis_id_subtype: 4,
+            chassis_id: &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+            port_id_subtype: 5,
+            port_id: b"eth0",
+            ttl: 120,
+            port_description: None,
+            system_name: Some(b"switch"),
+            system_description: None,
+        }
+    }
+
+    #[test]
+    fn test_deconstruct() {
+        let frame = Frame::new_checked(&FRAME_BYTES[..]).unwrap();
+        assert_eq!(frame.chassis_id_subtype(), 4);
+        assert_eq!(frame.chassis_id(), &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        assert_eq!(frame.port_id_subtype(), 5);
+        assert_eq!(frame.port_id(), b"eth0");
+        assert_eq!(frame.ttl(), 120);
+        assert_eq!(frame.system_name(), Some(&b"switch"[..]));
+        assert_eq!(frame.tlv_value(TlvType::SystemDescription), None);
+    }
+
+    #[test]
+    fn test_tlv_iterator() {
+        let frame = Frame::new_checked(&FRAME_BYTES[..]).unwrap();
+        let mut tlvs = frame.tlvs();
+        let types = [
+            TlvType::ChassisId,
+            TlvType::PortId,
+            TlvType::Ttl,
+            TlvType::SystemName,
+            TlvType::OrganizationallySpecific,
+        ];
+        for tlv_type in types {
+            assert_eq!(tlvs.next().unwrap().unwrap().tlv_type, tlv_type);
+        }
+        // The padding following the End Of LLDPDU TLV is not parsed.
+        assert_eq!(tlvs.next(), None);
+        assert_eq!(tlvs.next(), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let frame = Frame::new_checked(&FRAME_BYTES[..]).unwrap();
+        assert_eq!(Repr::parse(&frame), Ok(frame_repr()));
+    }
+
+    #[test]
+    fn test_emit() {
+        let repr = frame_repr();
+        let mut bytes = [0xa5; 46];
+        let len = repr.buffer_len();
+        assert_eq!(len, 30);
+        let mut frame = Frame::new_unchecked(&mut bytes[..len]);
+        repr.emit(&mut frame);
+
+        let mut expected = [0u8; 30];
+        expected[..28].copy_from_slice(&FRAME_BYTES[..28]);
+        assert_eq!(&bytes[..len], &expected[..]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        // Truncated TLV.
+        assert_eq!(Frame::new_checked(&FRAME_BYTES[..24]), Err(Error));
+        // Missing mandatory TLV.
+        assert_eq!(Frame::new_checked(&FRAME_BYTES[..16]), Err(Error));
+        // Mandatory TLVs out of order.
+        let mut bytes = FRAME_BYTES;
+        bytes[0] = 0x04;
+        assert_eq!(Frame::new_checked(&bytes[..]), Err(Error));
+    }
+
+    #[test]
+    fn test_parse_short_duplicate_ttl() {
+        // A second Time To Live TLV, one octet long, in place of the System
+        // Name TLV.
+        let mut bytes = FRAME_BYTES;
+        bytes[20..23].copy_from_slice(&[0x06, 0x01, 0x00]);
+        bytes[23..28].copy_from_slice(&[0x08, 0x03, 0x61, 0x62, 0x63]);
+        let frame = Frame::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&frame), Err(Error));
+    }
+}
@@ -82,23 +82,33 @@ pub mod pretty_print;
 
 #[cfg(all(feature = "proto-ipv4", feature = "medium-ethernet"))]
 mod arp;
+mod coap;
 #[cfg(feature = "proto-dhcpv4")]
 pub(crate) mod dhcpv4;
 #[cfg(feature = "proto-dns")]
 pub(crate) mod dns;
 #[cfg(feature = "medium-ethernet")]
 mod ethernet;
+#[cfg(all(feature = "alloc", any(feature = "proto-ipv4", feature = "proto-ipv6")))]
+mod fragment;
+pub mod framing;
+#[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
+pub mod gtpu;
 #[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
 mod icmp;
 #[cfg(feature = "proto-ipv4")]
 mod icmpv4;
 #[cfg(feature = "proto-ipv6")]
 mod icmpv6;
+#[cfg(feature = "medium-ethernet")]
+pub mod ieee80211;
 #[cfg(feature = "medium-ieee802154")]
 pub mod ieee802154;
 #[cfg(feature = "proto-igmp")]
 mod igmp;
 pub(crate) mod ip;
+#[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
+mod ipsec_esp;
 #[cfg(feature = "proto-ipv4")]
 mod ipv4;
 #[cfg(feature = "proto-ipv6")]
@@ -111,8 +121,12 @@ mod ipv6fragment;
 mod ipv6option;
 #[cfg(feature = "proto-ipv6")]
 mod ipv6routing;
+#[cfg(feature = "medium-ethernet")]
+pub mod lldp;
 #[cfg(feature = "proto-ipv6")]
 mod mld;
+#[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
+pub mod mqtt;
 #[cfg(all(
     feature = "proto-ipv6",
     any(feature = "medium-ethernet", feature = "medium-ieee802154")
@@ -123,12 +137,24 @@ mod ndisc;
     any(feature = "medium-ethernet", feature = "medium-ieee802154")
 ))]
 mod ndiscoption;
+mod ntp;
+#[cfg(feature = "proto-ipv6")]
+pub mod pmtu;
+pub mod proxy_protocol;
+#[cfg(feature = "proto-ipv4")]
+pub mod rip;
 #[cfg(feature = "proto-rpl")]
 mod rpl;
 #[cfg(all(feature = "proto-sixlowpan", feature = "medium-ieee802154"))]
 mod sixlowpan;
+#[cfg(feature = "medium-ethernet")]
+pub mod stp;
 mod tcp;
+#[cfg(all(feature = "proto-ipv4", feature = "proto-ipv6"))]
+pub mod transition;
 mod udp;
+#[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
+pub mod wireguard;
 
 use core::fmt;
 
@@ -139,18 +165,23 @@ pub use self::arp::{
 #[cfg(feature = "medium-ethernet")]
 pub use self::ethernet::{
     Address as EthernetAddress, EtherType as EthernetProtocol, Frame as EthernetFrame,
-    Repr as EthernetRepr, HEADER_LEN as ETHERNET_HEADER_LEN,
+    Repr as EthernetRepr, VlanRepr as EthernetVlanRepr, VlanTag as EthernetVlanTag,
+    HEADER_LEN as ETHERNET_HEADER_LEN, VLAN_TAG_LEN as ETHERNET_VLAN_TAG_LEN,
 };
+
 #[cfg(feature = "medium-ieee802154")]
 pub use self::ieee802154::{
     Address as Ieee802154Address, AddressingMode as Ieee802154AddressingMode,
     Frame as Ieee802154Frame, FrameType as Ieee802154FrameType,
     FrameVersion as Ieee802154FrameVersion, Pan as Ieee802154Pan, Repr as Ieee802154Repr,
 };
+#[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
+pub use self::ipsec_esp::{Packet as EspPacket, Repr as EspRepr, HEADER_LEN as ESP_HEADER_LEN};
 #[cfg(feature = "proto-ipv4")]
 pub use self::ipv4::{
-    Address as Ipv4Address, Cidr as Ipv4Cidr, Key as Ipv4FragKey, Packet as Ipv4Packet,
-    Repr as Ipv4Repr, HEADER_LEN as IPV4_HEADER_LEN, MIN_MTU as IPV4_MIN_MTU,
+    Address as Ipv4Address, Cidr as Ipv4Cidr, Key as Ipv4FragKey, OptionType as Ipv4OptionType,
+    Packet as Ipv4Packet, Repr as Ipv4Repr, HEADER_LEN as IPV4_HEADER_LEN, MIN_MTU as IPV4_MIN_MTU,
+    ROUTER_ALERT_LEN as IPV4_ROUTER_ALERT_LEN,
 };
 #[cfg(feature = "proto-ipv6")]
 pub use self::ipv6::{
@@ -158,11 +189,13 @@ pub use self::ipv6::{
     HEADER_LEN as IPV6_HEADER_LEN, MIN_MTU as IPV6_MIN_MTU,
 };
 #[cfg(feature = "proto-ipv6")]
-pub use self::ipv6ext_header::{Header as Ipv6ExtHeader, Repr as Ipv6ExtHeaderRepr};
+pub use self::ipv6ext_header::{
+    Header as Ipv6ExtHeader, Ipv6ExtHeaderIterator, Repr as Ipv6ExtHeaderRepr,
+};
 #[cfg(feature = "proto-ipv6")]
 pub use self::ipv6option::{
-    FailureType as Ipv6OptionFailureType, Ipv6Option, Ipv6OptionsIterator, Repr as Ipv6OptionRepr,
-    Type as Ipv6OptionType,
+    FailureType as Ipv6OptionFailureType, Ipv6Option, Ipv6OptionsIterator, Ipv6RawOptionsIterator,
+    Repr as Ipv6OptionRepr, Type as Ipv6OptionType,
 };
 #[cfg(feature = "proto-rpl")]
 pub use self::rpl::{
@@ -201,14 +234,19 @@ pub type Ipv6HopByHopRepr<'a> = Ipv6ExtHeaderRepr<'a>;
 #[cfg(feature = "proto-dhcpv4")]
 pub use self::dhcpv4::{
     DhcpOption, DhcpOptionWriter, MessageType as DhcpMessageType, Packet as DhcpPacket,
-    Repr as DhcpRepr, CLIENT_PORT as DHCP_CLIENT_PORT,
-    MAX_DNS_SERVER_COUNT as DHCP_MAX_DNS_SERVER_COUNT, SERVER_PORT as DHCP_SERVER_PORT,
+    RelayAgentInformation as DhcpRelayAgentInformation, Repr as DhcpRepr,
+    CLIENT_PORT as DHCP_CLIENT_PORT, MAX_DNS_SERVER_COUNT as DHCP_MAX_DNS_SERVER_COUNT,
+    SERVER_PORT as DHCP_SERVER_PORT,
 };
 #[cfg(feature = "proto-dns")]
 pub use self::dns::{
-    Flags as DnsFlags, Opcode as DnsOpcode, Packet as DnsPacket, Rcode as DnsRcode,
-    Repr as DnsRepr, Type as DnsQueryType,
+    Edns0 as DnsEdns0, EdnsFlags as DnsEdnsFlags, Flags as DnsFlags, Opcode as DnsOpcode,
+    Packet as DnsPacket, Rcode as DnsRcode, Repr as DnsRepr, Type as DnsQueryType,
 };
+#[cfg(all(feature = "alloc", feature = "proto-ipv4"))]
+pub use self::fragment::fragment_ipv4;
+#[cfg(all(feature = "alloc", feature = "proto-ipv6"))]
+pub use self::fragment::fragment_ipv6;
 #[cfg(any(feature = "proto-ipv4", feature = "proto-ipv6"))]
 pub use self::icmp::Repr as IcmpRepr;
 #[cfg(feature = "proto-ipv4")]
@@ -238,6 +276,7 @@ pub use self::mld::{AddressRecord as MldAddressRecord, Repr as MldRepr};
 ))]
 pub use self::ndisc::{
     NeighborFlags as NdiscNeighborFlags, Repr as NdiscRepr, RouterFlags as NdiscRouterFlags,
+    MAX_PREFIX_INFOS as NDISC_MAX_PREFIX_INFOS,
 };
 #[cfg(all(
     feature = "proto-ipv6",
@@ -249,9 +288,21 @@ pub use self::ndiscoption::{
     Repr as NdiscOptionRepr, Type as NdiscOptionType,
 };
 pub use self::{
+    coap::{
+        CoapOption, Code as CoapCode, MessageType as CoapMessageType,
+        OptionNumber as CoapOptionNumber, Options as CoapOptions,
+        OptionsIterator as CoapOptionsIterator, Packet as CoapPacket, Repr as CoapRepr,
+        DEFAULT_PORT as COAP_DEFAULT_PORT, HEADER_LEN as COAP_HEADER_LEN,
+    },
+    ntp::{
+        LeapIndicator as NtpLeapIndicator, Mode as NtpMode, Packet as NtpPacket, Repr as NtpRepr,
+        Timestamp as NtpTimestamp, HEADER_LEN as NTP_HEADER_LEN, SERVER_PORT as NTP_SERVER_PORT,
+    },
     tcp::{
+        AccEcn as TcpAccEcn, AccEcnOrder as TcpAccEcnOrder, Authentication as TcpAuthentication,
         Control as TcpControl, Packet as TcpPacket, Repr as TcpRepr, SeqNumber as TcpSeqNumber,
-        TcpOption, HEADER_LEN as TCP_HEADER_LEN,
+        TcpOption, ACCECN_COUNTER_MASK as TCP_ACCECN_COUNTER_MASK, AO_MAC_LEN as TCP_AO_MAC_LEN,
+        AO_OPTION_LEN as TCP_AO_OPTION_LEN, HEADER_LEN as TCP_HEADER_LEN,
     },
     udp::{Packet as UdpPacket, Repr as UdpRepr, HEADER_LEN as UDP_HEADER_LEN},
 };
@@ -0,0 +1,124 @@
+// See https://docs.oasis-open.org/mqtt/mqtt/v5.0/mqtt-v5.0.html for the MQTT
+// specification; the fixed header is the same in MQTT 3.1.1.
+
+//! MQTT control packet framing.
+//!
+//! MQTT is carried over a TCP stream, so control packets have to be framed
+//! out of the received octets. Every control packet starts with a fixed
+//! header holding its type and the length of the rest of the packet, which
+//! [`parse_fixed_header`] decodes.
+
+use super::{Error, Result};
+
+pub const PORT: u16 = 1883;
+
+/// The maximum length of a fixed header.
+pub const MAX_FIXED_HEADER_LEN: usize = 5;
+
+/// The maximum length of the remaining length field.
+const MAX_REMAINING_LEN_LEN: usize = 4;
+
+enum_with_unknown! {
+    /// MQTT control packet type.
+    pub enum PacketType(u8) {
+        Connect     = 1,
+        Connack     = 2,
+        Publish     = 3,
+        Puback      = 4,
+        Pubrec      = 5,
+        Pubrel      = 6,
+        Pubcomp     = 7,
+        Subscribe   = 8,
+        Suback      = 9,
+        Unsubscribe = 10,
+        Unsuback    = 11,
+        Pingreq     = 12,
+        Pingresp    = 13,
+        Disconnect  = 14,
+        Auth        = 15
+    }
+}
+
+/// Decode the fixed header at the start of an MQTT control packet.
+///
+/// Returns the packet type, the length of the rest of the packet, and the
+/// length of the fixed header itself; the whole packet is therefore
+/// `header_len + remaining_len` octets long. The flags in the low nibble of
+/// the first octet are not interpreted.
+///
+/// Returns `Ok(None)` if `bytes` does not hold the whole fixed header yet,
+/// and more octets have to be received, and `Err(Error)` if the remaining
+/// length is encoded in more than four octets, which makes the stream
+/// unusable.
+pub fn parse_fixed_header(bytes: &[u8]) -> Result<Option<(PacketType, usize, usize)>> {
+    let (&first, rest) = match bytes.split_first() {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+    let packet_type = PacketType::from(first >> 4);
+
+    let mut remaining_len = 0;
+    for (i, &byte) in rest.iter().enumerate() {
+        remaining_len |= ((byte & 0x7f) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((packet_type, remaining_len, i + 2)));
+        }
+        if i + 1 == MAX_REMAINING_LEN_LEN {
+            return Err(Error);
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_one_octet_remaining_len() {
+        assert_eq!(
+            parse_fixed_header(&[0xc0, 0x00]),
+            Ok(Some((PacketType::Pingreq, 0, 2)))
+        );
+        assert_eq!(
+            parse_fixed_header(&[0x32, 0x7f, 0x00, 0x03]),
+            Ok(Some((PacketType::Publish, 127, 2)))
+        );
+    }
+
+    #[test]
+    fn test_three_octet_remaining_len() {
+        assert_eq!(
+            parse_fixed_header(&[0x10, 0x80, 0x80, 0x01, 0x00]),
+            Ok(Some((PacketType::Connect, 16_384, 4)))
+        );
+        assert_eq!(
+            parse_fixed_header(&[0x30, 0xff, 0xff, 0x7f]),
+            Ok(Some((PacketType::Publish, 2_097_151, 4)))
+        );
+        assert_eq!(
+            parse_fixed_header(&[0x30, 0xff, 0xff, 0xff, 0x7f]),
+            Ok(Some((PacketType::Publish, 268_435_455, 5)))
+        );
+    }
+
+    #[test]
+    fn test_incomplete() {
+        assert_eq!(parse_fixed_header(&[]), Ok(None));
+        assert_eq!(parse_fixed_header(&[0x30]), Ok(None));
+        assert_eq!(parse_fixed_header(&[0x30, 0x80, 0x80]), Ok(None));
+        assert_eq!(parse_fixed_header(&[0x30, 0xff, 0xff, 0xff]), Ok(None));
+    }
+
+    #[test]
+    fn test_malformed_remaining_len() {
+        assert_eq!(
+            parse_fixed_header(&[0x30, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            Err(Error)
+        );
+        assert_eq!(
+            parse_fixed_header(&[0x30, 0xff, 0xff, 0xff, 0xff]),
+            Err(Error)
+        );
+    }
+}
@@ -28,6 +28,10 @@ bitflags! {
     }
 }
 
+/// The maximum number of Prefix Information options in a Router
+/// Advertisement representation. Further options are ignored when parsing.
+pub const MAX_PREFIX_INFOS: usize = 4;
+
 /// Getters for the Router Advertisement message header.
 /// See [RFC 4861 § 4.2].
 ///
@@ -205,7 +209,7 @@ pub enum Repr<'a> {
         retrans_time: Duration,
         lladdr: Option<RawHardwareAddress>,
         mtu: Option<u32>,
-        prefix_info: Option<NdiscPrefixInformation>,
+        prefix_infos: [Option<NdiscPrefixInformation>; MAX_PREFIX_INFOS],
     },
     NeighborSolicit {
         target_addr: Ipv6Address,
@@ -225,6 +229,55 @@ pub enum Repr<'a> {
 }
 
 impl<'a> Repr<'a> {
+    /// Build the representation of a Router Advertisement, as sent by a
+    /// router advertising the given prefixes.
+    ///
+    /// The reachable time and retransmission timer fields are left
+    /// unspecified, so that hosts keep their own values.
+    ///
+    /// # Panics
+    /// This function panics if more than [`MAX_PREFIX_INFOS`] prefixes are
+    /// given.
+    pub fn router_advertisement(
+        hop_limit: u8,
+        flags: RouterFlags,
+        router_lifetime: Duration,
+        prefixes: &[NdiscPrefixInformation],
+        mtu: Option<u32>,
+        lladdr: Option<RawHardwareAddress>,
+    ) -> Repr<'a> {
+        assert!(
+            prefixes.len() <= MAX_PREFIX_INFOS,
+            "too many prefix information options"
+        );
+        let mut prefix_infos = [None; MAX_PREFIX_INFOS];
+        for (slot, prefix) in prefix_infos.iter_mut().zip(prefixes) {
+            *slot = Some(*prefix);
+        }
+
+        Repr::RouterAdvert {
+            hop_limit,
+            flags,
+            router_lifetime,
+            reachable_time: Duration::ZERO,
+            retrans_time: Duration::ZERO,
+            lladdr,
+            mtu,
+            prefix_infos,
+        }
+    }
+
+    /// Return the first Prefix Information option of a Router Advertisement.
+    #[deprecated(note = "a Router Advertisement may carry several prefixes, use `prefix_infos`")]
+    pub fn prefix_info(&self) -> Option<NdiscPrefixInformation> {
+        match self {
+            Repr::RouterAdvert { prefix_infos, .. } => {
+                prefix_infos.iter().flatten().next().copied()
+            }
+            _ => None,
+        }
+    }
+
     /// Parse an NDISC packet and return a high-level representation of the
     /// packet.
     #[allow(clippy::single_match)]
@@ -267,12 +320,24 @@ impl<'a> Repr<'a> {
                 Ok(Repr::RouterSolicit { lladdr })
             }
             Message::RouterAdvert => {
-                let (mut lladdr, mut mtu, mut prefix_info) = (None, None, None);
+                let (mut lladdr, mut mtu) = (None, None);
+                let mut prefix_infos = [None; MAX_PREFIX_INFOS];
                 foreach_option(packet.payload(), |opt| {
                     match opt {
                         NdiscOptionRepr::SourceLinkLayerAddr(addr) => lladdr = Some(addr),
                         NdiscOptionRepr::Mtu(val) => mtu = Some(val),
-                        NdiscOptionRepr::PrefixInformation(info) => prefix_info = Some(info),
+                        NdiscOptionRepr::PrefixInformation(info) => {
+                            if let Some(slot) = prefix_infos.iter_mut().find(|p| p.is_none()) {
+                                *slot = Some(info);
+                            } else {
+                                net_debug!(
+                                    "ndisc: more than {} prefixes, ignoring {}/{}",
+                                    MAX_PREFIX_INFOS,
+                                    info.prefix,
+                                    info.prefix_len
+                                );
+                            }
+                        }
                         _ => {}
                     }
                     Ok(())
@@ -285,7 +350,7 @@ impl<'a> Repr<'a> {
                     retrans_time: packet.retrans_time(),
                     lladdr,
                     mtu,
-                    prefix_info,
+                    prefix_infos,
                 })
             }
             Message::NeighborSolicit => {
@@ -318,19 +383,30 @@ impl<'a> Repr<'a> {
                 })
             }
             Message::Redirect => {
-                let (mut lladdr, mut redirected_hdr) = (None, None);
+                let target_addr = packet.target_addr();
+                let dest_addr = packet.dest_addr();
+                // The destination of a redirect is never a multicast address, and
+                // the better first hop is either a link-local router address or the
+                // destination itself, if it is on-link.
+                if packet.msg_code() != 0
+                    || dest_addr.is_multicast()
+                    || !(target_addr.is_link_local() || target_addr == dest_addr)
+                {
+                    return Err(Error);
+                }
 
+                let (mut lladdr, mut redirected_hdr) = (None, None);
                 foreach_option(packet.payload(), |opt| {
                     match opt {
-                        NdiscOptionRepr::SourceLinkLayerAddr(addr) => lladdr = Some(addr),
+                        NdiscOptionRepr::TargetLinkLayerAddr(addr) => lladdr = Some(addr),
                         NdiscOptionRepr::RedirectedHeader(rh) => redirected_hdr = Some(rh),
                         _ => {}
                     }
                     Ok(())
                 })?;
                 Ok(Repr::Redirect {
-                    target_addr: packet.target_addr(),
-                    dest_addr: packet.dest_addr(),
+                    target_addr,
+                    dest_addr,
                     lladdr,
                     redirected_hdr,
                 })
@@ -350,7 +426,7 @@ impl<'a> Repr<'a> {
             &Repr::RouterAdvert {
                 lladdr,
                 mtu,
-                prefix_info,
+                prefix_infos,
                 ..
             } => {
                 let mut offset = 0;
@@ -360,8 +436,12 @@ impl<'a> Repr<'a> {
                 if let Some(mtu) = mtu {
                     offset += NdiscOptionRepr::Mtu(mtu).buffer_len();
                 }
-                if let Some(prefix_info) = prefix_info {
-                    offset += NdiscOptionRepr::PrefixInformation(prefix_info).buffer_len();
+                let mut i = 0;
+                while i < prefix_infos.len() {
+                    if let Some(prefix_info) = prefix_infos[i] {
+                        offset += NdiscOptionRepr::PrefixInformation(prefix_info).buffer_len();
+                    }
+                    i += 1;
                 }
                 field::RETRANS_TM.end + offset
             }
@@ -414,7 +494,7 @@ impl<'a> Repr<'a> {
                 retrans_time,
                 lladdr,
                 mtu,
-                prefix_info,
+                prefix_infos,
             } => {
                 packet.set_msg_type(Message::RouterAdvert);
                 packet.set_msg_code(0);
@@ -436,10 +516,12 @@ impl<'a> Repr<'a> {
                     NdiscOptionRepr::Mtu(mtu).emit(&mut opt_pkt);
                     offset += NdiscOptionRepr::Mtu(mtu).buffer_len();
                 }
-                if let Some(prefix_info) = prefix_info {
+                for prefix_info in prefix_infos.into_iter().flatten() {
                     let mut opt_pkt =
                         NdiscOption::new_unchecked(&mut packet.payload_mut()[offset..]);
-                    NdiscOptionRepr::PrefixInformation(prefix_info).emit(&mut opt_pkt)
+                    let opt = NdiscOptionRepr::PrefixInformation(prefix_info);
+                    opt.emit(&mut opt_pkt);
+                    offset += opt.buffer_len();
                 }
             }
 
@@ -510,7 +592,7 @@ mod test {
         phy::ChecksumCapabilities,
         wire::{
             ip::test::{MOCK_IP_ADDR_1, MOCK_IP_ADDR_2},
-            EthernetAddress, Icmpv6Repr,
+            EthernetAddress, Icmpv6Repr, NdiscPrefixInfoFlags,
         },
     };
 
@@ -529,7 +611,7 @@ mod test {
             retrans_time: Duration::from_millis(900),
             lladdr: Some(EthernetAddress([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]).into()),
             mtu: None,
-            prefix_info: None,
+            prefix_infos: [None; MAX_PREFIX_INFOS],
         })
     }
 
@@ -591,4 +673,214 @@ mod test {
         );
         assert_eq!(&*packet.into_inner(), &ROUTER_ADVERT_BYTES[..]);
     }
+
+    #[test]
+    fn test_router_advertisement_builder() {
+        let prefix = NdiscPrefixInformation {
+            prefix_len: 64,
+            flags: NdiscPrefixInfoFlags::ON_LINK | NdiscPrefixInfoFlags::ADDRCONF,
+            valid_lifetime: Duration::from_secs(86400),
+            preferred_lifetime: Duration::from_secs(14400),
+            prefix: Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+        };
+        let repr = Icmpv6Repr::Ndisc(Repr::router_advertisement(
+            64,
+            RouterFlags::OTHER,
+            Duration::from_secs(1800),
+            &[prefix],
+            Some(1280),
+            Some(EthernetAddress([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]).into()),
+        ));
+        assert_eq!(repr.buffer_len(), 16 + 8 + 8 + 32);
+
+        let mut bytes = vec![0x2a; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        repr.emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &ChecksumCapabilities::default(),
+        );
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert!(packet.verify_checksum(&MOCK_IP_ADDR_1, &MOCK_IP_ADDR_2));
+        assert_eq!(packet.msg_type(), Message::RouterAdvert);
+        assert_eq!(packet.current_hop_limit(), 64);
+        assert_eq!(packet.router_flags(), RouterFlags::OTHER);
+        assert_eq!(packet.router_lifetime(), Duration::from_secs(1800));
+        assert_eq!(packet.reachable_time(), Duration::ZERO);
+        assert_eq!(packet.retrans_time(), Duration::ZERO);
+        assert_eq!(&packet.payload()[..8], &SOURCE_LINK_LAYER_OPT[..]);
+        assert_eq!(
+            &packet.payload()[8..16],
+            &[0x05, 0x01, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00]
+        );
+        assert_eq!(
+            &packet.payload()[16..],
+            &[
+                0x03, 0x04, 0x40, 0xc0, 0x00, 0x01, 0x51, 0x80, 0x00, 0x00, 0x38, 0x40, 0x00, 0x00,
+                0x00, 0x00, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00,
+            ]
+        );
+
+        assert_eq!(
+            Icmpv6Repr::parse(
+                &MOCK_IP_ADDR_1,
+                &MOCK_IP_ADDR_2,
+                &packet,
+                &ChecksumCapabilities::default()
+            )
+            .unwrap(),
+            repr
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_router_advert_too_many_prefixes() {
+        let prefix = |i| NdiscPrefixInformation {
+            prefix_len: 64,
+            flags: NdiscPrefixInfoFlags::ON_LINK,
+            valid_lifetime: Duration::from_secs(86400),
+            preferred_lifetime: Duration::from_secs(14400),
+            prefix: Ipv6Address::new(0x2001, 0xdb8, i, 0, 0, 0, 0, 0),
+        };
+        let prefixes = [prefix(1), prefix(2), prefix(3), prefix(4)];
+        let repr = Icmpv6Repr::Ndisc(Repr::router_advertisement(
+            64,
+            RouterFlags::empty(),
+            Duration::from_secs(1800),
+            &prefixes,
+            None,
+            None,
+        ));
+
+        // Append a fifth Prefix Information option.
+        let len = repr.buffer_len();
+        let mut bytes = vec![0; len + 32];
+        repr.emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut Packet::new_unchecked(&mut bytes[..len]),
+            &ChecksumCapabilities::default(),
+        );
+        bytes.copy_within(len - 32..len, len);
+        bytes[len + 21] = 5;
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        let parsed = Repr::parse(&packet).unwrap();
+        match parsed {
+            Repr::RouterAdvert { prefix_infos, .. } => {
+                assert_eq!(prefix_infos, prefixes.map(Some))
+            }
+            _ => panic!("unexpected {parsed:?}"),
+        }
+        assert_eq!(parsed.prefix_info(), Some(prefix(1)));
+    }
+
+    fn create_redirect_repr<'a>() -> Icmpv6Repr<'a> {
+        Icmpv6Repr::Ndisc(Repr::Redirect {
+            target_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+            dest_addr: Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2),
+            lladdr: Some(EthernetAddress([0x52, 0x54, 0x00, 0x12, 0x34, 0x56]).into()),
+            redirected_hdr: None,
+        })
+    }
+
+    #[test]
+    fn test_redirect_repr_emit() {
+        let repr = create_redirect_repr();
+        assert_eq!(repr.buffer_len(), 48);
+        let mut bytes = vec![0x2a; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        repr.emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &ChecksumCapabilities::default(),
+        );
+        assert!(packet.verify_checksum(&MOCK_IP_ADDR_1, &MOCK_IP_ADDR_2));
+
+        let bytes = packet.into_inner();
+        assert_eq!(&bytes[..2], &[0x89, 0x00]);
+        assert_eq!(&bytes[4..8], &[0x00; 4]);
+        assert_eq!(
+            &bytes[8..24],
+            Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).as_bytes()
+        );
+        assert_eq!(
+            &bytes[24..40],
+            Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2).as_bytes()
+        );
+        assert_eq!(
+            &bytes[40..48],
+            &[0x02, 0x01, 0x52, 0x54, 0x00, 0x12, 0x34, 0x56]
+        );
+    }
+
+    #[test]
+    fn test_redirect_repr_parse() {
+        let repr = create_redirect_repr();
+        let mut bytes = vec![0x0; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        repr.emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &ChecksumCapabilities::default(),
+        );
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(
+            Icmpv6Repr::parse(
+                &MOCK_IP_ADDR_1,
+                &MOCK_IP_ADDR_2,
+                &packet,
+                &ChecksumCapabilities::default()
+            ),
+            Ok(repr)
+        );
+    }
+
+    #[test]
+    fn test_redirect_repr_parse_invalid() {
+        let checksum_caps = ChecksumCapabilities::ignored();
+        let mut bytes = vec![0x0; create_redirect_repr().buffer_len()];
+
+        // A global target that is not the destination.
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        create_redirect_repr().emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &checksum_caps,
+        );
+        packet.set_target_addr(Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Err(Error));
+
+        // A multicast destination.
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        create_redirect_repr().emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &checksum_caps,
+        );
+        packet.set_dest_addr(Ipv6Address::LINK_LOCAL_ALL_NODES);
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Err(Error));
+
+        // An option with a length of zero.
+        let mut packet = Packet::new_unchecked(&mut bytes[..]);
+        create_redirect_repr().emit(
+            &MOCK_IP_ADDR_1,
+            &MOCK_IP_ADDR_2,
+            &mut packet,
+            &checksum_caps,
+        );
+        packet.payload_mut()[1] = 0;
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Err(Error));
+    }
 }
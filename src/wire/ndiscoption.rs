@@ -139,6 +139,8 @@ mod field {
     //  |                              MTU                              |
     //  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 
+    // Reserved bits.
+    pub const MTU_RESERVED: Field = 2..4;
     //  MTU
     pub const MTU: Field = 4..8;
 }
@@ -316,6 +318,13 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> NdiscOption<T> {
         let data = self.buffer.as_mut();
         NetworkEndian::write_u32(&mut data[field::MTU], value);
     }
+
+    /// Clear the reserved bits.
+    #[inline]
+    pub fn clear_mtu_reserved(&mut self) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::MTU_RESERVED], 0);
+    }
 }
 
 /// Setter methods only relevant for the Prefix Information option.
@@ -566,6 +575,7 @@ impl<'a> Repr<'a> {
             Repr::Mtu(mtu) => {
                 opt.set_option_type(Type::Mtu);
                 opt.set_data_len(1);
+                opt.clear_mtu_reserved();
                 opt.set_mtu(mtu);
             }
             Repr::Unknown {
@@ -0,0 +1,592 @@
+// See https://tools.ietf.org/html/rfc5905 for the NTP specification, and
+// https://tools.ietf.org/html/rfc4330 for its SNTP subset.
+
+use core::fmt;
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{Error, Result};
+use crate::time::Instant;
+
+pub const SERVER_PORT: u16 = 123;
+
+/// The NTP version emitted by default.
+pub const VERSION: u8 = 4;
+
+/// Seconds between the NTP prime epoch (1900-01-01) and the Unix epoch
+/// (1970-01-01).
+const UNIX_EPOCH_OFFSET: i64 = 2_208_988_800;
+
+enum_with_unknown! {
+    /// The leap second warning of an NTP packet.
+    pub enum LeapIndicator(u8) {
+        NoWarning = 0,
+        LastMinute61 = 1,
+        LastMinute59 = 2,
+        Unsynchronized = 3,
+    }
+}
+
+enum_with_unknown! {
+    /// The association mode of an NTP packet.
+    pub enum Mode(u8) {
+        SymmetricActive = 1,
+        SymmetricPassive = 2,
+        Client = 3,
+        Server = 4,
+        Broadcast = 5,
+    }
+}
+
+/// An NTP timestamp, in seconds and fractions of a second since the NTP
+/// prime epoch.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timestamp {
+    pub seconds: u32,
+    pub fraction: u32,
+}
+
+impl Timestamp {
+    /// The all-zero timestamp, used to mark a timestamp as unknown.
+    pub const ZERO: Timestamp = Timestamp {
+        seconds: 0,
+        fraction: 0,
+    };
+
+    /// Create a timestamp from its raw 64-bit representation.
+    pub const fn from_bits(bits: u64) -> Timestamp {
+        Timestamp {
+            seconds: (bits >> 32) as u32,
+            fraction: bits as u32,
+        }
+    }
+
+    /// Return the raw 64-bit representation of the timestamp.
+    pub const fn to_bits(&self) -> u64 {
+        ((self.seconds as u64) << 32) | self.fraction as u64
+    }
+
+    /// Convert the timestamp to an [Instant] counted from the Unix epoch, as
+    /// used by the `std::time::SystemTime` conversions.
+    ///
+    /// Only NTP era 0 (up to the year 2036) is supported. The result is
+    /// truncated to whole microseconds.
+    pub fn to_instant(&self) -> Instant {
+        let secs = self.seconds as i64 - UNIX_EPOCH_OFFSET;
+        let micros = (self.fraction as u64 * 1_000_000) >> 32;
+        Instant::from_micros(secs * 1_000_000 + micros as i64)
+    }
+
+    /// Convert an [Instant] counted from the Unix epoch into a timestamp.
+    ///
+    /// See [to_instant](#method.to_instant) for the limitations.
+    pub fn from_instant(instant: Instant) -> Timestamp {
+        let micros = instant.total_micros();
+        let secs = micros.div_euclid(1_000_000) + UNIX_EPOCH_OFFSET;
+        let frac_micros = micros.rem_euclid(1_000_000) as u64;
+        Timestamp {
+            seconds: secs as u32,
+            fraction: ((frac_micros << 32) / 1_000_000) as u32,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{:08x}", self.seconds, self.fraction)
+    }
+}
+
+/// A read/write wrapper around an NTP packet buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |LI | VN  |Mode |    Stratum     |     Poll      |  Precision   |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                         Root Delay                            |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                         Root Dispersion                       |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                          Reference ID                         |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// +                     Reference Timestamp (64)                  +
+// +                      Origin Timestamp (64)                    +
+// +                      Receive Timestamp (64)                   +
+// +                      Transmit Timestamp (64)                  +
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+mod field {
+    use crate::wire::field::*;
+
+    pub const LI_VN_MODE: usize = 0;
+    pub const STRATUM: usize = 1;
+    pub const POLL: usize = 2;
+    pub const PRECISION: usize = 3;
+    pub const ROOT_DELAY: Field = 4..8;
+    pub const ROOT_DISPERSION: Field = 8..12;
+    pub const REF_ID: Field = 12..16;
+    pub const REF_TIMESTAMP: Field = 16..24;
+    pub const ORIG_TIMESTAMP: Field = 24..32;
+    pub const RECV_TIMESTAMP: Field = 32..40;
+    pub const XMIT_TIMESTAMP: Field = 40..48;
+}
+
+/// The length of an NTP packet without extension fields or a MAC.
+pub const HEADER_LEN: usize = field::XMIT_TIMESTAMP.end;
+
+impl<T: AsRef<[u8]>> Packet<T> {
+    /// Imbue a raw octet buffer with NTP packet structure.
+    pub const fn new_unchecked(buffer: T) -> Packet<T> {
+        Packet { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Packet<T>> {
+        let packet = Self::new_unchecked(buffer);
+        packet.check_len()?;
+        Ok(packet)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is too short.
+    pub fn check_len(&self) -> Result<()> {
+        if self.buffer.as_ref().len() < HEADER_LEN {
+            Err(Error)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Consume the packet, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the leap indicator field.
+    #[inline]
+    pub fn leap_indicator(&self) -> LeapIndicator {
+        let data = self.buffer.as_ref();
+        LeapIndicator::from(data[field::LI_VN_MODE] >> 6)
+    }
+
+    /// Return the version number field.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        (data[field::LI_VN_MODE] >> 3) & 0b111
+    }
+
+    /// Return the mode field.
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        let data = self.buffer.as_ref();
+        Mode::from(data[field::LI_VN_MODE] & 0b111)
+    }
+
+    /// Return the stratum field.
+    #[inline]
+    pub fn stratum(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::STRATUM]
+    }
+
+    /// Return the poll interval field, as a log2 number of seconds.
+    #[inline]
+    pub fn poll(&self) -> i8 {
+        let data = self.buffer.as_ref();
+        data[field::POLL] as i8
+    }
+
+    /// Return the precision field, as a log2 number of seconds.
+    #[inline]
+    pub fn precision(&self) -> i8 {
+        let data = self.buffer.as_ref();
+        data[field::PRECISION] as i8
+    }
+
+    /// Return the root delay field, in NTP short format.
+    #[inline]
+    pub fn root_delay(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u32(&data[field::ROOT_DELAY])
+    }
+
+    /// Return the root dispersion field, in NTP short format.
+    #[inline]
+    pub fn root_dispersion(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u32(&data[field::ROOT_DISPERSION])
+    }
+
+    /// Return the reference ID field.
+    #[inline]
+    pub fn ref_id(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u32(&data[field::REF_ID])
+    }
+
+    /// Return the reference timestamp field.
+    #[inline]
+    pub fn ref_timestamp(&self) -> Timestamp {
+        let data = self.buffer.as_ref();
+        Timestamp::from_bits(NetworkEndian::read_u64(&data[field::REF_TIMESTAMP]))
+    }
+
+    /// Return the origin timestamp field.
+    #[inline]
+    pub fn orig_timestamp(&self) -> Timestamp {
+        let data = self.buffer.as_ref();
+        Timestamp::from_bits(NetworkEndian::read_u64(&data[field::ORIG_TIMESTAMP]))
+    }
+
+    /// Return the receive timestamp field.
+    #[inline]
+    pub fn recv_timestamp(&self) -> Timestamp {
+        let data = self.buffer.as_ref();
+        Timestamp::from_bits(NetworkEndian::read_u64(&data[field::RECV_TIMESTAMP]))
+    }
+
+    /// Return the transmit timestamp field.
+    #[inline]
+    pub fn xmit_timestamp(&self) -> Timestamp {
+        let data = self.buffer.as_ref();
+        Timestamp::from_bits(NetworkEndian::read_u64(&data[field::XMIT_TIMESTAMP]))
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
+    /// Set the leap indicator field.
+    #[inline]
+    pub fn set_leap_indicator(&mut self, value: LeapIndicator) {
+        let data = self.buffer.as_mut();
+        data[field::LI_VN_MODE] = (data[field::LI_VN_MODE] & 0x3f) | (u8::from(value) << 6);
+    }
+
+    /// Set the version number field.
+    #[inline]
+    pub fn set_version(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::LI_VN_MODE] = (data[field::LI_VN_MODE] & !0x38) | ((value & 0b111) << 3);
+    }
+
+    /// Set the mode field.
+    #[inline]
+    pub fn set_mode(&mut self, value: Mode) {
+        let data = self.buffer.as_mut();
+        data[field::LI_VN_MODE] = (data[field::LI_VN_MODE] & !0x07) | (u8::from(value) & 0b111);
+    }
+
+    /// Set the stratum field.
+    #[inline]
+    pub fn set_stratum(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::STRATUM] = value;
+    }
+
+    /// Set the poll interval field.
+    #[inline]
+    pub fn set_poll(&mut self, value: i8) {
+        let data = self.buffer.as_mut();
+        data[field::POLL] = value as u8;
+    }
+
+    /// Set the precision field.
+    #[inline]
+    pub fn set_precision(&mut self, value: i8) {
+        let data = self.buffer.as_mut();
+        data[field::PRECISION] = value as u8;
+    }
+
+    /// Set the root delay field.
+    #[inline]
+    pub fn set_root_delay(&mut self, value: u32) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u32(&mut data[field::ROOT_DELAY], value)
+    }
+
+    /// Set the root dispersion field.
+    #[inline]
+    pub fn set_root_dispersion(&mut self, value: u32) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u32(&mut data[field::ROOT_DISPERSION], value)
+    }
+
+    /// Set the reference ID field.
+    #[inline]
+    pub fn set_ref_id(&mut self, value: u32) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u32(&mut data[field::REF_ID], value)
+    }
+
+    /// Set the reference timestamp field.
+    #[inline]
+    pub fn set_ref_timestamp(&mut self, value: Timestamp) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u64(&mut data[field::REF_TIMESTAMP], value.to_bits())
+    }
+
+    /// Set the origin timestamp field.
+    #[inline]
+    pub fn set_orig_timestamp(&mut self, value: Timestamp) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u64(&mut data[field::ORIG_TIMESTAMP], value.to_bits())
+    }
+
+    /// Set the receive timestamp field.
+    #[inline]
+    pub fn set_recv_timestamp(&mut self, value: Timestamp) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u64(&mut data[field::RECV_TIMESTAMP], value.to_bits())
+    }
+
+    /// Set the transmit timestamp field.
+    #[inline]
+    pub fn set_xmit_timestamp(&mut self, value: Timestamp) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u64(&mut data[field::XMIT_TIMESTAMP], value.to_bits())
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Packet<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// A high-level representation of an NTP packet.
+///
+/// Extension fields and the message authentication code are not supported.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Repr {
+    pub leap_indicator: LeapIndicator,
+    pub version: u8,
+    pub mode: Mode,
+    pub stratum: u8,
+    pub poll: i8,
+    pub precision: i8,
+    pub root_delay: u32,
+    pub root_dispersion: u32,
+    pub ref_id: u32,
+    pub ref_timestamp: Timestamp,
+    pub orig_timestamp: Timestamp,
+    pub recv_timestamp: Timestamp,
+    pub xmit_timestamp: Timestamp,
+}
+
+impl Repr {
+    /// Create the representation of an SNTP client request sent at
+    /// `xmit_timestamp`.
+    pub const fn client_request(xmit_timestamp: Timestamp) -> Repr {
+        Repr {
+            leap_indicator: LeapIndicator::NoWarning,
+            version: VERSION,
+            mode: Mode::Client,
+            stratum: 0,
+            poll: 0,
+            precision: 0,
+            root_delay: 0,
+            root_dispersion: 0,
+            ref_id: 0,
+            ref_timestamp: Timestamp::ZERO,
+            orig_timestamp: Timestamp::ZERO,
+            recv_timestamp: Timestamp::ZERO,
+            xmit_timestamp,
+        }
+    }
+
+    /// Parse an NTP packet and return a high-level representation.
+    ///
+    /// Packets with an unsupported version, or whose mode is reserved,
+    /// control (6) or private (7), are rejected.
+    pub fn parse<T: AsRef<[u8]> + ?Sized>(packet: &Packet<&T>) -> Result<Repr> {
+        packet.check_len()?;
+
+        let version = packet.version();
+        if !(1..=VERSION).contains(&version) {
+            return Err(Error);
+        }
+
+        let mode = packet.mode();
+        if let Mode::Unknown(_) = mode {
+            return Err(Error);
+        }
+
+        Ok(Repr {
+            leap_indicator: packet.leap_indicator(),
+            version,
+            mode,
+            stratum: packet.stratum(),
+            poll: packet.poll(),
+            precision: packet.precision(),
+            root_delay: packet.root_delay(),
+            root_dispersion: packet.root_dispersion(),
+            ref_id: packet.ref_id(),
+            ref_timestamp: packet.ref_timestamp(),
+            orig_timestamp: packet.orig_timestamp(),
+            recv_timestamp: packet.recv_timestamp(),
+            xmit_timestamp: packet.xmit_timestamp(),
+        })
+    }
+
+    /// Return the length of a packet that will be emitted from this
+    /// high-level representation.
+    pub const fn buffer_len(&self) -> usize {
+        HEADER_LEN
+    }
+
+    /// Emit a high-level representation into an NTP packet.
+    pub fn emit<T: AsRef<[u8]> + AsMut<[u8]> + ?Sized>(&self, packet: &mut Packet<&mut T>) {
+        packet.set_leap_indicator(self.leap_indicator);
+        packet.set_version(self.version);
+        packet.set_mode(self.mode);
+        packet.set_stratum(self.stratum);
+        packet.set_poll(self.poll);
+        packet.set_precision(self.precision);
+        packet.set_root_delay(self.root_delay);
+        packet.set_root_dispersion(self.root_dispersion);
+        packet.set_ref_id(self.ref_id);
+        packet.set_ref_timestamp(self.ref_timestamp);
+        packet.set_orig_timestamp(self.orig_timestamp);
+        packet.set_recv_timestamp(self.recv_timestamp);
+        packet.set_xmit_timestamp(self.xmit_timestamp);
+    }
+}
+
+impl fmt::Display for Repr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "NTPv{} mode={} stratum={} xmit={}",
+            self.version, self.mode, self.stratum, self.xmit_timestamp
+        )
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mode::SymmetricActive => write!(f, "symmetric-active"),
+            Mode::SymmetricPassive => write!(f, "symmetric-passive"),
+            Mode::Client => write!(f, "client"),
+            Mode::Server => write!(f, "server"),
+            Mode::Broadcast => write!(f, "broadcast"),
+            Mode::Unknown(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static CLIENT_REQUEST_BYTES: [u8; 48] = [
+        0x23, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe8, 0xfe, 0x6f, 0x7f, 0x00,
+        0x00, 0x00, 0x00,
+    ];
+
+    static SERVER_RESPONSE_BYTES: [u8; 48] = [
+        0x24, 0x02, 0x06, 0xec, 0x00, 0x00, 0x01, 0x23, 0x00, 0x00, 0x04, 0x56, 0xc0, 0xa8, 0x01,
+        0x01, 0xe8, 0xfe, 0x6f, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe8, 0xfe, 0x6f, 0x7f, 0x00, 0x00,
+        0x00, 0x00, 0xe8, 0xfe, 0x6f, 0x80, 0x00, 0x00, 0x00, 0x00, 0xe8, 0xfe, 0x6f, 0x80, 0x80,
+        0x00, 0x00, 0x00,
+    ];
+
+    fn client_request_repr() -> Repr {
+        Repr::client_request(Timestamp {
+            seconds: 0xe8fe6f7f,
+            fraction: 0,
+        })
+    }
+
+    #[test]
+    fn test_client_request_roundtrip() {
+        let repr = client_request_repr();
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes));
+        assert_eq!(&bytes[..], &CLIENT_REQUEST_BYTES[..]);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Ok(repr));
+    }
+
+    #[test]
+    fn test_parse_server_response() {
+        let packet = Packet::new_checked(&SERVER_RESPONSE_BYTES[..]).unwrap();
+        let repr = Repr::parse(&packet).unwrap();
+        assert_eq!(repr.leap_indicator, LeapIndicator::NoWarning);
+        assert_eq!(repr.version, 4);
+        assert_eq!(repr.mode, Mode::Server);
+        assert_eq!(repr.stratum, 2);
+        assert_eq!(repr.poll, 6);
+        assert_eq!(repr.precision, -20);
+        assert_eq!(repr.root_delay, 0x123);
+        assert_eq!(repr.root_dispersion, 0x456);
+        assert_eq!(repr.ref_id, 0xc0a80101);
+        assert_eq!(repr.orig_timestamp, client_request_repr().xmit_timestamp,);
+        assert_eq!(
+            repr.xmit_timestamp,
+            Timestamp {
+                seconds: 0xe8fe6f80,
+                fraction: 0x80000000,
+            }
+        );
+
+        // 2023-11-14T22:13:20.5Z
+        assert_eq!(
+            repr.xmit_timestamp.to_instant(),
+            Instant::from_micros(1_700_000_000_500_000i64)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_from_instant() {
+        let instant = Instant::from_micros(1_700_000_000_500_000i64);
+        let timestamp = Timestamp::from_instant(instant);
+        assert_eq!(
+            timestamp,
+            Timestamp {
+                seconds: 0xe8fe6f80,
+                fraction: 0x80000000,
+            }
+        );
+        assert_eq!(timestamp.to_instant(), instant);
+    }
+
+    #[test]
+    fn test_parse_invalid_mode() {
+        let mut bytes = SERVER_RESPONSE_BYTES;
+        for mode in [0, 6, 7] {
+            bytes[0] = 0x20 | mode;
+            let packet = Packet::new_unchecked(&bytes[..]);
+            assert_eq!(Repr::parse(&packet), Err(Error));
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_version() {
+        let mut bytes = SERVER_RESPONSE_BYTES;
+        bytes[0] = 0x3c;
+        let packet = Packet::new_unchecked(&bytes[..]);
+        assert_eq!(Repr::parse(&packet), Err(Error));
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert_eq!(
+            Packet::new_checked(&SERVER_RESPONSE_BYTES[..HEADER_LEN - 1]),
+            Err(Error)
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Helpers for IPv6 Path MTU Discovery.
+//!
+//! A multicast source may receive a Packet Too Big message from several
+//! receivers, and must use the smallest MTU reported by any of them. See
+//! [RFC 8201].
+//!
+//! [RFC 8201]: https://www.rfc-editor.org/rfc/rfc8201
+
+use super::{Icmpv6Repr, IPV6_MIN_MTU};
+
+/// Return the smallest MTU reported by the Packet Too Big messages among
+/// `errors`, or `None` if there are none.
+///
+/// Other messages are ignored. An MTU below the IPv6 minimum link MTU is
+/// raised to it, since a path MTU is never reduced below 1280 octets.
+pub fn min_mtu_from_errors(errors: &[Icmpv6Repr]) -> Option<u32> {
+    errors
+        .iter()
+        .filter_map(|error| match error {
+            Icmpv6Repr::PktTooBig { mtu, .. } => Some((*mtu).max(IPV6_MIN_MTU as u32)),
+            _ => None,
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wire::{Icmpv6DstUnreachable, IpProtocol, Ipv6Address, Ipv6Repr};
+
+    const HEADER: Ipv6Repr = Ipv6Repr {
+        src_addr: Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+        dst_addr: Ipv6Address::new(0xff02, 0, 0, 0, 0, 0, 0, 0x101),
+        next_header: IpProtocol::Udp,
+        payload_len: 8,
+        hop_limit: 64,
+    };
+
+    fn too_big(mtu: u32) -> Icmpv6Repr<'static> {
+        Icmpv6Repr::PktTooBig {
+            mtu,
+            header: HEADER,
+            data: &[0; 8],
+        }
+    }
+
+    #[test]
+    fn test_min_mtu_from_errors() {
+        let unreachable = Icmpv6Repr::DstUnreachable {
+            reason: Icmpv6DstUnreachable::NoRoute,
+            header: HEADER,
+            data: &[0; 8],
+        };
+        let echo = Icmpv6Repr::EchoRequest {
+            ident: 1,
+            seq_no: 1,
+            data: &[],
+        };
+
+        assert_eq!(min_mtu_from_errors(&[]), None);
+        assert_eq!(min_mtu_from_errors(&[unreachable, echo]), None);
+        assert_eq!(
+            min_mtu_from_errors(&[
+                too_big(1500),
+                unreachable,
+                too_big(1400),
+                echo,
+                too_big(1480)
+            ]),
+            Some(1400)
+        );
+
+        // The path MTU is never below the IPv6 minimum.
+        assert_eq!(
+            min_mtu_from_errors(&[too_big(1500), too_big(576)]),
+            Some(1280)
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// See https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt for the
+// PROXY protocol specification.
+
+//! PROXY protocol version 2 headers.
+//!
+//! A proxy or load balancer may prepend a PROXY protocol header to the TCP
+//! stream it forwards, to convey the endpoints of the original connection.
+//! Version 2 headers are binary: a 12-octet signature, a version and command
+//! octet, an address family and transport protocol octet, and the length of
+//! the address block that follows, which [`parse_v2`] decodes.
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{Error, IpAddress, IpEndpoint, Result};
+
+/// The signature starting every version 2 header.
+pub const SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The length of the fixed part of a version 2 header.
+pub const HEADER_LEN: usize = 16;
+
+const VERSION: u8 = 0x2;
+const COMMAND_PROXY: u8 = 0x1;
+const TRANSPORT_STREAM: u8 = 0x1;
+
+/// The address family of the original connection.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProxyAddrFamily {
+    Inet,
+    Inet6,
+}
+
+impl ProxyAddrFamily {
+    /// Return the length of the address block of this family.
+    const fn addr_len(&self) -> usize {
+        match *self {
+            ProxyAddrFamily::Inet => 12,
+            ProxyAddrFamily::Inet6 => 36,
+        }
+    }
+}
+
+/// The endpoints of the original connection.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SocketAddrPair {
+    /// The endpoint of the client.
+    pub src: IpEndpoint,
+    /// The endpoint the client connected to.
+    pub dst: IpEndpoint,
+}
+
+/// Decode the version 2 header at the start of a TCP stream.
+///
+/// Returns the address family and endpoints of the original connection, and
+/// the length of the whole header, including any TLVs following the
+/// addresses; the stream data starts right after it.
+///
+/// Only PROXY commands for TCP over IPv4 or IPv6 are supported. Returns
+/// `Err(Error)` if `bytes` does not start with the signature, if the header
+/// is truncated, or if it is for another version, command, address family
+/// or transport protocol, such as the LOCAL command used by health checks.
+pub fn parse_v2(bytes: &[u8]) -> Result<(ProxyAddrFamily, SocketAddrPair, usize)> {
+    if bytes.len() < HEADER_LEN || bytes[..SIGNATURE.len()] != SIGNATURE {
+        return Err(Error);
+    }
+    if bytes[12] != (VERSION << 4 | COMMAND_PROXY) {
+        return Err(Error);
+    }
+
+    let family = match (bytes[13] >> 4, bytes[13] & 0xf) {
+        (0x1, TRANSPORT_STREAM) => ProxyAddrFamily::Inet,
+        (0x2, TRANSPORT_STREAM) => ProxyAddrFamily::Inet6,
+        _ => return Err(Error),
+    };
+
+    let len = NetworkEndian::read_u16(&bytes[14..HEADER_LEN]) as usize;
+    if len < family.addr_len() {
+        return Err(Error);
+    }
+    let addrs = bytes.get(HEADER_LEN..HEADER_LEN + len).ok_or(Error)?;
+
+    let (src_addr, dst_addr, ports) = match family {
+        #[cfg(feature = "proto-ipv4")]
+        ProxyAddrFamily::Inet => (
+            IpAddress::Ipv4(super::Ipv4Address::from_bytes(&addrs[0..4])),
+            IpAddress::Ipv4(super::Ipv4Address::from_bytes(&addrs[4..8])),
+            &addrs[8..12],
+        ),
+        #[cfg(feature = "proto-ipv6")]
+        ProxyAddrFamily::Inet6 => (
+            IpAddress::Ipv6(super::Ipv6Address::from_bytes(&addrs[0..16])),
+            IpAddress::Ipv6(super::Ipv6Address::from_bytes(&addrs[16..32])),
+            &addrs[32..36],
+        ),
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error),
+    };
+
+    let pair = SocketAddrPair {
+        src: IpEndpoint::new(src_addr, NetworkEndian::read_u16(&ports[0..2])),
+        dst: IpEndpoint::new(dst_addr, NetworkEndian::read_u16(&ports[2..4])),
+    };
+    Ok((family, pair, HEADER_LEN + len))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "proto-ipv4")]
+    fn test_parse_ipv4() {
+        use crate::wire::Ipv4Address;
+
+        #[rustfmt::skip]
+        let bytes = [
+            // Signature
+            0x0d, 0x0a, 0x0d, 0x0a, 0x00, 0x0d, 0x0a, 0x51, 0x55, 0x49, 0x54, 0x0a,
+            // Version 2, PROXY, TCP over IPv4, length 12
+            0x21, 0x11, 0x00, 0x0c,
+            // Source and destination addresses
+            0xc0, 0x00, 0x02, 0x01, 0x0a, 0x00, 0x00, 0x01,
+            // Source and destination ports
+            0xd4, 0x31, 0x00, 0x50,
+            // Stream data
+            0x47, 0x45, 0x54,
+        ];
+        assert_eq!(
+            parse_v2(&bytes),
+            Ok((
+                ProxyAddrFamily::Inet,
+                SocketAddrPair {
+                    src: IpEndpoint::new(Ipv4Address::new(192, 0, 2, 1).into(), 54321),
+                    dst: IpEndpoint::new(Ipv4Address::new(10, 0, 0, 1).into(), 80),
+                },
+                28
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv6")]
+    fn test_parse_ipv6() {
+        use crate::wire::Ipv6Address;
+
+        #[rustfmt::skip]
+        let bytes = [
+            // Signature
+            0x0d, 0x0a, 0x0d, 0x0a, 0x00, 0x0d, 0x0a, 0x51, 0x55, 0x49, 0x54, 0x0a,
+            // Version 2, PROXY, TCP over IPv6, length 36 plus a 4-octet TLV
+            0x21, 0x21, 0x00, 0x28,
+            // Source address
+            0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            // Destination address
+            0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+            // Source and destination ports
+            0xd4, 0x31, 0x01, 0xbb,
+            // NOOP TLV
+            0x04, 0x00, 0x01, 0x00,
+        ];
+        assert_eq!(
+            parse_v2(&bytes),
+            Ok((
+                ProxyAddrFamily::Inet6,
+                SocketAddrPair {
+                    src: IpEndpoint::new(
+                        Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).into(),
+                        54321
+                    ),
+                    dst: IpEndpoint::new(
+                        Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2).into(),
+                        443
+                    ),
+                },
+                56
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let mut bytes = [0u8; 28];
+        bytes[..12].copy_from_slice(&SIGNATURE);
+        bytes[12..16].copy_from_slice(&[0x21, 0x11, 0x00, 0x0c]);
+
+        // Truncated address block.
+        assert_eq!(parse_v2(&bytes[..27]), Err(Error));
+        // Bad signature.
+        let mut bad = bytes;
+        bad[7] = b'q';
+        assert_eq!(parse_v2(&bad), Err(Error));
+        // Version 1 is textual.
+        let mut bad = bytes;
+        bad[12] = 0x11;
+        assert_eq!(parse_v2(&bad), Err(Error));
+        // LOCAL command.
+        let mut bad = bytes;
+        bad[12] = 0x20;
+        assert_eq!(parse_v2(&bad), Err(Error));
+        // UDP over IPv4.
+        let mut bad = bytes;
+        bad[13] = 0x12;
+        assert_eq!(parse_v2(&bad), Err(Error));
+        // Address block too short for the family.
+        let mut bad = bytes;
+        bad[15] = 0x08;
+        assert_eq!(parse_v2(&bad), Err(Error));
+    }
+}
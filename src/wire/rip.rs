@@ -0,0 +1,399 @@
+// See https://tools.ietf.org/html/rfc2453 for the RIP version 2
+// specification.
+
+//! Routing Information Protocol version 2 messages.
+//!
+//! RIP messages are carried over UDP and made of a 4-octet header, holding
+//! the command and the version, followed by up to 25 route entries of 20
+//! octets each. Authentication entries, which reuse the route entry layout
+//! with a different meaning, are not supported.
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{Error, Ipv4Address, Result};
+
+pub const PORT: u16 = 520;
+
+/// The version of the protocol understood by this module.
+pub const VERSION: u8 = 2;
+
+/// The address family identifier of IPv4 route entries.
+pub const AFI_INET: u16 = 2;
+
+/// The metric of an unreachable destination.
+pub const METRIC_INFINITY: u32 = 16;
+
+/// The maximum number of route entries in a message.
+pub const MAX_ENTRIES: usize = 25;
+
+enum_with_unknown! {
+    /// RIP command.
+    pub enum Command(u8) {
+        Request  = 1,
+        Response = 2
+    }
+}
+
+mod field {
+    use crate::wire::field::*;
+
+    pub const COMMAND: usize = 0;
+    pub const VERSION: usize = 1;
+    pub const RESERVED: Field = 2..4;
+
+    pub const AFI: Field = 0..2;
+    pub const ROUTE_TAG: Field = 2..4;
+    pub const ADDR: Field = 4..8;
+    pub const NETMASK: Field = 8..12;
+    pub const NEXT_HOP: Field = 12..16;
+    pub const METRIC: Field = 16..20;
+}
+
+/// The length of a message header.
+pub const HEADER_LEN: usize = field::RESERVED.end;
+
+/// The length of a route entry.
+pub const ENTRY_LEN: usize = field::METRIC.end;
+
+/// A high-level representation of a route entry.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RouteEntry {
+    pub address_family: u16,
+    pub route_tag: u16,
+    pub addr: Ipv4Address,
+    pub netmask: Ipv4Address,
+    /// The gateway to use instead of the sender of the message, or the
+    /// unspecified address for the sender itself.
+    pub next_hop: Ipv4Address,
+    pub metric: u32,
+}
+
+impl RouteEntry {
+    /// Parse a route entry from the start of a sequence of octets.
+    ///
+    /// Returns `Err(Error)` if the buffer is too short for a route entry, or
+    /// if the metric is not between 1 and [`METRIC_INFINITY`].
+    pub fn parse(data: &[u8]) -> Result<RouteEntry> {
+        let data = data.get(..ENTRY_LEN).ok_or(Error)?;
+        let metric = NetworkEndian::read_u32(&data[field::METRIC]);
+        if !(1..=METRIC_INFINITY).contains(&metric) {
+            return Err(Error);
+        }
+        Ok(RouteEntry {
+            address_family: NetworkEndian::read_u16(&data[field::AFI]),
+            route_tag: NetworkEndian::read_u16(&data[field::ROUTE_TAG]),
+            addr: Ipv4Address::from_bytes(&data[field::ADDR]),
+            netmask: Ipv4Address::from_bytes(&data[field::NETMASK]),
+            next_hop: Ipv4Address::from_bytes(&data[field::NEXT_HOP]),
+            metric,
+        })
+    }
+
+    /// Emit the route entry into the start of a sequence of octets.
+    ///
+    /// # Panics
+    /// This function panics if the buffer is shorter than [`ENTRY_LEN`].
+    pub fn emit(&self, data: &mut [u8]) {
+        NetworkEndian::write_u16(&mut data[field::AFI], self.address_family);
+        NetworkEndian::write_u16(&mut data[field::ROUTE_TAG], self.route_tag);
+        data[field::ADDR].copy_from_slice(self.addr.as_bytes());
+        data[field::NETMASK].copy_from_slice(self.netmask.as_bytes());
+        data[field::NEXT_HOP].copy_from_slice(self.next_hop.as_bytes());
+        NetworkEndian::write_u32(&mut data[field::METRIC], self.metric);
+    }
+}
+
+/// An iterator over the route entries of a message.
+///
+/// An invalid or truncated route entry is returned as an error, after which
+/// the iteration stops.
+#[derive(Debug, Clone)]
+pub struct RouteEntryIterator<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RouteEntryIterator<'a> {
+    /// Create an iterator over a sequence of route entries.
+    pub const fn new(data: &'a [u8]) -> RouteEntryIterator<'a> {
+        RouteEntryIterator { data }
+    }
+}
+
+impl<'a> Iterator for RouteEntryIterator<'a> {
+    type Item = Result<RouteEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        match RouteEntry::parse(self.data) {
+            Ok(entry) => {
+                self.data = &self.data[ENTRY_LEN..];
+                Some(Ok(entry))
+            }
+            Err(e) => {
+                self.data = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A read/write wrapper around a RIP message buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+impl<T: AsRef<[u8]>> Packet<T> {
+    /// Imbue a raw octet buffer with RIP message structure.
+    pub const fn new_unchecked(buffer: T) -> Packet<T> {
+        Packet { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Packet<T>> {
+        let packet = Self::new_unchecked(buffer);
+        packet.check_len()?;
+        Ok(packet)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is too short for the header, or if
+    /// the route entries following it do not fill the buffer exactly.
+    pub fn check_len(&self) -> Result<()> {
+        let len = self.buffer.as_ref().len();
+        if len < HEADER_LEN || (len - HEADER_LEN) % ENTRY_LEN != 0 {
+            Err(Error)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Consume the packet, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the command field.
+    #[inline]
+    pub fn command(&self) -> Command {
+        let data = self.buffer.as_ref();
+        Command::from(data[field::COMMAND])
+    }
+
+    /// Return the version field.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::VERSION]
+    }
+
+    /// Return the number of route entries.
+    #[inline]
+    pub fn entry_count(&self) -> usize {
+        (self.buffer.as_ref().len() - HEADER_LEN) / ENTRY_LEN
+    }
+
+    /// Return an iterator over the route entries.
+    pub fn entries(&self) -> RouteEntryIterator<'_> {
+        RouteEntryIterator::new(&self.buffer.as_ref()[HEADER_LEN..])
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
+    /// Set the command field.
+    #[inline]
+    pub fn set_command(&mut self, value: Command) {
+        let data = self.buffer.as_mut();
+        data[field::COMMAND] = value.into();
+    }
+
+    /// Set the version field.
+    #[inline]
+    pub fn set_version(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::VERSION] = value;
+    }
+
+    /// Clear the reserved field.
+    #[inline]
+    pub fn clear_reserved(&mut self) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::RESERVED], 0);
+    }
+
+    /// Return a mutable pointer to the route entries.
+    #[inline]
+    pub fn entries_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer.as_mut()[HEADER_LEN..]
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Packet<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// A high-level representation of a RIP version 2 message.
+///
+/// Route entries are read with [`Packet::entries`] instead, so that no
+/// storage is needed to parse a message.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Repr<'a> {
+    pub command: Command,
+    pub entries: &'a [RouteEntry],
+}
+
+impl<'a> Repr<'a> {
+    /// Parse the header of a RIP message and return its command.
+    ///
+    /// Returns `Err(Error)` if the message is malformed, is not a version 2
+    /// message, or has an invalid route entry.
+    pub fn parse_command<T: AsRef<[u8]> + ?Sized>(packet: &Packet<&T>) -> Result<Command> {
+        packet.check_len()?;
+        if packet.version() != VERSION {
+            return Err(Error);
+        }
+        if let Command::Unknown(_) = packet.command() {
+            return Err(Error);
+        }
+        for entry in packet.entries() {
+            entry?;
+        }
+        Ok(packet.command())
+    }
+
+    /// Return the length of the message that will be emitted from this
+    /// high-level representation.
+    pub const fn buffer_len(&self) -> usize {
+        HEADER_LEN + self.entries.len() * ENTRY_LEN
+    }
+
+    /// Emit a high-level representation into a RIP message.
+    ///
+    /// # Panics
+    /// This function panics if the buffer is shorter than
+    /// [`buffer_len`](Self::buffer_len).
+    pub fn emit<T: AsRef<[u8]> + AsMut<[u8]>>(&self, packet: &mut Packet<T>) {
+        packet.set_command(self.command);
+        packet.set_version(VERSION);
+        packet.clear_reserved();
+        let data = packet.entries_mut();
+        for (entry, data) in self.entries.iter().zip(data.chunks_mut(ENTRY_LEN)) {
+            entry.emit(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[rustfmt::skip]
+    static RESPONSE_BYTES: [u8; 44] = [
+        // Response, version 2
+        0x02, 0x02, 0x00, 0x00,
+        // IPv4, route tag 0, 10.1.0.0/16 via the sender, metric 1
+        0x00, 0x02, 0x00, 0x00,
+        0x0a, 0x01, 0x00, 0x00,
+        0xff, 0xff, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x01,
+        // IPv4, route tag 7, 192.168.5.0/24 via 10.0.0.254, metric 3
+        0x00, 0x02, 0x00, 0x07,
+        0xc0, 0xa8, 0x05, 0x00,
+        0xff, 0xff, 0xff, 0x00,
+        0x0a, 0x00, 0x00, 0xfe,
+        0x00, 0x00, 0x00, 0x03,
+    ];
+
+    static ENTRIES: [RouteEntry; 2] = [
+        RouteEntry {
+            address_family: AFI_INET,
+            route_tag: 0,
+            addr: Ipv4Address([10, 1, 0, 0]),
+            netmask: Ipv4Address([255, 255, 0, 0]),
+            next_hop: Ipv4Address::UNSPECIFIED,
+            metric: 1,
+        },
+        RouteEntry {
+            address_family: AFI_INET,
+            route_tag: 7,
+            addr: Ipv4Address([192, 168, 5, 0]),
+            netmask: Ipv4Address([255, 255, 255, 0]),
+            next_hop: Ipv4Address([10, 0, 0, 254]),
+            metric: 3,
+        },
+    ];
+
+    #[test]
+    fn test_deconstruct() {
+        let packet = Packet::new_checked(&RESPONSE_BYTES[..]).unwrap();
+        assert_eq!(packet.command(), Command::Response);
+        assert_eq!(packet.version(), 2);
+        assert_eq!(packet.entry_count(), 2);
+        assert_eq!(Repr::parse_command(&packet), Ok(Command::Response));
+
+        let mut entries = packet.entries();
+        assert_eq!(entries.next(), Some(Ok(ENTRIES[0])));
+        assert_eq!(entries.next(), Some(Ok(ENTRIES[1])));
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn test_emit() {
+        let repr = Repr {
+            command: Command::Response,
+            entries: &ENTRIES,
+        };
+        assert_eq!(repr.buffer_len(), RESPONSE_BYTES.len());
+        let mut bytes = [0xa5; 44];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes[..]));
+        assert_eq!(bytes, RESPONSE_BYTES);
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        assert_eq!(
+            Packet::new_checked(&RESPONSE_BYTES[..43]).err(),
+            Some(Error)
+        );
+        assert_eq!(Packet::new_checked(&RESPONSE_BYTES[..3]).err(), Some(Error));
+
+        let packet = Packet::new_unchecked(&RESPONSE_BYTES[..40]);
+        let mut entries = packet.entries();
+        assert_eq!(entries.next(), Some(Ok(ENTRIES[0])));
+        assert_eq!(entries.next(), Some(Err(Error)));
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        // Metric out of range.
+        let mut bytes = RESPONSE_BYTES;
+        bytes[43] = 17;
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(packet.entries().nth(1), Some(Err(Error)));
+        assert_eq!(Repr::parse_command(&packet), Err(Error));
+
+        let mut bytes = RESPONSE_BYTES;
+        bytes[23] = 0;
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse_command(&packet), Err(Error));
+
+        // RIP version 1.
+        let mut bytes = RESPONSE_BYTES;
+        bytes[1] = 1;
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse_command(&packet), Err(Error));
+    }
+}
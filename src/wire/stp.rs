@@ -0,0 +1,648 @@
+//! Spanning Tree Protocol bridge protocol data units, as specified in
+//! IEEE 802.1D.
+//!
+//! BPDUs are carried in IEEE 802.3 frames with an LLC header, see
+//! [`LLC_HEADER`]. The [`Packet`] and [`Repr`] types in this module operate on
+//! the BPDU following that header.
+
+use byteorder::{ByteOrder, NetworkEndian};
+use core::fmt;
+
+use super::{Error, EthernetAddress, Result};
+use crate::time::Duration;
+
+/// The LLC header (DSAP, SSAP and control field) preceding every BPDU.
+pub const LLC_HEADER: [u8; 3] = [0x42, 0x42, 0x03];
+
+/// The destination address of BPDUs, the "Bridge Group Address".
+pub const BRIDGE_GROUP_ADDRESS: EthernetAddress =
+    EthernetAddress([0x01, 0x80, 0xc2, 0x00, 0x00, 0x00]);
+
+enum_with_unknown! {
+    /// Protocol version identifier.
+    pub enum Version(u8) {
+        /// Spanning Tree Protocol.
+        Stp  = 0,
+        /// Rapid Spanning Tree Protocol.
+        Rstp = 2
+    }
+}
+
+enum_with_unknown! {
+    /// BPDU type.
+    pub enum BpduType(u8) {
+        /// Configuration BPDU.
+        Config = 0x00,
+        /// Rapid Spanning Tree BPDU.
+        Rst    = 0x02,
+        /// Topology Change Notification BPDU.
+        Tcn    = 0x80
+    }
+}
+
+/// Flag bits of a Configuration BPDU.
+pub mod flags {
+    /// Topology Change.
+    pub const TOPOLOGY_CHANGE: u8 = 0x01;
+    /// Proposal, only used by RSTP.
+    pub const PROPOSAL: u8 = 0x02;
+    /// Learning, only used by RSTP.
+    pub const LEARNING: u8 = 0x10;
+    /// Forwarding, only used by RSTP.
+    pub const FORWARDING: u8 = 0x20;
+    /// Agreement, only used by RSTP.
+    pub const AGREEMENT: u8 = 0x40;
+    /// Topology Change Acknowledgment.
+    pub const TOPOLOGY_CHANGE_ACK: u8 = 0x80;
+}
+
+/// A bridge identifier, made of a priority and a MAC address.
+///
+/// Bridge identifiers are compared numerically, the lowest one being the
+/// best; the derived ordering matches that comparison.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BridgeId {
+    pub priority: u16,
+    pub addr: EthernetAddress,
+}
+
+impl BridgeId {
+    /// Construct a bridge identifier from a sequence of octets, in big-endian.
+    ///
+    /// # Panics
+    /// The function panics if `data` is not eight octets long.
+    pub fn from_bytes(data: &[u8]) -> BridgeId {
+        BridgeId {
+            priority: NetworkEndian::read_u16(&data[0..2]),
+            addr: EthernetAddress::from_bytes(&data[2..8]),
+        }
+    }
+
+    /// Write the bridge identifier into a sequence of octets, in big-endian.
+    ///
+    /// # Panics
+    /// The function panics if `data` is not eight octets long.
+    pub fn write_bytes(&self, data: &mut [u8]) {
+        NetworkEndian::write_u16(&mut data[0..2], self.priority);
+        data[2..8].copy_from_slice(self.addr.as_bytes());
+    }
+}
+
+impl fmt::Display for BridgeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}.{}", self.priority, self.addr)
+    }
+}
+
+/// A read/write wrapper around a bridge protocol data unit buffer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+mod field {
+    use crate::wire::field::*;
+
+    pub const PROTOCOL_ID: Field = 0..2;
+    pub const VERSION: usize = 2;
+    pub const BPDU_TYPE: usize = 3;
+    pub const FLAGS: usize = 4;
+    pub const ROOT_ID: Field = 5..13;
+    pub const ROOT_PATH_COST: Field = 13..17;
+    pub const BRIDGE_ID: Field = 17..25;
+    pub const PORT_ID: Field = 25..27;
+    pub const MESSAGE_AGE: Field = 27..29;
+    pub const MAX_AGE: Field = 29..31;
+    pub const HELLO_TIME: Field = 31..33;
+    pub const FORWARD_DELAY: Field = 33..35;
+    pub const VERSION_1_LEN: usize = 35;
+}
+
+/// Length of a Topology Change Notification BPDU.
+pub const TCN_LEN: usize = field::FLAGS;
+/// Length of a Configuration BPDU.
+pub const CONFIG_LEN: usize = field::FORWARD_DELAY.end;
+/// Length of a Rapid Spanning Tree BPDU.
+pub const RST_LEN: usize = field::VERSION_1_LEN + 1;
+
+/// Convert a timer value, in units of 1/256 of a second, into a duration.
+fn timer_to_duration(value: u16) -> Duration {
+    Duration::from_micros(value as u64 * 1_000_000 / 256)
+}
+
+/// Convert a duration into a timer value, in units of 1/256 of a second.
+fn duration_to_timer(duration: Duration) -> u16 {
+    (duration.total_micros() * 256 / 1_000_000).min(u16::MAX as u64) as u16
+}
+
+impl<T: AsRef<[u8]>> Packet<T> {
+    /// Imbue a raw octet buffer with BPDU structure.
+    pub const fn new_unchecked(buffer: T) -> Packet<T> {
+        Packet { buffer }
+    }
+
+    /// Shorthand for a combination of [new_unchecked] and [check_len].
+    ///
+    /// [new_unchecked]: #method.new_unchecked
+    /// [check_len]: #method.check_len
+    pub fn new_checked(buffer: T) -> Result<Packet<T>> {
+        let packet = Self::new_unchecked(buffer);
+        packet.check_len()?;
+        Ok(packet)
+    }
+
+    /// Ensure that no accessor method will panic if called.
+    /// Returns `Err(Error)` if the buffer is too short for the BPDU type.
+    ///
+    /// The result of this check is invalidated by calling [set_bpdu_type].
+    ///
+    /// [set_bpdu_type]: #method.set_bpdu_type
+    pub fn check_len(&self) -> Result<()> {
+        let len = self.buffer.as_ref().len();
+        if len < TCN_LEN {
+            return Err(Error);
+        }
+        let min_len = match self.bpdu_type() {
+            BpduType::Tcn => TCN_LEN,
+            BpduType::Config => CONFIG_LEN,
+            BpduType::Rst => RST_LEN,
+            BpduType::Unknown(_) => return Err(Error),
+        };
+        if len < min_len {
+            Err(Error)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Consume the packet, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+
+    /// Return the protocol identifier field.
+    #[inline]
+    pub fn protocol_id(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::PROTOCOL_ID])
+    }
+
+    /// Return the protocol version identifier field.
+    #[inline]
+    pub fn version(&self) -> Version {
+        let data = self.buffer.as_ref();
+        Version::from(data[field::VERSION])
+    }
+
+    /// Return the BPDU type field.
+    #[inline]
+    pub fn bpdu_type(&self) -> BpduType {
+        let data = self.buffer.as_ref();
+        BpduType::from(data[field::BPDU_TYPE])
+    }
+
+    /// Return the flags field.
+    #[inline]
+    pub fn flags(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::FLAGS]
+    }
+
+    /// Return the root identifier field.
+    #[inline]
+    pub fn root_id(&self) -> BridgeId {
+        let data = self.buffer.as_ref();
+        BridgeId::from_bytes(&data[field::ROOT_ID])
+    }
+
+    /// Return the root path cost field.
+    #[inline]
+    pub fn root_path_cost(&self) -> u32 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u32(&data[field::ROOT_PATH_COST])
+    }
+
+    /// Return the bridge identifier field.
+    #[inline]
+    pub fn bridge_id(&self) -> BridgeId {
+        let data = self.buffer.as_ref();
+        BridgeId::from_bytes(&data[field::BRIDGE_ID])
+    }
+
+    /// Return the port identifier field.
+    #[inline]
+    pub fn port_id(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::PORT_ID])
+    }
+
+    /// Return the message age field, in units of 1/256 of a second.
+    #[inline]
+    pub fn message_age(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::MESSAGE_AGE])
+    }
+
+    /// Return the max age field, in units of 1/256 of a second.
+    #[inline]
+    pub fn max_age(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::MAX_AGE])
+    }
+
+    /// Return the hello time field, in units of 1/256 of a second.
+    #[inline]
+    pub fn hello_time(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::HELLO_TIME])
+    }
+
+    /// Return the forward delay field, in units of 1/256 of a second.
+    #[inline]
+    pub fn forward_delay(&self) -> u16 {
+        let data = self.buffer.as_ref();
+        NetworkEndian::read_u16(&data[field::FORWARD_DELAY])
+    }
+
+    /// Return the version 1 length field.
+    #[inline]
+    pub fn version_1_len(&self) -> u8 {
+        let data = self.buffer.as_ref();
+        data[field::VERSION_1_LEN]
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Packet<T> {
+    /// Set the protocol identifier field.
+    #[inline]
+    pub fn set_protocol_id(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::PROTOCOL_ID], value)
+    }
+
+    /// Set the protocol version identifier field.
+    #[inline]
+    pub fn set_version(&mut self, value: Version) {
+        let data = self.buffer.as_mut();
+        data[field::VERSION] = value.into()
+    }
+
+    /// Set the BPDU type field.
+    #[inline]
+    pub fn set_bpdu_type(&mut self, value: BpduType) {
+        let data = self.buffer.as_mut();
+        data[field::BPDU_TYPE] = value.into()
+    }
+
+    /// Set the flags field.
+    #[inline]
+    pub fn set_flags(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::FLAGS] = value
+    }
+
+    /// Set the root identifier field.
+    #[inline]
+    pub fn set_root_id(&mut self, value: BridgeId) {
+        let data = self.buffer.as_mut();
+        value.write_bytes(&mut data[field::ROOT_ID])
+    }
+
+    /// Set the root path cost field.
+    #[inline]
+    pub fn set_root_path_cost(&mut self, value: u32) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u32(&mut data[field::ROOT_PATH_COST], value)
+    }
+
+    /// Set the bridge identifier field.
+    #[inline]
+    pub fn set_bridge_id(&mut self, value: BridgeId) {
+        let data = self.buffer.as_mut();
+        value.write_bytes(&mut data[field::BRIDGE_ID])
+    }
+
+    /// Set the port identifier field.
+    #[inline]
+    pub fn set_port_id(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::PORT_ID], value)
+    }
+
+    /// Set the message age field, in units of 1/256 of a second.
+    #[inline]
+    pub fn set_message_age(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::MESSAGE_AGE], value)
+    }
+
+    /// Set the max age field, in units of 1/256 of a second.
+    #[inline]
+    pub fn set_max_age(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::MAX_AGE], value)
+    }
+
+    /// Set the hello time field, in units of 1/256 of a second.
+    #[inline]
+    pub fn set_hello_time(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::HELLO_TIME], value)
+    }
+
+    /// Set the forward delay field, in units of 1/256 of a second.
+    #[inline]
+    pub fn set_forward_delay(&mut self, value: u16) {
+        let data = self.buffer.as_mut();
+        NetworkEndian::write_u16(&mut data[field::FORWARD_DELAY], value)
+    }
+
+    /// Set the version 1 length field.
+    #[inline]
+    pub fn set_version_1_len(&mut self, value: u8) {
+        let data = self.buffer.as_mut();
+        data[field::VERSION_1_LEN] = value
+    }
+}
+
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Packet<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.buffer.as_ref()
+    }
+}
+
+/// A high-level representation of a bridge protocol data unit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Repr {
+    /// A Configuration BPDU, or a Rapid Spanning Tree BPDU if the version is
+    /// [`Version::Rstp`].
+    Config {
+        version: Version,
+        flags: u8,
+        root_id: BridgeId,
+        root_path_cost: u32,
+        bridge_id: BridgeId,
+        port_id: u16,
+        message_age: Duration,
+        max_age: Duration,
+        hello_time: Duration,
+        forward_delay: Duration,
+    },
+    /// A Topology Change Notification BPDU.
+    TopologyChangeNotification,
+}
+
+impl Repr {
+    /// Parse a bridge protocol data unit and return a high-level representation.
+    ///
+    /// The protocol identifier must be zero and the version must be known.
+    /// Rapid Spanning Tree BPDUs are only accepted with the RSTP version.
+    pub fn parse<T: AsRef<[u8]> + ?Sized>(packet: &Packet<&T>) -> Result<Repr> {
+        packet.check_len()?;
+
+        if packet.protocol_id() != 0 {
+            return Err(Error);
+        }
+        let version = packet.version();
+        if let Version::Unknown(_) = version {
+            return Err(Error);
+        }
+
+        match packet.bpdu_type() {
+            BpduType::Tcn => Ok(Repr::TopologyChangeNotification),
+            BpduType::Rst if version != Version::Rstp => Err(Error),
+            BpduType::Config | BpduType::Rst => Ok(Repr::Config {
+                version,
+                flags: packet.flags(),
+                root_id: packet.root_id(),
+                root_path_cost: packet.root_path_cost(),
+                bridge_id: packet.bridge_id(),
+                port_id: packet.port_id(),
+                message_age: timer_to_duration(packet.message_age()),
+                max_age: timer_to_duration(packet.max_age()),
+                hello_time: timer_to_duration(packet.hello_time()),
+                forward_delay: timer_to_duration(packet.forward_delay()),
+            }),
+            BpduType::Unknown(_) => Err(Error),
+        }
+    }
+
+    /// Return the length of a BPDU that will be emitted from this high-level
+    /// representation.
+    pub const fn buffer_len(&self) -> usize {
+        match self {
+            Repr::Config {
+                version: Version::Rstp,
+                ..
+            } => RST_LEN,
+            Repr::Config { .. } => CONFIG_LEN,
+            Repr::TopologyChangeNotification => TCN_LEN,
+        }
+    }
+
+    /// Emit a high-level representation into a bridge protocol data unit.
+    ///
+    /// Timers are rounded down to a multiple of 1/256 of a second.
+    pub fn emit<T: AsRef<[u8]> + AsMut<[u8]>>(&self, packet: &mut Packet<T>) {
+        packet.set_protocol_id(0);
+        match *self {
+            Repr::Config {
+                version,
+                flags,
+                root_id,
+                root_path_cost,
+                bridge_id,
+                port_id,
+                message_age,
+                max_age,
+                hello_time,
+                forward_delay,
+            } => {
+                packet.set_version(version);
+                packet.set_bpdu_type(if version == Version::Rstp {
+                    BpduType::Rst
+                } else {
+                    BpduType::Config
+                });
+                packet.set_flags(flags);
+                packet.set_root_id(root_id);
+                packet.set_root_path_cost(root_path_cost);
+                packet.set_bridge_id(bridge_id);
+                packet.set_port_id(port_id);
+                packet.set_message_age(duration_to_timer(message_age));
+                packet.set_max_age(duration_to_timer(max_age));
+                packet.set_hello_time(duration_to_timer(hello_time));
+                packet.set_forward_delay(duration_to_timer(forward_delay));
+                if version == Version::Rstp {
+                    packet.set_version_1_len(0);
+                }
+            }
+            Repr::TopologyChangeNotification => {
+                packet.set_version(Version::Stp);
+                packet.set_bpdu_type(BpduType::Tcn);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Repr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Repr::Config {
+                version,
+                root_id,
+                root_path_cost,
+                bridge_id,
+                port_id,
+                ..
+            } => write!(
+                f,
+                "BPDU ({version:?}) root={root_id} cost={root_path_cost} bridge={bridge_id} port={port_id:04x}"
+            ),
+            Repr::TopologyChangeNotification => write!(f, "BPDU TCN"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static CONFIG_BPDU_BYTES: [u8; 35] = [
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x80, 0x00, 0x00, 0x1c, 0x0e, 0x87, 0x78, 0x00, 0x00, 0x00,
+        0x00, 0x04, 0x80, 0x00, 0x00, 0x1c, 0x0e, 0x87, 0x85, 0x00, 0x80, 0x04, 0x01, 0x00, 0x14,
+        0x00, 0x02, 0x00, 0x0f, 0x00,
+    ];
+
+    static TCN_BPDU_BYTES: [u8; 4] = [0x00, 0x00, 0x00, 0x80];
+
+    fn config_repr() -> Repr {
+        Repr::Config {
+            version: Version::Stp,
+            flags: flags::TOPOLOGY_CHANGE,
+            root_id: BridgeId {
+                priority: 0x8000,
+                addr: EthernetAddress([0x00, 0x1c, 0x0e, 0x87, 0x78, 0x00]),
+            },
+            root_path_cost: 4,
+            bridge_id: BridgeId {
+                priority: 0x8000,
+                addr: EthernetAddress([0x00, 0x1c, 0x0e, 0x87, 0x85, 0x00]),
+            },
+            port_id: 0x8004,
+            message_age: Duration::from_secs(1),
+            max_age: Duration::from_secs(20),
+            hello_time: Duration::from_secs(2),
+            forward_delay: Duration::from_secs(15),
+        }
+    }
+
+    #[test]
+    fn test_deconstruct_config() {
+        let packet = Packet::new_checked(&CONFIG_BPDU_BYTES[..]).unwrap();
+        assert_eq!(packet.protocol_id(), 0);
+        assert_eq!(packet.version(), Version::Stp);
+        assert_eq!(packet.bpdu_type(), BpduType::Config);
+        assert_eq!(packet.flags(), flags::TOPOLOGY_CHANGE);
+        assert_eq!(packet.root_path_cost(), 4);
+        assert_eq!(packet.port_id(), 0x8004);
+        assert_eq!(packet.message_age(), 256);
+        assert_eq!(packet.max_age(), 20 * 256);
+        assert_eq!(packet.hello_time(), 2 * 256);
+        assert_eq!(packet.forward_delay(), 15 * 256);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let packet = Packet::new_checked(&CONFIG_BPDU_BYTES[..]).unwrap();
+        let repr = Repr::parse(&packet).unwrap();
+        assert_eq!(repr, config_repr());
+
+        if let Repr::Config {
+            root_id,
+            root_path_cost,
+            message_age,
+            max_age,
+            hello_time,
+            forward_delay,
+            ..
+        } = repr
+        {
+            assert_eq!(root_id.priority, 0x8000);
+            assert_eq!(
+                root_id.addr,
+                EthernetAddress([0x00, 0x1c, 0x0e, 0x87, 0x78, 0x00])
+            );
+            assert_eq!(root_path_cost, 4);
+            assert_eq!(message_age, Duration::from_secs(1));
+            assert_eq!(max_age, Duration::from_secs(20));
+            assert_eq!(hello_time, Duration::from_secs(2));
+            assert_eq!(forward_delay, Duration::from_secs(15));
+        }
+    }
+
+    #[test]
+    fn test_emit_config() {
+        let repr = config_repr();
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        repr.emit(&mut Packet::new_unchecked(&mut bytes));
+        assert_eq!(&bytes[..], &CONFIG_BPDU_BYTES[..]);
+    }
+
+    #[test]
+    fn test_rst_roundtrip() {
+        let mut repr = config_repr();
+        if let Repr::Config {
+            ref mut version, ..
+        } = repr
+        {
+            *version = Version::Rstp;
+        }
+
+        let mut bytes = vec![0xa5; repr.buffer_len()];
+        assert_eq!(bytes.len(), RST_LEN);
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(&mut packet);
+        assert_eq!(packet.bpdu_type(), BpduType::Rst);
+        assert_eq!(packet.version_1_len(), 0);
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Ok(repr));
+    }
+
+    #[test]
+    fn test_tcn() {
+        let packet = Packet::new_checked(&TCN_BPDU_BYTES[..]).unwrap();
+        assert_eq!(Repr::parse(&packet), Ok(Repr::TopologyChangeNotification));
+
+        let mut bytes = [0xa5; TCN_LEN];
+        Repr::TopologyChangeNotification.emit(&mut Packet::new_unchecked(&mut bytes));
+        assert_eq!(bytes, TCN_BPDU_BYTES);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        // Truncated.
+        let packet = Packet::new_unchecked(&CONFIG_BPDU_BYTES[..34]);
+        assert_eq!(Repr::parse(&packet), Err(Error));
+
+        // Wrong protocol identifier.
+        let mut bytes = CONFIG_BPDU_BYTES;
+        bytes[1] = 0x01;
+        assert_eq!(Repr::parse(&Packet::new_unchecked(&bytes[..])), Err(Error));
+
+        // Unknown version.
+        let mut bytes = CONFIG_BPDU_BYTES;
+        bytes[2] = 0x01;
+        assert_eq!(Repr::parse(&Packet::new_unchecked(&bytes[..])), Err(Error));
+
+        // RST BPDU with the STP version.
+        let mut bytes = [0; RST_LEN];
+        bytes[..CONFIG_LEN].copy_from_slice(&CONFIG_BPDU_BYTES);
+        bytes[3] = 0x02;
+        assert_eq!(Repr::parse(&Packet::new_unchecked(&bytes[..])), Err(Error));
+    }
+}
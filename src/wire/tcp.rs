@@ -134,10 +134,21 @@ mod field {
     pub const OPT_WS: u8 = 0x03;
     pub const OPT_SACKPERM: u8 = 0x04;
     pub const OPT_SACKRNG: u8 = 0x05;
+    pub const OPT_AO: u8 = 0x1d;
+    pub const OPT_ACCECN0: u8 = 0xac;
+    pub const OPT_ACCECN1: u8 = 0xae;
 }
 
 pub const HEADER_LEN: usize = field::URGENT.end;
 
+/// The length of the MAC of a TCP Authentication Option computed by the
+/// algorithms of RFC 5926.
+pub const AO_MAC_LEN: usize = 12;
+
+/// The length of a TCP Authentication Option with a MAC of [`AO_MAC_LEN`]
+/// octets.
+pub const AO_OPTION_LEN: usize = 4 + AO_MAC_LEN;
+
 impl<T: AsRef<[u8]>> Packet<T> {
     /// Imbue a raw octet buffer with TCP packet structure.
     pub const fn new_unchecked(buffer: T) -> Packet<T> {
@@ -626,7 +637,18 @@ pub enum TcpOption<'a> {
     WindowScale(u8),
     SackPermitted,
     SackRange([Option<(u32, u32)>; 3]),
-    Unknown { kind: u8, data: &'a [u8] },
+    /// The TCP Authentication Option, see RFC 5925.
+    Authentication {
+        key_id: u8,
+        rnext_key_id: u8,
+        mac: &'a [u8],
+    },
+    /// The Accurate ECN option, see draft-ietf-tcpm-accurate-ecn.
+    AccEcn(AccEcn),
+    Unknown {
+        kind: u8,
+        data: &'a [u8],
+    },
 }
 
 impl<'a> TcpOption<'a> {
@@ -689,6 +711,26 @@ impl<'a> TcpOption<'a> {
                         });
                         option = TcpOption::SackRange(sack_ranges);
                     }
+                    (field::OPT_AO, n) if n >= 4 => {
+                        option = TcpOption::Authentication {
+                            key_id: data[0],
+                            rnext_key_id: data[1],
+                            mac: &data[2..],
+                        }
+                    }
+                    (field::OPT_AO, _) => option = TcpOption::Unknown { kind, data },
+                    (field::OPT_ACCECN0 | field::OPT_ACCECN1, 2 | 5 | 8 | 11) => {
+                        let order = if kind == field::OPT_ACCECN0 {
+                            AccEcnOrder::Order0
+                        } else {
+                            AccEcnOrder::Order1
+                        };
+                        let mut counters = [None; 3];
+                        for (counter, field) in counters.iter_mut().zip(data.chunks_exact(3)) {
+                            *counter = Some(NetworkEndian::read_u24(field));
+                        }
+                        option = TcpOption::AccEcn(AccEcn::from_counters(order, counters));
+                    }
                     (..) => option = TcpOption::Unknown { kind, data },
                 }
             }
@@ -704,6 +746,8 @@ impl<'a> TcpOption<'a> {
             TcpOption::WindowScale(_) => 3,
             TcpOption::SackPermitted => 2,
             TcpOption::SackRange(s) => s.iter().filter(|s| s.is_some()).count() * 8 + 2,
+            TcpOption::Authentication { mac, .. } => 4 + mac.len(),
+            TcpOption::AccEcn(accecn) => 2 + accecn.field_count() * 3,
             TcpOption::Unknown { data, .. } => 2 + data.len(),
         }
     }
@@ -751,6 +795,27 @@ impl<'a> TcpOption<'a> {
                                 NetworkEndian::write_u32(&mut buffer[pos + 4..], second);
                             });
                     }
+                    &TcpOption::Authentication {
+                        key_id,
+                        rnext_key_id,
+                        mac,
+                    } => {
+                        buffer[0] = field::OPT_AO;
+                        buffer[2] = key_id;
+                        buffer[3] = rnext_key_id;
+                        buffer[4..length].copy_from_slice(mac);
+                    }
+                    &TcpOption::AccEcn(accecn) => {
+                        buffer[0] = match accecn.order {
+                            AccEcnOrder::Order0 => field::OPT_ACCECN0,
+                            AccEcnOrder::Order1 => field::OPT_ACCECN1,
+                        };
+                        let fields = buffer[2..length].chunks_exact_mut(3);
+                        for (field, counter) in fields.zip(accecn.counters()) {
+                            // Only the leading counters which are present are emitted.
+                            NetworkEndian::write_u24(field, counter.unwrap() & ACCECN_COUNTER_MASK);
+                        }
+                    }
                     &TcpOption::Unknown {
                         kind,
                         data: provided,
@@ -795,6 +860,82 @@ impl Control {
     }
 }
 
+/// The TCP Authentication Option of a segment, see RFC 5925.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Authentication {
+    /// The identifier of the key the segment is authenticated with.
+    pub key_id: u8,
+    /// The identifier of the key the sender wants to receive segments with.
+    pub rnext_key_id: u8,
+    pub mac: [u8; AO_MAC_LEN],
+}
+
+/// The mask of the 24 bits kept of each Accurate ECN byte counter.
+pub const ACCECN_COUNTER_MASK: u32 = 0x00ff_ffff;
+
+/// The order of the counters in an Accurate ECN option.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccEcnOrder {
+    /// The AccECN0 option, carrying the ECT(0), CE and ECT(1) counters.
+    Order0,
+    /// The AccECN1 option, carrying the ECT(1), CE and ECT(0) counters.
+    Order1,
+}
+
+/// The Accurate ECN option of a segment, see draft-ietf-tcpm-accurate-ecn.
+///
+/// Each counter holds the 24 least significant bits of the number of payload
+/// bytes received with the corresponding ECN codepoint. The option may be
+/// truncated to carry only its leading counters, in the order given by
+/// [`AccEcnOrder`]; a counter following a missing one is not emitted.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccEcn {
+    pub order: AccEcnOrder,
+    /// The number of bytes received marked ECT(0).
+    pub ect0: Option<u32>,
+    /// The number of bytes received marked CE.
+    pub ce: Option<u32>,
+    /// The number of bytes received marked ECT(1).
+    pub ect1: Option<u32>,
+}
+
+impl AccEcn {
+    fn from_counters(order: AccEcnOrder, counters: [Option<u32>; 3]) -> AccEcn {
+        let [first, ce, last] = counters;
+        let (ect0, ect1) = match order {
+            AccEcnOrder::Order0 => (first, last),
+            AccEcnOrder::Order1 => (last, first),
+        };
+        AccEcn {
+            order,
+            ect0,
+            ce,
+            ect1,
+        }
+    }
+
+    /// Return the counters in the order they appear in the option.
+    const fn counters(&self) -> [Option<u32>; 3] {
+        match self.order {
+            AccEcnOrder::Order0 => [self.ect0, self.ce, self.ect1],
+            AccEcnOrder::Order1 => [self.ect1, self.ce, self.ect0],
+        }
+    }
+
+    /// Return the number of counters carried by the option.
+    fn field_count(&self) -> usize {
+        self.counters().iter().take_while(|c| c.is_some()).count()
+    }
+
+    /// Return the length of the option.
+    pub fn buffer_len(&self) -> usize {
+        TcpOption::AccEcn(*self).buffer_len()
+    }
+}
+
 /// A high-level representation of a Transmission Control Protocol packet.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Repr<'a> {
@@ -808,6 +949,12 @@ pub struct Repr<'a> {
     pub max_seg_size: Option<u16>,
     pub sack_permitted: bool,
     pub sack_ranges: [Option<(u32, u32)>; 3],
+    /// The TCP Authentication Option. Options with a MAC of a length other
+    /// than [`AO_MAC_LEN`] are ignored when parsing, so that sockets using
+    /// the option drop such segments as unauthenticated.
+    pub authentication: Option<Authentication>,
+    /// The Accurate ECN option.
+    pub accecn: Option<AccEcn>,
     pub payload: &'a [u8],
 }
 
@@ -858,6 +1005,8 @@ impl<'a> Repr<'a> {
         let mut options = packet.options();
         let mut sack_permitted = false;
         let mut sack_ranges = [None, None, None];
+        let mut authentication = None;
+        let mut accecn = None;
         while !options.is_empty() {
             let (next_options, option) = TcpOption::parse(options)?;
             match option {
@@ -884,6 +1033,18 @@ impl<'a> Repr<'a> {
                 }
                 TcpOption::SackPermitted => sack_permitted = true,
                 TcpOption::SackRange(slice) => sack_ranges = slice,
+                TcpOption::Authentication {
+                    key_id,
+                    rnext_key_id,
+                    mac,
+                } => {
+                    authentication = mac.try_into().ok().map(|mac| Authentication {
+                        key_id,
+                        rnext_key_id,
+                        mac,
+                    })
+                }
+                TcpOption::AccEcn(value) => accecn = Some(value),
                 _ => (),
             }
             options = next_options;
@@ -900,6 +1061,8 @@ impl<'a> Repr<'a> {
             max_seg_size,
             sack_permitted,
             sack_ranges,
+            authentication,
+            accecn,
             payload: packet.payload(),
         })
     }
@@ -928,6 +1091,12 @@ impl<'a> Repr<'a> {
         if sack_range_len > 0 {
             length += sack_range_len + 2;
         }
+        if self.authentication.is_some() {
+            length += AO_OPTION_LEN;
+        }
+        if let Some(accecn) = self.accecn {
+            length += accecn.buffer_len();
+        }
         if length % 4 != 0 {
             length += 4 - length % 4;
         }
@@ -983,6 +1152,19 @@ impl<'a> Repr<'a> {
                 let tmp = options;
                 options = TcpOption::SackRange(self.sack_ranges).emit(tmp);
             }
+            if let Some(auth) = self.authentication {
+                let tmp = options;
+                options = TcpOption::Authentication {
+                    key_id: auth.key_id,
+                    rnext_key_id: auth.rnext_key_id,
+                    mac: &auth.mac,
+                }
+                .emit(tmp);
+            }
+            if let Some(accecn) = self.accecn {
+                let tmp = options;
+                options = TcpOption::AccEcn(accecn).emit(tmp);
+            }
 
             if !options.is_empty() {
                 TcpOption::EndOfList.emit(options);
@@ -1066,6 +1248,8 @@ impl<'a, T: AsRef<[u8]> + ?Sized> fmt::Display for Packet<&'a T> {
                 // conveniently
                 // includes the
                 // []s
+                TcpOption::Authentication { key_id, .. } => write!(f, " ao={key_id}")?,
+                TcpOption::AccEcn(_) => write!(f, " accecn")?,
                 TcpOption::Unknown { kind, .. } => write!(f, " opt({kind})")?,
             }
             options = next_options;
@@ -1241,6 +1425,8 @@ mod test {
             max_seg_size: None,
             sack_permitted: false,
             sack_ranges: [None, None, None],
+            authentication: None,
+            accecn: None,
             payload: &PAYLOAD_BYTES,
         }
     }
@@ -1327,6 +1513,14 @@ mod test {
                 data: &[1, 2, 3][..]
             },
             &[0x0c, 0x05, 0x01, 0x02, 0x03]
+        );
+        assert_option_parses!(
+            TcpOption::Authentication {
+                key_id: 3,
+                rnext_key_id: 4,
+                mac: &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12][..]
+            },
+            &[0x1d, 0x10, 0x03, 0x04, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]
         )
     }
 
@@ -1339,4 +1533,206 @@ mod test {
         assert_eq!(TcpOption::parse(&[0x2, 0x02]), Err(Error));
         assert_eq!(TcpOption::parse(&[0x3, 0x02]), Err(Error));
     }
+
+    #[test]
+    fn test_short_authentication_option() {
+        // Without room for the key IDs, the option is left uninterpreted.
+        assert_eq!(
+            TcpOption::parse(&[0x1d, 0x03, 0x01]),
+            Ok((
+                &[][..],
+                TcpOption::Unknown {
+                    kind: 0x1d,
+                    data: &[0x01][..]
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_accecn_options() {
+        // The short forms carry only the leading counters.
+        assert_option_parses!(
+            TcpOption::AccEcn(AccEcn {
+                order: AccEcnOrder::Order0,
+                ect0: None,
+                ce: None,
+                ect1: None,
+            }),
+            &[0xac, 0x02]
+        );
+        assert_option_parses!(
+            TcpOption::AccEcn(AccEcn {
+                order: AccEcnOrder::Order0,
+                ect0: Some(0x010203),
+                ce: None,
+                ect1: None,
+            }),
+            &[0xac, 0x05, 0x01, 0x02, 0x03]
+        );
+        assert_option_parses!(
+            TcpOption::AccEcn(AccEcn {
+                order: AccEcnOrder::Order1,
+                ect0: None,
+                ce: Some(0x040506),
+                ect1: Some(0x010203),
+            }),
+            &[0xae, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06]
+        );
+        // The long forms carry all three counters.
+        assert_option_parses!(
+            TcpOption::AccEcn(AccEcn {
+                order: AccEcnOrder::Order0,
+                ect0: Some(0x010203),
+                ce: Some(0x040506),
+                ect1: Some(0x070809),
+            }),
+            &[0xac, 0x0b, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]
+        );
+        assert_option_parses!(
+            TcpOption::AccEcn(AccEcn {
+                order: AccEcnOrder::Order1,
+                ect0: Some(0x070809),
+                ce: Some(0x040506),
+                ect1: Some(0x010203),
+            }),
+            &[0xae, 0x0b, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]
+        );
+
+        // Counters are not split across fields; such options are left unparsed.
+        assert_eq!(
+            TcpOption::parse(&[0xac, 0x03, 0x01]),
+            Ok((
+                &[][..],
+                TcpOption::Unknown {
+                    kind: 0xac,
+                    data: &[0x01][..]
+                }
+            ))
+        );
+        assert_eq!(
+            TcpOption::parse(&[0xae, 0x0c, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Ok((
+                &[][..],
+                TcpOption::Unknown {
+                    kind: 0xae,
+                    data: &[0; 10][..]
+                }
+            ))
+        );
+        // Counters following a missing one are not emitted.
+        let option = TcpOption::AccEcn(AccEcn {
+            order: AccEcnOrder::Order0,
+            ect0: Some(1),
+            ce: None,
+            ect1: Some(3),
+        });
+        assert_eq!(option.buffer_len(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv4")]
+    fn test_accecn() {
+        let mut repr = packet_repr();
+        repr.accecn = Some(AccEcn {
+            order: AccEcnOrder::Order1,
+            ect0: Some(0x000100),
+            ce: Some(0xabcdef),
+            ect1: Some(0x1234_5678),
+        });
+        assert_eq!(repr.header_len(), 32);
+
+        let mut bytes = vec![0; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(
+            &mut packet,
+            &SRC_ADDR.into(),
+            &DST_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        );
+
+        // Counters are truncated to 24 bits.
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(
+            packet.options(),
+            &[0xae, 0x0b, 0x34, 0x56, 0x78, 0xab, 0xcd, 0xef, 0x00, 0x01, 0x00, 0x00]
+        );
+        let parsed = Repr::parse(
+            &packet,
+            &SRC_ADDR.into(),
+            &DST_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        )
+        .unwrap();
+        let accecn = parsed.accecn.unwrap();
+        assert_eq!(accecn.ect0, Some(0x000100));
+        assert_eq!(accecn.ce, Some(0xabcdef));
+        assert_eq!(accecn.ect1, Some(0x345678));
+
+        // With a bad length, the option is ignored, not the segment.
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        packet.options_mut()[1] = 0x0a;
+        packet.options_mut()[10..].copy_from_slice(&[0x01, 0x00]);
+        let packet = Packet::new_unchecked(&bytes[..]);
+        let parsed = Repr::parse(
+            &packet,
+            &SRC_ADDR.into(),
+            &DST_ADDR.into(),
+            &ChecksumCapabilities::ignored(),
+        )
+        .unwrap();
+        assert_eq!(parsed.accecn, None);
+        assert_eq!(parsed.seq_number, repr.seq_number);
+    }
+
+    #[test]
+    #[cfg(feature = "proto-ipv4")]
+    fn test_authentication() {
+        let mut repr = packet_repr();
+        repr.authentication = Some(Authentication {
+            key_id: 1,
+            rnext_key_id: 2,
+            mac: [0xa5; AO_MAC_LEN],
+        });
+        assert_eq!(repr.header_len(), 36);
+
+        let mut bytes = vec![0; repr.buffer_len()];
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        repr.emit(
+            &mut packet,
+            &SRC_ADDR.into(),
+            &DST_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        );
+
+        let packet = Packet::new_checked(&bytes[..]).unwrap();
+        assert_eq!(&packet.options()[..4], &[0x1d, 0x10, 0x01, 0x02]);
+        let parsed = Repr::parse(
+            &packet,
+            &SRC_ADDR.into(),
+            &DST_ADDR.into(),
+            &ChecksumCapabilities::default(),
+        )
+        .unwrap();
+        assert_eq!(parsed, repr);
+
+        // Only MACs of the length used by this implementation are decoded,
+        // others are ignored.
+        let mut bytes = bytes.clone();
+        let mut packet = Packet::new_unchecked(&mut bytes);
+        packet.options_mut()[1] = 0x0c;
+        packet.options_mut()[12..16].fill(0x01);
+        assert_eq!(
+            Repr::parse(
+                &Packet::new_unchecked(&*packet.into_inner()),
+                &SRC_ADDR.into(),
+                &DST_ADDR.into(),
+                &ChecksumCapabilities::ignored(),
+            ),
+            Ok(Repr {
+                authentication: None,
+                ..repr
+            })
+        );
+    }
 }
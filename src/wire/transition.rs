@@ -0,0 +1,99 @@
+//! Helpers for IPv6 transition mechanisms that carry IPv6 over IPv4.
+//!
+//! Only [6to4] is covered: IPv6 packets are sent as the payload of IPv4
+//! datagrams with protocol number 41, between hosts whose `2002::/16`
+//! addresses embed their public IPv4 address.
+//!
+//! [6to4]: https://www.rfc-editor.org/rfc/rfc3056
+
+use super::{Error, IpProtocol, Ipv4Packet, Ipv6Packet, Result};
+
+/// The IPv4 protocol number of an encapsulated IPv6 packet.
+pub const PROTOCOL: IpProtocol = IpProtocol::Unknown(41);
+
+/// Return the IPv6 packet encapsulated in a 6to4 IPv4 datagram.
+///
+/// `ipv4_packet` is the whole IPv4 datagram, header included, so that the
+/// protocol number can be checked. Fragments are rejected, since the inner
+/// packet cannot be parsed before reassembly.
+pub fn sixto4_inner(ipv4_packet: &[u8]) -> Result<Ipv6Packet<&[u8]>> {
+    let outer = Ipv4Packet::new_checked(ipv4_packet)?;
+    if outer.version() != 4 || outer.next_header() != PROTOCOL {
+        return Err(Error);
+    }
+    if outer.more_frags() || outer.frag_offset() != 0 {
+        return Err(Error);
+    }
+
+    let inner = Ipv6Packet::new_checked(outer.payload())?;
+    if inner.version() != 6 {
+        return Err(Error);
+    }
+    Ok(inner)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::wire::{Ipv4Address, Ipv6Address};
+
+    static PACKET_BYTES: [u8; 64] = [
+        0x45, 0x00, 0x00, 0x40, 0x00, 0x00, 0x40, 0x00, 0x40, 0x29, 0x00, 0x00, 0xc0, 0x00, 0x02,
+        0x01, 0xc6, 0x33, 0x64, 0x01, 0x60, 0x00, 0x00, 0x00, 0x00, 0x04, 0x3b, 0x40, 0x20, 0x02,
+        0xc0, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x20,
+        0x02, 0xc6, 0x33, 0x64, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_sixto4_inner() {
+        let inner = sixto4_inner(&PACKET_BYTES[..]).unwrap();
+        assert_eq!(inner.next_header(), IpProtocol::Ipv6NoNxt);
+        assert_eq!(
+            inner.src_addr(),
+            Ipv6Address::new(0x2002, 0xc000, 0x0201, 0, 0, 0, 0, 1)
+        );
+        assert_eq!(
+            inner.dst_addr(),
+            Ipv6Address::new(0x2002, 0xc633, 0x6401, 0, 0, 0, 0, 1)
+        );
+        assert_eq!(inner.payload(), &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            inner.src_addr().sixto4_embedded_v4(),
+            Some(Ipv4Address::new(192, 0, 2, 1))
+        );
+    }
+
+    #[test]
+    fn test_sixto4_inner_wrong_protocol() {
+        let mut bytes = PACKET_BYTES;
+        bytes[9] = 0x11;
+        assert_eq!(sixto4_inner(&bytes[..]), Err(Error));
+    }
+
+    #[test]
+    fn test_sixto4_inner_fragment() {
+        let mut bytes = PACKET_BYTES;
+        bytes[6] = 0x20;
+        assert_eq!(sixto4_inner(&bytes[..]), Err(Error));
+    }
+
+    #[test]
+    fn test_sixto4_inner_truncated() {
+        let mut bytes = PACKET_BYTES;
+        bytes[3] = 0x30;
+        assert_eq!(sixto4_inner(&bytes[..48]), Err(Error));
+    }
+
+    #[test]
+    fn test_sixto4_embedded_v4() {
+        assert_eq!(
+            Ipv6Address::new(0x2002, 0xc000, 0x0201, 0, 0, 0, 0, 0).sixto4_embedded_v4(),
+            Some(Ipv4Address::new(192, 0, 2, 1))
+        );
+        assert_eq!(
+            Ipv6Address::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).sixto4_embedded_v4(),
+            None
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Classification of WireGuard messages carried in UDP datagrams.
+//!
+//! This is not an implementation of WireGuard; it only recognizes the framing
+//! of its messages, so that datagrams can be steered without being parsed.
+//! See the [WireGuard whitepaper], section 5.4.
+//!
+//! [WireGuard whitepaper]: https://www.wireguard.com/papers/wireguard.pdf
+
+/// The type of a WireGuard message.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MessageType {
+    HandshakeInitiation,
+    HandshakeResponse,
+    CookieReply,
+    TransportData,
+}
+
+impl MessageType {
+    /// Return the value of the type field of this message type.
+    pub const fn id(&self) -> u8 {
+        match *self {
+            MessageType::HandshakeInitiation => 1,
+            MessageType::HandshakeResponse => 2,
+            MessageType::CookieReply => 3,
+            MessageType::TransportData => 4,
+        }
+    }
+
+    /// Return whether a message of this type has a length of `len` octets.
+    ///
+    /// Handshake and cookie messages have a fixed size; transport data
+    /// messages carry at least a header and an authentication tag.
+    const fn valid_len(&self, len: usize) -> bool {
+        match *self {
+            MessageType::HandshakeInitiation => len == 148,
+            MessageType::HandshakeResponse => len == 92,
+            MessageType::CookieReply => len == 64,
+            MessageType::TransportData => len >= 32,
+        }
+    }
+}
+
+/// Classify a UDP payload as a WireGuard message.
+///
+/// The first octet must be a known message type, the following three
+/// reserved octets must be zero, and the payload must have a length that
+/// is valid for that type. `None` is returned for anything else.
+pub fn message_type(payload: &[u8]) -> Option<MessageType> {
+    if payload.len() < 4 || payload[1..4] != [0, 0, 0] {
+        return None;
+    }
+
+    let message_type = match payload[0] {
+        1 => MessageType::HandshakeInitiation,
+        2 => MessageType::HandshakeResponse,
+        3 => MessageType::CookieReply,
+        4 => MessageType::TransportData,
+        _ => return None,
+    };
+
+    if message_type.valid_len(payload.len()) {
+        Some(message_type)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message(id: u8, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0xa5; len];
+        bytes[0] = id;
+        bytes[1..4].copy_from_slice(&[0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn test_message_types() {
+        for (id, len, expected) in [
+            (1, 148, MessageType::HandshakeInitiation),
+            (2, 92, MessageType::HandshakeResponse),
+            (3, 64, MessageType::CookieReply),
+            (4, 32, MessageType::TransportData),
+            (4, 1420, MessageType::TransportData),
+        ] {
+            assert_eq!(message_type(&message(id, len)), Some(expected));
+            assert_eq!(expected.id(), id);
+        }
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        assert_eq!(message_type(&message(1, 147)), None);
+        assert_eq!(message_type(&message(2, 93)), None);
+        assert_eq!(message_type(&message(3, 80)), None);
+        assert_eq!(message_type(&message(4, 31)), None);
+    }
+
+    #[test]
+    fn test_not_wireguard() {
+        assert_eq!(message_type(&[]), None);
+        assert_eq!(message_type(&[4, 0, 0]), None);
+        assert_eq!(message_type(&message(0, 64)), None);
+        assert_eq!(message_type(&message(5, 64)), None);
+
+        let mut bytes = message(4, 64);
+        bytes[2] = 1;
+        assert_eq!(message_type(&bytes), None);
+
+        assert_eq!(
+            message_type(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"),
+            None
+        );
+    }
+}